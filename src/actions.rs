@@ -0,0 +1,391 @@
+use druid::Command;
+use crate::commands::*;
+use crate::keyboard_nav::{FocusMove, FocusPane};
+use crate::models::{AppState, SelectionMode};
+
+/// 动作生效的范围
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActionScope {
+    /// 在主窗口中任何时候都可以触发（输入框获得焦点时只响应带 Ctrl、Alt 或功能键的组合）
+    Global,
+    /// 只在目录树或文件列表拥有键盘焦点时触发
+    Panes,
+}
+
+/// 可以绑定按键、也可以在命令面板中执行的动作
+///
+/// 新功能在 `ACTIONS` 中登记后，按键绑定、快捷键列表和命令面板都会自动包含它。
+pub struct Action {
+    /// 按键配置中使用的ID
+    pub id: &'static str,
+    /// 显示给用户的名称
+    pub name: &'static str,
+    /// 在命令面板中显示的说明
+    pub description: &'static str,
+    pub scope: ActionScope,
+    /// 根据当前状态生成要执行的命令，当前不可用时返回 None（命令面板中不显示）
+    pub command: fn(&AppState) -> Option<Command>,
+}
+
+impl Action {
+    /// 当前状态下是否可用
+    pub fn is_available(&self, data: &AppState) -> bool {
+        (self.command)(data).is_some()
+    }
+}
+
+/// 所有可以绑定按键的动作
+pub static ACTIONS: &[Action] = &[
+    // 导航
+    Action {
+        id: "navigate.back",
+        name: "后退",
+        description: "返回上一个访问的目录",
+        scope: ActionScope::Global,
+        command: |data| data.can_navigate_back().then(|| NAVIGATE_BACK.into()),
+    },
+    Action {
+        id: "navigate.forward",
+        name: "前进",
+        description: "前进到后退之前的目录",
+        scope: ActionScope::Global,
+        command: |data| data.can_navigate_forward().then(|| NAVIGATE_FORWARD.into()),
+    },
+    Action {
+        id: "navigate.up",
+        name: "上级目录",
+        description: "打开当前目录的上一级目录",
+        scope: ActionScope::Panes,
+        command: |data| data.selected_path.as_ref()?.parent().map(|_| NAVIGATE_UP.into()),
+    },
+    Action {
+        id: "navigate.home",
+        name: "主目录",
+        description: "打开用户主目录",
+        scope: ActionScope::Global,
+        command: |_| Some(NAVIGATE_HOME.into()),
+    },
+    Action {
+        id: "navigate.refresh",
+        name: "刷新",
+        description: "重新读取当前目录",
+        scope: ActionScope::Global,
+        command: |_| Some(REFRESH_DIRECTORY.into()),
+    },
+    Action {
+        id: "navigate.quick_jump",
+        name: "快速跳转",
+        description: "输入路径片段，跳到最常访问的匹配目录",
+        scope: ActionScope::Global,
+        command: |_| Some(SHOW_QUICK_JUMP.into()),
+    },
+
+    // 焦点和选择
+    Action {
+        id: "focus.previous",
+        name: "上一项",
+        description: "焦点移到上一项并选中",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::Previous, Some(SelectionMode::Replace)),
+    },
+    Action {
+        id: "focus.next",
+        name: "下一项",
+        description: "焦点移到下一项并选中",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::Next, Some(SelectionMode::Replace)),
+    },
+    Action {
+        id: "focus.page_up",
+        name: "向上翻页",
+        description: "焦点向上移动一页并选中",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::PageUp, Some(SelectionMode::Replace)),
+    },
+    Action {
+        id: "focus.page_down",
+        name: "向下翻页",
+        description: "焦点向下移动一页并选中",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::PageDown, Some(SelectionMode::Replace)),
+    },
+    Action {
+        id: "focus.first",
+        name: "第一项",
+        description: "焦点移到第一项并选中",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::First, Some(SelectionMode::Replace)),
+    },
+    Action {
+        id: "focus.last",
+        name: "最后一项",
+        description: "焦点移到最后一项并选中",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::Last, Some(SelectionMode::Replace)),
+    },
+    Action {
+        id: "focus.extend_previous",
+        name: "向上扩展选择",
+        description: "从选择起点选中到上一项",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::Previous, Some(SelectionMode::Range)),
+    },
+    Action {
+        id: "focus.extend_next",
+        name: "向下扩展选择",
+        description: "从选择起点选中到下一项",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::Next, Some(SelectionMode::Range)),
+    },
+    Action {
+        id: "focus.extend_page_up",
+        name: "向上翻页并扩展选择",
+        description: "从选择起点选中到上一页",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::PageUp, Some(SelectionMode::Range)),
+    },
+    Action {
+        id: "focus.extend_page_down",
+        name: "向下翻页并扩展选择",
+        description: "从选择起点选中到下一页",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::PageDown, Some(SelectionMode::Range)),
+    },
+    Action {
+        id: "focus.extend_first",
+        name: "扩展选择到第一项",
+        description: "从选择起点选中到第一项",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::First, Some(SelectionMode::Range)),
+    },
+    Action {
+        id: "focus.extend_last",
+        name: "扩展选择到最后一项",
+        description: "从选择起点选中到最后一项",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::Last, Some(SelectionMode::Range)),
+    },
+    Action {
+        id: "focus.move_previous",
+        name: "焦点移到上一项（不改变选择）",
+        description: "只移动焦点，配合切换选中状态选择不连续的项",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::Previous, None),
+    },
+    Action {
+        id: "focus.move_next",
+        name: "焦点移到下一项（不改变选择）",
+        description: "只移动焦点，配合切换选中状态选择不连续的项",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::Next, None),
+    },
+    Action {
+        id: "focus.move_page_up",
+        name: "焦点向上翻页（不改变选择）",
+        description: "只移动焦点，配合切换选中状态选择不连续的项",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::PageUp, None),
+    },
+    Action {
+        id: "focus.move_page_down",
+        name: "焦点向下翻页（不改变选择）",
+        description: "只移动焦点，配合切换选中状态选择不连续的项",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::PageDown, None),
+    },
+    Action {
+        id: "focus.move_first",
+        name: "焦点移到第一项（不改变选择）",
+        description: "只移动焦点，配合切换选中状态选择不连续的项",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::First, None),
+    },
+    Action {
+        id: "focus.move_last",
+        name: "焦点移到最后一项（不改变选择）",
+        description: "只移动焦点，配合切换选中状态选择不连续的项",
+        scope: ActionScope::Panes,
+        command: |_| focus(FocusMove::Last, None),
+    },
+    Action {
+        id: "focus.open",
+        name: "打开焦点所在的项",
+        description: "进入目录或用系统默认程序打开文件",
+        scope: ActionScope::Panes,
+        command: |_| Some(OPEN_FOCUSED.into()),
+    },
+    Action {
+        id: "focus.toggle_selection",
+        name: "切换焦点所在项的选中状态",
+        description: "选中或取消选中文件列表中焦点所在的行",
+        scope: ActionScope::Panes,
+        command: |data| data.focused_file.as_ref().map(|_| TOGGLE_FOCUSED_SELECTION.into()),
+    },
+    Action {
+        id: "focus.switch_pane",
+        name: "在目录树和文件列表之间切换",
+        description: "把键盘焦点移到另一个面板",
+        scope: ActionScope::Panes,
+        command: |_| Some(SWITCH_FOCUS_PANE.into()),
+    },
+    Action {
+        id: "tree.expand",
+        name: "展开目录树节点",
+        description: "展开选中的目录，已经展开时移到第一个子目录",
+        scope: ActionScope::Panes,
+        command: |data| in_tree(data).then(|| TREE_STEP.with(true)),
+    },
+    Action {
+        id: "tree.collapse",
+        name: "折叠目录树节点",
+        description: "折叠选中的目录，已经折叠时移到上一级目录",
+        scope: ActionScope::Panes,
+        command: |data| in_tree(data).then(|| TREE_STEP.with(false)),
+    },
+
+    // 查看
+    Action {
+        id: "view.toggle_hidden",
+        name: "显示隐藏文件",
+        description: "切换是否显示隐藏文件",
+        scope: ActionScope::Global,
+        command: |_| Some(TOGGLE_HIDDEN_FILES.into()),
+    },
+    Action {
+        id: "view.toggle_folder_sizes",
+        name: "计算文件夹大小",
+        description: "切换是否在文件列表中递归计算文件夹大小",
+        scope: ActionScope::Global,
+        command: |_| Some(TOGGLE_FOLDER_SIZES.into()),
+    },
+    Action {
+        id: "view.quick_filter",
+        name: "筛选当前目录",
+        description: "在文件列表上方的筛选栏中输入文字，只显示名称匹配的项目",
+        scope: ActionScope::Global,
+        command: |data| (!data.drive_overview && data.selected_path.is_some()).then(|| FOCUS_QUICK_FILTER.into()),
+    },
+
+    // 书签
+    Action {
+        id: "bookmarks.add",
+        name: "将当前目录加入书签",
+        description: "加入书签的目录可以在命令面板中直接打开",
+        scope: ActionScope::Global,
+        command: |data| {
+            let path = data.selected_path.clone()?;
+            (!data.settings.bookmarks.contains(&path)).then(|| ADD_BOOKMARK.with(path))
+        },
+    },
+    Action {
+        id: "bookmarks.remove",
+        name: "从书签中移除当前目录",
+        description: "当前目录不再出现在命令面板的书签中",
+        scope: ActionScope::Global,
+        command: |data| {
+            let path = data.selected_path.clone()?;
+            data.settings.bookmarks.contains(&path).then(|| REMOVE_BOOKMARK.with(path))
+        },
+    },
+
+    // 文件
+    Action {
+        id: "tools.properties",
+        name: "属性",
+        description: "查看焦点所在文件或当前目录的属性和权限",
+        scope: ActionScope::Global,
+        command: |data| {
+            let path = data.focused_file.clone().or_else(|| data.selected_path.clone())?;
+            Some(SHOW_PROPERTIES.with(path))
+        },
+    },
+    Action {
+        id: "tools.checksums",
+        name: "计算选中文件的校验和",
+        description: "计算选中文件的校验和并导出为 sha256sum 格式",
+        scope: ActionScope::Global,
+        command: |data| selected(data).map(|paths| COMPUTE_CHECKSUMS.with(paths)),
+    },
+    Action {
+        id: "tools.batch_rename",
+        name: "批量重命名选中的文件",
+        description: "按规则重命名选中的文件，执行前预览结果",
+        scope: ActionScope::Global,
+        command: |data| selected(data).map(|paths| BATCH_RENAME.with(paths)),
+    },
+
+    // 工具
+    Action {
+        id: "tools.disk_usage",
+        name: "分析磁盘占用",
+        description: "统计当前目录中各子目录和文件类型的占用",
+        scope: ActionScope::Global,
+        command: |data| data.selected_path.clone().map(|path| ANALYZE_DISK_USAGE.with(path)),
+    },
+    Action {
+        id: "tools.find_duplicates",
+        name: "查找重复文件",
+        description: "在当前目录中查找内容相同的文件",
+        scope: ActionScope::Global,
+        command: |data| data.selected_path.clone().map(|path| FIND_DUPLICATES.with(path)),
+    },
+    Action {
+        id: "tools.compare_directories",
+        name: "比较目录",
+        description: "比较当前目录和另一个目录的差异",
+        scope: ActionScope::Global,
+        command: |data| Some(COMPARE_DIRECTORIES.with(data.selected_path.clone().unwrap_or_default())),
+    },
+    Action {
+        id: "sync.manage",
+        name: "管理同步任务",
+        description: "编辑、预览和执行单向同步任务",
+        scope: ActionScope::Global,
+        command: |_| Some(SHOW_SYNC_JOBS.with(None)),
+    },
+    Action {
+        id: "app.command_palette",
+        name: "命令面板",
+        description: "搜索并执行动作、打开书签和最近访问的目录",
+        scope: ActionScope::Global,
+        command: |_| Some(SHOW_COMMAND_PALETTE.into()),
+    },
+    Action {
+        id: "app.settings",
+        name: "设置",
+        description: "打开设置窗口",
+        scope: ActionScope::Global,
+        command: |_| Some(SHOW_SETTINGS.into()),
+    },
+    Action {
+        id: "app.keybindings",
+        name: "显示键盘快捷键",
+        description: "列出所有动作的按键和按键冲突",
+        scope: ActionScope::Global,
+        command: |_| Some(SHOW_KEYBINDINGS.into()),
+    },
+];
+
+/// 按ID查找动作
+pub fn find(id: &str) -> Option<&'static Action> {
+    ACTIONS.iter().find(|action| action.id == id)
+}
+
+fn focus(movement: FocusMove, mode: Option<SelectionMode>) -> Option<Command> {
+    Some(MOVE_FOCUS.with((movement, mode)))
+}
+
+/// 键盘焦点是否在目录树中
+fn in_tree(data: &AppState) -> bool {
+    data.focus_pane == FocusPane::Tree
+}
+
+/// 文件列表中选中的文件，没有选中时返回 None
+fn selected(data: &AppState) -> Option<Vec<std::path::PathBuf>> {
+    let paths = data.selected_files();
+    if paths.is_empty() {
+        None
+    } else {
+        Some(paths)
+    }
+}
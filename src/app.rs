@@ -1,28 +1,94 @@
+use std::collections::HashSet;
 use std::path::PathBuf;
 use druid::{AppLauncher, WindowDesc, Target};
+use druid::im::Vector;
 use std::thread;
 use std::time::Duration;
 
+use crate::batch_rename::BatchRenameState;
+use crate::checksum::ChecksumState;
+use crate::command_palette::PaletteState;
+use crate::dir_compare::DirCompareState;
+use crate::disk_usage::DiskUsageState;
+use crate::duplicates::DuplicateState;
+use crate::detection;
+use crate::file_types;
+use crate::keyboard_nav::FocusPane;
+use crate::keymap;
+use crate::locations::{self, build_recent_section};
+use crate::folder_size;
+use crate::volumes;
 use crate::models::{AppState, FileItem};
+use crate::permissions::PermissionEdit;
+use crate::properties::FileProperties;
+use crate::quick_filter::QuickFilterState;
 use crate::file_system::{get_directory_contents, get_drives, build_file_tree};
-use crate::ui::build_ui;
-use crate::delegate::{FileExplorerDelegate, update_selection};
+use crate::ui::{build_ui, build_menu};
+use crate::ui::theme::{self, Theme};
+use crate::delegate::{FileExplorerDelegate, find_item_by_path, update_selection};
+use crate::session::{self, SessionState};
+use crate::settings::{self, Settings};
+use crate::sync::SyncState;
+use crate::system;
 use crate::{LOAD_SUBDIRECTORIES, SELECT_DIRECTORY};
 
 /// 初始化应用程序并运行
 pub fn run_app() {
+    // 读取配置文件，并报告其中的问题
+    let (settings, warnings) = settings::load_settings();
+    for warning in &warnings {
+        eprintln!("配置文件警告: {}", warning);
+    }
+    settings::set_current(settings.clone());
+
+    // 加载文件类型注册表（内置类型和用户自定义类型）
+    for warning in file_types::load_registry() {
+        eprintln!("文件类型配置警告: {}", warning);
+    }
+
+    // 加载按键绑定（默认绑定和用户修改）
+    for warning in keymap::load_keymap() {
+        eprintln!("按键绑定配置警告: {}", warning);
+    }
+
+    // 读取访问过的目录（用于最近访问和快速跳转）
+    locations::load_locations();
+
+    // 读取上次退出时保存的会话，没有则使用默认值
+    let session = session::load_session().unwrap_or_default();
+
     // 创建主窗口描述
-    let main_window = WindowDesc::new(build_ui())
+    let main_window = WindowDesc::new(build_ui(session.split_point))
         .title("柠檬文件管理器")
-        .window_size((1000.0, 600.0));
+        .menu(|_window_id, data, _env| build_menu(data))
+        .window_size(session.window_size);
+    let main_window_id = main_window.id;
 
     // 创建初始状态
-    let initial_state = create_initial_state();
+    let mut initial_state = create_initial_state(&session, settings);
+    initial_state.settings_notice = warnings.join("\n");
+    
+    // 加载设置中指定的主题，加载失败时使用默认主题
+    initial_state.theme = match theme::load_theme(&initial_state.settings.theme) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("加载主题失败: {}", e);
+            Theme::default_theme()
+        }
+    };
+    theme::set_active(&initial_state.theme);
 
     // 创建应用启动器
     let launcher = AppLauncher::with_window(main_window)
-        .delegate(FileExplorerDelegate);
+        .delegate(FileExplorerDelegate::new(main_window_id));
         
+    // 启动文件类型识别和文件夹大小计算线程
+    detection::start_worker(launcher.get_external_handle());
+    folder_size::start_worker(launcher.get_external_handle());
+
+    // 插入U盘、挂载网络共享等变化时刷新驱动器列表
+    volumes::watch_mounts(launcher.get_external_handle());
+
     // 启动初始化线程
     initialize_folders(launcher.get_external_handle(), &initial_state);
 
@@ -32,8 +98,8 @@ pub fn run_app() {
         .expect("启动应用程序失败");
 }
 
-/// 创建应用程序的初始状态
-fn create_initial_state() -> AppState {
+/// 创建应用程序的初始状态，并恢复会话中保存的目录、展开状态和历史记录
+fn create_initial_state(session: &SessionState, settings: Settings) -> AppState {
     // 获取当前系统的驱动器列表
     let mut drives = get_drives();
     println!("初始驱动器数量: {}", drives.len());
@@ -49,10 +115,10 @@ fn create_initial_state() -> AppState {
     }
     
     // 获取用户主目录路径
-    let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("C:\\Users"));
+    let home_dir = home_dir();
     
     // 获取桌面目录路径
-    let desktop_dir = dirs::desktop_dir().unwrap_or_else(|| home_dir.join("Desktop"));
+    let desktop_dir = desktop_dir();
     
     // 确定初始选中的驱动器和目录
     let default_drive = if !drives.is_empty() {
@@ -69,6 +135,10 @@ fn create_initial_state() -> AppState {
         is_expanded: false,
         path: home_dir.clone(),
         is_selected: false,
+        is_dir: true,
+        diff: None,
+        volume: None,
+        is_hidden: false,
     };
     
     // 创建桌面项
@@ -78,6 +148,10 @@ fn create_initial_state() -> AppState {
         is_expanded: true, // 默认展开
         path: desktop_dir.clone(),
         is_selected: false,
+        is_dir: true,
+        diff: None,
+        volume: None,
+        is_hidden: false,
     };
     
     // 创建我的电脑项（包含驱动器）
@@ -87,40 +161,101 @@ fn create_initial_state() -> AppState {
         is_expanded: true,  // 设置为默认展开状态
         path: PathBuf::from("C:\\"), // 使用有效路径而不是空字符串
         is_selected: false,
+        is_dir: true,
+        diff: None,
+        volume: None,
+        is_hidden: false,
     };
     
     // 创建根文件项
     let mut root = FileItem {
         name: "文件导航".to_string(),  // 根节点名称
-        children: vec![home_item, desktop_item, computer_item],  // 添加主文件夹、桌面和我的电脑作为子项
+        children: vec![build_recent_section(None), home_item, desktop_item, computer_item],  // 添加最近访问、主文件夹、桌面和我的电脑作为子项
         is_expanded: true,  // 默认展开根节点
         path: PathBuf::from("ROOT"), // 使用特殊标识而不是空字符串
         is_selected: false,
+        is_dir: true,
+        diff: None,
+        volume: None,
+        is_hidden: false,
     };
     
-    // 设置默认选中的驱动器
-    update_selection(&mut root, &default_drive);
+    // 恢复上次展开的目录树节点
+    let expanded: HashSet<PathBuf> = session.expanded_paths.iter().cloned().collect();
+    session::restore_expanded_paths(&mut root, &expanded);
+
+    // 优先打开设置中的启动目录，其次是上次的目录，都不可用时回退到默认驱动器
+    let start_dir = settings.start_directory_path()
+        .filter(|path| path.is_dir())
+        .or_else(|| session.last_directory.clone())
+        .unwrap_or(default_drive);
 
-    // 创建初始导航历史记录
-    let navigation_history = vec![default_drive.clone()];
+    // 设置默认选中的目录
+    update_selection(&mut root, &start_dir);
+
+    // 恢复导航历史记录，确保当前位置指向起始目录
+    let (navigation_history, history_position) = if session.navigation_history.get(session.history_position) == Some(&start_dir) {
+        (session.navigation_history.clone(), session.history_position)
+    } else {
+        (vec![start_dir.clone()], 0)
+    };
     
     // 创建初始应用程序状态
     AppState {
         root,
-        selected_path: Some(default_drive.clone()),
-        current_dir_files: get_directory_contents(&default_drive),
+        selected_path: Some(start_dir.clone()),
+        current_dir_files: get_directory_contents(&start_dir),
+        selection_anchor: None,
+        focus_pane: FocusPane::List,
+        focused_file: None,
+        panes_focused: true,
+        palette: PaletteState::default(),
         navigation_history,
-        history_position: 0,
+        history_position,
+        address_input: None,
+        drive_overview: false,
+        current_space: system::get_drive_space(&start_dir),
+        loading_path: None,
+        quick_filter: QuickFilterState::default(),
+        window_size: session.window_size,
+        split_point: session.split_point,
+        settings_draft: settings.clone(),
+        settings,
+        settings_notice: String::new(),
+        theme: Theme::default_theme(),
+        properties: FileProperties::default(),
+        permission_edit: PermissionEdit::default(),
+        jobs: Vector::new(),
+        status_notice: String::new(),
+        disk_usage: DiskUsageState::default(),
+        duplicates: DuplicateState::default(),
+        checksums: ChecksumState::default(),
+        batch_rename: BatchRenameState::default(),
+        dir_compare: DirCompareState::default(),
+        sync: SyncState::default(),
     }
 }
 
+/// 用户主目录
+fn home_dir() -> PathBuf {
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from("C:\\Users"))
+}
+
+/// 桌面目录，系统没有提供时使用主目录下的 Desktop
+fn desktop_dir() -> PathBuf {
+    dirs::desktop_dir().unwrap_or_else(|| home_dir().join("Desktop"))
+}
+
 /// 初始化应用程序文件夹
 fn initialize_folders(event_sink: druid::ExtEventSink, initial_state: &AppState) {
-    // 启用桌面文件夹的展开
-    let desktop_path = initial_state.root.children[1].path.clone();
+    // 启用桌面文件夹的展开（按路径查找桌面节点）
+    let desktop_path = find_item_by_path(&initial_state.root, &desktop_dir()).map(|item| item.path.clone());
     
     // 获取我的电脑路径
     let computer_path = PathBuf::from("C:\\");
+
+    // 初始化完成后重新选中起始目录（可能是从会话恢复的目录）
+    let start_path = initial_state.selected_path.clone().unwrap_or_else(|| computer_path.clone());
     
     // 延迟一小段时间后发送展开命令，确保应用已完全初始化
     thread::spawn(move || {
@@ -128,8 +263,10 @@ fn initialize_folders(event_sink: druid::ExtEventSink, initial_state: &AppState)
         thread::sleep(Duration::from_millis(1000));
         
         // 展开桌面文件夹
-        if let Err(e) = event_sink.submit_command(LOAD_SUBDIRECTORIES, desktop_path, Target::Auto) {
-            eprintln!("发送桌面展开命令失败: {}", e);
+        if let Some(desktop_path) = desktop_path {
+            if let Err(e) = event_sink.submit_command(LOAD_SUBDIRECTORIES, desktop_path, Target::Auto) {
+                eprintln!("发送桌面展开命令失败: {}", e);
+            }
         }
         
         // 强制展开我的电脑文件夹
@@ -137,10 +274,10 @@ fn initialize_folders(event_sink: druid::ExtEventSink, initial_state: &AppState)
             eprintln!("发送我的电脑展开命令失败: {}", e);
         }
         
-        // 强制选中我的电脑节点，确保其UI状态正确
+        // 重新选中起始目录，确保其UI状态正确
         thread::sleep(Duration::from_millis(100));
-        if let Err(e) = event_sink.submit_command(SELECT_DIRECTORY, computer_path.clone(), Target::Auto) {
-            eprintln!("发送我的电脑选择命令失败: {}", e);
+        if let Err(e) = event_sink.submit_command(SELECT_DIRECTORY, start_path, Target::Auto) {
+            eprintln!("发送起始目录选择命令失败: {}", e);
         }
         
         // 再次强制展开
@@ -0,0 +1,525 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use chrono::{DateTime, Local};
+use druid::im::Vector;
+use druid::{Data, Lens};
+use regex::Regex;
+
+/// `{date}` 没有指定格式时使用的日期格式
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 名称的大小写转换
+#[derive(Clone, Copy, Data, Debug, PartialEq, Eq)]
+pub enum CaseChange {
+    /// 不转换
+    Keep,
+    /// 全部小写
+    Lower,
+    /// 全部大写
+    Upper,
+    /// 每个单词首字母大写
+    Title,
+}
+
+/// 扩展名的修改方式
+#[derive(Clone, Copy, Data, Debug, PartialEq, Eq)]
+pub enum ExtensionChange {
+    /// 保持不变
+    Keep,
+    /// 转为小写
+    Lower,
+    /// 转为大写
+    Upper,
+    /// 替换为 `RenameOptions::new_extension`（为空时去掉扩展名）
+    Replace,
+}
+
+/// 批量重命名的规则
+///
+/// 新的文件名（不含扩展名）按以下顺序生成：
+/// 1. 按 `template` 生成，其中 `{name}` 为原文件名，`{n}` / `{n:3}` 为计数器（可指定补零的宽度），
+///    `{date}` / `{date:%Y%m%d}` 为修改时间；
+/// 2. 对结果查找替换，使用正则表达式时替换文本中可以用 `$1`、`${name}` 引用捕获组；
+/// 3. 转换大小写。
+///
+/// 最后按 `extension` 修改扩展名。
+#[derive(Clone, Data, Lens, Debug, PartialEq)]
+pub struct RenameOptions {
+    pub template: String,
+    pub find: String,
+    pub replace: String,
+    pub use_regex: bool,
+    pub case: CaseChange,
+    pub extension: ExtensionChange,
+    pub new_extension: String,
+    /// 计数器的起始值和步长（文本形式，便于在对话框中编辑）
+    pub counter_start: String,
+    pub counter_step: String,
+}
+
+impl Default for RenameOptions {
+    fn default() -> Self {
+        Self {
+            template: "{name}".to_string(),
+            find: String::new(),
+            replace: String::new(),
+            use_regex: false,
+            case: CaseChange::Keep,
+            extension: ExtensionChange::Keep,
+            new_extension: String::new(),
+            counter_start: "1".to_string(),
+            counter_step: "1".to_string(),
+        }
+    }
+}
+
+/// 要重命名的文件
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenameSource {
+    pub path: PathBuf,
+    /// 修改时间（用于 `{date}`）
+    pub modified: Option<SystemTime>,
+}
+
+impl RenameSource {
+    /// 读取文件的修改时间
+    pub fn from_path(path: PathBuf) -> Self {
+        let modified = fs::symlink_metadata(&path).and_then(|m| m.modified()).ok();
+        Self { path, modified }
+    }
+}
+
+/// 预览中的一项
+#[derive(Clone, Data, Lens, Debug, PartialEq)]
+pub struct PlannedRename {
+    #[data(same_fn = "PartialEq::eq")]
+    pub from: PathBuf,
+    #[data(same_fn = "PartialEq::eq")]
+    pub to: PathBuf,
+    /// 冲突或无效名称的说明（没有问题时为空）
+    pub conflict: String,
+}
+
+impl PlannedRename {
+    /// 名称是否有变化
+    pub fn is_changed(&self) -> bool {
+        self.from != self.to
+    }
+}
+
+/// 批量重命名窗口的状态
+#[derive(Clone, Data, Lens, Debug, Default, PartialEq)]
+pub struct BatchRenameState {
+    /// 要重命名的文件
+    #[data(same_fn = "PartialEq::eq")]
+    pub sources: Vec<RenameSource>,
+    pub options: RenameOptions,
+    /// 按当前规则生成的预览
+    pub preview: Vector<PlannedRename>,
+    /// 规则错误、冲突数量或执行结果
+    pub notice: String,
+    /// 执行重命名的任务ID
+    pub job_id: Option<u64>,
+}
+
+impl BatchRenameState {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        let mut state = Self {
+            sources: paths.into_iter().map(RenameSource::from_path).collect(),
+            ..Default::default()
+        };
+        state.update_preview();
+        state
+    }
+
+    /// 按当前规则重新生成预览
+    pub fn update_preview(&mut self) {
+        match plan(&self.sources, &self.options) {
+            Ok(items) => {
+                let conflicts = items.iter().filter(|item| !item.conflict.is_empty()).count();
+                let changed = items.iter().filter(|item| item.is_changed()).count();
+                self.notice = if conflicts > 0 {
+                    format!("{} 个名称有冲突或无效，无法执行", conflicts)
+                } else {
+                    format!("将重命名 {} 个文件", changed)
+                };
+                self.preview = items.into_iter().collect();
+            }
+            Err(e) => {
+                self.notice = e;
+                self.preview = Vector::new();
+            }
+        }
+    }
+
+    /// 重命名成功后以新名称作为下一次重命名的文件
+    pub fn finish(&mut self, renamed: usize) {
+        self.sources = self.preview
+            .iter()
+            .map(|item| RenameSource::from_path(item.to.clone()))
+            .collect();
+        self.options = RenameOptions::default();
+        self.update_preview();
+        self.notice = format!("已重命名 {} 个文件", renamed);
+    }
+
+    /// 预览是否可以执行：没有冲突且至少有一个名称变化
+    pub fn can_apply(&self) -> bool {
+        !self.preview.is_empty()
+            && self.preview.iter().all(|item| item.conflict.is_empty())
+            && self.preview.iter().any(PlannedRename::is_changed)
+    }
+}
+
+/// 按规则生成每个文件的新路径，并检查冲突
+///
+/// 规则本身无效（正则表达式、计数器或模板错误）时返回错误。
+pub fn plan(sources: &[RenameSource], options: &RenameOptions) -> Result<Vec<PlannedRename>, String> {
+    let start: i64 = options.counter_start.trim().parse()
+        .map_err(|_| format!("无效的计数器起始值: {}", options.counter_start))?;
+    let step: i64 = options.counter_step.trim().parse()
+        .map_err(|_| format!("无效的计数器步长: {}", options.counter_step))?;
+    let regex = if options.use_regex && !options.find.is_empty() {
+        Some(Regex::new(&options.find).map_err(|e| format!("无效的正则表达式: {}", e))?)
+    } else {
+        None
+    };
+
+    let mut items = Vec::with_capacity(sources.len());
+    for (index, source) in sources.iter().enumerate() {
+        let counter = start + step * index as i64;
+        let name = new_name(source, options, regex.as_ref(), counter)?;
+        items.push(PlannedRename {
+            from: source.path.clone(),
+            to: source.path.with_file_name(&name),
+            conflict: invalid_name_reason(&name).unwrap_or_default(),
+        });
+    }
+
+    mark_conflicts(&mut items);
+    Ok(items)
+}
+
+/// 生成一个文件的新文件名（含扩展名）
+fn new_name(source: &RenameSource, options: &RenameOptions, regex: Option<&Regex>, counter: i64) -> Result<String, String> {
+    let (stem, extension) = split_name(&source.path);
+
+    let mut name = expand_template(&options.template, &stem, source.modified, counter)?;
+    if !options.find.is_empty() {
+        name = match regex {
+            Some(regex) => regex.replace_all(&name, options.replace.as_str()).into_owned(),
+            None => name.replace(&options.find, &options.replace),
+        };
+    }
+    name = change_case(&name, options.case);
+
+    let extension = match options.extension {
+        ExtensionChange::Keep => extension,
+        ExtensionChange::Lower => extension.map(|ext| ext.to_lowercase()),
+        ExtensionChange::Upper => extension.map(|ext| ext.to_uppercase()),
+        ExtensionChange::Replace => {
+            let ext = options.new_extension.trim().trim_start_matches('.');
+            if ext.is_empty() { None } else { Some(ext.to_string()) }
+        }
+    };
+
+    Ok(match extension {
+        Some(ext) => format!("{}.{}", name, ext),
+        None => name,
+    })
+}
+
+/// 将文件名拆分为主名和扩展名（以点开头的隐藏文件没有扩展名）
+fn split_name(path: &Path) -> (String, Option<String>) {
+    let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    match file_name.rfind('.') {
+        Some(index) if index > 0 => (file_name[..index].to_string(), Some(file_name[index + 1..].to_string())),
+        _ => (file_name, None),
+    }
+}
+
+/// 展开模板中的 `{name}`、`{n}`、`{n:宽度}`、`{date}`、`{date:格式}`
+fn expand_template(template: &str, stem: &str, modified: Option<SystemTime>, counter: i64) -> Result<String, String> {
+    let mut result = String::with_capacity(template.len() + stem.len());
+    let mut rest = template;
+
+    while let Some(open) = rest.find('{') {
+        result.push_str(&rest[..open]);
+        let close = rest[open..].find('}')
+            .map(|close| open + close)
+            .ok_or_else(|| format!("模板中的 {{ 没有对应的 }}: {}", template))?;
+
+        let token = &rest[open + 1..close];
+        let (key, argument) = match token.split_once(':') {
+            Some((key, argument)) => (key, Some(argument)),
+            None => (token, None),
+        };
+        match key {
+            "name" => result.push_str(stem),
+            "n" => {
+                let width: usize = match argument {
+                    Some(width) => width.parse().map_err(|_| format!("无效的计数器宽度: {}", width))?,
+                    None => 0,
+                };
+                result.push_str(&format!("{:0width$}", counter, width = width));
+            }
+            "date" => {
+                let time: DateTime<Local> = modified.unwrap_or(SystemTime::UNIX_EPOCH).into();
+                // 格式字符串无效时 chrono 会在格式化时出错，提前检查
+                let format = argument.unwrap_or(DEFAULT_DATE_FORMAT);
+                let items: Vec<_> = chrono::format::StrftimeItems::new(format).collect();
+                if items.iter().any(|item| matches!(item, chrono::format::Item::Error)) {
+                    return Err(format!("无效的日期格式: {}", format));
+                }
+                result.push_str(&time.format_with_items(items.into_iter()).to_string());
+            }
+            _ => return Err(format!("未知的模板标记: {{{}}}", token)),
+        }
+        rest = &rest[close + 1..];
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// 转换大小写
+fn change_case(name: &str, case: CaseChange) -> String {
+    match case {
+        CaseChange::Keep => name.to_string(),
+        CaseChange::Lower => name.to_lowercase(),
+        CaseChange::Upper => name.to_uppercase(),
+        CaseChange::Title => {
+            let mut result = String::with_capacity(name.len());
+            let mut word_start = true;
+            for ch in name.chars() {
+                if word_start {
+                    result.extend(ch.to_uppercase());
+                } else {
+                    result.extend(ch.to_lowercase());
+                }
+                word_start = ch.is_whitespace() || ch == '_' || ch == '-' || ch == '.';
+            }
+            result
+        }
+    }
+}
+
+/// 文件名无效的原因
+fn invalid_name_reason(name: &str) -> Option<String> {
+    if name.is_empty() {
+        return Some("名称为空".to_string());
+    }
+    if name == "." || name == ".." {
+        return Some("无效的名称".to_string());
+    }
+    // 同时检查Windows不允许的字符，保证在各平台上都能重命名
+    if let Some(ch) = name.chars().find(|ch| matches!(ch, '/' | '\\' | '\0' | '<' | '>' | ':' | '"' | '|' | '?' | '*')) {
+        return Some(format!("包含不允许的字符 {:?}", ch));
+    }
+    None
+}
+
+/// 标记重名的目标，以及与不参与重命名的已有文件冲突的目标
+fn mark_conflicts(items: &mut [PlannedRename]) {
+    // 同一批中会被移走的路径不算冲突
+    let sources: HashSet<PathBuf> = items.iter().map(|item| item.from.clone()).collect();
+
+    let mut counts: HashMap<PathBuf, usize> = HashMap::new();
+    for item in items.iter() {
+        *counts.entry(item.to.clone()).or_default() += 1;
+    }
+
+    for item in items.iter_mut() {
+        if !item.conflict.is_empty() || !item.is_changed() {
+            continue;
+        }
+        if counts[&item.to] > 1 {
+            item.conflict = "与其他文件的新名称重复".to_string();
+        } else if !sources.contains(&item.to) && !is_same_file(&item.from, &item.to) && fs::symlink_metadata(&item.to).is_ok() {
+            item.conflict = "目标已存在".to_string();
+        }
+    }
+}
+
+/// 只改变大小写时，在不区分大小写的文件系统上目标就是文件本身
+fn is_same_file(from: &Path, to: &Path) -> bool {
+    from.to_string_lossy().to_lowercase() == to.to_string_lossy().to_lowercase()
+        && fs::canonicalize(from).ok() == fs::canonicalize(to).ok()
+}
+
+/// 执行重命名，任何一步失败时撤销已经完成的步骤
+///
+/// 先把所有文件改为同目录下的临时名称，再改为最终名称，因此文件之间互换名称（a→b, b→a）
+/// 也能正确执行。成功时返回重命名的文件数量。
+pub fn apply(items: &[PlannedRename]) -> Result<usize, String> {
+    let items: Vec<&PlannedRename> = items.iter().filter(|item| item.is_changed()).collect();
+    if let Some(item) = items.iter().find(|item| !item.conflict.is_empty()) {
+        return Err(format!("{}: {}", item.from.display(), item.conflict));
+    }
+
+    // 已经完成的重命名（原路径, 新路径），失败时倒序撤销
+    let mut done: Vec<(PathBuf, PathBuf)> = Vec::with_capacity(items.len() * 2);
+
+    let mut temps = Vec::with_capacity(items.len());
+    for (index, item) in items.iter().enumerate() {
+        let temp = temp_path(&item.from, index);
+        if let Err(e) = fs::rename(&item.from, &temp) {
+            return Err(rollback(done, format!("{}: {}", item.from.display(), e)));
+        }
+        done.push((item.from.clone(), temp.clone()));
+        temps.push(temp);
+    }
+
+    for (item, temp) in items.iter().zip(temps) {
+        // 临时名称改为最终名称时不能覆盖已有文件
+        if fs::symlink_metadata(&item.to).is_ok() {
+            return Err(rollback(done, format!("{}: 目标已存在", item.to.display())));
+        }
+        if let Err(e) = fs::rename(&temp, &item.to) {
+            return Err(rollback(done, format!("{}: {}", item.from.display(), e)));
+        }
+        done.push((temp, item.to.clone()));
+    }
+
+    Ok(items.len())
+}
+
+/// 同目录下不会与已有文件重复的临时路径
+fn temp_path(path: &Path, index: usize) -> PathBuf {
+    let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let mut attempt = 0;
+    loop {
+        let temp = path.with_file_name(format!(".{}.rename-{}-{}-{}", name, std::process::id(), index, attempt));
+        if fs::symlink_metadata(&temp).is_err() {
+            return temp;
+        }
+        attempt += 1;
+    }
+}
+
+/// 倒序撤销已经完成的重命名，返回包含原始错误和撤销结果的说明
+fn rollback(done: Vec<(PathBuf, PathBuf)>, error: String) -> String {
+    let failed: Vec<String> = done
+        .into_iter()
+        .rev()
+        .filter_map(|(from, to)| fs::rename(&to, &from).err().map(|e| format!("{} -> {}: {}", to.display(), from.display(), e)))
+        .collect();
+
+    if failed.is_empty() {
+        format!("重命名失败，已全部撤销: {}", error)
+    } else {
+        format!("重命名失败: {}\n以下撤销也失败了:\n{}", error, failed.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(name: &str) -> RenameSource {
+        RenameSource { path: PathBuf::from("/nonexistent/dir").join(name), modified: Some(SystemTime::UNIX_EPOCH) }
+    }
+
+    fn names(items: &[PlannedRename]) -> Vec<String> {
+        items.iter().map(|item| item.to.file_name().unwrap().to_string_lossy().to_string()).collect()
+    }
+
+    #[test]
+    fn template_with_padded_counter() {
+        let options = RenameOptions { template: "photo_{n:3}".to_string(), ..Default::default() };
+        let items = plan(&[source("a.JPG"), source("b.JPG")], &options).unwrap();
+        assert_eq!(names(&items), ["photo_001.JPG", "photo_002.JPG"]);
+    }
+
+    #[test]
+    fn regex_replace_with_captures() {
+        let options = RenameOptions {
+            find: r"IMG_(\d+)".to_string(),
+            replace: "holiday-$1".to_string(),
+            use_regex: true,
+            ..Default::default()
+        };
+        let items = plan(&[source("IMG_0042.jpg")], &options).unwrap();
+        assert_eq!(names(&items), ["holiday-0042.jpg"]);
+    }
+
+    #[test]
+    fn case_and_extension_changes() {
+        let options = RenameOptions {
+            case: CaseChange::Title,
+            extension: ExtensionChange::Lower,
+            ..Default::default()
+        };
+        let items = plan(&[source("my summer_trip.JPEG")], &options).unwrap();
+        assert_eq!(names(&items), ["My Summer_Trip.jpeg"]);
+
+        let options = RenameOptions {
+            extension: ExtensionChange::Replace,
+            new_extension: ".txt".to_string(),
+            ..Default::default()
+        };
+        let items = plan(&[source("notes.md"), source(".hidden")], &options).unwrap();
+        assert_eq!(names(&items), ["notes.txt", ".hidden.txt"]);
+    }
+
+    #[test]
+    fn date_from_modified_time() {
+        let options = RenameOptions { template: "{date:%Y}_{name}".to_string(), ..Default::default() };
+        let items = plan(&[source("a.txt")], &options).unwrap();
+        let year = DateTime::<Local>::from(SystemTime::UNIX_EPOCH).format("%Y").to_string();
+        assert_eq!(names(&items), [format!("{}_a.txt", year)]);
+    }
+
+    #[test]
+    fn invalid_rules_are_errors() {
+        let bad_regex = RenameOptions { find: "(".to_string(), use_regex: true, ..Default::default() };
+        assert!(plan(&[source("a")], &bad_regex).is_err());
+
+        let bad_token = RenameOptions { template: "{size}".to_string(), ..Default::default() };
+        assert!(plan(&[source("a")], &bad_token).is_err());
+
+        let bad_counter = RenameOptions { counter_start: "x".to_string(), ..Default::default() };
+        assert!(plan(&[source("a")], &bad_counter).is_err());
+    }
+
+    #[test]
+    fn duplicate_and_invalid_targets_are_conflicts() {
+        let options = RenameOptions { template: "same".to_string(), ..Default::default() };
+        let items = plan(&[source("a.txt"), source("b.txt")], &options).unwrap();
+        assert!(items.iter().all(|item| !item.conflict.is_empty()));
+
+        let options = RenameOptions { find: "a".to_string(), replace: "x/y".to_string(), ..Default::default() };
+        let items = plan(&[source("a.txt")], &options).unwrap();
+        assert!(!items[0].conflict.is_empty());
+    }
+
+    #[test]
+    fn apply_swaps_names_and_rolls_back_on_failure() {
+        let dir = std::env::temp_dir().join(format!("batch-rename-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a"), "a").unwrap();
+        fs::write(dir.join("b"), "b").unwrap();
+
+        let swap = vec![
+            PlannedRename { from: dir.join("a"), to: dir.join("b"), conflict: String::new() },
+            PlannedRename { from: dir.join("b"), to: dir.join("a"), conflict: String::new() },
+        ];
+        assert_eq!(apply(&swap), Ok(2));
+        assert_eq!(fs::read_to_string(dir.join("a")).unwrap(), "b");
+        assert_eq!(fs::read_to_string(dir.join("b")).unwrap(), "a");
+
+        // 第二个源文件不存在，第一个文件的重命名被撤销
+        let failing = vec![
+            PlannedRename { from: dir.join("a"), to: dir.join("c"), conflict: String::new() },
+            PlannedRename { from: dir.join("missing"), to: dir.join("d"), conflict: String::new() },
+        ];
+        assert!(apply(&failing).is_err());
+        assert_eq!(fs::read_to_string(dir.join("a")).unwrap(), "b");
+        assert!(!dir.join("c").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
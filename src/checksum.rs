@@ -0,0 +1,468 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use druid::im::Vector;
+use druid::{Data, Lens};
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::jobs::JobContext;
+
+/// 读取文件时的缓冲区大小
+const READ_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// 超过该大小的文件单独报告字节进度
+const LARGE_FILE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// 支持的哈希算法
+#[derive(Clone, Copy, Data, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Blake3,
+}
+
+impl Algorithm {
+    /// 所有算法（计算校验和时全部计算）
+    pub const ALL: [Algorithm; 4] = [Algorithm::Md5, Algorithm::Sha1, Algorithm::Sha256, Algorithm::Blake3];
+
+    /// BSD格式校验和文件中的算法标记
+    pub fn tag(self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "MD5",
+            Algorithm::Sha1 => "SHA1",
+            Algorithm::Sha256 => "SHA256",
+            Algorithm::Blake3 => "BLAKE3",
+        }
+    }
+
+    fn from_tag(tag: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|algorithm| algorithm.tag().eq_ignore_ascii_case(tag))
+    }
+
+    fn hasher(self) -> Hasher {
+        match self {
+            Algorithm::Md5 => Hasher::Md5(Md5::new()),
+            Algorithm::Sha1 => Hasher::Sha1(Sha1::new()),
+            Algorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            Algorithm::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+        }
+    }
+}
+
+/// 正在计算的哈希
+enum Hasher {
+    Md5(Md5),
+    Sha1(Sha1),
+    Sha256(Sha256),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Md5(hasher) => hasher.update(data),
+            Hasher::Sha1(hasher) => hasher.update(data),
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Blake3(hasher) => {
+                hasher.update(data);
+            }
+        }
+    }
+
+    /// 小写十六进制形式的结果
+    fn finalize_hex(self) -> String {
+        match self {
+            Hasher::Md5(hasher) => format!("{:x}", hasher.finalize()),
+            Hasher::Sha1(hasher) => format!("{:x}", hasher.finalize()),
+            Hasher::Sha256(hasher) => format!("{:x}", hasher.finalize()),
+            Hasher::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+/// 一个文件的校验和
+#[derive(Clone, Data, Lens, Debug, PartialEq)]
+pub struct FileChecksums {
+    #[data(same_fn = "PartialEq::eq")]
+    pub path: PathBuf,
+    pub size: u64,
+    pub md5: String,
+    pub sha1: String,
+    pub sha256: String,
+    pub blake3: String,
+}
+
+/// 校验和文件中一个条目的校验结果
+#[derive(Clone, Copy, Data, Debug, PartialEq, Eq)]
+pub enum VerifyStatus {
+    /// 校验和一致
+    Ok,
+    /// 校验和不一致
+    Mismatch,
+    /// 文件不存在
+    Missing,
+    /// 无法读取文件
+    Error,
+}
+
+impl VerifyStatus {
+    pub fn name(self) -> &'static str {
+        match self {
+            VerifyStatus::Ok => "一致",
+            VerifyStatus::Mismatch => "不一致",
+            VerifyStatus::Missing => "缺失",
+            VerifyStatus::Error => "无法读取",
+        }
+    }
+}
+
+/// 校验和文件中的一个条目
+#[derive(Clone, Data, Lens, Debug, PartialEq)]
+pub struct VerifyEntry {
+    /// 校验和文件中记录的文件名
+    pub name: String,
+    pub status: VerifyStatus,
+    /// 错误信息
+    pub detail: String,
+}
+
+/// 校验和窗口的状态
+#[derive(Clone, Data, Lens, Debug, Default, PartialEq)]
+pub struct ChecksumState {
+    /// 计算或校验任务ID
+    pub job_id: Option<u64>,
+    /// 校验的校验和文件（计算校验和时为空）
+    #[data(same_fn = "PartialEq::eq")]
+    pub list_path: Option<PathBuf>,
+    /// 计算出的校验和
+    pub results: Vector<FileChecksums>,
+    /// 校验一致的文件数量
+    pub ok_count: usize,
+    /// 校验不一致、缺失或无法读取的条目
+    pub problems: Vector<VerifyEntry>,
+    /// 提示（复制结果、校验和文件错误等）
+    pub notice: String,
+}
+
+impl ChecksumState {
+    /// 接收校验结果：一致的条目只计数，其余条目列出
+    pub fn set_verify_entries(&mut self, entries: &Vector<VerifyEntry>) {
+        self.ok_count = entries.iter().filter(|entry| entry.status == VerifyStatus::Ok).count();
+        self.problems = entries.iter().filter(|entry| entry.status != VerifyStatus::Ok).cloned().collect();
+    }
+
+    /// 各种校验结果的数量
+    pub fn count(&self, status: VerifyStatus) -> usize {
+        match status {
+            VerifyStatus::Ok => self.ok_count,
+            _ => self.problems.iter().filter(|entry| entry.status == status).count(),
+        }
+    }
+
+    /// 所有算法的结果，每个文件一段
+    pub fn full_report(&self) -> String {
+        self.results
+            .iter()
+            .map(|result| {
+                format!(
+                    "{}\nMD5     {}\nSHA-1   {}\nSHA-256 {}\nBLAKE3  {}\n",
+                    result.path.display(), result.md5, result.sha1, result.sha256, result.blake3
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// 计算多个文件的所有校验和（在后台任务中运行），目录和读取失败的文件记录到任务中
+pub fn compute_all(paths: &[PathBuf], job: &mut JobContext) -> Vector<FileChecksums> {
+    let mut results = Vector::new();
+    for path in paths {
+        if job.is_cancelled() {
+            break;
+        }
+        if path.is_dir() {
+            job.failed(path, "不能计算目录的校验和");
+            continue;
+        }
+
+        match hash_file(path, &Algorithm::ALL, job) {
+            Ok((size, mut hashes)) => {
+                // 结果与 Algorithm::ALL 的顺序一致
+                let blake3 = hashes.pop().unwrap_or_default();
+                let sha256 = hashes.pop().unwrap_or_default();
+                let sha1 = hashes.pop().unwrap_or_default();
+                let md5 = hashes.pop().unwrap_or_default();
+                results.push_back(FileChecksums { path: path.clone(), size, md5, sha1, sha256, blake3 });
+                job.processed();
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => break,
+            Err(e) => job.failed(path, e),
+        }
+    }
+    results
+}
+
+/// 用给定的算法计算文件的哈希，返回文件大小和各算法的结果
+///
+/// 大文件在计算过程中报告字节进度；任务被取消时返回 `Interrupted` 错误。
+fn hash_file(path: &Path, algorithms: &[Algorithm], job: &mut JobContext) -> io::Result<(u64, Vec<String>)> {
+    let mut file = File::open(path)?;
+    let total = file.metadata()?.len();
+    let mut hashers: Vec<Hasher> = algorithms.iter().map(|algorithm| algorithm.hasher()).collect();
+    let mut buffer = vec![0; READ_BUFFER_SIZE];
+    let mut done = 0;
+
+    loop {
+        if job.is_cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "已取消"));
+        }
+
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        for hasher in hashers.iter_mut() {
+            hasher.update(&buffer[..read]);
+        }
+
+        done += read as u64;
+        if total >= LARGE_FILE_SIZE {
+            job.set_bytes(done, total);
+        }
+    }
+    if total >= LARGE_FILE_SIZE {
+        job.set_bytes(0, 0);
+    }
+
+    Ok((done, hashers.into_iter().map(Hasher::finalize_hex).collect()))
+}
+
+/// 生成与 `sha256sum` 兼容的文本
+///
+/// 所有文件在同一目录中时只写文件名（可以在该目录中用 `sha256sum -c` 校验），否则写完整路径。
+pub fn to_sha256sum(results: &[FileChecksums]) -> String {
+    let first_parent = results.first().and_then(|result| result.path.parent());
+    let same_dir = results.iter().all(|result| result.path.parent() == first_parent);
+
+    results
+        .iter()
+        .map(|result| {
+            let name = match (same_dir, result.path.file_name()) {
+                (true, Some(name)) => name.to_string_lossy().to_string(),
+                _ => result.path.display().to_string(),
+            };
+            // 与 sha256sum 相同：文件名包含反斜杠或换行时转义，并在行首加反斜杠
+            if name.contains('\\') || name.contains('\n') {
+                let escaped = name.replace('\\', "\\\\").replace('\n', "\\n");
+                format!("\\{}  {}", result.sha256, escaped)
+            } else {
+                format!("{}  {}", result.sha256, name)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n"
+}
+
+/// 解析校验和文件中的一行，返回算法、小写的哈希和文件名
+///
+/// 支持GNU格式（`哈希  文件名` 或 `哈希 *文件名`）和BSD格式（`SHA256 (文件名) = 哈希`）；
+/// GNU格式按哈希长度判断算法，64位的哈希在 `default_64` 中指定是SHA-256还是BLAKE3。
+pub fn parse_line(line: &str, default_64: Algorithm) -> Option<(Algorithm, String, String)> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    if line.trim().is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    if let Some(parsed) = parse_bsd_line(line) {
+        return Some(parsed);
+    }
+
+    // GNU格式，行首的反斜杠表示文件名经过转义
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let (hash, rest) = line.split_once(' ')?;
+    if hash.is_empty() || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let name = rest.strip_prefix(' ').or_else(|| rest.strip_prefix('*')).unwrap_or(rest);
+    let name = if escaped { unescape_name(name) } else { name.to_string() };
+
+    let algorithm = match hash.len() {
+        32 => Algorithm::Md5,
+        40 => Algorithm::Sha1,
+        64 => default_64,
+        _ => return None,
+    };
+    Some((algorithm, hash.to_lowercase(), name))
+}
+
+/// 还原 `sha256sum` 转义的文件名（`\\` 为反斜杠，`\n` 为换行）
+fn unescape_name(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// 解析BSD格式的一行：`SHA256 (文件名) = 哈希`
+fn parse_bsd_line(line: &str) -> Option<(Algorithm, String, String)> {
+    let (left, hash) = line.rsplit_once(") = ")?;
+    let (tag, name) = left.split_once(" (")?;
+    let algorithm = Algorithm::from_tag(tag.trim())?;
+    let hash = hash.trim();
+    if hash.is_empty() || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some((algorithm, hash.to_lowercase(), name.to_string()))
+}
+
+/// 按校验和文件的名称推断64位哈希使用的算法（如 B3SUMS、*.b3 为BLAKE3，其余为SHA-256）
+fn default_64_for(list_path: &Path) -> Algorithm {
+    let name = list_path.file_name().map(|name| name.to_string_lossy().to_lowercase()).unwrap_or_default();
+    if name.contains("b3") || name.contains("blake3") {
+        Algorithm::Blake3
+    } else {
+        Algorithm::Sha256
+    }
+}
+
+/// 按校验和文件校验其所在目录中的文件（在后台任务中运行）
+///
+/// 文件名相对于校验和文件所在的目录；无法识别的行被忽略。
+pub fn verify(list_path: &Path, job: &mut JobContext) -> Result<Vector<VerifyEntry>, String> {
+    let text = std::fs::read_to_string(list_path)
+        .map_err(|e| format!("无法读取校验和文件: {}", e))?;
+    let base = list_path.parent().unwrap_or_else(|| Path::new("."));
+    let default_64 = default_64_for(list_path);
+
+    let lines: Vec<_> = text.lines().filter_map(|line| parse_line(line, default_64)).collect();
+    if lines.is_empty() {
+        return Err("校验和文件中没有可识别的条目".to_string());
+    }
+
+    let mut entries = Vector::new();
+    for (algorithm, expected, name) in lines {
+        if job.is_cancelled() {
+            break;
+        }
+
+        let path = base.join(&name);
+        let (status, detail) = if !path.is_file() {
+            (VerifyStatus::Missing, String::new())
+        } else {
+            match hash_file(&path, &[algorithm], job) {
+                Ok((_, hashes)) if hashes.first() == Some(&expected) => (VerifyStatus::Ok, String::new()),
+                Ok(_) => (VerifyStatus::Mismatch, String::new()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => break,
+                Err(e) => (VerifyStatus::Error, e.to_string()),
+            }
+        };
+        entries.push_back(VerifyEntry { name, status, detail });
+        job.processed();
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    const MD5: &str = "d41d8cd98f00b204e9800998ecf8427e";
+    const SHA1: &str = "da39a3ee5e6b4b0d3255bfef95601890afd80709";
+
+    fn checksums(path: &str) -> FileChecksums {
+        FileChecksums {
+            path: PathBuf::from(path),
+            size: 0,
+            md5: MD5.to_string(),
+            sha1: SHA1.to_string(),
+            sha256: SHA256.to_string(),
+            blake3: String::new(),
+        }
+    }
+
+    #[test]
+    fn gnu_lines_pick_algorithm_by_length() {
+        let line = format!("{}  empty.txt", SHA256);
+        assert_eq!(parse_line(&line, Algorithm::Sha256), Some((Algorithm::Sha256, SHA256.to_string(), "empty.txt".to_string())));
+        assert_eq!(parse_line(&line, Algorithm::Blake3).map(|parsed| parsed.0), Some(Algorithm::Blake3));
+
+        // 二进制模式的星号和大写哈希
+        let line = format!("{} *a b.iso\r\n", MD5.to_uppercase());
+        assert_eq!(parse_line(&line, Algorithm::Sha256), Some((Algorithm::Md5, MD5.to_string(), "a b.iso".to_string())));
+
+        let line = format!("{}  x", SHA1);
+        assert_eq!(parse_line(&line, Algorithm::Sha256).map(|parsed| parsed.0), Some(Algorithm::Sha1));
+    }
+
+    #[test]
+    fn gnu_lines_with_escaped_names() {
+        let line = format!("\\{}  dir\\\\a\\nb", SHA256);
+        assert_eq!(parse_line(&line, Algorithm::Sha256).map(|parsed| parsed.2), Some("dir\\a\nb".to_string()));
+
+        // 反斜杠后面跟着 n 的文件名
+        let line = format!("\\{}  a\\\\nb", SHA256);
+        assert_eq!(parse_line(&line, Algorithm::Sha256).map(|parsed| parsed.2), Some("a\\nb".to_string()));
+    }
+
+    #[test]
+    fn unrecognized_lines_are_skipped() {
+        assert_eq!(parse_line("", Algorithm::Sha256), None);
+        assert_eq!(parse_line("# comment", Algorithm::Sha256), None);
+        assert_eq!(parse_line("not-a-hash  file", Algorithm::Sha256), None);
+        assert_eq!(parse_line("abc123  too-short", Algorithm::Sha256), None);
+    }
+
+    #[test]
+    fn bsd_lines() {
+        let line = format!("SHA256 (release (final).tar.gz) = {}", SHA256);
+        assert_eq!(parse_bsd_line(&line), Some((Algorithm::Sha256, SHA256.to_string(), "release (final).tar.gz".to_string())));
+        assert_eq!(parse_line(&format!("md5 (a) = {}", MD5), Algorithm::Sha256).map(|parsed| parsed.0), Some(Algorithm::Md5));
+        assert_eq!(parse_bsd_line(&format!("CRC32 (a) = {}", MD5)), None);
+        assert_eq!(parse_bsd_line("SHA256 (a) = not-hex"), None);
+    }
+
+    #[test]
+    fn sha256sum_output_uses_names_in_same_directory() {
+        let text = to_sha256sum(&[checksums("/data/a.txt"), checksums("/data/b.txt")]);
+        assert_eq!(text, format!("{0}  a.txt\n{0}  b.txt\n", SHA256));
+
+        let text = to_sha256sum(&[checksums("/data/a.txt"), checksums("/other/b.txt")]);
+        assert_eq!(text, format!("{0}  /data/a.txt\n{0}  /other/b.txt\n", SHA256));
+    }
+
+    #[test]
+    fn sha256sum_output_round_trips_escaped_names() {
+        let text = to_sha256sum(&[checksums("/data/a\\nb"), checksums("/data/c\nd")]);
+        let names: Vec<_> = text.lines()
+            .filter_map(|line| parse_line(line, Algorithm::Sha256))
+            .map(|parsed| parsed.2)
+            .collect();
+        assert_eq!(names, ["a\\nb", "c\nd"]);
+    }
+
+    #[test]
+    fn default_algorithm_for_64_digit_hashes() {
+        assert_eq!(default_64_for(Path::new("/x/B3SUMS")), Algorithm::Blake3);
+        assert_eq!(default_64_for(Path::new("/x/file.blake3")), Algorithm::Blake3);
+        assert_eq!(default_64_for(Path::new("/x/SHA256SUMS")), Algorithm::Sha256);
+    }
+}
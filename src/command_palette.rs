@@ -0,0 +1,332 @@
+use druid::im::Vector;
+use druid::{Data, Lens};
+use std::path::{Path, PathBuf};
+use crate::actions::{ActionScope, ACTIONS};
+use crate::keymap;
+use crate::locations;
+use crate::models::AppState;
+
+/// 命令面板最多显示的条目数量
+const MAX_RESULTS: usize = 50;
+
+/// 命令面板中列出的最近访问目录数量
+const MAX_RECENT: usize = 20;
+
+/// 命令面板自身的动作，不在面板中列出
+const PALETTE_ACTION: &str = "app.command_palette";
+
+/// 选择命令面板中的一项后执行的操作
+#[derive(Clone, Debug, PartialEq)]
+pub enum PaletteTarget {
+    /// 执行动作（动作ID）
+    Action(&'static str),
+    /// 打开目录（书签或最近访问的目录）
+    Directory(PathBuf),
+}
+
+/// 命令面板中的一项
+#[derive(Clone, Data, Lens, Debug)]
+pub struct PaletteItem {
+    /// 分类：动作、书签、最近访问、常去
+    pub category: String,
+    pub title: String,
+    /// 动作的说明或目录的完整路径
+    pub detail: String,
+    /// 动作绑定的按键，没有绑定时为空
+    pub keys: String,
+    #[data(same_fn = "PartialEq::eq")]
+    pub target: PaletteTarget,
+    /// 是否为当前选中的项（回车执行）
+    pub is_selected: bool,
+}
+
+/// 命令面板的用途
+#[derive(Clone, Copy, Data, Debug, Default, PartialEq, Eq)]
+pub enum PaletteMode {
+    /// 搜索动作、书签和最近访问的目录
+    #[default]
+    Commands,
+    /// 快速跳转：按路径片段搜索访问过的目录
+    Jump,
+}
+
+/// 命令面板的状态
+#[derive(Clone, Data, Lens, Debug, Default)]
+pub struct PaletteState {
+    /// 命令面板是否显示
+    pub open: bool,
+    pub mode: PaletteMode,
+    /// 输入的搜索内容
+    pub query: String,
+    /// 打开面板时可用的所有条目
+    pub candidates: Vector<PaletteItem>,
+    /// 与搜索内容匹配的条目，按匹配程度排序
+    pub items: Vector<PaletteItem>,
+}
+
+impl PaletteState {
+    /// 打开命令面板，列出当前可用的所有条目
+    pub fn open_commands(candidates: Vec<PaletteItem>) -> Self {
+        let mut state = PaletteState {
+            open: true,
+            mode: PaletteMode::Commands,
+            query: String::new(),
+            candidates: candidates.into_iter().collect(),
+            items: Vector::new(),
+        };
+        state.refilter();
+        state
+    }
+
+    /// 打开快速跳转，列出最常访问的目录
+    pub fn open_jump() -> Self {
+        let mut state = PaletteState {
+            open: true,
+            mode: PaletteMode::Jump,
+            ..PaletteState::default()
+        };
+        state.refilter();
+        state
+    }
+
+    /// 面板上方的标题
+    pub fn title(&self) -> &'static str {
+        match self.mode {
+            PaletteMode::Commands => "命令面板：搜索动作、书签和最近访问的目录",
+            PaletteMode::Jump => "快速跳转：输入路径中的片段，多个片段用空格分隔",
+        }
+    }
+
+    /// 按搜索内容重新过滤和排序，并选中第一项
+    pub fn refilter(&mut self) {
+        let items: Vec<PaletteItem> = match self.mode {
+            PaletteMode::Commands => {
+                let mut scored: Vec<(i64, &PaletteItem)> = self.candidates
+                    .iter()
+                    .filter_map(|item| item_score(&self.query, item).map(|score| (score, item)))
+                    .collect();
+                // 稳定排序，分数相同时保持原来的顺序
+                scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+                scored.into_iter().take(MAX_RESULTS).map(|(_, item)| item.clone()).collect()
+            }
+            // 访问记录已经按 frecency 排序
+            PaletteMode::Jump => locations::jump_candidates(&self.query, MAX_RESULTS)
+                .iter()
+                .map(|path| directory_item("常去", path))
+                .collect(),
+        };
+
+        self.items = items.into_iter()
+            .enumerate()
+            .map(|(index, item)| PaletteItem { is_selected: index == 0, ..item })
+            .collect();
+    }
+
+    /// 上下移动选中的项（到头后从另一端继续）
+    pub fn move_selection(&mut self, delta: isize) {
+        let len = self.items.len() as isize;
+        if len == 0 {
+            return;
+        }
+        let current = self.items.iter().position(|item| item.is_selected).unwrap_or(0) as isize;
+        let next = (current + delta).rem_euclid(len) as usize;
+        for (index, item) in self.items.iter_mut().enumerate() {
+            if item.is_selected != (index == next) {
+                item.is_selected = index == next;
+            }
+        }
+    }
+
+    /// 当前选中的项要执行的操作
+    pub fn selected_target(&self) -> Option<PaletteTarget> {
+        self.items.iter().find(|item| item.is_selected).map(|item| item.target.clone())
+    }
+}
+
+/// 当前状态下命令面板中可以选择的所有条目：可用的动作、书签和最近访问的目录
+///
+/// 只在目录树或文件列表拥有焦点时生效的动作（移动焦点等）不列出。目录在这里不检查是否存在，
+/// 避免打开面板时逐个访问文件系统，选择后再检查。
+pub fn candidates(data: &AppState) -> Vec<PaletteItem> {
+    let mut items: Vec<PaletteItem> = keymap::with_keymap(|keymap| {
+        ACTIONS.iter()
+            .filter(|action| action.id != PALETTE_ACTION && action.scope != ActionScope::Panes)
+            .filter(|action| action.is_available(data))
+            .map(|action| PaletteItem {
+                category: "动作".to_string(),
+                title: action.name.to_string(),
+                detail: action.description.to_string(),
+                keys: keymap.keys_for(action.id).into_iter().next().unwrap_or_default(),
+                target: PaletteTarget::Action(action.id),
+                is_selected: false,
+            })
+            .collect()
+    });
+
+    items.extend(data.settings.bookmarks.iter().map(|path| directory_item("书签", path)));
+
+    // 导航历史中最近访问的目录，不包括当前目录和已经加入书签的目录
+    let mut recent: Vec<&PathBuf> = Vec::new();
+    for path in data.navigation_history.iter().rev() {
+        if Some(path) != data.selected_path.as_ref()
+            && !data.settings.bookmarks.contains(path)
+            && !recent.contains(&path)
+        {
+            recent.push(path);
+        }
+    }
+    items.extend(recent.into_iter().take(MAX_RECENT).map(|path| directory_item("最近访问", path)));
+    items
+}
+
+fn directory_item(category: &str, path: &Path) -> PaletteItem {
+    let title = path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    PaletteItem {
+        category: category.to_string(),
+        title,
+        detail: path.display().to_string(),
+        keys: String::new(),
+        target: PaletteTarget::Directory(path.to_path_buf()),
+        is_selected: false,
+    }
+}
+
+/// 条目与搜索内容的匹配分数，不匹配时返回 None
+///
+/// 搜索内容按空格分成多个词，每个词都要在名称或说明（包括分类）中匹配；名称中的匹配分数更高。
+fn item_score(query: &str, item: &PaletteItem) -> Option<i64> {
+    let detail = format!("{} {}", item.category, item.detail);
+    query.split_whitespace().try_fold(0, |total, term| {
+        let score = match (fuzzy_score(term, &item.title), fuzzy_score(term, &detail)) {
+            (Some(title), _) => title * 2,
+            (None, Some(detail)) => detail,
+            (None, None) => return None,
+        };
+        Some(total + score)
+    })
+}
+
+/// 模糊匹配：`query` 中的字符按顺序出现在 `text` 中即匹配（不区分大小写）
+///
+/// 连续匹配和匹配在词首的字符加分，跳过的字符扣分，返回所有起点中最高的分数。
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let first = *query.first()?;
+
+    (0..text.len())
+        .filter(|&start| text[start] == first)
+        .filter_map(|start| score_from(&query, &text, start))
+        .max()
+}
+
+/// 从 `start` 开始贪心匹配的分数
+fn score_from(query: &[char], text: &[char], start: usize) -> Option<i64> {
+    let mut score = 0;
+    let mut position = start;
+    let mut previous: Option<usize> = None;
+    for &c in query {
+        let index = position + text[position..].iter().position(|&t| t == c)?;
+        score += 1;
+        if is_word_start(text, index) {
+            score += 8;
+        }
+        match previous {
+            Some(previous) if index == previous + 1 => score += 5,
+            Some(previous) => score -= (index - previous - 1).min(10) as i64,
+            None => {}
+        }
+        previous = Some(index);
+        position = index + 1;
+    }
+    Some(score)
+}
+
+/// 字符是否在词首（文本开头、分隔符之后，或者是汉字）
+fn is_word_start(text: &[char], index: usize) -> bool {
+    index == 0
+        || matches!(text[index - 1], ' ' | '/' | '\\' | '.' | '_' | '-' | ':')
+        || !text[index].is_ascii()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(title: &str, detail: &str) -> PaletteItem {
+        PaletteItem {
+            category: "动作".to_string(),
+            title: title.to_string(),
+            detail: detail.to_string(),
+            keys: String::new(),
+            target: PaletteTarget::Directory(PathBuf::from(title)),
+            is_selected: false,
+        }
+    }
+
+    fn selected_index(state: &PaletteState) -> Option<usize> {
+        state.items.iter().position(|item| item.is_selected)
+    }
+
+    #[test]
+    fn fuzzy_score_matches_in_order() {
+        assert!(fuzzy_score("abc", "a-b-c").is_some());
+        assert!(fuzzy_score("ABC", "abc").is_some());
+        assert!(fuzzy_score("文件", "新建文件夹").is_some());
+        assert_eq!(fuzzy_score("abc", "acb"), None);
+        assert_eq!(fuzzy_score("", "abc"), None);
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_word_starts_and_runs() {
+        // 词首的匹配优于词中间的匹配
+        assert!(fuzzy_score("nf", "New Folder") > fuzzy_score("nf", "confine"));
+        // 连续的匹配优于分散的匹配
+        assert!(fuzzy_score("ref", "refresh") > fuzzy_score("ref", "rxexf"));
+        // 取所有起点中最好的一个
+        assert_eq!(fuzzy_score("ab", "a ab"), fuzzy_score("ab", "ab"));
+    }
+
+    #[test]
+    fn item_score_weights_title_and_requires_all_terms() {
+        let refresh = item("刷新", "重新读取当前目录");
+        // 名称中的匹配分数加倍
+        assert!(item_score("刷新", &refresh) > item_score("读取", &refresh));
+        // 分类也参与匹配
+        assert!(item_score("动作", &refresh).is_some());
+        assert!(item_score("刷新 目录", &refresh).is_some());
+        assert_eq!(item_score("刷新 书签", &refresh), None);
+        assert_eq!(item_score("", &refresh), Some(0));
+    }
+
+    #[test]
+    fn refilter_sorts_by_score() {
+        let mut state = PaletteState::open_commands(vec![item("Open terminal", ""), item("Create folder", ""), item("Refresh", "")]);
+        assert_eq!(state.items.len(), 3);
+        state.query = "re".to_string();
+        state.refilter();
+        let titles: Vec<_> = state.items.iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(titles, vec!["Refresh", "Create folder"]);
+        assert_eq!(selected_index(&state), Some(0));
+    }
+
+    #[test]
+    fn move_selection_wraps_around() {
+        let mut state = PaletteState::open_commands(vec![item("a", ""), item("b", ""), item("c", "")]);
+        assert_eq!(selected_index(&state), Some(0));
+        state.move_selection(1);
+        assert_eq!(selected_index(&state), Some(1));
+        state.move_selection(-2);
+        assert_eq!(selected_index(&state), Some(2));
+        state.move_selection(1);
+        assert_eq!(selected_index(&state), Some(0));
+        assert_eq!(state.selected_target(), Some(PaletteTarget::Directory(PathBuf::from("a"))));
+
+        // 没有条目时不做任何事
+        let mut empty = PaletteState::open_commands(Vec::new());
+        empty.move_selection(1);
+        assert_eq!(empty.selected_target(), None);
+    }
+}
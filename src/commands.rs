@@ -10,13 +10,16 @@ pub const OPEN_FILE: Selector<PathBuf> = Selector::new("file-explorer.open-file"
 /// 重置鼠标光标的命令
 pub const RESET_CURSOR: Selector<()> = Selector::new("file-explorer.reset-cursor");
 
-/// 后台加载完成后更新文件列表的命令
-pub const UPDATE_FILE_LIST: Selector<druid::im::Vector<crate::models::FileDetail>> = 
+/// 后台加载完成后更新文件列表的命令（加载的目录和其中的文件）
+pub const UPDATE_FILE_LIST: Selector<(PathBuf, druid::im::Vector<crate::models::FileDetail>)> = 
     Selector::new("file-explorer.update-file-list");
 
 /// 导航到上一个目录（后退）
 pub const NAVIGATE_BACK: Selector<()> = Selector::new("file-explorer.navigate-back");
 
+/// 跳到导航历史中的某一项（历史记录中的位置）
+pub const NAVIGATE_HISTORY: Selector<usize> = Selector::new("file-explorer.navigate-history");
+
 /// 导航到下一个目录（前进）
 pub const NAVIGATE_FORWARD: Selector<()> = Selector::new("file-explorer.navigate-forward");
 
@@ -27,4 +30,206 @@ pub const NAVIGATE_UP: Selector<()> = Selector::new("file-explorer.navigate-up")
 pub const REFRESH_DIRECTORY: Selector<()> = Selector::new("file-explorer.refresh-directory");
 
 /// 导航到主目录
-pub const NAVIGATE_HOME: Selector<()> = Selector::new("file-explorer.navigate-home"); 
\ No newline at end of file
+pub const NAVIGATE_HOME: Selector<()> = Selector::new("file-explorer.navigate-home");
+
+/// 打开设置窗口
+pub const SHOW_SETTINGS: Selector<()> = Selector::new("file-explorer.show-settings");
+
+/// 校验并保存设置窗口中的修改
+pub const SAVE_SETTINGS: Selector<()> = Selector::new("file-explorer.save-settings");
+
+/// 切换是否显示隐藏文件（Ctrl+H）
+pub const TOGGLE_HIDDEN_FILES: Selector<()> = Selector::new("file-explorer.toggle-hidden-files");
+
+/// 切换是否计算文件夹大小
+pub const TOGGLE_FOLDER_SIZES: Selector<()> = Selector::new("file-explorer.toggle-folder-sizes");
+
+/// 切换到指定名称的主题
+pub const SET_THEME: Selector<String> = Selector::new("file-explorer.set-theme");
+
+/// 后台按文件内容识别出文件类型（路径, 识别结果）
+pub const FILE_TYPE_DETECTED: Selector<(PathBuf, Option<crate::file_types::FileKind>)> =
+    Selector::new("file-explorer.file-type-detected");
+
+/// 打开指定文件或目录的属性窗口
+pub const SHOW_PROPERTIES: Selector<PathBuf> = Selector::new("file-explorer.show-properties");
+
+/// 属性窗口中目录递归统计的进度（目录路径, 当前统计结果）
+pub const DIRECTORY_SUMMARY_PROGRESS: Selector<(PathBuf, crate::properties::DirectorySummary)> =
+    Selector::new("file-explorer.directory-summary-progress");
+
+/// 将属性窗口中编辑的权限应用到文件
+pub const APPLY_PERMISSIONS: Selector<()> = Selector::new("file-explorer.apply-permissions");
+
+/// 后台任务的进度和结果
+pub const JOB_PROGRESS: Selector<crate::jobs::JobStatus> = Selector::new("file-explorer.job-progress");
+
+/// 取消指定ID的后台任务
+pub const CANCEL_JOB: Selector<u64> = Selector::new("file-explorer.cancel-job");
+
+/// 后台计算出目录的递归大小（目录路径, 大小）
+pub const FOLDER_SIZE_COMPUTED: Selector<(PathBuf, crate::folder_size::FolderSize)> =
+    Selector::new("file-explorer.folder-size-computed");
+
+/// 分析指定目录的磁盘占用
+pub const ANALYZE_DISK_USAGE: Selector<PathBuf> = Selector::new("file-explorer.analyze-disk-usage");
+
+/// 磁盘占用扫描完成（任务ID, 扫描结果）
+pub const DISK_USAGE_RESULT: Selector<(u64, std::sync::Arc<crate::disk_usage::UsageReport>)> =
+    Selector::new("file-explorer.disk-usage-result");
+
+/// 查找指定目录中的重复文件
+pub const FIND_DUPLICATES: Selector<PathBuf> = Selector::new("file-explorer.find-duplicates");
+
+/// 重复文件查找完成（任务ID, 重复文件组）
+pub const DUPLICATES_FOUND: Selector<(u64, druid::im::Vector<crate::duplicates::DuplicateGroup>)> =
+    Selector::new("file-explorer.duplicates-found");
+
+/// 对重复文件窗口中选中的文件执行操作
+pub const APPLY_DUPLICATE_ACTION: Selector<()> = Selector::new("file-explorer.apply-duplicate-action");
+
+/// 在文件列表中点击文件时修改选择（路径, 选择方式）
+pub const SELECT_FILE: Selector<(PathBuf, crate::models::SelectionMode)> = Selector::new("file-explorer.select-file");
+
+/// 计算指定文件的校验和
+pub const COMPUTE_CHECKSUMS: Selector<Vec<PathBuf>> = Selector::new("file-explorer.compute-checksums");
+
+/// 按校验和文件校验其所在目录中的文件
+pub const VERIFY_CHECKSUMS: Selector<PathBuf> = Selector::new("file-explorer.verify-checksums");
+
+/// 校验和计算完成（任务ID, 结果）
+pub const CHECKSUMS_COMPUTED: Selector<(u64, druid::im::Vector<crate::checksum::FileChecksums>)> =
+    Selector::new("file-explorer.checksums-computed");
+
+/// 校验完成（任务ID, 校验结果或校验和文件的错误）
+pub const CHECKSUMS_VERIFIED: Selector<(u64, Result<druid::im::Vector<crate::checksum::VerifyEntry>, String>)> =
+    Selector::new("file-explorer.checksums-verified");
+
+/// 打开批量重命名窗口，重命名指定的文件
+pub const BATCH_RENAME: Selector<Vec<PathBuf>> = Selector::new("file-explorer.batch-rename");
+
+/// 按批量重命名窗口中的预览执行重命名
+pub const APPLY_BATCH_RENAME: Selector<()> = Selector::new("file-explorer.apply-batch-rename");
+
+/// 批量重命名完成（任务ID, 重命名的数量或失败原因）
+pub const BATCH_RENAME_APPLIED: Selector<(u64, Result<usize, String>)> =
+    Selector::new("file-explorer.batch-rename-applied");
+
+/// 打开目录比较窗口，以指定目录作为左侧
+pub const COMPARE_DIRECTORIES: Selector<PathBuf> = Selector::new("file-explorer.compare-directories");
+
+/// 按目录比较窗口中输入的两个目录开始比较
+pub const START_DIR_COMPARE: Selector<()> = Selector::new("file-explorer.start-dir-compare");
+
+/// 目录比较完成（任务ID, 比较结果）
+pub const DIR_COMPARE_RESULT: Selector<(u64, std::sync::Arc<crate::models::FileItem>)> =
+    Selector::new("file-explorer.dir-compare-result");
+
+/// 在比较结果中选中一个条目（相对路径）
+pub const SELECT_COMPARE_ENTRY: Selector<PathBuf> = Selector::new("file-explorer.select-compare-entry");
+
+/// 将选中条目中不同的文件复制到另一侧
+pub const COPY_DIFFERENCES: Selector<crate::dir_compare::CopyDirection> =
+    Selector::new("file-explorer.copy-differences");
+
+/// 打开同步任务窗口，指定任务名称时立即预览该任务
+pub const SHOW_SYNC_JOBS: Selector<Option<String>> = Selector::new("file-explorer.show-sync-jobs");
+
+/// 在同步任务窗口中编辑指定名称的任务
+pub const SELECT_SYNC_JOB: Selector<String> = Selector::new("file-explorer.select-sync-job");
+
+/// 保存正在编辑的同步任务到设置
+pub const SAVE_SYNC_JOB: Selector<()> = Selector::new("file-explorer.save-sync-job");
+
+/// 从设置中删除正在编辑的同步任务
+pub const DELETE_SYNC_JOB: Selector<()> = Selector::new("file-explorer.delete-sync-job");
+
+/// 预览正在编辑的同步任务（不修改任何文件）
+pub const PREVIEW_SYNC: Selector<()> = Selector::new("file-explorer.preview-sync");
+
+/// 执行预览中的同步操作
+pub const RUN_SYNC: Selector<()> = Selector::new("file-explorer.run-sync");
+
+/// 同步预览完成（任务ID, 操作列表或错误）
+pub const SYNC_PLANNED: Selector<(u64, Result<druid::im::Vector<crate::sync::SyncAction>, String>)> =
+    Selector::new("file-explorer.sync-planned");
+
+/// 同步执行完成（任务ID, 执行日志）
+pub const SYNC_FINISHED: Selector<(u64, druid::im::Vector<String>)> = Selector::new("file-explorer.sync-finished");
+
+/// 开始拖动文件列表或目录树中的一项（拖动选中的文件时展开为整个选择）
+pub const START_DRAG: Selector<PathBuf> = Selector::new("file-explorer.start-drag");
+
+/// 结束拖动
+pub const END_DRAG: Selector<()> = Selector::new("file-explorer.end-drag");
+
+/// 将拖动的文件放到目录中（文件, 目标目录, 是否复制）
+pub const DROP_FILES: Selector<(Vec<PathBuf>, PathBuf, bool)> = Selector::new("file-explorer.drop-files");
+
+/// 用键盘移动焦点（移动方式, 修改选择的方式；为 None 时只移动焦点）
+pub const MOVE_FOCUS: Selector<(crate::keyboard_nav::FocusMove, Option<crate::models::SelectionMode>)> =
+    Selector::new("file-explorer.move-focus");
+
+/// 打开焦点所在的项：文件列表中进入目录或打开文件，目录树中切换到文件列表
+pub const OPEN_FOCUSED: Selector<()> = Selector::new("file-explorer.open-focused");
+
+/// 切换文件列表中焦点所在行的选中状态
+pub const TOGGLE_FOCUSED_SELECTION: Selector<()> = Selector::new("file-explorer.toggle-focused-selection");
+
+/// 在目录树和文件列表之间切换键盘焦点
+pub const SWITCH_FOCUS_PANE: Selector<()> = Selector::new("file-explorer.switch-focus-pane");
+
+/// 展开（true）或折叠（false）目录树中选中的节点
+pub const TREE_STEP: Selector<bool> = Selector::new("file-explorer.tree-step");
+
+/// 目录树中用键盘移动选择后停留了一段时间，打开选中的目录（移动序号, 目录）
+pub const OPEN_TREE_SELECTION: Selector<(u64, PathBuf)> = Selector::new("file-explorer.open-tree-selection");
+
+/// 按输入的名称开头跳转
+pub const JUMP_TO_NAME: Selector<String> = Selector::new("file-explorer.jump-to-name");
+
+/// 文件列表重新加载后恢复焦点（加载前所在的目录）
+pub const RESTORE_LIST_FOCUS: Selector<Option<PathBuf>> = Selector::new("file-explorer.restore-list-focus");
+
+/// 目录树和文件列表获得（true）或失去（false）键盘焦点
+pub const SET_PANES_FOCUSED: Selector<bool> = Selector::new("file-explorer.set-panes-focused");
+
+/// 显示键盘快捷键列表
+pub const SHOW_KEYBINDINGS: Selector<()> = Selector::new("file-explorer.show-keybindings");
+
+/// 打开命令面板（已经打开时关闭）
+pub const SHOW_COMMAND_PALETTE: Selector<()> = Selector::new("file-explorer.show-command-palette");
+
+/// 打开快速跳转（命令面板的目录搜索模式，已经打开时关闭）
+pub const SHOW_QUICK_JUMP: Selector<()> = Selector::new("file-explorer.show-quick-jump");
+
+/// 关闭命令面板（是否让目录树和文件列表重新获得键盘焦点；因点击其他控件而关闭时为 false）
+pub const CLOSE_COMMAND_PALETTE: Selector<bool> = Selector::new("file-explorer.close-command-palette");
+
+/// 让命令面板的输入框获得键盘焦点
+pub const FOCUS_COMMAND_PALETTE: Selector<()> = Selector::new("file-explorer.focus-command-palette");
+
+/// 让目录树和文件列表重新获得键盘焦点
+pub const FOCUS_PANES: Selector<()> = Selector::new("file-explorer.focus-panes");
+
+/// 执行命令面板中选择的项
+pub const RUN_PALETTE_ITEM: Selector<crate::command_palette::PaletteTarget> =
+    Selector::new("file-explorer.run-palette-item");
+
+/// 将目录加入书签
+pub const ADD_BOOKMARK: Selector<PathBuf> = Selector::new("file-explorer.add-bookmark");
+
+/// 从书签中移除目录
+pub const REMOVE_BOOKMARK: Selector<PathBuf> = Selector::new("file-explorer.remove-bookmark");
+
+/// 在后台线程中重新读取的驱动器（挂载的卷发生变化或打开驱动器概览时），替换"我的电脑"下的驱动器
+pub const VOLUMES_CHANGED: Selector<Vec<crate::models::FileItem>> = Selector::new("file-explorer.volumes-changed");
+
+/// 在右侧面板显示"我的电脑"的驱动器概览
+pub const SHOW_DRIVE_OVERVIEW: Selector<()> = Selector::new("file-explorer.show-drive-overview");
+
+/// 快速筛选的文字、写法或类型发生变化，重新筛选当前目录
+pub const QUICK_FILTER_CHANGED: Selector<()> = Selector::new("file-explorer.quick-filter-changed");
+
+/// 让快速筛选的输入框获得键盘焦点
+pub const FOCUS_QUICK_FILTER: Selector<()> = Selector::new("file-explorer.focus-quick-filter");
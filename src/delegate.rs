@@ -1,18 +1,263 @@
-use druid::{AppDelegate, Env, Command, Target, DelegateCtx, Handled};
+use druid::{AppDelegate, Env, Command, Target, DelegateCtx, Handled, Event, WindowId, WindowDesc};
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
-use crate::models::{AppState, FileItem};
+use crate::models::{AppState, FileDetail, FileItem, SelectionMode};
 use crate::file_system::{get_directory_contents, get_directory_contents_paged, build_file_tree, 
-                        get_drives, get_directory_item_count, preload_directory, invalidate_cache};
+                        get_drives, get_directory_item_count, preload_directory, invalidate_cache, clear_cache,
+                        update_cached_detail};
 use crate::commands::*;
 use crate::system;
+use crate::actions;
 use crate::{SELECT_DIRECTORY, LOAD_SUBDIRECTORIES};
 use crate::utils::format_size;
+use crate::session::{self, SessionState};
+use crate::settings;
+use crate::sync::{self, SyncState};
+use crate::batch_rename::{self, BatchRenameState};
+use crate::checksum::{self, ChecksumState};
+use crate::command_palette::{self, PaletteMode, PaletteState, PaletteTarget};
+use crate::dir_compare::{self, DirCompareState};
+use crate::disk_usage::{self, DiskUsageState};
+use crate::duplicates::{self, DuplicateState};
+use crate::file_ops;
+use crate::folder_size;
+use crate::jobs;
+use crate::keyboard_nav::{self, FocusPane, TreeStep};
+use crate::keymap::{KeyHandler, KeyOutcome};
+use crate::locations;
+use crate::permissions::{self, PermissionEdit};
+use crate::volumes;
+use crate::properties::{FileProperties, spawn_directory_summary};
+use crate::ui::{build_settings_dialog, build_properties_dialog, build_disk_usage_window,
+                build_duplicates_window, build_checksum_window, build_batch_rename_dialog,
+                build_dir_compare_window, build_sync_window, build_keybindings_dialog, end_drag, set_drag_sources};
+use crate::ui::theme;
+
+/// 目录树中用键盘移动选择后，停留多久才打开选中的目录
+const TREE_OPEN_DELAY: Duration = Duration::from_millis(250);
 
 /// 自定义AppDelegate实现，处理目录选择命令
-pub struct FileExplorerDelegate;
+pub struct FileExplorerDelegate {
+    /// 主窗口ID，用于区分主窗口与其他窗口的事件
+    main_window: WindowId,
+    /// 当前打开的属性窗口（同一时间只保留一个）
+    properties_window: Option<WindowId>,
+    /// 取消属性窗口中正在进行的目录统计
+    properties_cancel: Option<Arc<AtomicBool>>,
+    /// 当前打开的磁盘占用分析窗口（同一时间只保留一个）
+    disk_usage_window: Option<WindowId>,
+    /// 当前打开的重复文件窗口（同一时间只保留一个）
+    duplicates_window: Option<WindowId>,
+    /// 当前打开的校验和窗口（同一时间只保留一个）
+    checksum_window: Option<WindowId>,
+    /// 当前打开的批量重命名窗口（同一时间只保留一个）
+    batch_rename_window: Option<WindowId>,
+    /// 当前打开的目录比较窗口（同一时间只保留一个）
+    dir_compare_window: Option<WindowId>,
+    /// 当前打开的同步任务窗口（同一时间只保留一个）
+    sync_window: Option<WindowId>,
+    /// 当前打开的键盘快捷键窗口（同一时间只保留一个）
+    keybindings_window: Option<WindowId>,
+    /// 按照按键绑定把主窗口中的按键转换为命令
+    key_handler: KeyHandler,
+    /// 正在进行的拖放移动或复制任务，以及完成后需要重新读取的目录
+    transfer_jobs: HashMap<u64, Vec<PathBuf>>,
+    /// 目录树中键盘移动的序号，只有最后一次移动停留后才打开目录
+    tree_move_serial: u64,
+}
+
+impl FileExplorerDelegate {
+    pub fn new(main_window: WindowId) -> Self {
+        Self {
+            main_window,
+            properties_window: None,
+            properties_cancel: None,
+            disk_usage_window: None,
+            duplicates_window: None,
+            checksum_window: None,
+            batch_rename_window: None,
+            dir_compare_window: None,
+            sync_window: None,
+            keybindings_window: None,
+            key_handler: KeyHandler::default(),
+            transfer_jobs: HashMap::new(),
+            tree_move_serial: 0,
+        }
+    }
+
+    /// 用键盘在目录树中移动选择：立即移动选中的节点，停留一段时间后才打开目录
+    ///
+    /// 连续按方向键经过的目录不会逐个读取，也不会加入导航历史和访问记录。
+    fn move_tree_selection(&mut self, ctx: &mut DelegateCtx, data: &mut AppState, path: PathBuf) {
+        update_selection(&mut data.root, &path);
+        self.tree_move_serial += 1;
+
+        let serial = self.tree_move_serial;
+        let event_sink = ctx.get_external_handle();
+        std::thread::spawn(move || {
+            std::thread::sleep(TREE_OPEN_DELAY);
+            if let Err(e) = event_sink.submit_command(OPEN_TREE_SELECTION, (serial, path), Target::Auto) {
+                eprintln!("打开目录树选择失败: {:?}", e);
+            }
+        });
+    }
+
+    /// 关闭已经打开的校验和窗口，再打开新的窗口
+    fn open_checksum_window(&mut self, ctx: &mut DelegateCtx, title: String) {
+        if let Some(window) = self.checksum_window.take() {
+            ctx.submit_command(druid::commands::CLOSE_WINDOW.to(window));
+        }
+
+        let window = WindowDesc::new(build_checksum_window())
+            .title(title)
+            .window_size((720.0, 520.0));
+        self.checksum_window = Some(window.id);
+        ctx.new_window(window);
+    }
+
+    /// 将同步窗口中的任务写回设置并保存
+    fn save_sync_jobs(&mut self, data: &mut AppState) {
+        let jobs: Vec<_> = data.sync.jobs.iter().cloned().collect();
+        data.settings.sync_jobs = jobs.clone();
+        data.settings_draft.sync_jobs = jobs;
+        settings::set_current(data.settings.clone());
+        if let Err(e) = settings::save_settings(&data.settings) {
+            data.sync.notice = format!("保存设置失败: {}", e);
+        }
+    }
+
+    /// 关闭命令面板，`refocus` 为 true 时让目录树和文件列表重新获得键盘焦点
+    fn close_palette(&mut self, ctx: &mut DelegateCtx, data: &mut AppState, refocus: bool) {
+        if !data.palette.open {
+            return;
+        }
+        data.palette.open = false;
+        if refocus {
+            ctx.submit_command(FOCUS_PANES.to(self.main_window));
+        }
+    }
+
+    /// 保存书签
+    fn save_bookmarks(&mut self, data: &mut AppState) {
+        data.settings_draft.bookmarks = data.settings.bookmarks.clone();
+        settings::set_current(data.settings.clone());
+        if let Err(e) = settings::save_settings(&data.settings) {
+            eprintln!("保存设置失败: {}", e);
+        }
+    }
+
+    /// 停止属性窗口中正在进行的目录统计
+    fn cancel_properties_summary(&mut self) {
+        if let Some(cancel) = self.properties_cancel.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    }
+}
 
 impl AppDelegate<AppState> for FileExplorerDelegate {
+    fn event(
+        &mut self,
+        ctx: &mut DelegateCtx,
+        window_id: WindowId,
+        event: Event,
+        data: &mut AppState,
+        _env: &Env,
+    ) -> Option<Event> {
+        // 记录主窗口尺寸，退出时写入会话
+        if let Event::WindowSize(size) = &event {
+            if window_id == self.main_window {
+                data.window_size = (size.width, size.height);
+            }
+        }
+
+        // 主窗口中的按键先按照按键绑定处理，未绑定的按键交给控件
+        if let Event::KeyDown(key) = &event {
+            if window_id == self.main_window {
+                match self.key_handler.handle(key, data) {
+                    KeyOutcome::Command(command) => {
+                        ctx.submit_command(command);
+                        return None;
+                    }
+                    KeyOutcome::Consumed => return None,
+                    KeyOutcome::Ignored => {}
+                }
+            }
+        }
+
+        Some(event)
+    }
+
+    fn window_removed(&mut self, id: WindowId, data: &mut AppState, _env: &Env, _ctx: &mut DelegateCtx) {
+        // 属性窗口关闭时停止目录统计
+        if self.properties_window == Some(id) {
+            self.properties_window = None;
+            self.cancel_properties_summary();
+        }
+
+        // 磁盘占用分析窗口关闭时停止扫描
+        if self.disk_usage_window == Some(id) {
+            self.disk_usage_window = None;
+            if let Some(job_id) = data.disk_usage.job_id {
+                jobs::cancel(job_id);
+            }
+        }
+
+        // 重复文件窗口关闭时停止查找（已经开始的删除或替换继续进行）
+        if self.duplicates_window == Some(id) {
+            self.duplicates_window = None;
+            if let Some(job_id) = data.duplicates.scan_job_id {
+                jobs::cancel(job_id);
+            }
+        }
+
+        // 校验和窗口关闭时停止计算
+        if self.checksum_window == Some(id) {
+            self.checksum_window = None;
+            if let Some(job_id) = data.checksums.job_id {
+                jobs::cancel(job_id);
+            }
+        }
+
+        if self.keybindings_window == Some(id) {
+            self.keybindings_window = None;
+        }
+
+        // 批量重命名窗口关闭时不取消正在进行的重命名，避免只完成一部分
+        if self.batch_rename_window == Some(id) {
+            self.batch_rename_window = None;
+        }
+
+        // 目录比较窗口关闭时停止比较（已经开始的复制继续进行）
+        if self.dir_compare_window == Some(id) {
+            self.dir_compare_window = None;
+            if let Some(job_id) = data.dir_compare.job_id {
+                jobs::cancel(job_id);
+            }
+        }
+
+        // 同步任务窗口关闭时停止预览（已经开始的同步继续进行）
+        if self.sync_window == Some(id) {
+            self.sync_window = None;
+            if let Some(job_id) = data.sync.preview_job_id {
+                jobs::cancel(job_id);
+            }
+        }
+
+        // 主窗口关闭时保存会话和访问记录
+        if id == self.main_window {
+            if let Err(e) = session::save_session(&SessionState::capture(data)) {
+                eprintln!("保存会话失败: {}", e);
+            }
+            if let Err(e) = locations::save_locations() {
+                eprintln!("保存访问记录失败: {}", e);
+            }
+        }
+    }
+
     fn command(
         &mut self,
         ctx: &mut DelegateCtx,
@@ -25,10 +270,6 @@ impl AppDelegate<AppState> for FileExplorerDelegate {
             // 处理导航命令
             let dir_path = PathBuf::from(dir_path);
             
-            // 加载当前目录内容
-            let contents = get_directory_contents(&dir_path);
-            data.current_dir_files = contents;
-            
             // 异步预加载父目录和兄弟目录
             if let Some(parent) = dir_path.parent() {
                 // 预加载父目录（便于快速向上导航）
@@ -54,11 +295,7 @@ impl AppDelegate<AppState> for FileExplorerDelegate {
                 }
             }
             
-            // 更新选中路径
-            data.selected_path = Some(dir_path.clone());
-            
-            // 更新树的选中状态
-            update_selection(&mut data.root, &dir_path);
+            load_directory(data, ctx, &dir_path, History::Record);
             
             Handled::Yes
         } else if let Some(path) = cmd.get(OPEN_FILE) {
@@ -78,60 +315,69 @@ impl AppDelegate<AppState> for FileExplorerDelegate {
             // 如果选择的是一个有效目录
             if path.exists() && path.is_dir() {
                 // 将目录内容加载到右侧面板
-                data.current_dir_files = get_directory_contents(path);
-                
-                // 更新当前选中的路径
-                data.add_to_history(path.to_path_buf());
-                data.selected_path = Some(path.to_path_buf());
-                
-                // 如果是大目录，则仅加载部分内容，其余在后台加载
-                let total_count = get_directory_item_count(path);
-                
-                if total_count > 30 {
-                    println!("目录含有大量文件 ({}个)，使用分页加载，初始加载30个", total_count);
-                    
-                    // 创建一个线程安全的上下文引用，供后台线程使用
-                    let event_sink = ctx.get_external_handle();
-                    let path_clone = path.to_path_buf();
-                    
-                    // 将额外的文件加载放到后台线程，避免阻塞UI
-                    std::thread::spawn(move || {
-                        // 后台加载剩余的文件
-                        println!("后台加载更多文件...");
-                        
-                        // 等待一段时间让UI先渲染
-                        std::thread::sleep(std::time::Duration::from_millis(50));
-                        
-                        // 加载更多文件 - 使用较大数量加载所有文件
-                        let more_files = get_directory_contents_paged(&path_clone, 0, 10000);
-                        println!("后台加载完成，总共加载 {} 个文件", more_files.len());
-                        
-                        // 发送命令更新UI
-                        if let Err(e) = event_sink.submit_command(UPDATE_FILE_LIST, more_files, Target::Auto) {
-                            eprintln!("更新文件列表失败: {:?}", e);
-                        }
-                    });
-                }
+                load_directory(data, ctx, path, History::Record);
             } else {
                 // 正常加载目录内容，使用分页加载提高性能
-                data.current_dir_files = get_directory_contents_paged(path, 0, 30);
+                data.current_dir_files = get_directory_contents_paged(path, 0, data.settings.page_size);
             }
             
-            // 更新树的选中状态
-            update_selection(&mut data.root, path);
-            
             Handled::Yes
         } else if let Some(path) = cmd.get(LOAD_SUBDIRECTORIES) {
             // 处理加载子目录命令
             load_subdirectories(&mut data.root, path);
             Handled::Yes
+        } else if let Some(()) = cmd.get(SHOW_DRIVE_OVERVIEW) {
+            // 先显示已有的驱动器，后台重新读取后概览中显示最新的可用空间
+            volumes::refresh_drives(ctx.get_external_handle());
+            data.drive_overview = true;
+            Handled::Yes
+        } else if let Some(drives) = cmd.get(VOLUMES_CHANGED) {
+            replace_drives(&mut data.root, drives);
+            if let Some(path) = data.selected_path.clone() {
+                update_selection(&mut data.root, &path);
+            }
+            Handled::Yes
         } else if let Some(()) = cmd.get(RESET_CURSOR) {
             // 处理重置光标命令
             Handled::Yes
-        } else if let Some(files) = cmd.get(UPDATE_FILE_LIST) {
-            // 处理更新文件列表命令
-            println!("收到后台加载的文件列表，更新UI，文件数量: {}", files.len());
-            data.current_dir_files = files.clone();
+        } else if let Some((path, files)) = cmd.get(UPDATE_FILE_LIST) {
+            // 处理更新文件列表命令，加载期间已经离开该目录时忽略
+            if data.loading_path.as_ref() == Some(path) {
+                println!("收到后台加载的文件列表，更新UI，文件数量: {}", files.len());
+                data.loading_path = None;
+                data.quick_filter.receive_full_listing(&mut data.current_dir_files, files.clone());
+            }
+            Handled::Yes
+        } else if let Some((path, detected)) = cmd.get(FILE_TYPE_DETECTED) {
+            // 按文件内容识别出的类型，只更新仍在列表中的文件
+            let update = |detail: &mut FileDetail| {
+                if let Some(kind) = detected {
+                    detail.kind = kind.clone();
+                }
+                detail.kind_detected = true;
+            };
+            update_cached_detail(path, update);
+            data.current_dir_files.iter_mut()
+                .filter(|detail| &detail.full_path == path)
+                .for_each(update);
+            if let Some(source) = &mut data.quick_filter.source {
+                source.iter_mut()
+                    .filter(|detail| &detail.full_path == path)
+                    .for_each(update);
+            }
+            Handled::Yes
+        } else if let Some((path, size)) = cmd.get(FOLDER_SIZE_COMPUTED) {
+            // 目录大小计算完成
+            let update = |detail: &mut FileDetail| detail.folder_size = Some(*size);
+            update_cached_detail(path, update);
+            data.current_dir_files.iter_mut()
+                .filter(|detail| &detail.full_path == path)
+                .for_each(update);
+            if let Some(source) = &mut data.quick_filter.source {
+                source.iter_mut()
+                    .filter(|detail| &detail.full_path == path)
+                    .for_each(update);
+            }
             Handled::Yes
         } else if let Some(()) = cmd.get(NAVIGATE_UP) {
             // 处理上级目录导航命令
@@ -140,73 +386,780 @@ impl AppDelegate<AppState> for FileExplorerDelegate {
                     // 创建路径的可拥有拷贝
                     let parent_path = parent.to_path_buf();
                     
-                    // 从缓存获取目录内容
-                    let directory_contents = get_directory_contents(parent);
-                    
-                    // 添加到历史记录并更新当前路径
-                    data.add_to_history(parent_path.clone());
-                    data.selected_path = Some(parent_path.clone());
-                    
-                    // 更新UI
-                    data.current_dir_files = directory_contents;
-                    
-                    // 更新树的选中状态
-                    update_selection(&mut data.root, &parent_path);
+                    // 加载上级目录内容并更新UI
+                    load_directory(data, ctx, &parent_path, History::Record);
                 }
             }
             Handled::Yes
         } else if let Some(()) = cmd.get(NAVIGATE_BACK) {
             // 处理后退命令
             if let Some(prev_path) = data.navigate_back() {
-                // 获取历史记录中的上一个路径内容
-                let contents = get_directory_contents(&prev_path);
-                
-                // 更新UI
-                data.current_dir_files = contents;
-                data.selected_path = Some(prev_path.clone());
-                
-                // 更新树的选中状态
-                update_selection(&mut data.root, &prev_path);
+                // 加载历史记录中的上一个路径内容并更新UI
+                load_directory(data, ctx, &prev_path, History::Keep);
+            }
+            Handled::Yes
+        } else if let Some(index) = cmd.get(NAVIGATE_HISTORY) {
+            // 跳到历史下拉菜单中选择的目录
+            if let Some(path) = data.navigate_to_history_index(*index) {
+                load_directory(data, ctx, &path, History::Keep);
             }
             Handled::Yes
         } else if let Some(()) = cmd.get(NAVIGATE_FORWARD) {
             // 处理前进命令
             if let Some(next_path) = data.navigate_forward() {
-                // 获取历史记录中的下一个路径内容
-                let contents = get_directory_contents(&next_path);
-                
-                // 更新UI
-                data.current_dir_files = contents;
-                data.selected_path = Some(next_path.clone());
-                
-                // 更新树的选中状态
-                update_selection(&mut data.root, &next_path);
+                // 加载历史记录中的下一个路径内容并更新UI
+                load_directory(data, ctx, &next_path, History::Keep);
             }
             Handled::Yes
         } else if let Some(()) = cmd.get(REFRESH_DIRECTORY) {
             // 处理刷新目录命令
             if let Some(current_path) = data.selected_path.clone() {
-                // 清除目录缓存
+                // 清除目录缓存和其中的目录大小缓存
                 invalidate_cache(&current_path);
+                folder_size::invalidate(&current_path);
                 
                 // 重新加载目录内容
-                let contents = get_directory_contents(&current_path);
-                data.current_dir_files = contents;
+                reload_directory(data, ctx, &current_path);
+                data.current_space = system::get_drive_space(&current_path);
             }
             Handled::Yes
         } else if let Some(()) = cmd.get(NAVIGATE_HOME) {
             // 处理导航到主目录命令
             if let Some(home_dir) = dirs::home_dir() {
-                // 获取主目录内容
-                let contents = get_directory_contents(&home_dir);
-                
-                // 更新UI
-                data.current_dir_files = contents;
-                data.add_to_history(home_dir.clone());
-                data.selected_path = Some(home_dir.clone());
-                
-                // 更新树的选中状态
-                update_selection(&mut data.root, &home_dir);
+                // 加载主目录内容并更新UI
+                load_directory(data, ctx, &home_dir, History::Record);
+            }
+            Handled::Yes
+        } else if let Some(()) = cmd.get(TOGGLE_HIDDEN_FILES) {
+            // 切换隐藏文件的显示，并保存到设置
+            data.settings.show_hidden = !data.settings.show_hidden;
+            data.settings_draft.show_hidden = data.settings.show_hidden;
+            settings::set_current(data.settings.clone());
+            if let Err(e) = settings::save_settings(&data.settings) {
+                eprintln!("保存设置失败: {}", e);
+            }
+            println!("显示隐藏文件: {}", data.settings.show_hidden);
+            
+            // 可见性变化后，目录列表和目录树都需要重新加载
+            clear_cache();
+            if let Some(current_path) = data.selected_path.clone() {
+                reload_directory(data, ctx, &current_path);
+            }
+            refresh_loaded_subdirectories(&mut data.root);
+            Handled::Yes
+        } else if let Some(()) = cmd.get(TOGGLE_FOLDER_SIZES) {
+            // 切换是否计算文件夹大小，并保存到设置
+            data.settings.compute_folder_sizes = !data.settings.compute_folder_sizes;
+            data.settings_draft.compute_folder_sizes = data.settings.compute_folder_sizes;
+            settings::set_current(data.settings.clone());
+            if let Err(e) = settings::save_settings(&data.settings) {
+                eprintln!("保存设置失败: {}", e);
+            }
+
+            // 重新加载列表，使可见的目录行请求计算大小
+            clear_cache();
+            if let Some(current_path) = data.selected_path.clone() {
+                reload_directory(data, ctx, &current_path);
+            }
+            Handled::Yes
+        } else if let Some(()) = cmd.get(QUICK_FILTER_CHANGED) {
+            data.quick_filter.refresh(&mut data.current_dir_files);
+            Handled::Yes
+        } else if let Some(name) = cmd.get(SET_THEME) {
+            // 切换主题，无需重启即可生效
+            match theme::load_theme(name) {
+                Ok(new_theme) => {
+                    println!("切换主题: {}", name);
+                    theme::set_active(&new_theme);
+                    data.theme = new_theme;
+                    data.settings.theme = name.clone();
+                    data.settings_draft.theme = name.clone();
+                    settings::set_current(data.settings.clone());
+                    if let Err(e) = settings::save_settings(&data.settings) {
+                        eprintln!("保存设置失败: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("切换主题失败: {}", e),
+            }
+            Handled::Yes
+        } else if let Some(()) = cmd.get(SHOW_SETTINGS) {
+            // 打开设置窗口，编辑的是当前设置的副本
+            data.settings_draft = data.settings.clone();
+            data.settings_notice = String::new();
+            ctx.new_window(
+                WindowDesc::new(build_settings_dialog())
+                    .title("设置")
+                    .window_size((480.0, 440.0))
+                    .resizable(false)
+            );
+            Handled::Yes
+        } else if let Some(()) = cmd.get(SHOW_KEYBINDINGS) {
+            if let Some(window) = self.keybindings_window.take() {
+                ctx.submit_command(druid::commands::CLOSE_WINDOW.to(window));
+            }
+
+            let window = WindowDesc::new(build_keybindings_dialog())
+                .title("键盘快捷键")
+                .window_size((620.0, 560.0));
+            self.keybindings_window = Some(window.id);
+            ctx.new_window(window);
+            Handled::Yes
+        } else if let Some(()) = cmd.get(SHOW_COMMAND_PALETTE) {
+            if data.palette.open && data.palette.mode == PaletteMode::Commands {
+                self.close_palette(ctx, data, true);
+            } else {
+                data.palette = PaletteState::open_commands(command_palette::candidates(data));
+                ctx.submit_command(FOCUS_COMMAND_PALETTE.to(self.main_window));
+            }
+            Handled::Yes
+        } else if let Some(()) = cmd.get(SHOW_QUICK_JUMP) {
+            if data.palette.open && data.palette.mode == PaletteMode::Jump {
+                self.close_palette(ctx, data, true);
+            } else {
+                data.palette = PaletteState::open_jump();
+                ctx.submit_command(FOCUS_COMMAND_PALETTE.to(self.main_window));
+            }
+            Handled::Yes
+        } else if let Some(refocus) = cmd.get(CLOSE_COMMAND_PALETTE) {
+            self.close_palette(ctx, data, *refocus);
+            Handled::Yes
+        } else if let Some(target) = cmd.get(RUN_PALETTE_ITEM) {
+            self.close_palette(ctx, data, true);
+            match target {
+                PaletteTarget::Action(id) => {
+                    if let Some(command) = actions::find(id).and_then(|action| (action.command)(data)) {
+                        ctx.submit_command(command);
+                    }
+                }
+                PaletteTarget::Directory(path) if path.is_dir() => ctx.submit_command(SELECT_DIRECTORY.with(path.clone())),
+                PaletteTarget::Directory(path) => data.status_notice = format!("目录已不存在: {}", path.display()),
+            }
+            Handled::Yes
+        } else if let Some(path) = cmd.get(ADD_BOOKMARK) {
+            if !data.settings.bookmarks.contains(path) {
+                data.settings.bookmarks.push(path.clone());
+                self.save_bookmarks(data);
+            }
+            Handled::Yes
+        } else if let Some(path) = cmd.get(REMOVE_BOOKMARK) {
+            data.settings.bookmarks.retain(|bookmark| bookmark != path);
+            self.save_bookmarks(data);
+            Handled::Yes
+        } else if let Some(path) = cmd.get(SHOW_PROPERTIES) {
+            // 关闭已经打开的属性窗口，只显示最新选择的文件
+            self.cancel_properties_summary();
+            if let Some(window) = self.properties_window.take() {
+                ctx.submit_command(druid::commands::CLOSE_WINDOW.to(window));
+            }
+
+            data.properties = FileProperties::load(path);
+            data.permission_edit = PermissionEdit::from_mode(data.properties.mode);
+
+            // 目录在后台递归统计总大小和文件数量
+            if data.properties.is_dir {
+                let cancel = Arc::new(AtomicBool::new(false));
+                spawn_directory_summary(path.clone(), cancel.clone(), ctx.get_external_handle());
+                self.properties_cancel = Some(cancel);
+            }
+
+            let window = WindowDesc::new(build_properties_dialog())
+                .title(format!("{} 属性", data.properties.name))
+                .window_size((560.0, 760.0));
+            self.properties_window = Some(window.id);
+            ctx.new_window(window);
+            Handled::Yes
+        } else if let Some((path, summary)) = cmd.get(DIRECTORY_SUMMARY_PROGRESS) {
+            // 只接受当前属性窗口对应目录的统计结果
+            if &data.properties.path == path {
+                data.properties.summary = *summary;
+                if summary.finished {
+                    self.properties_cancel = None;
+                }
+            }
+            Handled::Yes
+        } else if let Some(()) = cmd.get(APPLY_PERMISSIONS) {
+            // 在后台任务中修改权限，失败的路径在属性窗口中列出
+            let running = data.jobs.iter()
+                .any(|job| Some(job.id) == data.permission_edit.job_id && !job.finished);
+            if running {
+                data.permission_edit.notice = "上一次修改仍在进行中".to_string();
+                return Handled::Yes;
+            }
+
+            match data.permission_edit.to_change() {
+                Ok(change) => {
+                    let path = data.properties.path.clone();
+                    let title = format!("修改权限 {}", data.properties.name);
+                    let id = jobs::spawn(title, ctx.get_external_handle(), move |job| {
+                        permissions::apply(&path, &change, job);
+                    });
+                    data.permission_edit.job_id = Some(id);
+                    data.permission_edit.notice = String::new();
+                }
+                Err(e) => data.permission_edit.notice = e,
+            }
+            Handled::Yes
+        } else if let Some(status) = cmd.get(JOB_PROGRESS) {
+            jobs::update_jobs(&mut data.jobs, status);
+
+            // 权限修改完成后重新读取属性
+            if status.finished && data.permission_edit.job_id == Some(status.id) {
+                let summary = data.properties.summary;
+                data.properties = FileProperties::load(&data.properties.path);
+                data.properties.summary = summary;
+                data.permission_edit.set_mode(data.properties.mode);
+            }
+
+            // 重复文件处理完成后从列表中移除已处理的文件，并刷新文件列表
+            if status.finished && data.duplicates.apply_job_id == Some(status.id) {
+                let failed = status.failures.iter().map(|failure| failure.path.clone()).collect();
+                data.duplicates.remove_processed(&failed, status.cancelled);
+                ctx.submit_command(REFRESH_DIRECTORY);
+            }
+
+            // 复制完成后重新比较，并刷新文件列表
+            if status.finished && data.dir_compare.copy_job_id == Some(status.id) {
+                ctx.submit_command(START_DIR_COMPARE);
+                ctx.submit_command(REFRESH_DIRECTORY);
+            }
+
+            // 拖放的移动或复制完成后刷新文件列表和目录树
+            let finished_transfer = if status.finished { self.transfer_jobs.remove(&status.id) } else { None };
+            if let Some(affected) = finished_transfer {
+                // 失败时在状态栏提示第一个失败的路径
+                data.status_notice = match status.failures.iter().next() {
+                    Some(failure) => format!("{} · {}: {}", status.summary(), failure.path.display(), failure.error),
+                    None => String::new(),
+                };
+                for dir in &affected {
+                    invalidate_cache(dir);
+                    folder_size::invalidate(dir);
+                }
+                refresh_loaded_subdirectories(&mut data.root);
+                ctx.submit_command(REFRESH_DIRECTORY);
+            }
+            Handled::Yes
+        } else if let Some(path) = cmd.get(ANALYZE_DISK_USAGE) {
+            // 停止上一次分析并关闭其窗口
+            if let Some(job_id) = data.disk_usage.job_id {
+                jobs::cancel(job_id);
+            }
+            if let Some(window) = self.disk_usage_window.take() {
+                ctx.submit_command(druid::commands::CLOSE_WINDOW.to(window));
+            }
+
+            let root = path.clone();
+            let title = format!("分析磁盘占用 {}", path.display());
+            let id = jobs::spawn(title, ctx.get_external_handle(), move |job| {
+                if let Some(report) = disk_usage::scan(&root, job) {
+                    let id = job.id();
+                    job.submit(DISK_USAGE_RESULT, (id, Arc::new(report)));
+                }
+            });
+            data.disk_usage = DiskUsageState::start(path, id);
+
+            let window = WindowDesc::new(build_disk_usage_window())
+                .title(format!("磁盘占用分析 - {}", path.display()))
+                .window_size((960.0, 640.0));
+            self.disk_usage_window = Some(window.id);
+            ctx.new_window(window);
+            Handled::Yes
+        } else if let Some((id, report)) = cmd.get(DISK_USAGE_RESULT) {
+            // 只接受当前分析任务的结果
+            if data.disk_usage.job_id == Some(*id) {
+                data.disk_usage.set_report(report.clone());
+            }
+            Handled::Yes
+        } else if let Some(path) = cmd.get(FIND_DUPLICATES) {
+            // 停止上一次查找并关闭其窗口
+            if let Some(job_id) = data.duplicates.scan_job_id {
+                jobs::cancel(job_id);
+            }
+            if let Some(window) = self.duplicates_window.take() {
+                ctx.submit_command(druid::commands::CLOSE_WINDOW.to(window));
+            }
+
+            let root = path.clone();
+            let title = format!("查找重复文件 {}", path.display());
+            let id = jobs::spawn(title, ctx.get_external_handle(), move |job| {
+                if let Some(groups) = duplicates::find_duplicates(&root, job) {
+                    let id = job.id();
+                    job.submit(DUPLICATES_FOUND, (id, groups));
+                }
+            });
+            data.duplicates = DuplicateState::start(path, id);
+
+            let window = WindowDesc::new(build_duplicates_window())
+                .title(format!("重复文件 - {}", path.display()))
+                .window_size((820.0, 640.0));
+            self.duplicates_window = Some(window.id);
+            ctx.new_window(window);
+            Handled::Yes
+        } else if let Some((id, groups)) = cmd.get(DUPLICATES_FOUND) {
+            if data.duplicates.scan_job_id == Some(*id) {
+                data.duplicates.groups = groups.clone();
+                data.duplicates.finished = true;
+            }
+            Handled::Yes
+        } else if let Some(()) = cmd.get(APPLY_DUPLICATE_ACTION) {
+            // 在后台任务中删除或替换选中的文件
+            let running = data.jobs.iter()
+                .any(|job| Some(job.id) == data.duplicates.apply_job_id && !job.finished);
+            if running {
+                data.duplicates.notice = "上一次操作仍在进行中".to_string();
+                return Handled::Yes;
+            }
+
+            match data.duplicates.validate_selection() {
+                Ok(()) => {
+                    let groups: Vec<_> = data.duplicates.groups.iter().cloned().collect();
+                    let action = data.duplicates.action;
+                    let title = match action {
+                        duplicates::DuplicateAction::Trash => "将重复文件移到回收站",
+                        duplicates::DuplicateAction::HardLink => "将重复文件替换为硬链接",
+                    };
+                    let id = jobs::spawn(title, ctx.get_external_handle(), move |job| {
+                        duplicates::apply(&groups, action, job);
+                    });
+                    data.duplicates.apply_job_id = Some(id);
+                    data.duplicates.notice = String::new();
+                }
+                Err(e) => data.duplicates.notice = e,
+            }
+            Handled::Yes
+        } else if let Some((path, mode)) = cmd.get(SELECT_FILE) {
+            data.select_file(path, *mode);
+            Handled::Yes
+        } else if let Some((movement, mode)) = cmd.get(MOVE_FOCUS) {
+            match data.focus_pane {
+                FocusPane::List => data.move_list_focus(*movement, *mode),
+                FocusPane::Tree => {
+                    if let Some(path) = keyboard_nav::tree_move(&data.root, *movement) {
+                        self.move_tree_selection(ctx, data, path);
+                    }
+                }
+            }
+            Handled::Yes
+        } else if let Some(prefix) = cmd.get(JUMP_TO_NAME) {
+            match data.focus_pane {
+                FocusPane::List => data.jump_to_name(prefix),
+                FocusPane::Tree => {
+                    if let Some(path) = keyboard_nav::tree_find(&data.root, prefix) {
+                        self.move_tree_selection(ctx, data, path);
+                    }
+                }
+            }
+            Handled::Yes
+        } else if let Some(expand) = cmd.get(TREE_STEP) {
+            match keyboard_nav::tree_step(&mut data.root, *expand) {
+                TreeStep::Load(path) => ctx.submit_command(LOAD_SUBDIRECTORIES.with(path)),
+                TreeStep::Select(path) => self.move_tree_selection(ctx, data, path),
+                TreeStep::Done => {}
+            }
+            Handled::Yes
+        } else if let Some((serial, path)) = cmd.get(OPEN_TREE_SELECTION) {
+            // 之后又移动过选择，或者已经用其他方式打开了别的目录时忽略
+            let settled = *serial == self.tree_move_serial
+                && keyboard_nav::tree_selected(&data.root).as_ref() == Some(path);
+            if settled && data.selected_path.as_ref() != Some(path) {
+                ctx.submit_command(SELECT_DIRECTORY.with(path.clone()));
+            }
+            Handled::Yes
+        } else if let Some(focused) = cmd.get(SET_PANES_FOCUSED) {
+            data.panes_focused = *focused;
+            Handled::Yes
+        } else if let Some(()) = cmd.get(OPEN_FOCUSED) {
+            if data.focus_pane == FocusPane::Tree {
+                // 回车切换到文件列表，选中的目录还没有打开时立即打开
+                if let Some(path) = keyboard_nav::tree_selected(&data.root) {
+                    if data.selected_path.as_ref() != Some(&path) {
+                        ctx.submit_command(SELECT_DIRECTORY.with(path));
+                    }
+                }
+                data.focus_pane = FocusPane::List;
+            } else if let Some(file) = data.focused_index().map(|index| data.current_dir_files[index].clone()) {
+                if file.kind.is_directory() {
+                    ctx.submit_command(NAVIGATE_TO.with(file.full_path));
+                } else {
+                    ctx.submit_command(OPEN_FILE.with(file.full_path));
+                }
+            }
+            Handled::Yes
+        } else if let Some(()) = cmd.get(TOGGLE_FOCUSED_SELECTION) {
+            if data.focus_pane == FocusPane::List {
+                if let Some(path) = data.focused_file.clone() {
+                    data.select_file(&path, SelectionMode::Toggle);
+                }
+            }
+            Handled::Yes
+        } else if let Some(()) = cmd.get(SWITCH_FOCUS_PANE) {
+            data.focus_pane = match data.focus_pane {
+                FocusPane::Tree => FocusPane::List,
+                FocusPane::List => FocusPane::Tree,
+            };
+            Handled::Yes
+        } else if let Some(previous_dir) = cmd.get(RESTORE_LIST_FOCUS) {
+            data.restore_list_focus(previous_dir.as_deref());
+            Handled::Yes
+        } else if let Some(paths) = cmd.get(COMPUTE_CHECKSUMS) {
+            if paths.is_empty() {
+                return Handled::Yes;
+            }
+            if let Some(job_id) = data.checksums.job_id {
+                jobs::cancel(job_id);
+            }
+
+            let paths = paths.clone();
+            let title = if paths.len() == 1 {
+                format!("计算校验和 {}", paths[0].display())
+            } else {
+                format!("计算 {} 个文件的校验和", paths.len())
+            };
+            let id = jobs::spawn(title, ctx.get_external_handle(), move |job| {
+                let results = checksum::compute_all(&paths, job);
+                let id = job.id();
+                job.submit(CHECKSUMS_COMPUTED, (id, results));
+            });
+            data.checksums = ChecksumState { job_id: Some(id), ..Default::default() };
+            self.open_checksum_window(ctx, "校验和".to_string());
+            Handled::Yes
+        } else if let Some(path) = cmd.get(VERIFY_CHECKSUMS) {
+            if let Some(job_id) = data.checksums.job_id {
+                jobs::cancel(job_id);
+            }
+            let list_path = path.clone();
+            let id = jobs::spawn(format!("校验 {}", path.display()), ctx.get_external_handle(), move |job| {
+                let result = checksum::verify(&list_path, job);
+                let id = job.id();
+                job.submit(CHECKSUMS_VERIFIED, (id, result));
+            });
+            data.checksums = ChecksumState {
+                job_id: Some(id),
+                list_path: Some(path.clone()),
+                ..Default::default()
+            };
+            self.open_checksum_window(ctx, format!("校验 - {}", path.display()));
+            Handled::Yes
+        } else if let Some((id, results)) = cmd.get(CHECKSUMS_COMPUTED) {
+            if data.checksums.job_id == Some(*id) {
+                data.checksums.results = results.clone();
+            }
+            Handled::Yes
+        } else if let Some((id, result)) = cmd.get(CHECKSUMS_VERIFIED) {
+            if data.checksums.job_id == Some(*id) {
+                match result {
+                    Ok(entries) => data.checksums.set_verify_entries(entries),
+                    Err(e) => data.checksums.notice = e.clone(),
+                }
+            }
+            Handled::Yes
+        } else if let Some(paths) = cmd.get(BATCH_RENAME) {
+            if paths.is_empty() {
+                return Handled::Yes;
+            }
+            if let Some(window) = self.batch_rename_window.take() {
+                ctx.submit_command(druid::commands::CLOSE_WINDOW.to(window));
+            }
+
+            data.batch_rename = BatchRenameState::new(paths.clone());
+            let window = WindowDesc::new(build_batch_rename_dialog())
+                .title(format!("批量重命名 {} 个文件", paths.len()))
+                .window_size((760.0, 600.0));
+            self.batch_rename_window = Some(window.id);
+            ctx.new_window(window);
+            Handled::Yes
+        } else if let Some(()) = cmd.get(APPLY_BATCH_RENAME) {
+            let running = data.jobs.iter()
+                .any(|job| Some(job.id) == data.batch_rename.job_id && !job.finished);
+            if running || !data.batch_rename.can_apply() {
+                return Handled::Yes;
+            }
+
+            // 执行前按当前的文件系统重新检查冲突
+            data.batch_rename.update_preview();
+            if !data.batch_rename.can_apply() {
+                return Handled::Yes;
+            }
+
+            let items: Vec<_> = data.batch_rename.preview.iter().cloned().collect();
+            let title = format!("批量重命名 {} 个文件", items.len());
+            let id = jobs::spawn(title, ctx.get_external_handle(), move |job| {
+                let result = batch_rename::apply(&items);
+                if let Ok(renamed) = result {
+                    job.set_processed(renamed as u64);
+                }
+                let id = job.id();
+                job.submit(BATCH_RENAME_APPLIED, (id, result));
+            });
+            data.batch_rename.job_id = Some(id);
+            Handled::Yes
+        } else if let Some((id, result)) = cmd.get(BATCH_RENAME_APPLIED) {
+            if data.batch_rename.job_id == Some(*id) {
+                match result {
+                    Ok(renamed) => data.batch_rename.finish(*renamed),
+                    Err(e) => data.batch_rename.notice = e.clone(),
+                }
+                ctx.submit_command(REFRESH_DIRECTORY);
+            }
+            Handled::Yes
+        } else if let Some(path) = cmd.get(COMPARE_DIRECTORIES) {
+            if let Some(job_id) = data.dir_compare.job_id {
+                jobs::cancel(job_id);
+            }
+            if let Some(window) = self.dir_compare_window.take() {
+                ctx.submit_command(druid::commands::CLOSE_WINDOW.to(window));
+            }
+
+            data.dir_compare = DirCompareState::with_left(path);
+            let window = WindowDesc::new(build_dir_compare_window())
+                .title("比较目录")
+                .window_size((900.0, 680.0));
+            self.dir_compare_window = Some(window.id);
+            ctx.new_window(window);
+            Handled::Yes
+        } else if let Some(()) = cmd.get(START_DIR_COMPARE) {
+            let (left, right) = match data.dir_compare.validate() {
+                Ok(paths) => paths,
+                Err(e) => {
+                    data.dir_compare.notice = e;
+                    return Handled::Yes;
+                }
+            };
+            if let Some(job_id) = data.dir_compare.job_id {
+                jobs::cancel(job_id);
+            }
+
+            let mode = data.dir_compare.mode;
+            let (job_left, job_right) = (left.clone(), right.clone());
+            let title = format!("比较 {} 和 {}", left.display(), right.display());
+            let id = jobs::spawn(title, ctx.get_external_handle(), move |job| {
+                if let Some(result) = dir_compare::compare(&job_left, &job_right, mode, job) {
+                    let id = job.id();
+                    job.submit(DIR_COMPARE_RESULT, (id, Arc::new(result)));
+                }
+            });
+            data.dir_compare.start(left, right, id);
+            Handled::Yes
+        } else if let Some((id, result)) = cmd.get(DIR_COMPARE_RESULT) {
+            if data.dir_compare.job_id == Some(*id) {
+                data.dir_compare.set_result(result.clone());
+            }
+            Handled::Yes
+        } else if let Some(path) = cmd.get(SELECT_COMPARE_ENTRY) {
+            data.dir_compare.select(path);
+            Handled::Yes
+        } else if let Some(direction) = cmd.get(COPY_DIFFERENCES) {
+            let running = data.jobs.iter()
+                .any(|job| Some(job.id) == data.dir_compare.copy_job_id && !job.finished);
+            if running {
+                data.dir_compare.notice = "上一次复制仍在进行中".to_string();
+                return Handled::Yes;
+            }
+            let item = match data.dir_compare.selected_item() {
+                Some(item) => item.clone(),
+                None => return Handled::Yes,
+            };
+
+            let direction = *direction;
+            let left = data.dir_compare.compared_left.clone();
+            let right = data.dir_compare.compared_right.clone();
+            let title = match direction {
+                dir_compare::CopyDirection::LeftToRight => "将不同的文件复制到右侧",
+                dir_compare::CopyDirection::RightToLeft => "将不同的文件复制到左侧",
+            };
+            let id = jobs::spawn(title, ctx.get_external_handle(), move |job| {
+                dir_compare::copy_differences(&item, &left, &right, direction, job);
+            });
+            data.dir_compare.copy_job_id = Some(id);
+            data.dir_compare.notice = String::new();
+            Handled::Yes
+        } else if let Some(name) = cmd.get(SHOW_SYNC_JOBS) {
+            if let Some(window) = self.sync_window.take() {
+                ctx.submit_command(druid::commands::CLOSE_WINDOW.to(window));
+            }
+
+            data.sync = SyncState::new(&data.settings.sync_jobs, name.as_deref());
+            let window = WindowDesc::new(build_sync_window())
+                .title("同步任务")
+                .window_size((900.0, 640.0));
+            self.sync_window = Some(window.id);
+            ctx.new_window(window);
+            if name.is_some() {
+                ctx.submit_command(PREVIEW_SYNC);
+            }
+            Handled::Yes
+        } else if let Some(name) = cmd.get(SELECT_SYNC_JOB) {
+            data.sync.select(name);
+            Handled::Yes
+        } else if let Some(()) = cmd.get(SAVE_SYNC_JOB) {
+            match data.sync.save_draft() {
+                Ok(()) => {
+                    data.sync.notice = "任务已保存".to_string();
+                    self.save_sync_jobs(data);
+                }
+                Err(e) => data.sync.notice = e,
+            }
+            Handled::Yes
+        } else if let Some(()) = cmd.get(DELETE_SYNC_JOB) {
+            data.sync.delete_selected();
+            self.save_sync_jobs(data);
+            Handled::Yes
+        } else if let Some(()) = cmd.get(PREVIEW_SYNC) {
+            let job = match data.sync.draft.to_job() {
+                Ok(job) => job,
+                Err(e) => {
+                    data.sync.notice = e;
+                    return Handled::Yes;
+                }
+            };
+            let running = data.jobs.iter()
+                .any(|status| Some(status.id) == data.sync.run_job_id && !status.finished);
+            if running {
+                data.sync.notice = "同步仍在进行中，完成后再预览".to_string();
+                return Handled::Yes;
+            }
+            if let Some(job_id) = data.sync.preview_job_id {
+                jobs::cancel(job_id);
+            }
+
+            let planned = job.clone();
+            let id = jobs::spawn(format!("预览同步 {}", job.name), ctx.get_external_handle(), move |context| {
+                if let Some(result) = sync::plan(&planned, context) {
+                    let id = context.id();
+                    context.submit(SYNC_PLANNED, (id, result));
+                }
+            });
+            data.sync.preview_job_id = Some(id);
+            data.sync.run_job_id = None;
+            data.sync.previewing = Some(job);
+            data.sync.planned_for = None;
+            data.sync.actions = druid::im::Vector::new();
+            data.sync.log = druid::im::Vector::new();
+            data.sync.notice = String::new();
+            Handled::Yes
+        } else if let Some((id, result)) = cmd.get(SYNC_PLANNED) {
+            if data.sync.preview_job_id == Some(*id) {
+                match result {
+                    Ok(actions) => {
+                        data.sync.actions = actions.clone();
+                        data.sync.planned_for = data.sync.previewing.take();
+                        if actions.is_empty() {
+                            data.sync.notice = "目标已是最新，无需同步".to_string();
+                        }
+                    }
+                    Err(e) => data.sync.notice = e.clone(),
+                }
+            }
+            Handled::Yes
+        } else if let Some(()) = cmd.get(RUN_SYNC) {
+            let running = data.jobs.iter()
+                .any(|status| Some(status.id) == data.sync.run_job_id && !status.finished);
+            if running {
+                data.sync.notice = "上一次同步仍在进行中".to_string();
+                return Handled::Yes;
+            }
+            if !data.sync.can_run() || data.sync.actions.is_empty() {
+                return Handled::Yes;
+            }
+            let job = match data.sync.planned_for.take() {
+                Some(job) => job,
+                None => return Handled::Yes,
+            };
+
+            // 执行的是预览中列出的操作，执行后需要重新预览才能再次执行
+            let actions: Vec<_> = data.sync.actions.iter().cloned().collect();
+            let id = jobs::spawn(format!("同步 {}", job.name), ctx.get_external_handle(), move |context| {
+                let log = sync::execute(&job, &actions, context);
+                let id = context.id();
+                context.submit(SYNC_FINISHED, (id, log));
+            });
+            data.sync.run_job_id = Some(id);
+            data.sync.log = druid::im::Vector::unit("正在同步...".to_string());
+            Handled::Yes
+        } else if let Some((id, log)) = cmd.get(SYNC_FINISHED) {
+            if data.sync.run_job_id == Some(*id) {
+                data.sync.log = log.clone();
+            }
+            ctx.submit_command(REFRESH_DIRECTORY);
+            Handled::Yes
+        } else if let Some(path) = cmd.get(START_DRAG) {
+            // 拖动选中的文件时拖动整个选择
+            set_drag_sources(data.selection_for(path));
+            Handled::Yes
+        } else if let Some(()) = cmd.get(END_DRAG) {
+            end_drag();
+            Handled::Yes
+        } else if let Some((sources, target, copy)) = cmd.get(DROP_FILES) {
+            // 放下时按当前的文件系统重新检查
+            if let Err(e) = file_ops::validate_drop(sources, target) {
+                data.status_notice = format!("无法放到 {}: {}", target.display(), e);
+                return Handled::Yes;
+            }
+            data.status_notice = String::new();
+
+            // 完成后目标目录和（移动时）原来所在的目录都需要重新读取
+            let mut affected = vec![target.clone()];
+            if !copy {
+                for parent in sources.iter().filter_map(|source| source.parent()) {
+                    if !affected.iter().any(|dir| dir == parent) {
+                        affected.push(parent.to_path_buf());
+                    }
+                }
+            }
+
+            let (sources, target, copy) = (sources.clone(), target.clone(), *copy);
+            let title = format!(
+                "{} {} 项到 {}",
+                if copy { "复制" } else { "移动" },
+                sources.len(),
+                target.display(),
+            );
+            let id = jobs::spawn(title, ctx.get_external_handle(), move |job| {
+                file_ops::transfer(&sources, &target, copy, job);
+            });
+            self.transfer_jobs.insert(id, affected);
+            Handled::Yes
+        } else if let Some(id) = cmd.get(CANCEL_JOB) {
+            jobs::cancel(*id);
+            Handled::Yes
+        } else if let Some(()) = cmd.get(SAVE_SETTINGS) {
+            // 校验并保存设置
+            let problems = data.settings_draft.validate();
+            if !problems.is_empty() {
+                data.settings_notice = problems.join("\n");
+                return Handled::Yes;
+            }
+            
+            // 主题变化时先确认新主题可以加载
+            if data.settings_draft.theme != data.theme.name {
+                match theme::load_theme(&data.settings_draft.theme) {
+                    Ok(new_theme) => {
+                        theme::set_active(&new_theme);
+                        data.theme = new_theme;
+                    }
+                    Err(e) => {
+                        data.settings_notice = e;
+                        return Handled::Yes;
+                    }
+                }
+            }
+            
+            let new_settings = data.settings_draft.clone();
+            match settings::save_settings(&new_settings) {
+                Ok(path) => data.settings_notice = format!("已保存到 {}", path.display()),
+                Err(e) => data.settings_notice = format!("保存设置失败: {}", e),
+            }
+            
+            // 页面大小、隐藏文件和时间格式都会影响列表内容，需要重新加载
+            let visibility_changed = new_settings.show_hidden != data.settings.show_hidden;
+            settings::set_current(new_settings.clone());
+            data.settings = new_settings;
+            clear_cache();
+            if let Some(current_path) = data.selected_path.clone() {
+                reload_directory(data, ctx, &current_path);
+            }
+            if visibility_changed {
+                refresh_loaded_subdirectories(&mut data.root);
             }
             Handled::Yes
         } else {
@@ -216,26 +1169,122 @@ impl AppDelegate<AppState> for FileExplorerDelegate {
 }
 
 /// 递归更新树的选中状态，确保选中的路径在树中高亮显示
+///
+/// 遍历所有已加载的节点，清除之前选中的节点；选中路径的上级目录被展开，
+/// "最近访问"分组中的目录也会被选中，但不会因此展开。
 pub fn update_selection(item: &mut FileItem, selected_path: &std::path::Path) {
-    // 清除当前选中状态
-    item.is_selected = false;
-    
-    // 检查当前项是否匹配
-    if item.path == selected_path {
-        item.is_selected = true;
+    select_in_tree(item, selected_path, true);
+}
+
+fn select_in_tree(item: &mut FileItem, selected_path: &std::path::Path, in_tree: bool) {
+    // 根节点、"我的电脑"等分组节点不对应真实目录，只向下查找
+    let is_virtual = keyboard_nav::is_virtual(item);
+    item.is_selected = in_tree && !is_virtual && item.path == selected_path;
+
+    // 检查选中路径是否是当前项的子孙路径，确保当前目录处于展开状态
+    if in_tree && !is_virtual && !item.is_selected && selected_path.starts_with(&item.path) {
+        item.is_expanded = true;
+    }
+
+    // "最近访问"分组中的目录只是快捷方式，不选中也不展开，选中的只有树中原来位置的节点
+    let in_tree = in_tree && !locations::is_recent_section(item);
+    for child in &mut item.children {
+        select_in_tree(child, selected_path, in_tree);
+    }
+}
+
+/// 切换目录时如何处理导航历史
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum History {
+    /// 新的导航：加入历史记录，丢弃当前位置之后的记录
+    Record,
+    /// 在历史记录中移动（后退、前进、跳到某一项）：历史位置已经更新，不新增记录
+    Keep,
+}
+
+/// 切换到目录后更新当前路径、导航历史、访问记录和目录树的选中状态（目录内容由调用方加载）
+///
+/// 所有导航都经过这里，界面控件只发送命令，不直接修改历史记录。
+fn enter_directory(data: &mut AppState, path: &std::path::Path, history: History) {
+    if history == History::Record {
+        data.add_to_history(path.to_path_buf());
+    }
+    data.selected_path = Some(path.to_path_buf());
+    data.address_input = None;
+    data.drive_overview = false;
+    data.loading_path = None;
+    data.status_notice = String::new();
+    data.quick_filter.reset();
+    data.current_space = system::get_drive_space(path);
+
+    // 记录访问，并更新目录树中的"最近访问"分组
+    locations::record_visit(path);
+    locations::refresh_recent_section(&mut data.root);
+    update_selection(&mut data.root, path);
+}
+
+/// 加载目录的第一页并进入目录，大目录的其余文件在后台加载
+///
+/// 所有导航（目录树、地址栏、后退/前进、上级目录、主目录等）都通过这里打开目录。
+fn load_directory(data: &mut AppState, ctx: &mut DelegateCtx, path: &std::path::Path, history: History) {
+    data.current_dir_files = get_directory_contents(path);
+    enter_directory(data, path, history);
+    load_rest_in_background(data, ctx, path);
+}
+
+/// 重新加载当前目录（刷新或设置变化后），保留快速筛选条件
+fn reload_directory(data: &mut AppState, ctx: &mut DelegateCtx, path: &std::path::Path) {
+    data.current_dir_files = get_directory_contents(path);
+    data.quick_filter.reload(&mut data.current_dir_files);
+    load_rest_in_background(data, ctx, path);
+}
+
+/// 目录中的条目多于一页时，在后台线程中加载完整列表，完成后通过 `UPDATE_FILE_LIST` 更新界面
+fn load_rest_in_background(data: &mut AppState, ctx: &mut DelegateCtx, path: &std::path::Path) {
+    let total_count = get_directory_item_count(path);
+    let page_size = data.settings.page_size;
+    if total_count <= page_size {
+        data.loading_path = None;
         return;
     }
-    
-    // 检查选中路径是否是当前项的子孙路径
-    if selected_path.starts_with(&item.path) {
-        // 确保当前目录处于展开状态
-        item.is_expanded = true;
-        
-        // 递归检查子目录
-        for child in &mut item.children {
-            update_selection(child, selected_path);
+    println!("目录含有大量文件 ({}个)，使用分页加载，初始加载{}个", total_count, page_size);
+
+    // 创建一个线程安全的上下文引用，供后台线程使用
+    let event_sink = ctx.get_external_handle();
+    let path = path.to_path_buf();
+    let load_limit = data.settings.background_load_limit;
+    data.loading_path = Some(path.clone());
+
+    // 将额外的文件加载放到后台线程，避免阻塞UI
+    std::thread::spawn(move || {
+        // 等待一段时间让UI先渲染
+        std::thread::sleep(std::time::Duration::from_millis(50));
+
+        // 加载更多文件 - 使用较大数量加载所有文件
+        let more_files = get_directory_contents_paged(&path, 0, load_limit);
+        println!("后台加载完成，总共加载 {} 个文件", more_files.len());
+
+        // 发送命令更新UI
+        if let Err(e) = event_sink.submit_command(UPDATE_FILE_LIST, (path, more_files), Target::Auto) {
+            eprintln!("更新文件列表失败: {:?}", e);
         }
-    }
+    });
+}
+
+/// 用后台读取的驱动器替换"我的电脑"下的驱动器，仍然挂载的驱动器保留展开状态和已加载的子目录
+fn replace_drives(root: &mut FileItem, drives: &[FileItem]) {
+    let computer = match root.children.iter_mut().find(|child| child.name == "我的电脑") {
+        Some(computer) => computer,
+        None => return,
+    };
+    let previous = std::mem::take(&mut computer.children);
+    computer.children = drives
+        .iter()
+        .map(|drive| match previous.iter().find(|old| old.path == drive.path) {
+            Some(old) => FileItem { name: drive.name.clone(), volume: drive.volume.clone(), ..old.clone() },
+            None => drive.clone(),
+        })
+        .collect();
 }
 
 /// 递归加载子目录
@@ -294,6 +1343,29 @@ pub fn load_subdirectories(item: &mut FileItem, target_path: &PathBuf) {
     }
 }
 
+/// 重新加载目录树中已加载的子目录，保留原有的展开状态
+///
+/// 用于隐藏文件可见性变化后刷新目录树
+pub fn refresh_loaded_subdirectories(item: &mut FileItem) {
+    // 根节点、"我的电脑"和"最近访问"的子项不是目录内容，只需继续向下刷新
+    if !keyboard_nav::is_virtual(item) && !item.children.is_empty() {
+        let mut old_children = std::mem::take(&mut item.children);
+        item.children = build_file_tree(&item.path, 1)
+            .into_iter()
+            .map(|new_child| {
+                match old_children.iter().position(|old| old.path == new_child.path) {
+                    Some(index) => old_children.swap_remove(index),
+                    None => new_child,
+                }
+            })
+            .collect();
+    }
+    
+    for child in &mut item.children {
+        refresh_loaded_subdirectories(child);
+    }
+}
+
 /// 查找特定路径对应的FileItem
 pub fn find_item_by_path<'a>(item: &'a FileItem, target_path: &PathBuf) -> Option<&'a FileItem> {
     if item.path == *target_path {
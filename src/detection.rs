@@ -0,0 +1,307 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use druid::{ExtEventSink, Target};
+
+use crate::commands::FILE_TYPE_DETECTED;
+use crate::file_types::{self, FileCategory, FileKind, FileTypeRegistry, UNKNOWN_TYPE_ID};
+
+/// 识别文件类型时读取的文件头长度（TAR的特征位于偏移257处）
+const HEADER_SIZE: usize = 512;
+
+/// 识别为普通文本时使用的类型ID
+const TEXT_TYPE_ID: &str = "text";
+
+/// 判断为文本时允许的控制字符比例
+const MAX_CONTROL_RATIO: f64 = 0.05;
+
+// 等待识别的文件队列，由后台识别线程处理
+lazy_static::lazy_static! {
+    static ref REQUESTS: Mutex<Option<Sender<PathBuf>>> = Mutex::new(None);
+    static ref PENDING: Mutex<HashSet<PathBuf>> = Mutex::new(HashSet::new());
+}
+
+/// 启动后台识别线程，识别结果通过 `FILE_TYPE_DETECTED` 命令发回界面
+pub fn start_worker(event_sink: ExtEventSink) {
+    let (sender, receiver) = mpsc::channel::<PathBuf>();
+    *REQUESTS.lock().unwrap() = Some(sender);
+
+    thread::spawn(move || {
+        for path in receiver {
+            let kind = detect(&path);
+            PENDING.lock().unwrap().remove(&path);
+
+            if let Err(e) = event_sink.submit_command(FILE_TYPE_DETECTED, (path, kind), Target::Auto) {
+                eprintln!("发送文件类型识别结果失败: {:?}", e);
+                break;
+            }
+        }
+    });
+}
+
+/// 请求识别文件类型（同一文件在识别完成前只排队一次）
+pub fn request(path: &Path) {
+    if !PENDING.lock().unwrap().insert(path.to_path_buf()) {
+        return;
+    }
+
+    let sent = REQUESTS.lock().unwrap()
+        .as_ref()
+        .is_some_and(|sender| sender.send(path.to_path_buf()).is_ok());
+
+    if !sent {
+        PENDING.lock().unwrap().remove(path);
+    }
+}
+
+/// 读取文件头部并识别文件类型，返回合并了扩展名类型后的结果，无法识别时返回 None
+pub fn detect(path: &Path) -> Option<FileKind> {
+    // 只读取普通文件，避免在管道、设备文件上阻塞
+    if !path.metadata().map(|m| m.is_file()).unwrap_or(false) {
+        return None;
+    }
+
+    let header = read_header(path)?;
+    let extension = path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    file_types::with_registry(|registry| {
+        let current = registry.kind_for_path(path, false);
+        let detected = sniff(registry, &header, &extension)?;
+        Some(if should_replace(registry, &current, &detected) { detected } else { current })
+    })
+}
+
+/// 根据文件头识别文件类型
+///
+/// 依次检查文件头特征、shebang 和文本编码。
+pub fn sniff(registry: &FileTypeRegistry, header: &[u8], extension: &str) -> Option<FileKind> {
+    if header.is_empty() {
+        return None;
+    }
+
+    if let Some(def) = registry.by_magic(header) {
+        return Some(FileTypeRegistry::kind_from_def(def, extension));
+    }
+
+    if let Some(kind) = sniff_shebang(registry, header, extension) {
+        return Some(kind);
+    }
+
+    let encoding = text_encoding(header)?;
+    let description = match encoding {
+        TextEncoding::Utf8 => "文本文档",
+        TextEncoding::Utf16Le | TextEncoding::Utf16Be => "文本文档 (UTF-16)",
+    };
+
+    Some(match registry.get(TEXT_TYPE_ID) {
+        Some(def) => FileKind {
+            description: description.to_string(),
+            ..FileTypeRegistry::kind_from_def(def, extension)
+        },
+        None => FileKind {
+            type_id: TEXT_TYPE_ID.to_string(),
+            category: FileCategory::Text,
+            extension: extension.to_string(),
+            description: description.to_string(),
+        },
+    })
+}
+
+/// 判断是否用识别结果替换按扩展名得到的类型
+///
+/// 扩展名未知时总是替换；只识别出是文本时不替换，避免把源代码等文件降级为普通文本。
+/// 其余情况只在扩展名声称的格式可以验证（文本类或有文件头特征）却与内容不符时替换，
+/// 这样 docx 等以ZIP为容器的格式不会被显示为压缩包。
+fn should_replace(registry: &FileTypeRegistry, current: &FileKind, detected: &FileKind) -> bool {
+    if current.type_id == UNKNOWN_TYPE_ID {
+        return true;
+    }
+    if detected.type_id == current.type_id || detected.type_id == TEXT_TYPE_ID {
+        return false;
+    }
+
+    matches!(current.category, FileCategory::Text | FileCategory::Code)
+        || registry.get(&current.type_id).is_some_and(|def| !def.magic.is_empty())
+}
+
+/// 读取文件头
+fn read_header(path: &Path) -> Option<Vec<u8>> {
+    let mut file = File::open(path).ok()?;
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    file.by_ref().take(HEADER_SIZE as u64).read_to_end(&mut header).ok()?;
+    Some(header)
+}
+
+/// 从 shebang（#!）行识别脚本类型
+fn sniff_shebang(registry: &FileTypeRegistry, header: &[u8], extension: &str) -> Option<FileKind> {
+    let rest = header.strip_prefix(b"#!")?;
+    let line_end = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+    let line = String::from_utf8_lossy(&rest[..line_end]);
+
+    // "#!/usr/bin/env python3" 取 env 之后的解释器
+    let mut parts = line.split_whitespace();
+    let mut program = parts.next()?.rsplit('/').next()?.to_string();
+    if program == "env" {
+        program = parts.find(|arg| !arg.starts_with('-'))?.to_string();
+    }
+
+    // 去掉版本号（python3.11 -> python），再按解释器的MIME类型查找，
+    // 配置中用 mime_types 声明了同一MIME类型的自定义类型也会匹配
+    let interpreter = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let mime = match interpreter {
+        "python" | "pypy" => "text/x-python",
+        "sh" | "bash" | "zsh" | "dash" | "ksh" | "fish" => "application/x-sh",
+        "node" | "nodejs" | "deno" => "text/javascript",
+        "ruby" => "text/x-ruby",
+        "php" => "application/x-php",
+        "perl" => "text/x-perl",
+        "lua" => "text/x-lua",
+        _ => "",
+    };
+
+    Some(match registry.by_mime(mime) {
+        Some(def) => FileTypeRegistry::kind_from_def(def, extension),
+        None => FileKind {
+            type_id: "script".to_string(),
+            category: FileCategory::Code,
+            extension: extension.to_string(),
+            description: format!("{} 脚本", program),
+        },
+    })
+}
+
+/// 文本编码
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TextEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+/// 判断文件头是否为文本，返回其编码
+fn text_encoding(header: &[u8]) -> Option<TextEncoding> {
+    if header.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return Some(TextEncoding::Utf8);
+    }
+    if header.starts_with(&[0xFF, 0xFE]) {
+        return Some(TextEncoding::Utf16Le);
+    }
+    if header.starts_with(&[0xFE, 0xFF]) {
+        return Some(TextEncoding::Utf16Be);
+    }
+
+    // 没有BOM的UTF-16文本：ASCII字符的高位字节为0
+    if header.len() >= 4 {
+        let pairs = header.len() / 2;
+        let zero_odd = header.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+        let zero_even = header.iter().step_by(2).filter(|&&b| b == 0).count();
+        if zero_odd * 10 >= pairs * 9 && zero_even == 0 {
+            return Some(TextEncoding::Utf16Le);
+        }
+        if zero_even * 10 >= pairs * 9 && zero_odd == 0 {
+            return Some(TextEncoding::Utf16Be);
+        }
+    }
+
+    if header.contains(&0) {
+        return None;
+    }
+
+    // 文件头可能在多字节字符中间被截断
+    let valid = match std::str::from_utf8(header) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&header[..e.valid_up_to()]).ok()?,
+        Err(_) => return None,
+    };
+
+    let control = valid.chars()
+        .filter(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t' | '\x0C'))
+        .count();
+    if (control as f64) > valid.chars().count() as f64 * MAX_CONTROL_RATIO {
+        return None;
+    }
+
+    Some(TextEncoding::Utf8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 识别文件头，返回类型ID
+    fn sniff_id(header: &[u8], extension: &str) -> Option<String> {
+        sniff(&FileTypeRegistry::builtin(), header, extension).map(|kind| kind.type_id)
+    }
+
+    #[test]
+    fn sniff_magic_bytes() {
+        assert_eq!(sniff_id(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR", "").as_deref(), Some("png"));
+        assert_eq!(sniff_id(b"%PDF-1.7\n", "").as_deref(), Some("pdf"));
+        assert_eq!(sniff_id(b"\x7fELF\x02\x01\x01\0", "").as_deref(), Some("elf"));
+        assert_eq!(sniff_id(b"RIFF\0\0\0\0WEBPVP8 ", "").as_deref(), Some("webp"));
+        // 识别结果保留原来的扩展名
+        let kind = sniff(&FileTypeRegistry::builtin(), b"PK\x03\x04", "docx").unwrap();
+        assert_eq!((kind.type_id.as_str(), kind.extension.as_str()), ("zip", "docx"));
+        assert_eq!(sniff_id(b"", "png"), None);
+    }
+
+    #[test]
+    fn sniff_shebang_lines() {
+        assert_eq!(sniff_id(b"#!/usr/bin/env python3\nprint()\n", "").as_deref(), Some("python"));
+        assert_eq!(sniff_id(b"#!/usr/bin/python3.11\n", "").as_deref(), Some("python"));
+        assert_eq!(sniff_id(b"#!/bin/bash -e\n", "").as_deref(), Some("shell"));
+        assert_eq!(sniff_id(b"#!/usr/bin/env -S node --harmony\n", "").as_deref(), Some("javascript"));
+
+        // 没有对应类型的解释器仍然识别为脚本
+        let kind = sniff(&FileTypeRegistry::builtin(), b"#!/usr/bin/awk -f\n", "").unwrap();
+        assert_eq!(kind.type_id, "script");
+        assert_eq!(kind.description, "awk 脚本");
+        assert_eq!(sniff_shebang(&FileTypeRegistry::builtin(), b"#!\n", ""), None);
+    }
+
+    #[test]
+    fn text_encoding_boms() {
+        assert_eq!(text_encoding(b"\xEF\xBB\xBFhello"), Some(TextEncoding::Utf8));
+        assert_eq!(text_encoding(b"\xFF\xFEh\0i\0"), Some(TextEncoding::Utf16Le));
+        assert_eq!(text_encoding(b"\xFE\xFF\0h\0i"), Some(TextEncoding::Utf16Be));
+        // 没有BOM的UTF-16
+        assert_eq!(text_encoding(b"h\0e\0l\0l\0o\0"), Some(TextEncoding::Utf16Le));
+        assert_eq!(text_encoding(b"\0h\0e\0l\0l\0o"), Some(TextEncoding::Utf16Be));
+    }
+
+    #[test]
+    fn text_encoding_utf8_and_binary() {
+        assert_eq!(text_encoding("你好，世界\n".as_bytes()), Some(TextEncoding::Utf8));
+        // 在多字节字符中间截断的文件头仍然是文本
+        let text = "文本".as_bytes();
+        assert_eq!(text_encoding(&text[..text.len() - 1]), Some(TextEncoding::Utf8));
+
+        assert_eq!(text_encoding(b"abc\0\x01\x02def"), None);
+        assert_eq!(text_encoding(b"\xC3\x28 invalid"), None);
+        assert_eq!(text_encoding(b"\x01\x02\x03\x04\x05ab"), None);
+    }
+
+    #[test]
+    fn should_replace_precedence() {
+        let registry = FileTypeRegistry::builtin();
+        let kind = |name: &str| registry.kind_for_path(Path::new(name), false);
+        let detected = |id: &str, ext: &str| FileTypeRegistry::kind_from_def(registry.get(id).unwrap(), ext);
+
+        // 扩展名未知时总是使用识别结果
+        assert!(should_replace(&registry, &kind("data.bin"), &detected("png", "bin")));
+        // 只识别出文本时不降级源代码
+        assert!(!should_replace(&registry, &kind("main.rs"), &detected("text", "rs")));
+        // 容器格式不显示为压缩包
+        assert!(!should_replace(&registry, &kind("report.docx"), &detected("zip", "docx")));
+        // 扩展名声称的格式与内容不符
+        assert!(should_replace(&registry, &kind("photo.png"), &detected("jpeg", "png")));
+        assert!(should_replace(&registry, &kind("notes.txt"), &detected("elf", "txt")));
+        assert!(!should_replace(&registry, &kind("photo.png"), &detected("png", "png")));
+    }
+}
@@ -0,0 +1,587 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use druid::{Data, Lens};
+
+use crate::duplicates::hash_file;
+use crate::jobs::JobContext;
+use crate::models::FileItem;
+
+/// 按大小和修改时间比较时允许的时间误差（FAT 文件系统的时间精度为 2 秒）
+const MODIFIED_TOLERANCE: Duration = Duration::from_secs(2);
+
+/// 判断两个文件是否相同的方式
+#[derive(Clone, Copy, Data, Debug, PartialEq, Eq)]
+pub enum CompareMode {
+    /// 大小和修改时间都相同
+    SizeAndTime,
+    /// 内容的哈希相同
+    Content,
+}
+
+/// 比较结果中一个条目的状态
+#[derive(Clone, Copy, Data, Debug, PartialEq, Eq)]
+pub enum DiffStatus {
+    /// 两侧相同（目录：其中所有条目都相同）
+    Identical,
+    /// 两侧都存在但不同（目录：其中有不同的条目）
+    Different,
+    /// 只在左侧存在
+    OnlyLeft,
+    /// 只在右侧存在
+    OnlyRight,
+}
+
+impl DiffStatus {
+    /// 显示的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            DiffStatus::Identical => "相同",
+            DiffStatus::Different => "不同",
+            DiffStatus::OnlyLeft => "仅左侧",
+            DiffStatus::OnlyRight => "仅右侧",
+        }
+    }
+}
+
+/// 复制不同文件的方向
+#[derive(Clone, Copy, Data, Debug, PartialEq, Eq)]
+pub enum CopyDirection {
+    LeftToRight,
+    RightToLeft,
+}
+
+impl CopyDirection {
+    /// 需要复制的状态：两侧不同，或只在源一侧存在
+    fn copies(self, status: Option<DiffStatus>) -> bool {
+        matches!(
+            (self, status),
+            (_, Some(DiffStatus::Different))
+                | (CopyDirection::LeftToRight, Some(DiffStatus::OnlyLeft))
+                | (CopyDirection::RightToLeft, Some(DiffStatus::OnlyRight))
+        )
+    }
+}
+
+/// 比较结果中显示哪些状态
+#[derive(Clone, Data, Lens, Debug, PartialEq)]
+pub struct CompareFilter {
+    pub identical: bool,
+    pub different: bool,
+    pub only_left: bool,
+    pub only_right: bool,
+}
+
+impl Default for CompareFilter {
+    fn default() -> Self {
+        Self { identical: false, different: true, only_left: true, only_right: true }
+    }
+}
+
+impl CompareFilter {
+    /// 是否显示该状态
+    pub fn shows(&self, status: DiffStatus) -> bool {
+        match status {
+            DiffStatus::Identical => self.identical,
+            DiffStatus::Different => self.different,
+            DiffStatus::OnlyLeft => self.only_left,
+            DiffStatus::OnlyRight => self.only_right,
+        }
+    }
+}
+
+/// 目录比较窗口的状态
+///
+/// 比较结果使用与目录树相同的 `FileItem` 结构，节点的 `path` 为相对于比较根目录的路径，
+/// `diff` 为比较状态。
+#[derive(Clone, Data, Lens, Debug, PartialEq)]
+pub struct DirCompareState {
+    /// 正在编辑的左右两侧目录
+    pub left: String,
+    pub right: String,
+    pub mode: CompareMode,
+    /// 比较任务ID
+    pub job_id: Option<u64>,
+    /// 复制任务ID
+    pub copy_job_id: Option<u64>,
+    /// 当前结果对应的左右目录
+    #[data(same_fn = "PartialEq::eq")]
+    pub compared_left: PathBuf,
+    #[data(same_fn = "PartialEq::eq")]
+    pub compared_right: PathBuf,
+    /// 完整的比较结果
+    pub result: Option<Arc<FileItem>>,
+    /// 按过滤条件显示的结果
+    pub tree: FileItem,
+    pub filter: CompareFilter,
+    /// 选中的条目（相对路径）
+    #[data(same_fn = "PartialEq::eq")]
+    pub selected: Option<PathBuf>,
+    /// 校验错误或复制结果等提示
+    pub notice: String,
+}
+
+impl Default for DirCompareState {
+    fn default() -> Self {
+        Self {
+            left: String::new(),
+            right: String::new(),
+            mode: CompareMode::SizeAndTime,
+            job_id: None,
+            copy_job_id: None,
+            compared_left: PathBuf::new(),
+            compared_right: PathBuf::new(),
+            result: None,
+            tree: empty_item(String::new(), PathBuf::new(), None, true),
+            filter: CompareFilter::default(),
+            selected: None,
+            notice: String::new(),
+        }
+    }
+}
+
+impl DirCompareState {
+    /// 以指定目录作为左侧打开比较窗口
+    pub fn with_left(left: &Path) -> Self {
+        Self {
+            left: left.display().to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// 检查输入的两个目录，返回 (左, 右)
+    pub fn validate(&self) -> Result<(PathBuf, PathBuf), String> {
+        let left = PathBuf::from(self.left.trim());
+        let right = PathBuf::from(self.right.trim());
+        for (side, path) in [("左侧", &left), ("右侧", &right)] {
+            if !path.is_dir() {
+                return Err(format!("{}不是目录: {}", side, path.display()));
+            }
+        }
+        if fs::canonicalize(&left).ok() == fs::canonicalize(&right).ok() {
+            return Err("左右两侧是同一个目录".to_string());
+        }
+        Ok((left, right))
+    }
+
+    /// 开始新的比较
+    pub fn start(&mut self, left: PathBuf, right: PathBuf, job_id: u64) {
+        self.job_id = Some(job_id);
+        self.compared_left = left;
+        self.compared_right = right;
+        self.notice = String::new();
+    }
+
+    /// 设置比较结果，保留已展开的目录和选中的条目
+    pub fn set_result(&mut self, result: Arc<FileItem>) {
+        self.result = Some(result);
+        self.apply_filter();
+    }
+
+    /// 按过滤条件重新生成显示的结果
+    pub fn apply_filter(&mut self) {
+        let result = match &self.result {
+            Some(result) => result,
+            None => return,
+        };
+
+        let mut expanded = HashSet::new();
+        collect_expanded(&self.tree, &mut expanded);
+
+        let mut tree = filter_item(result, &self.filter).unwrap_or_else(|| {
+            empty_item(result.name.clone(), result.path.clone(), result.diff, true)
+        });
+        tree.is_expanded = true;
+        restore_state(&mut tree, &expanded, self.selected.as_deref());
+        self.tree = tree;
+    }
+
+    /// 选中一个条目
+    pub fn select(&mut self, path: &Path) {
+        self.selected = Some(path.to_path_buf());
+        restore_state(&mut self.tree, &HashSet::new(), Some(path));
+    }
+
+    /// 选中的条目在完整结果中的节点
+    pub fn selected_item(&self) -> Option<&FileItem> {
+        find_item(self.result.as_deref()?, self.selected.as_deref()?)
+    }
+
+    /// 各状态的文件数量（相同, 不同, 仅左侧, 仅右侧）
+    pub fn counts(&self) -> (usize, usize, usize, usize) {
+        let mut counts = (0, 0, 0, 0);
+        if let Some(result) = &self.result {
+            count_files(result, &mut counts);
+        }
+        counts
+    }
+}
+
+/// 比较两个目录（在后台任务中运行），任务被取消时返回 None
+///
+/// 两侧都存在的子目录递归比较，只在一侧存在的目录列出其中所有条目。符号链接不参与比较。
+pub fn compare(left: &Path, right: &Path, mode: CompareMode, job: &mut JobContext) -> Option<FileItem> {
+    let mut root = compare_dirs(left, right, Path::new(""), mode, job)?;
+    root.name = format!("{}  ⇄  {}", left.display(), right.display());
+    root.is_expanded = true;
+    Some(root)
+}
+
+/// 目录中的一个条目的类型
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EntryKind {
+    Directory,
+    File,
+}
+
+fn compare_dirs(left: &Path, right: &Path, relative: &Path, mode: CompareMode, job: &mut JobContext) -> Option<FileItem> {
+    // 名称 -> (左侧类型, 右侧类型)
+    let mut entries: BTreeMap<String, (Option<EntryKind>, Option<EntryKind>)> = BTreeMap::new();
+    for (name, kind) in list_dir(left, job) {
+        entries.entry(name).or_default().0 = Some(kind);
+    }
+    for (name, kind) in list_dir(right, job) {
+        entries.entry(name).or_default().1 = Some(kind);
+    }
+
+    let mut children = Vec::with_capacity(entries.len());
+    for (name, kinds) in entries {
+        if job.is_cancelled() {
+            return None;
+        }
+        let child_relative = relative.join(&name);
+        let child = match kinds {
+            (Some(EntryKind::Directory), Some(EntryKind::Directory)) => {
+                let mut child = compare_dirs(&left.join(&name), &right.join(&name), &child_relative, mode, job)?;
+                child.name = name;
+                child
+            }
+            (Some(EntryKind::File), Some(EntryKind::File)) => {
+                let status = compare_files(&left.join(&name), &right.join(&name), mode, job);
+                job.processed();
+                empty_item(name, child_relative, Some(status), false)
+            }
+            // 一侧是文件、另一侧是目录，按文件显示
+            (Some(_), Some(_)) => empty_item(name, child_relative, Some(DiffStatus::Different), false),
+            (Some(kind), None) => one_side(&left.join(&name), name, child_relative, kind, DiffStatus::OnlyLeft, job)?,
+            (None, Some(kind)) => one_side(&right.join(&name), name, child_relative, kind, DiffStatus::OnlyRight, job)?,
+            (None, None) => continue,
+        };
+        children.push(child);
+    }
+
+    sort_children(&mut children);
+    let status = if children.iter().all(|child| child.diff == Some(DiffStatus::Identical)) {
+        DiffStatus::Identical
+    } else {
+        DiffStatus::Different
+    };
+    let mut item = empty_item(String::new(), relative.to_path_buf(), Some(status), true);
+    item.children = children;
+    Some(item)
+}
+
+/// 只在一侧存在的条目，目录中的所有条目使用同一状态
+fn one_side(path: &Path, name: String, relative: PathBuf, kind: EntryKind, status: DiffStatus, job: &mut JobContext) -> Option<FileItem> {
+    let mut item = empty_item(name, relative, Some(status), kind == EntryKind::Directory);
+    if kind == EntryKind::File {
+        job.processed();
+        return Some(item);
+    }
+
+    for (child_name, child_kind) in list_dir(path, job) {
+        if job.is_cancelled() {
+            return None;
+        }
+        let child_relative = item.path.join(&child_name);
+        let child = one_side(&path.join(&child_name), child_name, child_relative, child_kind, status, job)?;
+        item.children.push(child);
+    }
+    sort_children(&mut item.children);
+    Some(item)
+}
+
+/// 列出目录中的文件和子目录（跳过符号链接），无法读取时记录到任务中
+fn list_dir(dir: &Path, job: &mut JobContext) -> Vec<(String, EntryKind)> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            job.failed(dir, format!("无法读取目录: {}", e));
+            return Vec::new();
+        }
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let file_type = entry.file_type().ok()?;
+            let kind = if file_type.is_dir() {
+                EntryKind::Directory
+            } else if file_type.is_file() {
+                EntryKind::File
+            } else {
+                return None;
+            };
+            Some((entry.file_name().to_string_lossy().to_string(), kind))
+        })
+        .collect()
+}
+
+/// 比较两侧的同名文件，无法读取时记录到任务中并视为不同
+fn compare_files(left: &Path, right: &Path, mode: CompareMode, job: &mut JobContext) -> DiffStatus {
+    match files_identical(left, right, mode) {
+        Ok(true) => DiffStatus::Identical,
+        Ok(false) => DiffStatus::Different,
+        Err(e) => {
+            job.failed(left, e);
+            DiffStatus::Different
+        }
+    }
+}
+
+/// 两个文件是否相同：大小不同时直接视为不同，否则按比较方式检查修改时间或内容
+pub fn files_identical(left: &Path, right: &Path, mode: CompareMode) -> io::Result<bool> {
+    let left_meta = fs::metadata(left)?;
+    let right_meta = fs::metadata(right)?;
+    if left_meta.len() != right_meta.len() {
+        return Ok(false);
+    }
+
+    match mode {
+        CompareMode::SizeAndTime => {
+            let (left_time, right_time) = (left_meta.modified()?, right_meta.modified()?);
+            let difference = left_time.duration_since(right_time)
+                .or_else(|_| right_time.duration_since(left_time))
+                .unwrap_or_default();
+            Ok(difference <= MODIFIED_TOLERANCE)
+        }
+        CompareMode::Content => Ok(hash_file(left, None)? == hash_file(right, None)?),
+    }
+}
+
+/// 没有子项的比较结果节点
+fn empty_item(name: String, path: PathBuf, diff: Option<DiffStatus>, is_dir: bool) -> FileItem {
+    FileItem {
+        name,
+        is_expanded: false,
+        children: Vec::new(),
+        path,
+        is_selected: false,
+        is_dir,
+        diff,
+        volume: None,
+        is_hidden: false,
+    }
+}
+
+/// 目录（包括空目录）在前，再按名称排序
+fn sort_children(children: &mut [FileItem]) {
+    children.sort_by(|a, b| {
+        b.is_dir.cmp(&a.is_dir)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+/// 按过滤条件复制节点：文件和空目录显示其状态时保留，其他目录有保留的子项时保留
+fn filter_item(item: &FileItem, filter: &CompareFilter) -> Option<FileItem> {
+    let shown = item.diff.is_some_and(|status| filter.shows(status));
+    if !item.is_dir || item.children.is_empty() {
+        return if shown { Some(item.clone()) } else { None };
+    }
+
+    let children: Vec<FileItem> = item.children.iter().filter_map(|child| filter_item(child, filter)).collect();
+    if children.is_empty() {
+        return None;
+    }
+    Some(FileItem { children, ..empty_item(item.name.clone(), item.path.clone(), item.diff, true) })
+}
+
+fn collect_expanded(item: &FileItem, expanded: &mut HashSet<PathBuf>) {
+    if item.is_expanded {
+        expanded.insert(item.path.clone());
+    }
+    for child in &item.children {
+        collect_expanded(child, expanded);
+    }
+}
+
+/// 恢复展开状态（`expanded` 中的节点展开，其余保持不变）并标记选中的节点
+fn restore_state(item: &mut FileItem, expanded: &HashSet<PathBuf>, selected: Option<&Path>) {
+    if expanded.contains(&item.path) {
+        item.is_expanded = true;
+    }
+    item.is_selected = selected == Some(item.path.as_path());
+    for child in item.children.iter_mut() {
+        restore_state(child, expanded, selected);
+    }
+}
+
+/// 按相对路径查找节点
+fn find_item<'a>(item: &'a FileItem, path: &Path) -> Option<&'a FileItem> {
+    if item.path == path {
+        return Some(item);
+    }
+    item.children
+        .iter()
+        .find(|child| path.starts_with(&child.path))
+        .and_then(|child| find_item(child, path))
+}
+
+/// 统计各状态的文件数量（目录不计入，包括空目录）
+fn count_files(item: &FileItem, counts: &mut (usize, usize, usize, usize)) {
+    if !item.is_dir {
+        match item.diff {
+            Some(DiffStatus::Identical) => counts.0 += 1,
+            Some(DiffStatus::Different) => counts.1 += 1,
+            Some(DiffStatus::OnlyLeft) => counts.2 += 1,
+            Some(DiffStatus::OnlyRight) => counts.3 += 1,
+            None => {}
+        }
+    }
+    for child in &item.children {
+        count_files(child, counts);
+    }
+}
+
+/// 将节点（及其中所有条目）中不同的和只在源一侧存在的文件复制到另一侧（在后台任务中运行）
+///
+/// 目标中不同的文件被覆盖；复制后保留源文件的修改时间，重新比较时两侧相同。
+pub fn copy_differences(item: &FileItem, left: &Path, right: &Path, direction: CopyDirection, job: &mut JobContext) {
+    let (source_root, target_root) = match direction {
+        CopyDirection::LeftToRight => (left, right),
+        CopyDirection::RightToLeft => (right, left),
+    };
+    copy_item(item, source_root, target_root, direction, job);
+}
+
+fn copy_item(item: &FileItem, source_root: &Path, target_root: &Path, direction: CopyDirection, job: &mut JobContext) {
+    if job.is_cancelled() {
+        return;
+    }
+    let source = source_root.join(&item.path);
+    let target = target_root.join(&item.path);
+
+    if source.is_dir() {
+        if !direction.copies(item.diff) {
+            return;
+        }
+        if target.exists() && !target.is_dir() {
+            job.failed(&target, "目标是文件，源是目录");
+            return;
+        }
+        if let Err(e) = fs::create_dir_all(&target) {
+            job.failed(&target, e);
+            return;
+        }
+        for child in &item.children {
+            copy_item(child, source_root, target_root, direction, job);
+        }
+    } else if source.is_file() && direction.copies(item.diff) {
+        if target.is_dir() {
+            job.failed(&target, "目标是目录，源是文件");
+            return;
+        }
+        match copy_file(&source, &target) {
+            Ok(()) => job.processed(),
+            Err(e) => job.failed(&source, e),
+        }
+    }
+}
+
+/// 复制文件并保留修改时间
+pub fn copy_file(source: &Path, target: &Path) -> io::Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(source, target)?;
+    let modified = fs::metadata(source)?.modified()?;
+    File::options().write(true).open(target)?.set_modified(modified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, status: DiffStatus) -> FileItem {
+        empty_item(name.to_string(), PathBuf::from(name), Some(status), false)
+    }
+
+    fn dir(name: &str, status: DiffStatus, children: Vec<FileItem>) -> FileItem {
+        FileItem { children, ..empty_item(name.to_string(), PathBuf::from(name), Some(status), true) }
+    }
+
+    fn names(items: &[FileItem]) -> Vec<&str> {
+        items.iter().map(|item| item.name.as_str()).collect()
+    }
+
+    #[test]
+    fn empty_directories_sort_with_directories() {
+        let mut children = vec![
+            file("a.txt", DiffStatus::Identical),
+            dir("empty", DiffStatus::OnlyLeft, Vec::new()),
+            dir("b", DiffStatus::Different, vec![file("x", DiffStatus::Different)]),
+        ];
+        sort_children(&mut children);
+        assert_eq!(names(&children), ["b", "empty", "a.txt"]);
+    }
+
+    #[test]
+    fn empty_directories_are_not_counted_as_files() {
+        let root = dir("", DiffStatus::Different, vec![
+            file("same", DiffStatus::Identical),
+            file("changed", DiffStatus::Different),
+            dir("empty", DiffStatus::OnlyLeft, Vec::new()),
+            dir("new", DiffStatus::OnlyRight, vec![file("new/a", DiffStatus::OnlyRight)]),
+        ]);
+        let mut counts = (0, 0, 0, 0);
+        count_files(&root, &mut counts);
+        assert_eq!(counts, (1, 1, 0, 1));
+    }
+
+    #[test]
+    fn filter_keeps_shown_empty_directories_and_drops_emptied_ones() {
+        let root = dir("", DiffStatus::Different, vec![
+            file("same", DiffStatus::Identical),
+            dir("empty", DiffStatus::OnlyLeft, Vec::new()),
+            dir("sub", DiffStatus::Identical, vec![file("sub/a", DiffStatus::Identical)]),
+        ]);
+        let filtered = filter_item(&root, &CompareFilter::default()).unwrap();
+        assert_eq!(names(&filtered.children), ["empty"]);
+        assert!(filtered.children[0].is_dir);
+
+        let only_right = CompareFilter { identical: false, different: false, only_left: false, only_right: true };
+        assert!(filter_item(&root, &only_right).is_none());
+    }
+
+    #[test]
+    fn copy_direction_copies_different_and_source_only_entries() {
+        let left_to_right = CopyDirection::LeftToRight;
+        assert!(left_to_right.copies(Some(DiffStatus::Different)));
+        assert!(left_to_right.copies(Some(DiffStatus::OnlyLeft)));
+        assert!(!left_to_right.copies(Some(DiffStatus::OnlyRight)));
+        assert!(!left_to_right.copies(Some(DiffStatus::Identical)));
+        assert!(CopyDirection::RightToLeft.copies(Some(DiffStatus::OnlyRight)));
+        assert!(!CopyDirection::RightToLeft.copies(None));
+    }
+
+    #[test]
+    fn files_identical_by_content_and_size() {
+        let dir = std::env::temp_dir().join(format!("dir-compare-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a"), "same").unwrap();
+        fs::write(dir.join("b"), "same").unwrap();
+        fs::write(dir.join("c"), "diff").unwrap();
+        fs::write(dir.join("d"), "longer").unwrap();
+
+        assert!(files_identical(&dir.join("a"), &dir.join("b"), CompareMode::Content).unwrap());
+        assert!(!files_identical(&dir.join("a"), &dir.join("c"), CompareMode::Content).unwrap());
+        assert!(!files_identical(&dir.join("a"), &dir.join("d"), CompareMode::SizeAndTime).unwrap());
+        assert!(files_identical(&dir.join("a"), &dir.join("missing"), CompareMode::Content).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
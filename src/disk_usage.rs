@@ -0,0 +1,462 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+use druid::im::Vector;
+use druid::{Data, Lens};
+
+use crate::jobs::JobContext;
+
+/// 每个目录中单独保留的文件数量，其余较小的文件合并为一个节点
+const MAX_FILES_PER_DIR: usize = 200;
+
+/// 最大文件列表的长度
+pub const TOP_FILES: usize = 50;
+
+/// 主线程汇报进度的间隔
+const PROGRESS_POLL: Duration = Duration::from_millis(100);
+
+/// 占用树中的一个节点
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageNode {
+    /// 显示名称
+    pub name: String,
+    /// 完整路径（合并的小文件节点为所在目录的路径）
+    pub path: PathBuf,
+    /// 总大小
+    pub size: u64,
+    /// 包含的文件数量
+    pub file_count: u64,
+    /// 节点类型
+    pub kind: UsageKind,
+    /// 子节点，按大小从大到小排列
+    pub children: Vec<UsageNode>,
+}
+
+/// 占用树节点的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageKind {
+    Directory,
+    File,
+    /// 合并在一起的若干小文件
+    Others,
+}
+
+impl UsageNode {
+    /// 按路径查找子孙节点
+    pub fn find(&self, path: &Path) -> Option<&UsageNode> {
+        if self.path == path && self.kind == UsageKind::Directory {
+            return Some(self);
+        }
+
+        let relative = path.strip_prefix(&self.path).ok()?;
+        let first = relative.components().next()?;
+        self.children
+            .iter()
+            .filter(|child| child.kind == UsageKind::Directory)
+            .find(|child| child.path.file_name() == Some(first.as_os_str()))
+            .and_then(|child| child.find(path))
+    }
+}
+
+/// 一个扩展名的占用统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtensionTotal {
+    /// 扩展名（小写，没有扩展名时为空）
+    pub extension: String,
+    pub bytes: u64,
+    pub count: u64,
+}
+
+/// 一次分析的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageReport {
+    /// 占用树的根节点
+    pub root: UsageNode,
+    /// 最大的文件（路径, 大小），从大到小
+    pub largest_files: Vec<(PathBuf, u64)>,
+    /// 按扩展名统计的占用，从大到小
+    pub extensions: Vec<ExtensionTotal>,
+    /// 无法读取的目录数量
+    pub error_count: u64,
+}
+
+/// 最大文件列表中的一项
+#[derive(Clone, Data, Debug, PartialEq)]
+pub struct LargeFile {
+    #[data(same_fn = "PartialEq::eq")]
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// 磁盘占用分析窗口的状态
+#[derive(Clone, Data, Lens, Debug, Default, PartialEq)]
+pub struct DiskUsageState {
+    /// 分析的目录
+    #[data(same_fn = "PartialEq::eq")]
+    pub root: PathBuf,
+    /// 正在运行或最近一次运行的扫描任务ID
+    pub job_id: Option<u64>,
+    /// 扫描结果（扫描完成前为空）
+    pub report: Option<Arc<UsageReport>>,
+    /// 矩形树图当前显示的目录
+    #[data(same_fn = "PartialEq::eq")]
+    pub focus: PathBuf,
+    /// 最大的文件
+    pub largest_files: Vector<LargeFile>,
+    /// 鼠标所在块的说明
+    pub hovered: String,
+}
+
+impl DiskUsageState {
+    /// 开始分析新的目录
+    pub fn start(root: &Path, job_id: u64) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            job_id: Some(job_id),
+            focus: root.to_path_buf(),
+            ..Default::default()
+        }
+    }
+
+    /// 接收扫描结果
+    pub fn set_report(&mut self, report: Arc<UsageReport>) {
+        self.focus = report.root.path.clone();
+        self.largest_files = report.largest_files
+            .iter()
+            .map(|(path, size)| LargeFile { path: path.clone(), size: *size })
+            .collect();
+        self.report = Some(report);
+    }
+
+    /// 矩形树图当前显示的节点
+    pub fn focus_node(&self) -> Option<&UsageNode> {
+        self.report.as_ref().and_then(|report| report.root.find(&self.focus))
+    }
+
+    /// 返回上一级目录（不超出分析的根目录）
+    pub fn focus_parent(&mut self) {
+        if self.focus != self.root {
+            if let Some(parent) = self.focus.parent() {
+                self.focus = parent.to_path_buf();
+            }
+        }
+    }
+}
+
+/// 扫描过程中的共享统计
+struct ScanState {
+    cancel: Arc<AtomicBool>,
+    scanned: AtomicU64,
+    errors: AtomicU64,
+    largest: Mutex<BinaryHeap<Reverse<(u64, PathBuf)>>>,
+    extensions: Mutex<HashMap<String, (u64, u64)>>,
+}
+
+impl ScanState {
+    fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// 记录一个文件：更新最大文件列表和扩展名统计
+    fn record_file(&self, path: &Path, size: u64, local_extensions: &mut HashMap<String, (u64, u64)>) {
+        self.scanned.fetch_add(1, Ordering::Relaxed);
+
+        let extension = path.extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let entry = local_extensions.entry(extension).or_insert((0, 0));
+        entry.0 += size;
+        entry.1 += 1;
+
+        let mut largest = self.largest.lock().unwrap();
+        if largest.len() < TOP_FILES {
+            largest.push(Reverse((size, path.to_path_buf())));
+        } else if largest.peek().is_some_and(|Reverse((smallest, _))| size > *smallest) {
+            largest.pop();
+            largest.push(Reverse((size, path.to_path_buf())));
+        }
+    }
+
+    fn merge_extensions(&self, local: HashMap<String, (u64, u64)>) {
+        let mut extensions = self.extensions.lock().unwrap();
+        for (extension, (bytes, count)) in local {
+            let entry = extensions.entry(extension).or_insert((0, 0));
+            entry.0 += bytes;
+            entry.1 += count;
+        }
+    }
+}
+
+/// 扫描线程共享的目录队列
+///
+/// 每个目录是一个任务，工作线程读取目录后把子目录放回队列，
+/// 因此无论目录树的形状如何，所有线程都能分到工作。
+struct WorkQueue {
+    state: Mutex<QueueState>,
+    condvar: Condvar,
+}
+
+struct QueueState {
+    /// 等待读取的目录
+    dirs: Vec<PathBuf>,
+    /// 已入队但还没有处理完的目录数量
+    pending: usize,
+}
+
+impl WorkQueue {
+    fn new(root: &Path) -> Self {
+        Self {
+            state: Mutex::new(QueueState { dirs: vec![root.to_path_buf()], pending: 1 }),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// 取出下一个目录，所有目录都处理完时返回 None
+    fn next(&self) -> Option<PathBuf> {
+        let mut queue = self.state.lock().unwrap();
+        loop {
+            if let Some(dir) = queue.dirs.pop() {
+                return Some(dir);
+            }
+            if queue.pending == 0 {
+                return None;
+            }
+            queue = self.condvar.wait(queue).unwrap();
+        }
+    }
+
+    /// 一个目录处理完毕，把它的子目录加入队列
+    fn finish(&self, subdirs: &[PathBuf]) {
+        let mut queue = self.state.lock().unwrap();
+        queue.dirs.extend_from_slice(subdirs);
+        queue.pending = queue.pending + subdirs.len() - 1;
+        if queue.pending == 0 || !subdirs.is_empty() {
+            self.condvar.notify_all();
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.state.lock().unwrap().pending == 0
+    }
+}
+
+/// 并行扫描目录，构建占用树（在后台任务中运行）
+///
+/// 所有层级的目录都通过共享队列分配给工作线程，不跟随符号链接；
+/// 全部读取完毕后再按目录结构组装占用树。任务被取消时返回 None。
+pub fn scan(root: &Path, job: &mut JobContext) -> Option<UsageReport> {
+    let state = ScanState {
+        cancel: job.cancel_flag(),
+        scanned: AtomicU64::new(0),
+        errors: AtomicU64::new(0),
+        largest: Mutex::new(BinaryHeap::new()),
+        extensions: Mutex::new(HashMap::new()),
+    };
+
+    let queue = WorkQueue::new(root);
+    let listings = Mutex::new(HashMap::new());
+    let workers = thread::available_parallelism().map(|n| n.get()).unwrap_or(4).min(8);
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                let mut local_extensions = HashMap::new();
+                while let Some(dir) = queue.next() {
+                    // 取消后只清空队列，不再读取目录
+                    if state.cancelled() {
+                        queue.finish(&[]);
+                        continue;
+                    }
+
+                    let (subdirs, files) = read_entries(&dir, &state, &mut local_extensions);
+                    queue.finish(&subdirs);
+                    listings.lock().unwrap().insert(dir, (subdirs, files));
+                }
+                state.merge_extensions(local_extensions);
+            });
+        }
+
+        // 工作线程运行期间由当前线程汇报进度
+        while !queue.is_done() && !state.cancelled() {
+            job.set_processed(state.scanned.load(Ordering::Relaxed));
+            thread::sleep(PROGRESS_POLL);
+        }
+    });
+
+    if state.cancelled() {
+        return None;
+    }
+    job.set_processed(state.scanned.load(Ordering::Relaxed));
+
+    let root = assemble(root, &mut listings.into_inner().unwrap());
+
+    let mut largest: Vec<(PathBuf, u64)> = state.largest.into_inner().unwrap()
+        .into_iter()
+        .map(|Reverse((size, path))| (path, size))
+        .collect();
+    largest.sort_by_key(|(_, size)| Reverse(*size));
+
+    let mut extensions: Vec<ExtensionTotal> = state.extensions.into_inner().unwrap()
+        .into_iter()
+        .map(|(extension, (bytes, count))| ExtensionTotal { extension, bytes, count })
+        .collect();
+    extensions.sort_by_key(|total| Reverse(total.bytes));
+
+    Some(UsageReport {
+        root,
+        largest_files: largest,
+        extensions,
+        error_count: state.errors.into_inner(),
+    })
+}
+
+/// 按读取到的目录内容组装占用树
+fn assemble(path: &Path, listings: &mut HashMap<PathBuf, (Vec<PathBuf>, Vec<UsageNode>)>) -> UsageNode {
+    let (subdirs, files) = listings.remove(path).unwrap_or_default();
+    let children = subdirs.iter().map(|dir| assemble(dir, listings)).collect();
+    make_dir_node(path, children, files)
+}
+
+/// 读取目录中的子目录和文件（不跟随符号链接）
+fn read_entries(
+    path: &Path,
+    state: &ScanState,
+    local_extensions: &mut HashMap<String, (u64, u64)>,
+) -> (Vec<PathBuf>, Vec<UsageNode>) {
+    let mut subdirs = Vec::new();
+    let mut files = Vec::new();
+
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => {
+            state.errors.fetch_add(1, Ordering::Relaxed);
+            return (subdirs, files);
+        }
+    };
+
+    for entry in entries.filter_map(Result::ok) {
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(_) => continue,
+        };
+
+        if file_type.is_dir() {
+            subdirs.push(entry.path());
+        } else if file_type.is_file() {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            let path = entry.path();
+            state.record_file(&path, size, local_extensions);
+            files.push(UsageNode {
+                name: entry.file_name().to_string_lossy().to_string(),
+                path,
+                size,
+                file_count: 1,
+                kind: UsageKind::File,
+                children: Vec::new(),
+            });
+        }
+    }
+
+    (subdirs, files)
+}
+
+/// 创建目录节点：汇总大小，子节点按大小排序，过多的小文件合并为一个节点
+fn make_dir_node(path: &Path, mut children: Vec<UsageNode>, mut files: Vec<UsageNode>) -> UsageNode {
+    files.sort_by_key(|file| Reverse(file.size));
+    if files.len() > MAX_FILES_PER_DIR {
+        let rest = files.split_off(MAX_FILES_PER_DIR);
+        files.push(UsageNode {
+            name: format!("其他 {} 个文件", rest.len()),
+            path: path.to_path_buf(),
+            size: rest.iter().map(|file| file.size).sum(),
+            file_count: rest.len() as u64,
+            kind: UsageKind::Others,
+            children: Vec::new(),
+        });
+    }
+
+    children.extend(files);
+    children.sort_by_key(|child| Reverse(child.size));
+
+    UsageNode {
+        name: path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string()),
+        path: path.to_path_buf(),
+        size: children.iter().map(|child| child.size).sum(),
+        file_count: children.iter().map(|child| child.file_count).sum(),
+        kind: UsageKind::Directory,
+        children,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 在临时目录中创建指定大小的文件
+    fn write_file(root: &Path, relative: &str, size: usize) {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![b'x'; size]).unwrap();
+    }
+
+    #[test]
+    fn scan_totals_sizes_and_extensions() {
+        let root = std::env::temp_dir().join(format!("disk-usage-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        write_file(&root, "a.txt", 10);
+        write_file(&root, "b.TXT", 5);
+        write_file(&root, "sub/c.rs", 100);
+        write_file(&root, "sub/deep/d", 1);
+        write_file(&root, "sub/deep/e.txt", 50);
+        fs::create_dir_all(root.join("empty")).unwrap();
+
+        let report = scan(&root, &mut JobContext::detached()).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(report.root.size, 166);
+        assert_eq!(report.root.file_count, 5);
+        assert_eq!(report.error_count, 0);
+        let deep = report.root.find(&root.join("sub/deep")).unwrap();
+        assert_eq!((deep.size, deep.children.len()), (51, 2));
+        assert!(report.root.find(&root.join("empty")).is_some());
+
+        let totals: Vec<(&str, u64, u64)> = report.extensions
+            .iter()
+            .map(|total| (total.extension.as_str(), total.bytes, total.count))
+            .collect();
+        assert_eq!(totals, vec![("rs", 100, 1), ("txt", 65, 3), ("", 1, 1)]);
+
+        let sizes: Vec<u64> = report.largest_files.iter().map(|(_, size)| *size).collect();
+        assert_eq!(sizes, vec![100, 50, 10, 5, 1]);
+        assert_eq!(report.largest_files[0].0, root.join("sub/c.rs"));
+    }
+
+    #[test]
+    fn largest_files_keep_top_n() {
+        let root = std::env::temp_dir().join(format!("disk-usage-top-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let count = TOP_FILES + 5;
+        for i in 0..count {
+            // 分散在多层目录中，由不同的线程统计
+            write_file(&root, &format!("d{}/d{}/f{}.bin", i % 3, i % 7, i), i);
+        }
+
+        let report = scan(&root, &mut JobContext::detached()).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(report.largest_files.len(), TOP_FILES);
+        assert_eq!(report.largest_files.first().map(|(_, size)| *size), Some(count as u64 - 1));
+        assert_eq!(report.largest_files.last().map(|(_, size)| *size), Some(5));
+        assert!(report.largest_files.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert_eq!(report.root.file_count, count as u64);
+        assert_eq!(report.extensions.len(), 1);
+        assert_eq!(report.extensions[0].count, count as u64);
+    }
+}
@@ -0,0 +1,549 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use druid::im::Vector;
+use druid::{Data, Lens};
+
+use crate::jobs::JobContext;
+
+/// 部分哈希读取的字节数
+const PARTIAL_HASH_SIZE: u64 = 64 * 1024;
+
+/// 读取文件时的缓冲区大小
+const READ_BUFFER_SIZE: usize = 256 * 1024;
+
+/// 自动选择时保留哪个文件
+#[derive(Clone, Copy, Data, Debug, PartialEq, Eq)]
+pub enum KeepRule {
+    /// 保留修改时间最新的文件
+    Newest,
+    /// 保留修改时间最早的文件
+    Oldest,
+    /// 保留路径最短的文件
+    ShortestPath,
+}
+
+/// 对选中的重复文件执行的操作
+#[derive(Clone, Copy, Data, Debug, PartialEq, Eq)]
+pub enum DuplicateAction {
+    /// 移到回收站
+    Trash,
+    /// 替换为指向保留文件的硬链接
+    HardLink,
+}
+
+/// 一组内容相同的文件中的一个
+#[derive(Clone, Data, Lens, Debug, PartialEq)]
+pub struct DuplicateFile {
+    #[data(same_fn = "PartialEq::eq")]
+    pub path: PathBuf,
+    /// 修改时间
+    #[data(same_fn = "PartialEq::eq")]
+    pub modified: Option<SystemTime>,
+    /// 是否选中（选中的文件将被删除或替换）
+    pub selected: bool,
+}
+
+/// 内容相同的一组文件
+#[derive(Clone, Data, Lens, Debug, PartialEq)]
+pub struct DuplicateGroup {
+    /// 每个文件的大小
+    pub size: u64,
+    pub files: Vector<DuplicateFile>,
+}
+
+impl DuplicateGroup {
+    /// 只保留一个文件时可以释放的空间
+    pub fn reclaimable(&self) -> u64 {
+        self.size * (self.files.len() as u64).saturating_sub(1)
+    }
+
+    /// 按规则选中除保留文件以外的所有文件
+    pub fn auto_select(&mut self, rule: KeepRule) {
+        let keep = match rule {
+            KeepRule::Newest => self.files.iter().enumerate().max_by_key(|(_, file)| file.modified),
+            KeepRule::Oldest => self.files.iter().enumerate().min_by_key(|(_, file)| file.modified),
+            KeepRule::ShortestPath => self.files.iter().enumerate()
+                .min_by_key(|(_, file)| file.path.as_os_str().len()),
+        }
+        .map(|(index, _)| index);
+
+        for (index, file) in self.files.iter_mut().enumerate() {
+            file.selected = Some(index) != keep;
+        }
+    }
+
+    /// 保留的文件：第一个未选中的文件
+    fn keeper(&self) -> Option<&DuplicateFile> {
+        self.files.iter().find(|file| !file.selected)
+    }
+}
+
+/// 重复文件窗口的状态
+#[derive(Clone, Data, Lens, Debug, PartialEq)]
+pub struct DuplicateState {
+    /// 查找的目录
+    #[data(same_fn = "PartialEq::eq")]
+    pub root: PathBuf,
+    /// 查找任务ID
+    pub scan_job_id: Option<u64>,
+    /// 删除或替换任务ID
+    pub apply_job_id: Option<u64>,
+    /// 找到的重复文件组（按可释放空间从大到小）
+    pub groups: Vector<DuplicateGroup>,
+    /// 查找是否已经完成
+    pub finished: bool,
+    pub keep_rule: KeepRule,
+    pub action: DuplicateAction,
+    /// 校验错误等提示
+    pub notice: String,
+}
+
+impl Default for DuplicateState {
+    fn default() -> Self {
+        Self {
+            root: PathBuf::new(),
+            scan_job_id: None,
+            apply_job_id: None,
+            groups: Vector::new(),
+            finished: false,
+            keep_rule: KeepRule::Newest,
+            action: DuplicateAction::Trash,
+            notice: String::new(),
+        }
+    }
+}
+
+impl DuplicateState {
+    /// 开始查找新的目录
+    pub fn start(root: &Path, job_id: u64) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            scan_job_id: Some(job_id),
+            ..Default::default()
+        }
+    }
+
+    /// 所有组可释放的空间
+    pub fn total_reclaimable(&self) -> u64 {
+        self.groups.iter().map(DuplicateGroup::reclaimable).sum()
+    }
+
+    /// 选中的文件数量和大小
+    pub fn selected_summary(&self) -> (u64, u64) {
+        self.groups.iter().fold((0, 0), |(count, bytes), group| {
+            let selected = group.files.iter().filter(|file| file.selected).count() as u64;
+            (count + selected, bytes + selected * group.size)
+        })
+    }
+
+    /// 按规则自动选择所有组
+    pub fn auto_select(&mut self) {
+        let rule = self.keep_rule;
+        for group in self.groups.iter_mut() {
+            group.auto_select(rule);
+        }
+    }
+
+    /// 操作结束后移除已处理的文件，只剩一个文件的组被移除
+    ///
+    /// `failed` 中的文件保留选中状态；操作被取消时不知道哪些文件已经处理，只移除已经不存在的文件
+    /// （替换为硬链接可以安全地重复执行）。
+    pub fn remove_processed(&mut self, failed: &HashSet<PathBuf>, cancelled: bool) {
+        for group in self.groups.iter_mut() {
+            group.files.retain(|file| {
+                if cancelled {
+                    file.path.exists()
+                } else {
+                    !file.selected || failed.contains(&file.path)
+                }
+            });
+        }
+        self.groups.retain(|group| group.files.len() > 1);
+    }
+
+    /// 检查选择是否可以执行：每组至少保留一个文件
+    pub fn validate_selection(&self) -> Result<(), String> {
+        if self.selected_summary().0 == 0 {
+            return Err("没有选中任何文件".to_string());
+        }
+        match self.groups.iter().find(|group| group.keeper().is_none()) {
+            Some(group) => Err(format!(
+                "每组至少需要保留一个文件：{}",
+                group.files[0].path.display()
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 查找目录中的重复文件（在后台任务中运行），任务被取消时返回 None
+///
+/// 先按大小分组，再依次比较前 64KB 的哈希和完整内容的哈希，只有三步都相同的文件才算重复。
+/// 不跟随符号链接；已经互为硬链接的文件只统计一次，空文件不参与比较。
+pub fn find_duplicates(root: &Path, job: &mut JobContext) -> Option<Vector<DuplicateGroup>> {
+    let by_size = collect_by_size(root, job)?;
+
+    let mut groups = Vec::new();
+    let mut hashed = 0;
+    for (size, paths) in by_size {
+        if paths.len() < 2 {
+            continue;
+        }
+
+        let mut by_partial: HashMap<blake3::Hash, Vec<PathBuf>> = HashMap::new();
+        for path in paths {
+            if job.is_cancelled() {
+                return None;
+            }
+            match hash_file(&path, Some(PARTIAL_HASH_SIZE)) {
+                Ok(hash) => by_partial.entry(hash).or_default().push(path),
+                Err(e) => job.failed(&path, e),
+            }
+            hashed += 1;
+            job.set_processed(hashed);
+        }
+
+        for candidates in by_partial.into_values().filter(|paths| paths.len() > 1) {
+            // 小于部分哈希长度的文件已经比较过完整内容
+            let full_groups = if size <= PARTIAL_HASH_SIZE {
+                vec![candidates]
+            } else {
+                let mut by_full: HashMap<blake3::Hash, Vec<PathBuf>> = HashMap::new();
+                for path in candidates {
+                    if job.is_cancelled() {
+                        return None;
+                    }
+                    match hash_file(&path, None) {
+                        Ok(hash) => by_full.entry(hash).or_default().push(path),
+                        Err(e) => job.failed(&path, e),
+                    }
+                }
+                by_full.into_values().collect()
+            };
+
+            groups.extend(
+                full_groups
+                    .into_iter()
+                    .filter(|paths| paths.len() > 1)
+                    .map(|paths| make_group(size, paths)),
+            );
+        }
+    }
+
+    groups.sort_by_key(|group| Reverse(group.reclaimable()));
+    Some(groups.into_iter().collect())
+}
+
+/// 遍历目录，按文件大小分组
+fn collect_by_size(root: &Path, job: &mut JobContext) -> Option<HashMap<u64, Vec<PathBuf>>> {
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    let mut seen_inodes = HashSet::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        if job.is_cancelled() {
+            return None;
+        }
+
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                job.failed(&dir, format!("无法读取目录: {}", e));
+                continue;
+            }
+        };
+
+        for entry in entries.filter_map(Result::ok) {
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(_) => continue,
+            };
+
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() {
+                let metadata = match entry.metadata() {
+                    Ok(metadata) => metadata,
+                    Err(_) => continue,
+                };
+                if metadata.len() == 0 {
+                    continue;
+                }
+                // 互为硬链接的文件不占用额外空间
+                if let Some(id) = file_id(&metadata) {
+                    if !seen_inodes.insert(id) {
+                        continue;
+                    }
+                }
+                by_size.entry(metadata.len()).or_default().push(entry.path());
+            }
+        }
+    }
+
+    Some(by_size)
+}
+
+/// 创建重复文件组，文件按路径排序
+fn make_group(size: u64, mut paths: Vec<PathBuf>) -> DuplicateGroup {
+    paths.sort();
+    DuplicateGroup {
+        size,
+        files: paths
+            .into_iter()
+            .map(|path| DuplicateFile {
+                modified: fs::metadata(&path).and_then(|m| m.modified()).ok(),
+                path,
+                selected: false,
+            })
+            .collect(),
+    }
+}
+
+/// 计算文件内容（或前 `limit` 个字节）的哈希
+pub fn hash_file(path: &Path, limit: Option<u64>) -> io::Result<blake3::Hash> {
+    let file = File::open(path)?;
+    let mut reader: Box<dyn Read> = match limit {
+        Some(limit) => Box::new(file.take(limit)),
+        None => Box::new(file),
+    };
+
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = vec![0; READ_BUFFER_SIZE];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize())
+}
+
+/// 对选中的文件执行操作（在后台任务中运行），失败的文件记录到任务中
+pub fn apply(groups: &[DuplicateGroup], action: DuplicateAction, job: &mut JobContext) {
+    for group in groups {
+        let keeper = match group.keeper() {
+            Some(keeper) => keeper,
+            None => continue,
+        };
+
+        for file in group.files.iter().filter(|file| file.selected) {
+            if job.is_cancelled() {
+                return;
+            }
+
+            // 保留的文件可能在查找之后被修改，确认内容仍然相同再处理
+            let result = verify_unchanged(&keeper.path, &file.path, group.size)
+                .map_err(|e| e.to_string())
+                .and_then(|_| match action {
+                    DuplicateAction::Trash => trash::delete(&file.path).map_err(|e| e.to_string()),
+                    DuplicateAction::HardLink => replace_with_link(&keeper.path, &file.path)
+                        .map_err(|e| e.to_string()),
+                });
+            match result {
+                Ok(()) => job.processed(),
+                Err(e) => job.failed(&file.path, e),
+            }
+        }
+    }
+}
+
+/// 确认 `keeper` 和 `target` 的内容仍然相同
+///
+/// 查找之后两个文件都可能被原地修改（大小不变时也是如此），因此重新比较大小和完整哈希。
+fn verify_unchanged(keeper: &Path, target: &Path, expected_size: u64) -> io::Result<()> {
+    for path in [keeper, target] {
+        if fs::symlink_metadata(path)?.len() != expected_size {
+            return Err(io::Error::other("文件在查找之后被修改"));
+        }
+    }
+    if hash_file(keeper, None)? != hash_file(target, None)? {
+        return Err(io::Error::other("文件内容在查找之后被修改"));
+    }
+    Ok(())
+}
+
+/// 将 `target` 替换为指向 `keeper` 的硬链接
+///
+/// 在同一目录中创建临时链接，重命名覆盖目标，失败时目标文件保持不变。
+fn replace_with_link(keeper: &Path, target: &Path) -> io::Result<()> {
+    let file_name = target.file_name().unwrap_or_default().to_string_lossy();
+    let temp = target.with_file_name(format!(".{}.link-tmp", file_name));
+    fs::hard_link(keeper, &temp)?;
+    fs::rename(&temp, target).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// 修改时间为 `age` 秒前的文件
+    fn file(path: &str, age: Option<u64>) -> DuplicateFile {
+        DuplicateFile {
+            path: PathBuf::from(path),
+            modified: age.map(|age| SystemTime::UNIX_EPOCH + Duration::from_secs(1000 - age)),
+            selected: false,
+        }
+    }
+
+    fn group(files: Vec<DuplicateFile>) -> DuplicateGroup {
+        DuplicateGroup { size: 10, files: files.into_iter().collect() }
+    }
+
+    /// 未选中的文件
+    fn kept(group: &DuplicateGroup) -> Vec<&Path> {
+        group.files.iter().filter(|file| !file.selected).map(|file| file.path.as_path()).collect()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("duplicates-{}-test-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn auto_select_keeps_one_file_per_rule() {
+        let mut group = group(vec![
+            file("/data/long/path/a", Some(30)),
+            file("/data/b", Some(10)),
+            file("/data/some/c", Some(20)),
+        ]);
+
+        group.auto_select(KeepRule::Newest);
+        assert_eq!(kept(&group), vec![Path::new("/data/b")]);
+        group.auto_select(KeepRule::Oldest);
+        assert_eq!(kept(&group), vec![Path::new("/data/long/path/a")]);
+        group.auto_select(KeepRule::ShortestPath);
+        assert_eq!(kept(&group), vec![Path::new("/data/b")]);
+        assert_eq!(group.files.iter().filter(|file| file.selected).count(), 2);
+    }
+
+    #[test]
+    fn validate_selection_requires_a_keeper() {
+        let mut state = DuplicateState {
+            groups: vec![
+                group(vec![file("/a1", None), file("/a2", None)]),
+                group(vec![file("/b1", None), file("/b2", None), file("/b3", None)]),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        assert!(state.validate_selection().is_err());
+
+        state.auto_select();
+        assert!(state.validate_selection().is_ok());
+        assert_eq!(state.selected_summary(), (3, 30));
+
+        // 第二组的所有文件都被选中
+        state.groups[1].files[0].selected = true;
+        state.groups[1].files[1].selected = true;
+        state.groups[1].files[2].selected = true;
+        let error = state.validate_selection().unwrap_err();
+        assert!(error.contains("/b1"), "{}", error);
+    }
+
+    #[test]
+    fn remove_processed_drops_handled_files() {
+        let mut a = group(vec![file("/a1", None), file("/a2", None)]);
+        a.files[1].selected = true;
+        let mut b = group(vec![file("/b1", None), file("/b2", None), file("/b3", None)]);
+        b.files[1].selected = true;
+        b.files[2].selected = true;
+        let mut state = DuplicateState { groups: vec![a, b].into_iter().collect(), ..Default::default() };
+
+        // 第一组只剩一个文件而被移除；第二组处理失败的文件保留
+        let failed = [PathBuf::from("/b3")].into_iter().collect();
+        state.remove_processed(&failed, false);
+        assert_eq!(state.groups.len(), 1);
+        assert_eq!(kept(&state.groups[0]), vec![Path::new("/b1")]);
+        assert!(state.groups[0].files[1].selected);
+    }
+
+    #[test]
+    fn remove_processed_after_cancel_keeps_existing_files() {
+        let dir = temp_dir("cancel");
+        let existing = [dir.join("x1"), dir.join("x2")];
+        for path in &existing {
+            fs::write(path, "same").unwrap();
+        }
+        let files = vec![
+            DuplicateFile { path: existing[0].clone(), modified: None, selected: false },
+            DuplicateFile { path: existing[1].clone(), modified: None, selected: true },
+            DuplicateFile { path: dir.join("gone"), modified: None, selected: true },
+        ];
+        let mut state = DuplicateState { groups: vec![group(files)].into_iter().collect(), ..Default::default() };
+
+        state.remove_processed(&HashSet::new(), true);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let paths: Vec<&PathBuf> = state.groups[0].files.iter().map(|file| &file.path).collect();
+        assert_eq!(paths, vec![&existing[0], &existing[1]]);
+    }
+
+    #[test]
+    fn find_duplicates_compares_full_content() {
+        let dir = temp_dir("find");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), "hello").unwrap();
+        fs::write(dir.join("sub/b.txt"), "hello").unwrap();
+        fs::write(dir.join("c.txt"), "world").unwrap();
+        fs::write(dir.join("empty1"), "").unwrap();
+        fs::write(dir.join("empty2"), "").unwrap();
+
+        // 前64KB相同、结尾不同的大文件不算重复
+        let mut big = vec![7u8; PARTIAL_HASH_SIZE as usize + 100];
+        fs::write(dir.join("big1"), &big).unwrap();
+        fs::write(dir.join("sub/big3"), &big).unwrap();
+        *big.last_mut().unwrap() = 8;
+        fs::write(dir.join("big2"), &big).unwrap();
+
+        let groups = find_duplicates(&dir, &mut JobContext::detached()).unwrap();
+        let paths: Vec<Vec<PathBuf>> = groups
+            .iter()
+            .map(|group| group.files.iter().map(|file| file.path.clone()).collect())
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(paths, vec![
+            vec![dir.join("big1"), dir.join("sub/big3")],
+            vec![dir.join("a.txt"), dir.join("sub/b.txt")],
+        ]);
+        assert_eq!(groups[0].size, PARTIAL_HASH_SIZE + 100);
+    }
+
+    #[test]
+    fn verify_unchanged_detects_modified_files() {
+        let dir = temp_dir("verify");
+        let (keeper, target) = (dir.join("keeper"), dir.join("target"));
+        fs::write(&keeper, "same").unwrap();
+        fs::write(&target, "same").unwrap();
+        assert!(verify_unchanged(&keeper, &target, 4).is_ok());
+
+        // 大小不变的原地修改
+        fs::write(&keeper, "diff").unwrap();
+        assert!(verify_unchanged(&keeper, &target, 4).is_err());
+        fs::write(&keeper, "longer").unwrap();
+        assert!(verify_unchanged(&keeper, &target, 4).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,137 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::dir_compare::copy_file;
+use crate::jobs::JobContext;
+
+/// 检查能否将文件移动或复制到目标目录，不能时返回原因
+///
+/// 拖放时用于决定是否高亮目标，放下时再检查一次。放回原来所在的目录
+/// 无论移动还是复制都会与自身重名，因此同样拒绝。
+pub fn validate_drop(sources: &[PathBuf], target: &Path) -> Result<(), String> {
+    if sources.is_empty() {
+        return Err("没有要处理的文件".to_string());
+    }
+    if !target.is_dir() {
+        return Err(format!("{} 不是目录", target.display()));
+    }
+
+    for source in sources {
+        if source.parent().is_none() || source.symlink_metadata().is_err() {
+            return Err(format!("不能移动或复制 {}", source.display()));
+        }
+        if source == target {
+            return Err(format!("不能将 {} 放到自身", source.display()));
+        }
+        if source.is_dir() && target.starts_with(source) {
+            return Err(format!("不能将 {} 放到它的子目录中", source.display()));
+        }
+        if source.parent() == Some(target) {
+            return Err(format!("{} 已经在目标目录中", source.display()));
+        }
+    }
+    Ok(())
+}
+
+/// 将文件和目录移动或复制到目标目录，每处理完一项记录一次进度
+///
+/// 目标目录中已有同名条目时记为失败，不覆盖。移动时先尝试重命名，
+/// 跨文件系统时改为复制再删除原文件。取消时删除复制了一部分的目标。
+pub fn transfer(sources: &[PathBuf], target: &Path, copy: bool, job: &mut JobContext) {
+    for source in sources {
+        if job.is_cancelled() {
+            return;
+        }
+
+        let name = match source.file_name() {
+            Some(name) => name,
+            None => {
+                job.failed(source, "无法确定文件名");
+                continue;
+            }
+        };
+        let destination = target.join(name);
+        if destination.symlink_metadata().is_ok() {
+            job.failed(source, format!("目标中已存在 {}", destination.display()));
+            continue;
+        }
+
+        let result = if copy {
+            copy_recursive(source, &destination, job)
+        } else {
+            move_entry(source, &destination, job)
+        };
+        match result {
+            Ok(()) => job.processed(),
+            Err(e) => {
+                // 目标原来不存在，取消时留下的只是复制了一部分的内容
+                if job.is_cancelled() && destination.symlink_metadata().is_ok() {
+                    if let Err(e) = remove_entry(&destination) {
+                        job.failed(&destination, format!("无法删除未完成的复制: {}", e));
+                    }
+                }
+                job.failed(source, e);
+            }
+        }
+    }
+}
+
+/// 移动一个条目，跨文件系统不能重命名时复制后删除原条目，其他重命名错误直接返回
+fn move_entry(source: &Path, destination: &Path, job: &JobContext) -> io::Result<()> {
+    match fs::rename(source, destination) {
+        Ok(()) => return Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {}
+        Err(e) => return Err(e),
+    }
+
+    copy_recursive(source, destination, job)?;
+    if job.is_cancelled() {
+        return Err(io::Error::new(io::ErrorKind::Interrupted, "已取消，原文件保留"));
+    }
+    remove_entry(source)
+}
+
+/// 删除文件或整个目录，符号链接只删除链接本身
+fn remove_entry(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// 复制文件或整个目录，符号链接按链接本身复制
+fn copy_recursive(source: &Path, destination: &Path, job: &JobContext) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(source)?;
+    if metadata.file_type().is_symlink() {
+        return copy_symlink(source, destination);
+    }
+    if !metadata.is_dir() {
+        return copy_file(source, destination);
+    }
+
+    fs::create_dir_all(destination)?;
+    for entry in fs::read_dir(source)? {
+        if job.is_cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "已取消"));
+        }
+        let entry = entry?;
+        copy_recursive(&entry.path(), &destination.join(entry.file_name()), job)?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, destination: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, destination)
+}
+
+#[cfg(windows)]
+fn copy_symlink(source: &Path, destination: &Path) -> io::Result<()> {
+    let link = fs::read_link(source)?;
+    if source.is_dir() {
+        std::os::windows::fs::symlink_dir(link, destination)
+    } else {
+        std::os::windows::fs::symlink_file(link, destination)
+    }
+}
@@ -1,10 +1,18 @@
+use std::fs::{DirEntry, Metadata};
 use std::path::Path;
 use std::path::PathBuf;
 use std::time::SystemTime;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex, Once};
 use druid::im::Vector;
+use crate::file_types;
 use crate::models::{FileItem, FileDetail};
+use crate::settings;
+use crate::utils::format_modified_time;
+#[cfg(target_os = "linux")]
+use crate::volumes;
+#[cfg(target_os = "windows")]
+use crate::volumes::Volume;
 
 // 全局目录缓存
 lazy_static::lazy_static! {
@@ -17,8 +25,6 @@ lazy_static::lazy_static! {
 
 // 缓存有效期（秒）
 const CACHE_TTL: u64 = 30;
-// 缓存大小限制
-const MAX_CACHE_ENTRIES: usize = 100;
 
 // 带时间戳的缓存条目
 struct CacheEntry<T> {
@@ -29,7 +35,7 @@ struct CacheEntry<T> {
 /// 清理过期缓存
 fn clean_cache() {
     let mut cache = DIRECTORY_CACHE.lock().unwrap();
-    if cache.len() > MAX_CACHE_ENTRIES {
+    if cache.len() > settings::current().max_cache_entries {
         // 如果缓存太大，清理一半
         let paths: Vec<PathBuf> = cache.keys().cloned().collect();
         for i in 0..(paths.len() / 2) {
@@ -38,8 +44,61 @@ fn clean_cache() {
     }
 }
 
+/// 判断文件或目录是否为隐藏项
+///
+/// 名称以点号开头的视为隐藏（Unix约定）；在Windows上还会检查隐藏和系统属性
+pub fn is_hidden(name: &str, metadata: Option<&Metadata>) -> bool {
+    if name.starts_with('.') {
+        return true;
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::fs::MetadataExt;
+        use windows_sys::Win32::Storage::FileSystem::{FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_SYSTEM};
+
+        if let Some(metadata) = metadata {
+            if metadata.file_attributes() & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0 {
+                return true;
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    let _ = metadata;
+
+    false
+}
+
+/// 隐藏文件的可见性策略，目录树、文件列表和条目计数共用同一策略
+#[derive(Clone, Copy, Debug)]
+pub struct VisibilityPolicy {
+    /// 是否显示隐藏文件
+    pub show_hidden: bool,
+}
+
+impl VisibilityPolicy {
+    /// 根据当前设置创建策略
+    pub fn current() -> Self {
+        Self {
+            show_hidden: settings::current().show_hidden,
+        }
+    }
+
+    /// 判断目录项是否应当显示
+    pub fn is_visible(&self, entry: &DirEntry) -> bool {
+        if self.show_hidden {
+            return true;
+        }
+
+        let name = entry.file_name().to_string_lossy().to_string();
+        !is_hidden(&name, entry.metadata().ok().as_ref())
+    }
+}
+
 /// 获取系统上所有可用的驱动器（盘符）
 /// 在Windows上返回所有可用的盘符（如C:, D:等）
+/// 在Linux上返回所有挂载的真实文件系统（见 `volumes` 模块）
 /// 在其他系统上返回根目录 "/"
 pub fn get_drives() -> Vec<FileItem> {
     let mut drives = Vec::new();
@@ -56,20 +115,52 @@ pub fn get_drives() -> Vec<FileItem> {
                 // 预先加载每个驱动器的子目录
                 let children = build_file_tree(&path, 1);
                 
+                let (free, total) = crate::system::get_drive_space(&path);
+                let volume = Volume {
+                    label: drive.clone(),
+                    fs_type: String::new(),
+                    device: drive.clone(),
+                    removable: false,
+                    free,
+                    total,
+                };
+                
                 drives.push(FileItem {
                     name: drive.clone(),
                     children, // 直接加载子目录，不再是空的
                     is_expanded: true, // 默认展开
                     path,
                     is_selected: false,
+                    is_dir: true,
+                    diff: None,
+                    volume: Some(volume),
+                    is_hidden: false,
                 });
             }
         }
     }
     
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(target_os = "linux")]
+    {
+        // 在Linux上列出所有挂载的真实文件系统，并预加载子目录
+        for (path, volume) in volumes::list_volumes() {
+            drives.push(FileItem {
+                name: volume.label.clone(),
+                children: build_file_tree(&path, 1),
+                is_expanded: true,
+                path,
+                is_selected: false,
+                is_dir: true,
+                diff: None,
+                volume: Some(volume),
+                is_hidden: false,
+            });
+        }
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
     {
-        // 在非Windows系统上，只添加根目录，并预加载子目录
+        // 在其他系统上，只添加根目录，并预加载子目录
         let root_path = PathBuf::from("/");
         let children = build_file_tree(&root_path, 1);
         
@@ -79,6 +170,10 @@ pub fn get_drives() -> Vec<FileItem> {
             is_expanded: true,
             path: root_path,
             is_selected: false,
+            is_dir: true,
+            diff: None,
+            volume: None,
+            is_hidden: false,
         });
     }
     
@@ -102,6 +197,7 @@ pub fn build_file_tree(path: &Path, depth: usize) -> Vec<FileItem> {
     }
 
     let mut directories = Vec::new();
+    let policy = VisibilityPolicy::current();
     
     // 读取目录内容
     if let Ok(entries) = std::fs::read_dir(path) {
@@ -117,11 +213,14 @@ pub fn build_file_tree(path: &Path, depth: usize) -> Vec<FileItem> {
                 // 获取目录名称
                 let name = path_buf.file_name().unwrap_or_default().to_string_lossy().to_string();
                 
-                // 跳过隐藏目录（以点号开头的目录，如.git）
-                if name.starts_with(".") {
+                // 按可见性策略跳过隐藏目录（如.git）
+                if !policy.is_visible(&entry) {
                     continue;
                 }
                 
+                // 只有显示隐藏文件时隐藏目录才会出现在这里，目录树中淡化显示
+                let is_hidden = is_hidden(&name, entry.metadata().ok().as_ref());
+
                 // 递归遍历子目录
                 let children = build_file_tree(&path_buf, depth - 1);
                 directories.push(FileItem { 
@@ -130,6 +229,10 @@ pub fn build_file_tree(path: &Path, depth: usize) -> Vec<FileItem> {
                     is_expanded: false,
                     path: path_buf,
                     is_selected: false,
+                    is_dir: true,
+                    diff: None,
+                    volume: None,
+                    is_hidden,
                 });
             }
         }
@@ -144,7 +247,8 @@ pub fn build_file_tree(path: &Path, depth: usize) -> Vec<FileItem> {
 /// 获取指定目录下的文件详情列表（包含子目录和文件）
 /// 默认一次加载页面显示所需数量
 pub fn get_directory_contents(path: &Path) -> Vector<FileDetail> {
-    get_directory_contents_paged(path, 0, 30) // 默认只加载前30个，足够填满一个屏幕
+    // 默认只加载设置中指定的一页（默认30个），足够填满一个屏幕
+    get_directory_contents_paged(path, 0, settings::current().page_size)
 }
 
 /// 获取指定目录下的文件详情列表（包含子目录和文件），优先从缓存加载，支持分页
@@ -177,6 +281,7 @@ pub fn get_directory_contents_paged(path: &Path, offset: usize, limit: usize) ->
     // 如果缓存中没有或数量不足，从文件系统加载
     let mut result = Vec::new();
     let mut count = 0;
+    let settings = settings::current();
     
     // 如果目录不存在，直接返回空列表
     if !path.exists() || !path.is_dir() {
@@ -187,7 +292,12 @@ pub fn get_directory_contents_paged(path: &Path, offset: usize, limit: usize) ->
     // 先收集所有目录项
     if let Ok(entries) = std::fs::read_dir(path) {
         // 转换为Vec以便排序和分页
-        let mut entry_vec: Vec<_> = entries.filter_map(Result::ok).collect();
+        // 先按可见性策略过滤，保证分页的偏移量与显示的条目一致
+        let policy = VisibilityPolicy::current();
+        let mut entry_vec: Vec<_> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| policy.is_visible(entry))
+            .collect();
         
         // 按照名称排序，同时把目录放在前面
         entry_vec.sort_by(|a, b| {
@@ -208,10 +318,8 @@ pub fn get_directory_contents_paged(path: &Path, offset: usize, limit: usize) ->
             let entry_path = entry.path();
             let name = entry.file_name().to_string_lossy().to_string();
             
-            // 跳过隐藏文件和目录
-            if name.starts_with(".") {
-                continue;
-            }
+            // 显示隐藏文件时用于淡化显示
+            let is_hidden = is_hidden(&name, entry.metadata().ok().as_ref());
             
             // 获取文件大小（仅对文件）
             let size = if entry_path.is_file() {
@@ -220,39 +328,15 @@ pub fn get_directory_contents_paged(path: &Path, offset: usize, limit: usize) ->
                 0 // 目录大小显示为0
             };
             
-            // 获取文件类型
-            let file_type = if entry_path.is_dir() {
-                "目录".to_string()
-            } else {
-                match entry_path.extension() {
-                    Some(ext) => format!("{} 文件", ext.to_string_lossy()),
-                    None => "文件".to_string()
-                }
-            };
+            // 从文件类型注册表获取文件类型
+            // 文件内容的识别在行显示时进行
+            let kind = file_types::kind_for_path(&entry_path, entry_path.is_dir());
+            let kind_detected = kind.is_directory();
             
-            // 简化获取修改时间的逻辑以提高性能
+            // 按设置中的时间格式显示修改时间
             let modified = std::fs::metadata(&entry_path)
                 .and_then(|m| m.modified())
-                .map(|time| {
-                    let system_time = std::time::SystemTime::now();
-                    let duration = system_time.duration_since(time).unwrap_or_default();
-                    
-                    if duration.as_secs() < 60 {
-                        "刚刚".to_string()
-                    } else if duration.as_secs() < 3600 {
-                        format!("{} 分钟前", duration.as_secs() / 60)
-                    } else if duration.as_secs() < 86400 {
-                        format!("{} 小时前", duration.as_secs() / 3600)
-                    } else {
-                        // 简单格式化为 年-月-日
-                        let secs = time.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs();
-                        let days = secs / 86400;
-                        let years = 1970 + (days / 365);
-                        let months = (days % 365) / 30 + 1;
-                        let day = (days % 365) % 30 + 1;
-                        format!("{}-{:02}-{:02}", years, months, day)
-                    }
-                })
+                .map(|time| format_modified_time(time, &settings.date_format))
                 .unwrap_or_else(|_| "未知".to_string());
             
             // 保存完整路径以便导航
@@ -261,9 +345,15 @@ pub fn get_directory_contents_paged(path: &Path, offset: usize, limit: usize) ->
             result.push(FileDetail {
                 name,
                 size,
-                file_type,
+                kind,
+                kind_detected,
+                folder_size: None,
                 modified,
                 full_path,
+                is_hidden,
+                is_selected: false,
+                is_focused: false,
+                name_matches: Vec::new(),
             });
             
             count += 1;
@@ -285,7 +375,8 @@ pub fn get_directory_contents_paged(path: &Path, offset: usize, limit: usize) ->
         cache.insert(path_buf, result_vector.clone());
         
         // 如果缓存过大，清理部分缓存
-        if cache.len() > MAX_CACHE_ENTRIES {
+        if cache.len() > settings.max_cache_entries {
+            drop(cache);
             clean_cache();
         }
     }
@@ -307,8 +398,8 @@ pub fn preload_directory(path: &Path) {
     // 在后台线程加载目录内容
     let path_buf = path.to_path_buf();
     std::thread::spawn(move || {
-        // 加载大量文件（10000足够覆盖大多数目录）
-        let contents = get_directory_contents_paged(&path_buf, 0, 10000);
+        // 加载大量文件（默认10000足够覆盖大多数目录）
+        let contents = get_directory_contents_paged(&path_buf, 0, settings::current().background_load_limit);
         let mut cache = DIRECTORY_CACHE.lock().unwrap();
         cache.insert(path_buf, contents);
     });
@@ -320,18 +411,36 @@ pub fn invalidate_cache(path: &Path) {
     cache.remove(&path.to_path_buf());
 }
 
+/// 清除所有目录缓存（设置变更后列表内容可能不同）
+pub fn clear_cache() {
+    let mut cache = DIRECTORY_CACHE.lock().unwrap();
+    cache.clear();
+}
+
+/// 更新缓存中某个文件的详情（内容识别、目录大小计算完成后调用）
+pub fn update_cached_detail(path: &Path, mut update: impl FnMut(&mut FileDetail)) {
+    let parent = match path.parent() {
+        Some(parent) => parent.to_path_buf(),
+        None => return,
+    };
+
+    let mut cache = DIRECTORY_CACHE.lock().unwrap();
+    if let Some(files) = cache.get_mut(&parent) {
+        for detail in files.iter_mut().filter(|detail| detail.full_path == path) {
+            update(detail);
+        }
+    }
+}
+
 /// 获取目录中的文件和目录总数
 pub fn get_directory_item_count(path: &Path) -> usize {
+    let policy = VisibilityPolicy::current();
     if let Ok(entries) = std::fs::read_dir(path) {
-        // 过滤掉隐藏文件
-        entries.filter(|entry| {
-            if let Ok(entry) = entry {
-                let name = entry.file_name().to_string_lossy().to_string();
-                !name.starts_with(".")
-            } else {
-                false
-            }
-        }).count()
+        // 按可见性策略过滤隐藏文件
+        entries
+            .filter_map(Result::ok)
+            .filter(|entry| policy.is_visible(entry))
+            .count()
     } else {
         0
     }
@@ -0,0 +1,740 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::RwLock;
+use druid::{Color, Data};
+use serde::Deserialize;
+
+use crate::settings;
+
+/// 用户文件类型定义的文件名（位于配置目录下）
+const USER_TYPES_FILE_NAME: &str = "file_types.toml";
+
+/// 目录的类型ID
+pub const DIRECTORY_TYPE_ID: &str = "directory";
+
+/// 驱动器的类型ID
+pub const DRIVE_TYPE_ID: &str = "drive";
+
+/// 未识别文件的类型ID
+pub const UNKNOWN_TYPE_ID: &str = "unknown";
+
+// 全局文件类型注册表，启动时加载内置类型和用户类型
+lazy_static::lazy_static! {
+    static ref REGISTRY: RwLock<FileTypeRegistry> = RwLock::new(FileTypeRegistry::builtin());
+}
+
+/// 文件类别，决定默认的颜色和图标
+#[derive(Clone, Copy, Data, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileCategory {
+    Folder,
+    Drive,
+    Text,
+    Code,
+    Document,
+    Image,
+    Audio,
+    Video,
+    Archive,
+    Executable,
+    Regular,
+}
+
+impl FileCategory {
+    /// 类别名称（用于主题文件中按类别覆盖颜色）
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileCategory::Folder => "folder",
+            FileCategory::Drive => "drive",
+            FileCategory::Text => "text",
+            FileCategory::Code => "code",
+            FileCategory::Document => "document",
+            FileCategory::Image => "image",
+            FileCategory::Audio => "audio",
+            FileCategory::Video => "video",
+            FileCategory::Archive => "archive",
+            FileCategory::Executable => "executable",
+            FileCategory::Regular => "regular",
+        }
+    }
+
+    /// 是否为目录或驱动器
+    pub fn is_container(&self) -> bool {
+        matches!(self, FileCategory::Folder | FileCategory::Drive)
+    }
+
+    /// 类别的默认图标
+    pub fn default_icon(&self) -> IconKind {
+        match self {
+            FileCategory::Folder | FileCategory::Drive => IconKind::Folder,
+            FileCategory::Text | FileCategory::Document => IconKind::Document,
+            FileCategory::Code => IconKind::Code,
+            FileCategory::Image => IconKind::Image,
+            FileCategory::Audio => IconKind::Audio,
+            FileCategory::Video => IconKind::Video,
+            FileCategory::Archive => IconKind::Archive,
+            FileCategory::Executable => IconKind::Executable,
+            FileCategory::Regular => IconKind::Generic,
+        }
+    }
+}
+
+/// 文件列表中使用的图标样式
+#[derive(Clone, Copy, Data, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IconKind {
+    Folder,
+    Document,
+    Code,
+    Image,
+    Audio,
+    Video,
+    Archive,
+    Executable,
+    Generic,
+}
+
+/// 文件头部的特征字节
+#[derive(Clone, Debug, PartialEq)]
+pub struct MagicSignature {
+    /// 特征字节在文件中的偏移量
+    pub offset: usize,
+    /// 特征字节
+    pub bytes: Vec<u8>,
+}
+
+impl MagicSignature {
+    /// 从十六进制字符串创建特征（如 "89504E47"）
+    pub fn from_hex(offset: usize, hex: &str) -> Option<Self> {
+        let hex: String = hex.chars().filter(|c| !c.is_whitespace()).collect();
+        if hex.is_empty() || hex.len() % 2 != 0 {
+            return None;
+        }
+
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+
+        Some(Self { offset, bytes })
+    }
+
+    /// 检查文件头部是否与特征匹配
+    pub fn matches(&self, header: &[u8]) -> bool {
+        header.len() >= self.offset + self.bytes.len()
+            && header[self.offset..self.offset + self.bytes.len()] == self.bytes[..]
+    }
+}
+
+/// 文件类型定义
+#[derive(Clone, Debug)]
+pub struct FileTypeDef {
+    /// 类型ID（唯一）
+    pub id: String,
+    /// 所属类别
+    pub category: FileCategory,
+    /// 显示给用户的类型描述
+    pub description: String,
+    /// 扩展名（小写，不含点号）
+    pub extensions: Vec<String>,
+    /// MIME类型
+    pub mime_types: Vec<String>,
+    /// 文件头特征
+    pub magic: Vec<MagicSignature>,
+    /// 该类型专用的颜色，未设置时使用主题中类别的颜色
+    pub color: Option<Color>,
+    /// 图标样式，未设置时使用类别的默认图标
+    pub icon: Option<IconKind>,
+}
+
+impl FileTypeDef {
+    /// 创建类型定义
+    fn new(id: &str, category: FileCategory, description: &str, extensions: &[&str]) -> Self {
+        Self {
+            id: id.to_string(),
+            category,
+            description: description.to_string(),
+            extensions: extensions.iter().map(|ext| ext.to_string()).collect(),
+            mime_types: Vec::new(),
+            magic: Vec::new(),
+            color: None,
+            icon: None,
+        }
+    }
+
+    /// 设置MIME类型
+    fn mime(mut self, mime_types: &[&str]) -> Self {
+        self.mime_types = mime_types.iter().map(|mime| mime.to_string()).collect();
+        self
+    }
+
+    /// 添加文件头特征
+    fn magic(mut self, offset: usize, hex: &str) -> Self {
+        if let Some(signature) = MagicSignature::from_hex(offset, hex) {
+            self.magic.push(signature);
+        }
+        self
+    }
+
+    /// 设置类型专用颜色
+    fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// 实际使用的图标
+    pub fn icon(&self) -> IconKind {
+        self.icon.unwrap_or_else(|| self.category.default_icon())
+    }
+}
+
+/// 文件的类型信息，保存在 `FileDetail` 中
+#[derive(Clone, Data, Debug, PartialEq)]
+pub struct FileKind {
+    /// 注册表中的类型ID
+    pub type_id: String,
+    /// 所属类别
+    pub category: FileCategory,
+    /// 扩展名（小写，不含点号）
+    pub extension: String,
+    /// 显示给用户的类型描述
+    pub description: String,
+}
+
+impl FileKind {
+    /// 目录
+    pub fn directory() -> Self {
+        Self {
+            type_id: DIRECTORY_TYPE_ID.to_string(),
+            category: FileCategory::Folder,
+            extension: String::new(),
+            description: "目录".to_string(),
+        }
+    }
+
+    /// 驱动器
+    pub fn drive() -> Self {
+        Self {
+            type_id: DRIVE_TYPE_ID.to_string(),
+            category: FileCategory::Drive,
+            extension: String::new(),
+            description: "驱动器".to_string(),
+        }
+    }
+
+    /// 是否为目录或驱动器
+    pub fn is_directory(&self) -> bool {
+        self.category.is_container()
+    }
+}
+
+/// 文件类型注册表：按扩展名、MIME类型和文件头特征查找类型
+pub struct FileTypeRegistry {
+    types: Vec<FileTypeDef>,
+    by_id: HashMap<String, usize>,
+    by_extension: HashMap<String, usize>,
+    by_mime: HashMap<String, usize>,
+}
+
+impl FileTypeRegistry {
+    /// 只包含内置类型的注册表
+    pub fn builtin() -> Self {
+        let mut registry = Self {
+            types: Vec::new(),
+            by_id: HashMap::new(),
+            by_extension: HashMap::new(),
+            by_mime: HashMap::new(),
+        };
+
+        for def in builtin_types() {
+            registry.register(def);
+        }
+
+        registry
+    }
+
+    /// 注册类型定义；ID相同的定义会被替换，扩展名和MIME类型指向最后注册的定义
+    pub fn register(&mut self, def: FileTypeDef) {
+        let index = match self.by_id.get(&def.id) {
+            Some(&index) => {
+                // 移除旧定义的扩展名和MIME类型索引
+                self.by_extension.retain(|_, i| *i != index);
+                self.by_mime.retain(|_, i| *i != index);
+                self.types[index] = def;
+                index
+            }
+            None => {
+                self.types.push(def);
+                self.types.len() - 1
+            }
+        };
+
+        let def = &self.types[index];
+        self.by_id.insert(def.id.clone(), index);
+        for ext in &def.extensions {
+            self.by_extension.insert(ext.to_lowercase(), index);
+        }
+        for mime in &def.mime_types {
+            self.by_mime.insert(mime.to_lowercase(), index);
+        }
+    }
+
+    /// 按类型ID查找
+    pub fn get(&self, id: &str) -> Option<&FileTypeDef> {
+        self.by_id.get(id).map(|&index| &self.types[index])
+    }
+
+    /// 按扩展名查找
+    pub fn by_extension(&self, ext: &str) -> Option<&FileTypeDef> {
+        self.by_extension.get(&ext.to_lowercase()).map(|&index| &self.types[index])
+    }
+
+    /// 按MIME类型查找
+    pub fn by_mime(&self, mime: &str) -> Option<&FileTypeDef> {
+        self.by_mime.get(&mime.to_lowercase()).map(|&index| &self.types[index])
+    }
+
+    /// 按文件头特征查找（特征越长越优先）
+    pub fn by_magic(&self, header: &[u8]) -> Option<&FileTypeDef> {
+        self.types
+            .iter()
+            .flat_map(|def| def.magic.iter().map(move |signature| (def, signature)))
+            .filter(|(_, signature)| signature.matches(header))
+            .max_by_key(|(_, signature)| signature.bytes.len())
+            .map(|(def, _)| def)
+    }
+
+    /// 根据类型定义创建文件类型信息
+    pub fn kind_from_def(def: &FileTypeDef, extension: &str) -> FileKind {
+        FileKind {
+            type_id: def.id.clone(),
+            category: def.category,
+            extension: extension.to_string(),
+            description: def.description.clone(),
+        }
+    }
+
+    /// 根据文件名确定文件类型
+    pub fn kind_for_path(&self, path: &Path, is_dir: bool) -> FileKind {
+        if is_dir {
+            return FileKind::directory();
+        }
+
+        let extension = path.extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match self.by_extension(&extension) {
+            Some(def) => Self::kind_from_def(def, &extension),
+            None => FileKind {
+                type_id: UNKNOWN_TYPE_ID.to_string(),
+                category: FileCategory::Regular,
+                description: if extension.is_empty() {
+                    "文件".to_string()
+                } else {
+                    format!("{} 文件", extension)
+                },
+                extension,
+            },
+        }
+    }
+}
+
+/// 内置文件类型
+fn builtin_types() -> Vec<FileTypeDef> {
+    use FileCategory::*;
+
+    vec![
+        // 文本和文档
+        FileTypeDef::new("text", Text, "文本文档", &["txt", "log"]).mime(&["text/plain"]),
+        FileTypeDef::new("markdown", Text, "Markdown 文档", &["md", "markdown"])
+            .mime(&["text/markdown"])
+            .color(Color::rgb8(108, 165, 209)),
+        FileTypeDef::new("json", Text, "JSON 数据", &["json"]).mime(&["application/json"]),
+        FileTypeDef::new("xml", Text, "XML 文档", &["xml"]).mime(&["application/xml", "text/xml"]),
+        FileTypeDef::new("toml", Text, "TOML 配置", &["toml"])
+            .mime(&["application/toml"])
+            .color(Color::rgb8(180, 180, 180)),
+        FileTypeDef::new("yaml", Text, "YAML 配置", &["yaml", "yml"]).mime(&["application/yaml"]),
+        FileTypeDef::new("csv", Text, "CSV 表格", &["csv"]).mime(&["text/csv"]),
+        FileTypeDef::new("pdf", Document, "PDF 文档", &["pdf"])
+            .mime(&["application/pdf"])
+            .magic(0, "25504446"),
+        FileTypeDef::new("word", Document, "Word 文档", &["doc", "docx"])
+            .mime(&["application/msword", "application/vnd.openxmlformats-officedocument.wordprocessingml.document"]),
+        FileTypeDef::new("excel", Document, "Excel 表格", &["xls", "xlsx"])
+            .mime(&["application/vnd.ms-excel", "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"]),
+        // 源代码
+        FileTypeDef::new("rust", Code, "Rust 源代码", &["rs"])
+            .mime(&["text/x-rust"])
+            .color(Color::rgb8(244, 110, 66)),
+        FileTypeDef::new("html", Code, "HTML 网页", &["html", "htm"])
+            .mime(&["text/html"])
+            .color(Color::rgb8(240, 101, 41)),
+        FileTypeDef::new("css", Code, "CSS 样式表", &["css"])
+            .mime(&["text/css"])
+            .color(Color::rgb8(86, 61, 124)),
+        FileTypeDef::new("javascript", Code, "JavaScript 脚本", &["js", "mjs", "cjs"])
+            .mime(&["text/javascript", "application/javascript"])
+            .color(Color::rgb8(247, 223, 30)),
+        FileTypeDef::new("typescript", Code, "TypeScript 脚本", &["ts", "tsx"]).mime(&["application/typescript"]),
+        FileTypeDef::new("python", Code, "Python 脚本", &["py", "pyw"]).mime(&["text/x-python"]),
+        FileTypeDef::new("shell", Code, "Shell 脚本", &["sh", "bash", "zsh"]).mime(&["application/x-sh"]),
+        FileTypeDef::new("c", Code, "C 源代码", &["c", "h"]).mime(&["text/x-c"]),
+        FileTypeDef::new("cpp", Code, "C++ 源代码", &["cpp", "cc", "cxx", "hpp", "hh"]).mime(&["text/x-c++"]),
+        FileTypeDef::new("java", Code, "Java 源代码", &["java"]).mime(&["text/x-java"]),
+        FileTypeDef::new("go", Code, "Go 源代码", &["go"]).mime(&["text/x-go"]),
+        FileTypeDef::new("php", Code, "PHP 脚本", &["php"]).mime(&["application/x-php"]),
+        FileTypeDef::new("ruby", Code, "Ruby 脚本", &["rb"]).mime(&["text/x-ruby"]),
+        // 图片
+        FileTypeDef::new("png", Image, "PNG 图像", &["png"])
+            .mime(&["image/png"])
+            .magic(0, "89504E470D0A1A0A"),
+        FileTypeDef::new("jpeg", Image, "JPEG 图像", &["jpg", "jpeg"])
+            .mime(&["image/jpeg"])
+            .magic(0, "FFD8FF"),
+        FileTypeDef::new("gif", Image, "GIF 图像", &["gif"])
+            .mime(&["image/gif"])
+            .magic(0, "474946383761")
+            .magic(0, "474946383961"),
+        FileTypeDef::new("bmp", Image, "BMP 图像", &["bmp"])
+            .mime(&["image/bmp"])
+            .magic(0, "424D"),
+        FileTypeDef::new("webp", Image, "WebP 图像", &["webp"])
+            .mime(&["image/webp"])
+            .magic(8, "57454250"),
+        FileTypeDef::new("svg", Image, "SVG 矢量图", &["svg"]).mime(&["image/svg+xml"]),
+        FileTypeDef::new("ico", Image, "图标文件", &["ico"])
+            .mime(&["image/x-icon", "image/vnd.microsoft.icon"])
+            .magic(0, "00000100")
+            .color(Color::rgb8(255, 165, 0)),
+        // 音频
+        FileTypeDef::new("mp3", Audio, "MP3 音频", &["mp3"])
+            .mime(&["audio/mpeg"])
+            .magic(0, "494433"),
+        FileTypeDef::new("wav", Audio, "WAV 音频", &["wav"])
+            .mime(&["audio/wav", "audio/x-wav"])
+            .magic(8, "57415645"),
+        FileTypeDef::new("flac", Audio, "FLAC 音频", &["flac"])
+            .mime(&["audio/flac"])
+            .magic(0, "664C6143"),
+        FileTypeDef::new("ogg", Audio, "Ogg 音频", &["ogg", "oga"])
+            .mime(&["audio/ogg"])
+            .magic(0, "4F676753"),
+        FileTypeDef::new("aac", Audio, "AAC 音频", &["aac", "m4a"]).mime(&["audio/aac", "audio/mp4"]),
+        // 视频
+        FileTypeDef::new("mp4", Video, "MP4 视频", &["mp4", "m4v"])
+            .mime(&["video/mp4"])
+            .magic(4, "66747970"),
+        FileTypeDef::new("avi", Video, "AVI 视频", &["avi"])
+            .mime(&["video/x-msvideo"])
+            .magic(8, "41564920"),
+        FileTypeDef::new("mkv", Video, "Matroska 视频", &["mkv", "webm"])
+            .mime(&["video/x-matroska", "video/webm"])
+            .magic(0, "1A45DFA3"),
+        FileTypeDef::new("mov", Video, "QuickTime 视频", &["mov"]).mime(&["video/quicktime"]),
+        FileTypeDef::new("wmv", Video, "WMV 视频", &["wmv"]).mime(&["video/x-ms-wmv"]),
+        // 压缩包
+        FileTypeDef::new("zip", Archive, "ZIP 压缩包", &["zip"])
+            .mime(&["application/zip"])
+            .magic(0, "504B0304")
+            .magic(0, "504B0506"),
+        FileTypeDef::new("gzip", Archive, "Gzip 压缩文件", &["gz", "tgz"])
+            .mime(&["application/gzip"])
+            .magic(0, "1F8B"),
+        FileTypeDef::new("7z", Archive, "7-Zip 压缩包", &["7z"])
+            .mime(&["application/x-7z-compressed"])
+            .magic(0, "377ABCAF271C"),
+        FileTypeDef::new("rar", Archive, "RAR 压缩包", &["rar"])
+            .mime(&["application/vnd.rar"])
+            .magic(0, "526172211A07"),
+        FileTypeDef::new("tar", Archive, "TAR 归档", &["tar"])
+            .mime(&["application/x-tar"])
+            .magic(257, "7573746172"),
+        // 可执行文件
+        FileTypeDef::new("exe", Executable, "Windows 可执行程序", &["exe", "dll", "sys"])
+            .mime(&["application/vnd.microsoft.portable-executable"])
+            .magic(0, "4D5A")
+            .color(Color::rgb8(80, 220, 100)),
+        FileTypeDef::new("elf", Executable, "ELF 可执行文件", &["so", "o"])
+            .mime(&["application/x-elf", "application/x-sharedlib", "application/x-executable"])
+            .magic(0, "7F454C46"),
+        FileTypeDef::new("macho", Executable, "Mach-O 可执行文件", &["dylib"])
+            .mime(&["application/x-mach-binary"])
+            .magic(0, "CFFAEDFE")
+            .magic(0, "CEFAEDFE"),
+        FileTypeDef::new("batch", Executable, "批处理脚本", &["bat", "cmd", "ps1"]),
+    ]
+}
+
+/// 用户文件类型定义（file_types.toml）
+///
+/// ```toml
+/// [[types]]
+/// id = "blend"
+/// category = "image"
+/// description = "Blender 工程"
+/// extensions = ["blend"]
+/// mime_types = ["application/x-blender"]
+/// magic = [{ offset = 0, bytes = "424C454E444552" }]
+/// color = "#e87d0d"
+/// icon = "image"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct UserTypesFile {
+    types: Vec<UserTypeDef>,
+}
+
+/// 用户定义的单个文件类型
+#[derive(Debug, Deserialize)]
+struct UserTypeDef {
+    id: String,
+    #[serde(default)]
+    category: Option<FileCategory>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    extensions: Vec<String>,
+    #[serde(default)]
+    mime_types: Vec<String>,
+    #[serde(default)]
+    magic: Vec<UserMagic>,
+    #[serde(default)]
+    color: Option<String>,
+    #[serde(default)]
+    icon: Option<IconKind>,
+}
+
+/// 用户定义的文件头特征
+#[derive(Debug, Deserialize)]
+struct UserMagic {
+    #[serde(default)]
+    offset: usize,
+    bytes: String,
+}
+
+/// 将用户定义合并到已有定义上（ID与内置类型相同时只覆盖给出的字段）
+fn merge_user_def(base: Option<&FileTypeDef>, user: UserTypeDef, warnings: &mut Vec<String>) -> Option<FileTypeDef> {
+    let mut def = match (base, user.category) {
+        (Some(base), _) => base.clone(),
+        (None, Some(category)) => FileTypeDef::new(&user.id, category, &user.id, &[]),
+        (None, None) => {
+            warnings.push(format!("文件类型 {} 缺少 category", user.id));
+            return None;
+        }
+    };
+
+    if let Some(category) = user.category {
+        def.category = category;
+    }
+    if let Some(description) = user.description {
+        def.description = description;
+    }
+    if !user.extensions.is_empty() {
+        def.extensions = user.extensions.iter()
+            .map(|ext| ext.trim_start_matches('.').to_lowercase())
+            .collect();
+    }
+    if !user.mime_types.is_empty() {
+        def.mime_types = user.mime_types;
+    }
+    if !user.magic.is_empty() {
+        def.magic.clear();
+        for magic in &user.magic {
+            match MagicSignature::from_hex(magic.offset, &magic.bytes) {
+                Some(signature) => def.magic.push(signature),
+                None => warnings.push(format!("文件类型 {} 的特征字节无效: {}", user.id, magic.bytes)),
+            }
+        }
+    }
+    if let Some(color) = user.color {
+        match Color::from_hex_str(&color) {
+            Ok(color) => def.color = Some(color),
+            Err(_) => warnings.push(format!("文件类型 {} 的颜色无效: {}", user.id, color)),
+        }
+    }
+    if user.icon.is_some() {
+        def.icon = user.icon;
+    }
+
+    Some(def)
+}
+
+/// 加载内置类型和配置目录中的用户类型，返回加载过程中的警告
+pub fn load_registry() -> Vec<String> {
+    let mut warnings = Vec::new();
+    let mut registry = FileTypeRegistry::builtin();
+
+    let path = settings::config_dir().map(|dir| dir.join(USER_TYPES_FILE_NAME));
+    if let Some(path) = path.filter(|path| path.is_file()) {
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| toml::from_str::<UserTypesFile>(&content).map_err(|e| e.to_string()));
+
+        match parsed {
+            Ok(file) => {
+                for user in file.types {
+                    let base = registry.get(&user.id).cloned();
+                    if let Some(def) = merge_user_def(base.as_ref(), user, &mut warnings) {
+                        registry.register(def);
+                    }
+                }
+                println!("已加载用户文件类型: {:?}", path);
+            }
+            Err(e) => warnings.push(format!("解析文件类型配置 {} 失败: {}", path.display(), e)),
+        }
+    }
+
+    *REGISTRY.write().unwrap() = registry;
+    warnings
+}
+
+/// 根据文件名确定文件类型
+pub fn kind_for_path(path: &Path, is_dir: bool) -> FileKind {
+    REGISTRY.read().unwrap().kind_for_path(path, is_dir)
+}
+
+/// 查找文件类型对应的颜色（类型专用颜色，没有时返回 None）
+pub fn type_color(kind: &FileKind) -> Option<Color> {
+    REGISTRY.read().unwrap().get(&kind.type_id).and_then(|def| def.color)
+}
+
+/// 查找文件类型对应的图标
+pub fn icon_for(kind: &FileKind) -> IconKind {
+    REGISTRY.read().unwrap()
+        .get(&kind.type_id)
+        .map(|def| def.icon())
+        .unwrap_or_else(|| kind.category.default_icon())
+}
+
+/// 在注册表上执行查询（供内容识别等模块使用）
+pub fn with_registry<R>(f: impl FnOnce(&FileTypeRegistry) -> R) -> R {
+    f(&REGISTRY.read().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 解析用户文件类型配置中的一个类型
+    fn user_def(content: &str) -> UserTypeDef {
+        let mut file: UserTypesFile = toml::from_str(content).unwrap();
+        file.types.remove(0)
+    }
+
+    #[test]
+    fn builtin_lookup_by_extension_and_mime() {
+        let registry = FileTypeRegistry::builtin();
+        assert_eq!(registry.by_extension("RS").unwrap().id, "rust");
+        assert_eq!(registry.by_extension("jpeg").unwrap().id, "jpeg");
+        assert_eq!(registry.by_extension("jpg").unwrap().id, "jpeg");
+        assert!(registry.by_extension("nosuchext").is_none());
+
+        assert_eq!(registry.by_mime("image/PNG").unwrap().id, "png");
+        assert_eq!(registry.by_mime("video/webm").unwrap().id, "mkv");
+        assert!(registry.by_mime("application/x-unknown").is_none());
+    }
+
+    #[test]
+    fn builtin_has_mime_types_used_by_shebangs() {
+        // 内容识别按解释器的MIME类型查找脚本类型
+        let registry = FileTypeRegistry::builtin();
+        assert_eq!(registry.by_mime("text/x-python").unwrap().id, "python");
+        assert_eq!(registry.by_mime("application/x-sh").unwrap().id, "shell");
+        assert_eq!(registry.by_mime("text/javascript").unwrap().id, "javascript");
+        assert_eq!(registry.by_mime("text/x-ruby").unwrap().id, "ruby");
+        assert_eq!(registry.by_mime("application/x-php").unwrap().id, "php");
+    }
+
+    #[test]
+    fn kind_for_path_uses_extension() {
+        let registry = FileTypeRegistry::builtin();
+        let kind = registry.kind_for_path(Path::new("photo.PNG"), false);
+        assert_eq!(kind.type_id, "png");
+        assert_eq!(kind.extension, "png");
+        assert_eq!(kind.category, FileCategory::Image);
+
+        let kind = registry.kind_for_path(Path::new("data.xyz"), false);
+        assert_eq!(kind.type_id, UNKNOWN_TYPE_ID);
+        assert_eq!(kind.description, "xyz 文件");
+        assert_eq!(registry.kind_for_path(Path::new("Makefile"), false).description, "文件");
+        assert!(registry.kind_for_path(Path::new("src.rs"), true).is_directory());
+    }
+
+    #[test]
+    fn magic_prefers_longest_signature() {
+        let registry = FileTypeRegistry::builtin();
+        assert_eq!(registry.by_magic(b"\x89PNG\r\n\x1a\n....").unwrap().id, "png");
+        assert_eq!(registry.by_magic(b"MZ\x90\x00").unwrap().id, "exe");
+        assert!(registry.by_magic(b"plain").is_none());
+        assert!(MagicSignature::from_hex(0, "ABC").is_none());
+        assert!(MagicSignature::from_hex(0, "ZZ").is_none());
+    }
+
+    #[test]
+    fn user_def_overrides_given_fields() {
+        let mut registry = FileTypeRegistry::builtin();
+        let base = registry.get("markdown").cloned();
+        let user = user_def(r##"
+            [[types]]
+            id = "markdown"
+            extensions = [".MDX"]
+            color = "#112233"
+        "##);
+
+        let mut warnings = Vec::new();
+        let def = merge_user_def(base.as_ref(), user, &mut warnings).unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(def.extensions, vec!["mdx".to_string()]);
+        assert_eq!(def.color.map(|color| color.as_rgba_u32()), Some(0x112233FF));
+        // 没有给出的字段沿用内置定义
+        assert_eq!(def.category, FileCategory::Text);
+        assert_eq!(def.mime_types, vec!["text/markdown".to_string()]);
+
+        registry.register(def);
+        assert_eq!(registry.by_extension("mdx").unwrap().id, "markdown");
+        assert!(registry.by_extension("md").is_none());
+    }
+
+    #[test]
+    fn user_def_adds_new_type() {
+        let mut registry = FileTypeRegistry::builtin();
+        let user = user_def(r#"
+            [[types]]
+            id = "blend"
+            category = "image"
+            description = "Blender 工程"
+            extensions = ["blend"]
+            magic = [{ offset = 0, bytes = "424C454E444552" }, { bytes = "XYZ" }]
+            color = "not a color"
+            icon = "image"
+        "#);
+
+        let mut warnings = Vec::new();
+        let def = merge_user_def(None, user, &mut warnings).unwrap();
+        // 无效的特征字节和颜色被忽略并给出警告
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert_eq!(def.magic.len(), 1);
+        assert!(def.color.is_none());
+        assert_eq!(def.icon(), IconKind::Image);
+
+        registry.register(def);
+        assert_eq!(registry.by_extension("blend").unwrap().description, "Blender 工程");
+        assert_eq!(registry.by_magic(b"BLENDER-v300").unwrap().id, "blend");
+    }
+
+    #[test]
+    fn user_def_without_category_is_rejected() {
+        let user = user_def(r#"
+            [[types]]
+            id = "mystery"
+            extensions = ["mys"]
+        "#);
+
+        let mut warnings = Vec::new();
+        assert!(merge_user_def(None, user, &mut warnings).is_none());
+        assert_eq!(warnings.len(), 1);
+    }
+}
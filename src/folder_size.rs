@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use druid::{Data, ExtEventSink, Target};
+
+use crate::commands::FOLDER_SIZE_COMPUTED;
+
+/// 缓存最多保存的目录数量
+const MAX_CACHED_FOLDERS: usize = 2000;
+
+/// 缓存的有效期：目录的修改时间只反映条目的增删和重命名，
+/// 文件原地变大（日志、正在下载的文件）不会改变它，过期后重新统计
+pub const CACHE_TTL: Duration = Duration::from_secs(30);
+
+// 目录大小缓存和等待计算的目录队列
+lazy_static::lazy_static! {
+    static ref CACHE: Mutex<HashMap<PathBuf, CachedSize>> = Mutex::new(HashMap::new());
+    static ref QUEUE: (Mutex<RequestQueue>, Condvar) = (Mutex::new(RequestQueue::default()), Condvar::new());
+}
+
+/// 等待计算的目录（后请求的先计算，即最近滚动到的行优先）
+#[derive(Default)]
+struct RequestQueue {
+    paths: VecDeque<PathBuf>,
+    pending: HashSet<PathBuf>,
+    started: bool,
+}
+
+/// 目录大小的计算结果
+#[derive(Clone, Copy, Data, Debug, PartialEq)]
+pub struct FolderSize {
+    /// 所有文件的总大小（硬链接的文件只统计一次）
+    pub bytes: u64,
+    /// 是否有无法读取的子目录（结果偏小）
+    pub incomplete: bool,
+}
+
+/// 缓存的目录大小
+///
+/// 记录统计时经过的所有目录及其修改时间；任何一个目录中增删或重命名了条目，
+/// 其修改时间就会变化，缓存随之失效。超过 `CACHE_TTL` 的缓存也会失效。
+struct CachedSize {
+    size: FolderSize,
+    directories: Vec<(PathBuf, Option<SystemTime>)>,
+    computed_at: Instant,
+}
+
+impl CachedSize {
+    /// 缓存未过期，且统计以来目录内容没有变化
+    fn is_valid(&self) -> bool {
+        self.computed_at.elapsed() < CACHE_TTL
+            && self.directories
+                .iter()
+                .all(|(dir, modified)| dir_modified(dir) == *modified)
+    }
+}
+
+/// 启动后台计算线程，结果通过 `FOLDER_SIZE_COMPUTED` 命令发回界面
+pub fn start_worker(event_sink: ExtEventSink) {
+    {
+        let mut queue = QUEUE.0.lock().unwrap();
+        if queue.started {
+            return;
+        }
+        queue.started = true;
+    }
+
+    thread::spawn(move || loop {
+        let path = {
+            let (lock, condvar) = &*QUEUE;
+            let mut queue = lock.lock().unwrap();
+            loop {
+                if let Some(path) = queue.paths.pop_back() {
+                    break path;
+                }
+                queue = condvar.wait(queue).unwrap();
+            }
+        };
+
+        let size = compute_cached(&path);
+        QUEUE.0.lock().unwrap().pending.remove(&path);
+
+        if let Err(e) = event_sink.submit_command(FOLDER_SIZE_COMPUTED, (path, size), Target::Auto) {
+            eprintln!("发送目录大小失败: {:?}", e);
+            break;
+        }
+    });
+}
+
+/// 请求计算目录大小（同一目录在计算完成前只排队一次）
+pub fn request(path: &Path) {
+    let (lock, condvar) = &*QUEUE;
+    let mut queue = lock.lock().unwrap();
+    if queue.pending.insert(path.to_path_buf()) {
+        queue.paths.push_back(path.to_path_buf());
+        condvar.notify_one();
+    }
+}
+
+/// 使目录及其所有子目录的缓存失效，同时使上级目录的缓存失效
+pub fn invalidate(path: &Path) {
+    CACHE.lock().unwrap()
+        .retain(|cached, _| !cached.starts_with(path) && !path.starts_with(cached));
+}
+
+/// 优先使用仍然有效的缓存，否则重新计算
+fn compute_cached(path: &Path) -> FolderSize {
+    if let Some(cached) = CACHE.lock().unwrap().get(path) {
+        if cached.is_valid() {
+            return cached.size;
+        }
+    }
+
+    let (size, directories) = compute(path);
+
+    let mut cache = CACHE.lock().unwrap();
+    if cache.len() >= MAX_CACHED_FOLDERS {
+        cache.clear();
+    }
+    cache.insert(path.to_path_buf(), CachedSize { size, directories, computed_at: Instant::now() });
+    size
+}
+
+/// 递归计算目录大小，返回结果和经过的所有目录（用于判断缓存是否失效）
+///
+/// 不跟随符号链接；Unix上按 (设备, inode) 记录已访问的目录和多链接文件，
+/// 避免绑定挂载造成的循环和硬链接重复统计。无法读取的目录跳过并标记结果不完整。
+pub fn compute(root: &Path) -> (FolderSize, Vec<(PathBuf, Option<SystemTime>)>) {
+    let mut size = FolderSize { bytes: 0, incomplete: false };
+    let mut directories = Vec::new();
+    let mut visited = HashSet::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let metadata = match fs::symlink_metadata(&dir) {
+            Ok(metadata) => metadata,
+            Err(_) => {
+                size.incomplete = true;
+                continue;
+            }
+        };
+        if let Some(id) = file_id(&metadata) {
+            if !visited.insert(id) {
+                continue;
+            }
+        }
+        directories.push((dir.clone(), metadata.modified().ok()));
+
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => {
+                size.incomplete = true;
+                continue;
+            }
+        };
+
+        for entry in entries.filter_map(Result::ok) {
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(_) => continue,
+            };
+
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() {
+                if let Ok(metadata) = entry.metadata() {
+                    // 多链接的文件只统计一次
+                    if link_count(&metadata) > 1 {
+                        if let Some(id) = file_id(&metadata) {
+                            if !visited.insert(id) {
+                                continue;
+                            }
+                        }
+                    }
+                    size.bytes += metadata.len();
+                }
+            }
+        }
+    }
+
+    (size, directories)
+}
+
+/// 目录的修改时间
+fn dir_modified(path: &Path) -> Option<SystemTime> {
+    fs::symlink_metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(unix)]
+fn link_count(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.nlink()
+}
+
+#[cfg(not(unix))]
+fn link_count(_metadata: &fs::Metadata) -> u64 {
+    1
+}
@@ -0,0 +1,239 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use druid::im::Vector;
+use druid::{Data, ExtEventSink, Lens, Selector, Target};
+
+use crate::commands::JOB_PROGRESS;
+
+/// 发送任务进度的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 保留的已结束任务数量
+const MAX_FINISHED_JOBS: usize = 20;
+
+// 下一个任务ID和正在运行的任务的取消标志
+lazy_static::lazy_static! {
+    static ref NEXT_JOB_ID: AtomicU64 = AtomicU64::new(1);
+    static ref CANCEL_FLAGS: Mutex<HashMap<u64, Arc<AtomicBool>>> = Mutex::new(HashMap::new());
+}
+
+/// 任务中处理失败的一个路径
+#[derive(Clone, Data, Debug, PartialEq)]
+pub struct JobFailure {
+    #[data(same_fn = "PartialEq::eq")]
+    pub path: PathBuf,
+    pub error: String,
+}
+
+/// 后台任务的状态，通过 `JOB_PROGRESS` 命令更新到 `AppState::jobs`
+#[derive(Clone, Data, Lens, Debug, PartialEq)]
+pub struct JobStatus {
+    /// 任务ID
+    pub id: u64,
+    /// 任务标题
+    pub title: String,
+    /// 已处理的条目数量
+    pub processed: u64,
+    /// 正在处理的大文件已完成的字节数和总字节数（没有大文件时都为0）
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    /// 处理失败的路径
+    pub failures: Vector<JobFailure>,
+    /// 任务是否已经结束
+    pub finished: bool,
+    /// 任务是否被取消
+    pub cancelled: bool,
+}
+
+impl JobStatus {
+    /// 一行文字描述任务状态
+    pub fn summary(&self) -> String {
+        let state = if self.cancelled {
+            "已取消"
+        } else if self.finished {
+            "已完成"
+        } else {
+            "进行中"
+        };
+
+        let mut summary = format!("{}：{}，已处理 {} 项", self.title, state, self.processed);
+        if !self.finished && self.bytes_total > 0 {
+            summary.push_str(&format!("，当前文件 {}%", self.bytes_done * 100 / self.bytes_total));
+        }
+        if !self.failures.is_empty() {
+            summary.push_str(&format!("，{} 项失败", self.failures.len()));
+        }
+        summary
+    }
+
+    /// 失败路径的详细列表
+    pub fn failure_details(&self) -> String {
+        self.failures
+            .iter()
+            .map(|failure| format!("{}: {}", failure.path.display(), failure.error))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// 任务执行时的上下文：记录进度和失败的路径，检查是否被取消
+pub struct JobContext {
+    status: JobStatus,
+    cancel: Arc<AtomicBool>,
+    event_sink: ExtEventSink,
+    last_progress: Instant,
+}
+
+impl JobContext {
+    /// 任务是否已被取消
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// 任务ID
+    pub fn id(&self) -> u64 {
+        self.status.id
+    }
+
+    /// 取消标志，供任务内部的工作线程检查
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        self.cancel.clone()
+    }
+
+    /// 直接设置已处理的条目数量（多线程任务自行计数时使用）
+    pub fn set_processed(&mut self, processed: u64) {
+        self.status.processed = processed;
+        self.send_progress(false);
+    }
+
+    /// 记录当前大文件的处理进度
+    pub fn set_bytes(&mut self, done: u64, total: u64) {
+        self.status.bytes_done = done;
+        self.status.bytes_total = total;
+        self.send_progress(false);
+    }
+
+    /// 记录成功处理了一个条目
+    pub fn processed(&mut self) {
+        self.status.processed += 1;
+        self.send_progress(false);
+    }
+
+    /// 记录处理失败的路径
+    pub fn failed(&mut self, path: &Path, error: impl ToString) {
+        self.status.failures.push_back(JobFailure {
+            path: path.to_path_buf(),
+            error: error.to_string(),
+        });
+        self.send_progress(false);
+    }
+
+    /// 向界面发送任务的结果（在任务结束的状态之前送达）
+    pub fn submit<T: Any + Send>(&self, selector: Selector<T>, payload: T) {
+        if let Err(e) = self.event_sink.submit_command(selector, payload, Target::Auto) {
+            eprintln!("发送任务结果失败: {:?}", e);
+        }
+    }
+
+    /// 发送进度，`force` 为 false 时按间隔限流
+    fn send_progress(&mut self, force: bool) {
+        if !force && self.last_progress.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+        self.last_progress = Instant::now();
+
+        if let Err(e) = self.event_sink.submit_command(JOB_PROGRESS, self.status.clone(), Target::Auto) {
+            eprintln!("发送任务进度失败: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+impl JobContext {
+    /// 测试用的上下文：没有运行中的应用，发送的进度不会被处理
+    pub fn detached() -> Self {
+        let launcher = druid::AppLauncher::<()>::with_window(druid::WindowDesc::new(druid::widget::Label::new("")));
+        Self {
+            status: JobStatus {
+                id: 0,
+                title: String::new(),
+                processed: 0,
+                bytes_done: 0,
+                bytes_total: 0,
+                failures: Vector::new(),
+                finished: false,
+                cancelled: false,
+            },
+            cancel: Arc::new(AtomicBool::new(false)),
+            event_sink: launcher.get_external_handle(),
+            last_progress: Instant::now(),
+        }
+    }
+}
+
+/// 在后台线程中运行任务，返回任务ID
+///
+/// 任务开始和结束时都会发送一次状态，运行期间按间隔发送进度。
+pub fn spawn<F>(title: impl Into<String>, event_sink: ExtEventSink, job: F) -> u64
+where
+    F: FnOnce(&mut JobContext) + Send + 'static,
+{
+    let id = NEXT_JOB_ID.fetch_add(1, Ordering::Relaxed);
+    let cancel = Arc::new(AtomicBool::new(false));
+    CANCEL_FLAGS.lock().unwrap().insert(id, cancel.clone());
+
+    let mut context = JobContext {
+        status: JobStatus {
+            id,
+            title: title.into(),
+            processed: 0,
+            bytes_done: 0,
+            bytes_total: 0,
+            failures: Vector::new(),
+            finished: false,
+            cancelled: false,
+        },
+        cancel,
+        event_sink,
+        last_progress: Instant::now(),
+    };
+    context.send_progress(true);
+
+    thread::spawn(move || {
+        job(&mut context);
+
+        CANCEL_FLAGS.lock().unwrap().remove(&id);
+        context.status.finished = true;
+        context.status.cancelled = context.is_cancelled();
+        context.send_progress(true);
+    });
+
+    id
+}
+
+/// 请求取消任务（任务在下一次检查时停止）
+pub fn cancel(id: u64) {
+    if let Some(flag) = CANCEL_FLAGS.lock().unwrap().get(&id) {
+        flag.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 用收到的任务状态更新任务列表，并只保留最近结束的若干任务
+pub fn update_jobs(jobs: &mut Vector<JobStatus>, status: &JobStatus) {
+    match jobs.iter().position(|job| job.id == status.id) {
+        Some(index) => jobs[index] = status.clone(),
+        None => jobs.push_back(status.clone()),
+    }
+
+    let finished = jobs.iter().filter(|job| job.finished).count();
+    if finished > MAX_FINISHED_JOBS {
+        if let Some(index) = jobs.iter().position(|job| job.finished) {
+            jobs.remove(index);
+        }
+    }
+}
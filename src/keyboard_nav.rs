@@ -0,0 +1,197 @@
+use druid::Data;
+use druid_widget_nursery::TreeNode;
+use std::path::PathBuf;
+use crate::locations::is_recent_section;
+use crate::models::FileItem;
+
+/// PageUp/PageDown 一次移动的行数
+pub const PAGE_ROWS: usize = 10;
+
+/// 键盘焦点所在的面板
+#[derive(Clone, Copy, Data, Debug, PartialEq, Eq)]
+pub enum FocusPane {
+    /// 左侧目录树
+    Tree,
+    /// 右侧文件列表
+    List,
+}
+
+/// 键盘移动焦点的方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FocusMove {
+    Previous,
+    Next,
+    PageUp,
+    PageDown,
+    First,
+    Last,
+}
+
+impl FocusMove {
+    /// 从 `current` 移动后的位置，`len` 为总行数（大于0）
+    pub fn apply(self, current: Option<usize>, len: usize) -> usize {
+        let last = len - 1;
+        match (self, current) {
+            (FocusMove::First, _) => 0,
+            (FocusMove::Last, _) => last,
+            // 还没有焦点时，向下从第一行开始，向上从最后一行开始
+            (FocusMove::Next | FocusMove::PageDown, None) => 0,
+            (FocusMove::Previous | FocusMove::PageUp, None) => last,
+            (FocusMove::Previous, Some(index)) => index.saturating_sub(1),
+            (FocusMove::Next, Some(index)) => (index + 1).min(last),
+            (FocusMove::PageUp, Some(index)) => index.saturating_sub(PAGE_ROWS),
+            (FocusMove::PageDown, Some(index)) => (index + PAGE_ROWS).min(last),
+        }
+    }
+}
+
+/// 按名称跳转：从 `current` 之后开始循环查找第一个以 `prefix` 开头的名称（不区分大小写）
+///
+/// 连续输入多个字符时当前项仍然匹配则留在当前项，只输入一个字符时跳到下一个匹配项，
+/// 因此重复按同一个字母可以在同一字母开头的项之间循环。
+pub fn find_by_prefix<'a>(names: impl ExactSizeIterator<Item = &'a str>, current: Option<usize>, prefix: &str) -> Option<usize> {
+    let prefix = prefix.to_lowercase();
+    let names: Vec<String> = names.map(str::to_lowercase).collect();
+    if names.is_empty() || prefix.is_empty() {
+        return None;
+    }
+
+    let start = match current {
+        Some(index) if prefix.chars().count() > 1 => index,
+        Some(index) => index + 1,
+        None => 0,
+    };
+    (0..names.len())
+        .map(|offset| (start + offset) % names.len())
+        .find(|&index| names[index].starts_with(&prefix))
+}
+
+/// 目录树中一个可见的节点
+#[derive(Clone, Debug)]
+pub struct VisibleNode {
+    /// 从根节点到该节点的子项索引
+    pub indices: Vec<usize>,
+    pub path: PathBuf,
+    pub name: String,
+}
+
+/// 按显示顺序列出目录树中可见的节点（跳过根节点和"我的电脑"这类不能选中的虚拟节点，以及"最近访问"分组中的目录）
+pub fn visible_nodes(root: &FileItem) -> Vec<VisibleNode> {
+    let mut nodes = Vec::new();
+    collect_visible(root, &mut Vec::new(), &mut nodes);
+    nodes
+}
+
+fn collect_visible(item: &FileItem, indices: &mut Vec<usize>, nodes: &mut Vec<VisibleNode>) {
+    if !is_virtual(item) {
+        nodes.push(VisibleNode {
+            indices: indices.clone(),
+            path: item.path.clone(),
+            name: item.name.clone(),
+        });
+    }
+    // "最近访问"分组中的目录在树中其他位置也有，键盘导航时跳过
+    if is_recent_section(item) {
+        return;
+    }
+    for index in 0..item.children_count() {
+        indices.push(index);
+        collect_visible(&item.children[index], indices, nodes);
+        indices.pop();
+    }
+}
+
+/// 根节点、"我的电脑"和"最近访问"只用于分组，不对应可以打开的目录
+pub fn is_virtual(item: &FileItem) -> bool {
+    item.path == PathBuf::from("ROOT") || item.name == "我的电脑" || is_recent_section(item)
+}
+
+/// 可见节点中当前选中的节点的位置
+pub fn selected_position(root: &FileItem, nodes: &[VisibleNode]) -> Option<usize> {
+    nodes.iter().position(|node| node_at(root, &node.indices).is_some_and(|item| item.is_selected))
+}
+
+/// 按索引取得节点
+pub fn node_at<'a>(root: &'a FileItem, indices: &[usize]) -> Option<&'a FileItem> {
+    indices.iter().try_fold(root, |item, &index| item.children.get(index))
+}
+
+/// 按索引取得可修改的节点
+pub fn node_at_mut<'a>(root: &'a mut FileItem, indices: &[usize]) -> Option<&'a mut FileItem> {
+    indices.iter().try_fold(root, |item, &index| item.children.get_mut(index))
+}
+
+/// 目录树中按方向键移动后应选中的目录
+pub fn tree_move(root: &FileItem, movement: FocusMove) -> Option<PathBuf> {
+    let nodes = visible_nodes(root);
+    if nodes.is_empty() {
+        return None;
+    }
+    let current = selected_position(root, &nodes);
+    Some(nodes[movement.apply(current, nodes.len())].path.clone())
+}
+
+/// 目录树中当前选中的目录
+pub fn tree_selected(root: &FileItem) -> Option<PathBuf> {
+    let nodes = visible_nodes(root);
+    selected_position(root, &nodes).map(|position| nodes[position].path.clone())
+}
+
+/// 目录树中按名称跳转后应选中的目录
+pub fn tree_find(root: &FileItem, prefix: &str) -> Option<PathBuf> {
+    let nodes = visible_nodes(root);
+    let current = selected_position(root, &nodes);
+    find_by_prefix(nodes.iter().map(|node| node.name.as_str()), current, prefix)
+        .map(|index| nodes[index].path.clone())
+}
+
+/// 在目录树中按右键或左键的结果
+#[derive(Clone, Debug, PartialEq)]
+pub enum TreeStep {
+    /// 展开了选中的节点，子目录还没有加载
+    Load(PathBuf),
+    /// 选中另一个节点（展开后的第一个子目录或上一级目录）
+    Select(PathBuf),
+    /// 只修改了展开状态，或者无需处理
+    Done,
+}
+
+/// 右键展开选中的节点，已经展开时移动到第一个子目录；左键折叠选中的节点，已经折叠时移动到上一级
+pub fn tree_step(root: &mut FileItem, expand: bool) -> TreeStep {
+    let nodes = visible_nodes(root);
+    let current = match selected_position(root, &nodes) {
+        Some(position) => nodes[position].indices.clone(),
+        None => return TreeStep::Done,
+    };
+    let item = match node_at_mut(root, &current) {
+        Some(item) => item,
+        None => return TreeStep::Done,
+    };
+
+    if expand {
+        if !item.is_expanded {
+            item.is_expanded = true;
+            if item.children.is_empty() {
+                return TreeStep::Load(item.path.clone());
+            }
+            TreeStep::Done
+        } else {
+            item.children.first().map_or(TreeStep::Done, |child| TreeStep::Select(child.path.clone()))
+        }
+    } else if item.is_expanded && !item.children.is_empty() && !force_expanded(item) {
+        item.is_expanded = false;
+        TreeStep::Done
+    } else {
+        // 上一级节点为虚拟节点时不再移动
+        let parent = &current[..current.len().saturating_sub(1)];
+        match node_at(root, parent) {
+            Some(parent) if !current.is_empty() && !is_virtual(parent) => TreeStep::Select(parent.path.clone()),
+            _ => TreeStep::Done,
+        }
+    }
+}
+
+/// 驱动器节点总是显示子项，折叠没有效果
+fn force_expanded(item: &FileItem) -> bool {
+    item.name.ends_with(":\\")
+}
@@ -0,0 +1,612 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use druid::{Command, KbKey, KeyEvent};
+use serde::Deserialize;
+use crate::actions::{self, ActionScope};
+use crate::commands::JUMP_TO_NAME;
+use crate::models::AppState;
+use crate::settings;
+
+/// 默认按键绑定
+const DEFAULT_KEYMAP: &str = include_str!("assets/default_keymap.toml");
+
+/// 配置目录中用户按键绑定的文件名
+const USER_KEYMAP_FILE_NAME: &str = "keymap.toml";
+
+/// 取消绑定时使用的动作名称
+const UNBIND_ACTION: &str = "none";
+
+/// 按键序列中两次按键之间的最长间隔
+const SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// 按名称跳转时，超过该时间没有输入则重新开始输入名称
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_millis(1000);
+
+lazy_static::lazy_static! {
+    static ref KEYMAP: RwLock<Keymap> = RwLock::new(Keymap::default());
+}
+
+/// 一个按键组合，如 Ctrl+Shift+P
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+    /// 规范化的按键名称：字母为大写，其他字符保持原样，功能键使用 Up、PageDown、F5 等名称
+    pub key: String,
+}
+
+impl KeyChord {
+    /// 解析 "Ctrl+Shift+P" 形式的按键组合
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        // 以加号结尾时最后一个按键就是加号本身
+        let (modifiers, key) = match text.strip_suffix("++") {
+            Some(rest) => (rest, "+"),
+            None if text == "+" => ("", "+"),
+            None => match text.rsplit_once('+') {
+                Some((modifiers, key)) => (modifiers, key),
+                None => ("", text),
+            },
+        };
+
+        let mut chord = KeyChord {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            meta: false,
+            key: normalize_key_name(key).ok_or_else(|| format!("无法识别的按键 \"{}\"", key))?,
+        };
+        for modifier in modifiers.split('+').filter(|part| !part.is_empty()) {
+            match modifier.trim().to_lowercase().as_str() {
+                "ctrl" | "control" => chord.ctrl = true,
+                "alt" | "option" => chord.alt = true,
+                "shift" => chord.shift = true,
+                "meta" | "cmd" | "super" | "win" => chord.meta = true,
+                other => return Err(format!("无法识别的修饰键 \"{}\"", other)),
+            }
+        }
+        chord.ignore_shift_for_symbols();
+        Ok(chord)
+    }
+
+    /// 由按键事件生成按键组合，单独按下修饰键时返回 None
+    pub fn from_event(event: &KeyEvent) -> Option<Self> {
+        let key = match &event.key {
+            KbKey::Character(c) => normalize_key_name(c)?,
+            KbKey::Enter => "Enter".to_string(),
+            KbKey::Tab => "Tab".to_string(),
+            KbKey::Backspace => "Backspace".to_string(),
+            KbKey::Escape => "Escape".to_string(),
+            KbKey::Delete => "Delete".to_string(),
+            KbKey::Insert => "Insert".to_string(),
+            KbKey::ArrowUp => "Up".to_string(),
+            KbKey::ArrowDown => "Down".to_string(),
+            KbKey::ArrowLeft => "Left".to_string(),
+            KbKey::ArrowRight => "Right".to_string(),
+            KbKey::PageUp => "PageUp".to_string(),
+            KbKey::PageDown => "PageDown".to_string(),
+            KbKey::Home => "Home".to_string(),
+            KbKey::End => "End".to_string(),
+            KbKey::F1 => "F1".to_string(),
+            KbKey::F2 => "F2".to_string(),
+            KbKey::F3 => "F3".to_string(),
+            KbKey::F4 => "F4".to_string(),
+            KbKey::F5 => "F5".to_string(),
+            KbKey::F6 => "F6".to_string(),
+            KbKey::F7 => "F7".to_string(),
+            KbKey::F8 => "F8".to_string(),
+            KbKey::F9 => "F9".to_string(),
+            KbKey::F10 => "F10".to_string(),
+            KbKey::F11 => "F11".to_string(),
+            KbKey::F12 => "F12".to_string(),
+            _ => return None,
+        };
+
+        let mut chord = KeyChord {
+            ctrl: event.mods.ctrl(),
+            alt: event.mods.alt(),
+            shift: event.mods.shift(),
+            meta: event.mods.meta(),
+            key,
+        };
+        chord.ignore_shift_for_symbols();
+        Some(chord)
+    }
+
+    /// 符号字符本身已经体现了 Shift（如 Shift+/ 为 ?），因此不再区分 Shift
+    fn ignore_shift_for_symbols(&mut self) {
+        let mut chars = self.key.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            if !c.is_alphabetic() {
+                self.shift = false;
+            }
+        }
+    }
+
+    /// 是否带有 Ctrl、Alt 或 Meta，或者是功能键（输入框获得焦点时只响应这些按键）
+    fn is_command_chord(&self) -> bool {
+        let is_function_key = self.key.len() > 1 && self.key.starts_with('F') && self.key[1..].parse::<u8>().is_ok();
+        self.ctrl || self.alt || self.meta || is_function_key
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.meta {
+            write!(f, "Meta+")?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+/// 规范化按键名称，无法识别时返回 None
+fn normalize_key_name(name: &str) -> Option<String> {
+    if name == " " {
+        return Some("Space".to_string());
+    }
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(c.to_uppercase().collect());
+    }
+
+    let named = match name.to_lowercase().as_str() {
+        "space" => "Space",
+        "enter" | "return" => "Enter",
+        "tab" => "Tab",
+        "backspace" => "Backspace",
+        "escape" | "esc" => "Escape",
+        "delete" | "del" => "Delete",
+        "insert" | "ins" => "Insert",
+        "up" | "arrowup" => "Up",
+        "down" | "arrowdown" => "Down",
+        "left" | "arrowleft" => "Left",
+        "right" | "arrowright" => "Right",
+        "pageup" | "pgup" => "PageUp",
+        "pagedown" | "pgdn" => "PageDown",
+        "home" => "Home",
+        "end" => "End",
+        "comma" => ",",
+        "period" => ".",
+        "plus" => "+",
+        "minus" => "-",
+        other => {
+            let number = other.strip_prefix('f')?.parse::<u8>().ok()?;
+            return (1..=12).contains(&number).then(|| format!("F{}", number));
+        }
+    };
+    Some(named.to_string())
+}
+
+/// 解析用空格分隔的按键序列，如 "Ctrl+K Ctrl+S"
+pub fn parse_sequence(text: &str) -> Result<Vec<KeyChord>, String> {
+    let chords = text.split_whitespace().map(KeyChord::parse).collect::<Result<Vec<_>, _>>()?;
+    if chords.is_empty() {
+        return Err("按键为空".to_string());
+    }
+    Ok(chords)
+}
+
+/// 按键序列的显示文本
+pub fn format_sequence(keys: &[KeyChord]) -> String {
+    keys.iter().map(|chord| chord.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+/// 按键绑定文件的格式
+#[derive(Debug, Default, Deserialize)]
+struct KeymapFile {
+    #[serde(default)]
+    bindings: Vec<BindingEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BindingEntry {
+    key: String,
+    action: String,
+}
+
+/// 一个按键绑定
+#[derive(Clone, Debug, PartialEq)]
+pub struct Binding {
+    pub keys: Vec<KeyChord>,
+    pub action: String,
+    /// 是否来自用户配置
+    pub user: bool,
+}
+
+/// 按键序列的查找结果
+#[derive(Debug, PartialEq)]
+enum Lookup<'a> {
+    /// 完整匹配一个绑定
+    Action(&'a str),
+    /// 是某些绑定的开头，需要继续按键
+    Prefix,
+    None,
+}
+
+/// 按键绑定表：默认绑定加上用户的修改
+#[derive(Clone, Debug, Default)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+    /// 加载时发现的无效绑定和冲突
+    problems: Vec<String>,
+}
+
+impl Keymap {
+    /// 由默认绑定和用户绑定（TOML格式）生成绑定表
+    ///
+    /// 用户绑定的按键与默认绑定相同时替换默认绑定，动作为 "none" 时取消该按键的绑定。
+    pub fn from_sources(default: &str, user: Option<(&str, &str)>) -> Self {
+        let mut keymap = Keymap::default();
+        keymap.merge("默认按键绑定", default, false);
+        if let Some((name, content)) = user {
+            keymap.merge(name, content, true);
+        }
+        keymap.check_prefixes();
+        keymap
+    }
+
+    fn merge(&mut self, source: &str, content: &str, user: bool) {
+        let file: KeymapFile = match toml::from_str(content) {
+            Ok(file) => file,
+            Err(e) => {
+                self.problems.push(format!("解析{}失败: {}", source, e));
+                return;
+            }
+        };
+
+        // 记录本文件中每个按键序列绑定的动作，用于发现同一文件中的重复绑定
+        let mut seen: HashMap<Vec<KeyChord>, String> = HashMap::new();
+        for entry in file.bindings {
+            let keys = match parse_sequence(&entry.key) {
+                Ok(keys) => keys,
+                Err(e) => {
+                    self.problems.push(format!("{}中的按键 \"{}\" 无效: {}", source, entry.key, e));
+                    continue;
+                }
+            };
+            let unbind = entry.action == UNBIND_ACTION || entry.action.is_empty();
+            if !unbind && actions::find(&entry.action).is_none() {
+                self.problems.push(format!("{}中的按键 {} 绑定到未知的动作 \"{}\"", source, format_sequence(&keys), entry.action));
+                continue;
+            }
+            if let Some(previous) = seen.insert(keys.clone(), entry.action.clone()) {
+                self.problems.push(format!(
+                    "{}中的按键 {} 同时绑定到 {} 和 {}，使用后者",
+                    source, format_sequence(&keys), previous, entry.action
+                ));
+            }
+
+            self.bindings.retain(|binding| binding.keys != keys);
+            if !unbind {
+                self.bindings.push(Binding { keys, action: entry.action, user });
+            }
+        }
+    }
+
+    /// 较短的绑定是较长绑定的开头时，较长的绑定永远无法触发
+    fn check_prefixes(&mut self) {
+        for short in &self.bindings {
+            for long in &self.bindings {
+                if long.keys.len() > short.keys.len() && long.keys.starts_with(&short.keys) {
+                    self.problems.push(format!(
+                        "按键 {}（{}）使得 {}（{}）无法触发",
+                        format_sequence(&short.keys), short.action, format_sequence(&long.keys), long.action
+                    ));
+                }
+            }
+        }
+    }
+
+    fn lookup(&self, keys: &[KeyChord]) -> Lookup<'_> {
+        if let Some(binding) = self.bindings.iter().find(|binding| binding.keys == keys) {
+            return Lookup::Action(&binding.action);
+        }
+        if self.bindings.iter().any(|binding| binding.keys.starts_with(keys)) {
+            return Lookup::Prefix;
+        }
+        Lookup::None
+    }
+
+    /// 加载时发现的问题
+    pub fn problems(&self) -> &[String] {
+        &self.problems
+    }
+
+    /// 动作的所有按键（显示文本）
+    pub fn keys_for(&self, action: &str) -> Vec<String> {
+        self.bindings.iter()
+            .filter(|binding| binding.action == action)
+            .map(|binding| format_sequence(&binding.keys))
+            .collect()
+    }
+}
+
+/// 加载默认绑定和配置目录中的用户绑定，返回其中的问题
+pub fn load_keymap() -> Vec<String> {
+    let path = settings::config_dir().map(|dir| dir.join(USER_KEYMAP_FILE_NAME));
+    let user = match path.filter(|path| path.is_file()) {
+        Some(path) => match fs::read_to_string(&path) {
+            Ok(content) => Some((path, content)),
+            Err(e) => {
+                eprintln!("读取按键绑定 {} 失败: {}", path.display(), e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let keymap = match &user {
+        Some((path, content)) => {
+            let source = format!("用户按键绑定 {} ", path.display());
+            Keymap::from_sources(DEFAULT_KEYMAP, Some((&source, content)))
+        }
+        None => Keymap::from_sources(DEFAULT_KEYMAP, None),
+    };
+    let problems = keymap.problems().to_vec();
+    *KEYMAP.write().unwrap() = keymap;
+    problems
+}
+
+/// 在当前绑定表上执行查询（供快捷键列表等使用）
+pub fn with_keymap<R>(f: impl FnOnce(&Keymap) -> R) -> R {
+    f(&KEYMAP.read().unwrap())
+}
+
+/// 动作的第一个按键，用于菜单中显示，如 " (Ctrl+H)"；没有绑定时为空
+pub fn shortcut_label(action: &str) -> String {
+    with_keymap(|keymap| keymap.keys_for(action).into_iter().next())
+        .map(|keys| format!(" ({})", keys))
+        .unwrap_or_default()
+}
+
+/// 按键的处理结果
+pub enum KeyOutcome {
+    /// 执行命令
+    Command(Command),
+    /// 按键已被使用（按键序列的一部分，或者动作当前不可用）
+    Consumed,
+    /// 交给控件处理
+    Ignored,
+}
+
+/// 把主窗口中的按键转换为命令：查找按键绑定（包括按键序列），未绑定的字符用于按名称跳转
+#[derive(Default)]
+pub struct KeyHandler {
+    /// 按键序列中已经按下的部分
+    pending: Vec<KeyChord>,
+    last_key: Option<Instant>,
+    /// 按名称跳转时已经输入的字符
+    typed: String,
+    last_typed: Option<Instant>,
+}
+
+impl KeyHandler {
+    pub fn handle(&mut self, event: &KeyEvent, data: &AppState) -> KeyOutcome {
+        let chord = match KeyChord::from_event(event) {
+            Some(chord) => chord,
+            None => return KeyOutcome::Ignored,
+        };
+        if self.last_key.is_some_and(|last| last.elapsed() > SEQUENCE_TIMEOUT) {
+            self.pending.clear();
+        }
+        self.last_key = Some(Instant::now());
+
+        // 输入框获得焦点时，普通按键交给输入框
+        if !data.panes_focused && self.pending.is_empty() && !chord.is_command_chord() {
+            return KeyOutcome::Ignored;
+        }
+
+        // 正在输入名称时，空格等字符仍属于名称
+        if self.pending.is_empty() && self.is_typing() && !chord.is_command_chord() {
+            if let KbKey::Character(_) = &event.key {
+                return self.type_ahead(event, data);
+            }
+        }
+
+        let mut keys = std::mem::take(&mut self.pending);
+        keys.push(chord);
+        let keymap = KEYMAP.read().unwrap();
+        match keymap.lookup(&keys) {
+            Lookup::Action(id) => {
+                let action = match actions::find(id) {
+                    Some(action) => action,
+                    None => return KeyOutcome::Consumed,
+                };
+                if action.scope == ActionScope::Panes && !data.panes_focused {
+                    return KeyOutcome::Ignored;
+                }
+                match (action.command)(data) {
+                    Some(command) => KeyOutcome::Command(command),
+                    None => KeyOutcome::Consumed,
+                }
+            }
+            Lookup::Prefix => {
+                self.pending = keys;
+                KeyOutcome::Consumed
+            }
+            // 按键序列中途按错时放弃整个序列
+            Lookup::None if keys.len() > 1 => KeyOutcome::Consumed,
+            Lookup::None => self.type_ahead(event, data),
+        }
+    }
+
+    /// 目录树或文件列表拥有焦点时，未绑定的字符用于按名称跳转
+    fn type_ahead(&mut self, event: &KeyEvent, data: &AppState) -> KeyOutcome {
+        let text = match &event.key {
+            KbKey::Character(text) if data.panes_focused && !event.mods.ctrl() && !event.mods.alt() && !event.mods.meta() => text,
+            _ => return KeyOutcome::Ignored,
+        };
+        if !self.is_typing() {
+            self.typed.clear();
+        }
+        self.typed.push_str(text);
+        self.last_typed = Some(Instant::now());
+        KeyOutcome::Command(JUMP_TO_NAME.with(self.typed.clone()))
+    }
+
+    /// 是否正在输入名称（距离上次输入还没有超时）
+    fn is_typing(&self) -> bool {
+        self.last_typed.is_some_and(|last| last.elapsed() < TYPE_AHEAD_TIMEOUT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(text: &str) -> KeyChord {
+        KeyChord::parse(text).unwrap()
+    }
+
+    fn load(default: &str, user: Option<&str>) -> Keymap {
+        Keymap::from_sources(default, user.map(|content| ("用户按键绑定", content)))
+    }
+
+    #[test]
+    fn parse_modifiers_and_keys() {
+        let parsed = chord("ctrl+shift+p");
+        assert!(parsed.ctrl && parsed.shift && !parsed.alt && !parsed.meta);
+        assert_eq!(parsed.key, "P");
+        assert_eq!(parsed.to_string(), "Ctrl+Shift+P");
+        // 修饰键的顺序不影响结果
+        assert_eq!(chord("Shift+Ctrl+P"), parsed);
+        assert_eq!(chord("Cmd+Alt+PgDn").to_string(), "Alt+Meta+PageDown");
+        assert_eq!(chord("f5").key, "F5");
+        assert_eq!(chord("Space").key, "Space");
+    }
+
+    #[test]
+    fn parse_plus_key() {
+        let plus = chord("Ctrl++");
+        assert!(plus.ctrl);
+        assert_eq!(plus.key, "+");
+        assert_eq!(chord("+").key, "+");
+        assert_eq!(chord("Ctrl+Plus"), plus);
+    }
+
+    #[test]
+    fn shift_is_ignored_for_symbols() {
+        assert_eq!(chord("Shift+/"), chord("/"));
+        assert_eq!(chord("Ctrl+Shift++"), chord("Ctrl++"));
+        // 字母仍然区分 Shift
+        assert!(chord("Shift+A").shift);
+    }
+
+    #[test]
+    fn parse_rejects_unknown_names() {
+        assert!(KeyChord::parse("Ctrl+Foo").is_err());
+        assert!(KeyChord::parse("Hyper+A").is_err());
+        assert!(KeyChord::parse("F13").is_err());
+        assert!(KeyChord::parse("").is_err());
+    }
+
+    #[test]
+    fn parse_sequences() {
+        let keys = parse_sequence("Ctrl+K  Ctrl+S").unwrap();
+        assert_eq!(keys, vec![chord("Ctrl+K"), chord("Ctrl+S")]);
+        assert_eq!(format_sequence(&keys), "Ctrl+K Ctrl+S");
+        assert!(parse_sequence("   ").is_err());
+        assert!(parse_sequence("Ctrl+K Bogus+S").is_err());
+    }
+
+    #[test]
+    fn default_keymap_has_no_problems() {
+        let keymap = load(DEFAULT_KEYMAP, None);
+        assert!(keymap.problems().is_empty(), "{:?}", keymap.problems());
+    }
+
+    #[test]
+    fn user_bindings_replace_and_unbind_defaults() {
+        let default = r#"
+            [[bindings]]
+            key = "F5"
+            action = "navigate.refresh"
+
+            [[bindings]]
+            key = "Alt+Left"
+            action = "navigate.back"
+        "#;
+        let user = r#"
+            [[bindings]]
+            key = "f5"
+            action = "navigate.forward"
+
+            [[bindings]]
+            key = "Alt+Left"
+            action = "none"
+        "#;
+        let keymap = load(default, Some(user));
+        assert!(keymap.problems().is_empty(), "{:?}", keymap.problems());
+        assert_eq!(keymap.keys_for("navigate.forward"), vec!["F5"]);
+        assert!(keymap.keys_for("navigate.refresh").is_empty());
+        assert!(keymap.keys_for("navigate.back").is_empty());
+        assert_eq!(keymap.lookup(&[chord("Alt+Left")]), Lookup::None);
+    }
+
+    #[test]
+    fn merge_reports_problems() {
+        let user = r#"
+            [[bindings]]
+            key = "Bogus+X"
+            action = "navigate.back"
+
+            [[bindings]]
+            key = "F6"
+            action = "no.such.action"
+
+            [[bindings]]
+            key = "F7"
+            action = "navigate.back"
+
+            [[bindings]]
+            key = "F7"
+            action = "navigate.forward"
+        "#;
+        let keymap = load("", Some(user));
+        assert_eq!(keymap.problems().len(), 3, "{:?}", keymap.problems());
+        // 同一文件中重复的按键使用后者
+        assert_eq!(keymap.keys_for("navigate.forward"), vec!["F7"]);
+        assert!(keymap.keys_for("navigate.back").is_empty());
+
+        let keymap = load("", Some("bindings = 1"));
+        assert_eq!(keymap.problems().len(), 1);
+    }
+
+    #[test]
+    fn sequences_and_prefix_conflicts() {
+        let default = r#"
+            [[bindings]]
+            key = "Ctrl+K Ctrl+S"
+            action = "navigate.refresh"
+        "#;
+        let keymap = load(default, None);
+        assert!(keymap.problems().is_empty());
+        assert_eq!(keymap.lookup(&[chord("Ctrl+K")]), Lookup::Prefix);
+        assert_eq!(keymap.lookup(&[chord("Ctrl+K"), chord("Ctrl+S")]), Lookup::Action("navigate.refresh"));
+        assert_eq!(keymap.lookup(&[chord("Ctrl+S")]), Lookup::None);
+
+        // 较短的绑定遮住了较长的按键序列
+        let user = r#"
+            [[bindings]]
+            key = "Ctrl+K"
+            action = "navigate.back"
+        "#;
+        let keymap = load(default, Some(user));
+        assert_eq!(keymap.problems().len(), 1, "{:?}", keymap.problems());
+        assert_eq!(keymap.lookup(&[chord("Ctrl+K")]), Lookup::Action("navigate.back"));
+    }
+}
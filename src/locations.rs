@@ -0,0 +1,371 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+
+use crate::file_system;
+use crate::models::FileItem;
+use crate::settings;
+
+/// 访问记录文件名（位于配置目录下）
+const LOCATIONS_FILE_NAME: &str = "locations.json";
+
+/// 访问记录文件的格式版本
+const LOCATIONS_VERSION: u32 = 1;
+
+/// 所有目录的访问分数之和超过该值时整体衰减，长期不访问的目录逐渐被移除
+const MAX_TOTAL_RANK: f64 = 10000.0;
+
+/// 衰减时每个目录的分数乘以该系数
+const AGING_FACTOR: f64 = 0.9;
+
+/// 衰减后分数低于该值的目录被移除
+const MIN_RANK: f64 = 1.0;
+
+/// 目录树"最近访问"分组中显示的目录数量
+const RECENT_SECTION_SIZE: usize = 10;
+
+/// 目录树中"最近访问"分组的名称
+pub const RECENT_SECTION_NAME: &str = "最近访问";
+
+/// 目录树中"最近访问"分组使用的特殊路径（不对应真实目录）
+pub const RECENT_SECTION_PATH: &str = "RECENT";
+
+lazy_static::lazy_static! {
+    static ref LOCATIONS: Mutex<LocationStore> = Mutex::new(LocationStore::default());
+}
+
+/// 一个访问过的目录
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    pub path: PathBuf,
+    /// 访问分数，每次访问加一，整体衰减时按比例减少
+    pub rank: f64,
+    /// 最近一次访问的时间（Unix时间戳，秒）
+    pub last_visit: u64,
+}
+
+impl Location {
+    /// frecency 分数：访问分数按距离上次访问的时间加权（一小时内 ×4，一天内 ×2，一周内 ×0.5，更早 ×0.25）
+    pub fn score(&self, now: u64) -> f64 {
+        let elapsed = now.saturating_sub(self.last_visit);
+        let weight = if elapsed < 60 * 60 {
+            4.0
+        } else if elapsed < 24 * 60 * 60 {
+            2.0
+        } else if elapsed < 7 * 24 * 60 * 60 {
+            0.5
+        } else {
+            0.25
+        };
+        self.rank * weight
+    }
+}
+
+/// 访问记录文件的格式
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct LocationsFile {
+    version: u32,
+    locations: Vec<Location>,
+}
+
+/// 访问过的目录，与前进后退的导航历史分开保存
+#[derive(Clone, Debug, Default)]
+pub struct LocationStore {
+    locations: Vec<Location>,
+}
+
+impl LocationStore {
+    /// 记录一次访问
+    pub fn record(&mut self, path: &Path, now: u64) {
+        match self.locations.iter_mut().find(|location| location.path == path) {
+            Some(location) => {
+                location.rank += 1.0;
+                location.last_visit = now;
+            }
+            None => self.locations.push(Location {
+                path: path.to_path_buf(),
+                rank: 1.0,
+                last_visit: now,
+            }),
+        }
+        self.age();
+    }
+
+    /// 分数之和过大时整体衰减，并移除分数过低的目录
+    fn age(&mut self) {
+        let total: f64 = self.locations.iter().map(|location| location.rank).sum();
+        if total <= MAX_TOTAL_RANK {
+            return;
+        }
+        for location in &mut self.locations {
+            location.rank *= AGING_FACTOR;
+        }
+        self.locations.retain(|location| location.rank >= MIN_RANK);
+    }
+
+    /// 按 frecency 分数从高到低排列的所有目录
+    pub fn ranked(&self, now: u64) -> Vec<&Location> {
+        let mut ranked: Vec<&Location> = self.locations.iter().collect();
+        ranked.sort_by(|a, b| b.score(now).total_cmp(&a.score(now)));
+        ranked
+    }
+
+    /// 与所有关键词匹配的目录，按 frecency 分数从高到低排列
+    pub fn query(&self, keywords: &str, now: u64) -> Vec<&Location> {
+        let keywords: Vec<String> = keywords.split_whitespace().map(str::to_lowercase).collect();
+        self.ranked(now)
+            .into_iter()
+            .filter(|location| matches_keywords(&location.path, &keywords))
+            .collect()
+    }
+}
+
+/// 路径是否与关键词匹配：关键词按顺序出现在路径中（不区分大小写），最后一个关键词出现在最后一级目录名中
+///
+/// 例如 "doc rep" 匹配 `/home/me/Documents/reports`，但不匹配 `/home/me/reports/docs`。
+pub fn matches_keywords(path: &Path, keywords: &[String]) -> bool {
+    let last = match keywords.last() {
+        Some(last) => last,
+        None => return true,
+    };
+    let text = path.to_string_lossy().to_lowercase();
+    let name = path.file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if !name.contains(last.as_str()) {
+        return false;
+    }
+
+    let mut rest = text.as_str();
+    for keyword in keywords {
+        match rest.find(keyword.as_str()) {
+            Some(index) => rest = &rest[index + keyword.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// 当前时间（Unix时间戳，秒）
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0)
+}
+
+/// 访问记录文件的保存位置
+fn locations_file_path() -> Option<PathBuf> {
+    settings::config_dir().map(|dir| dir.join(LOCATIONS_FILE_NAME))
+}
+
+/// 启动时读取访问记录，文件不存在时从空记录开始
+pub fn load_locations() {
+    let path = match locations_file_path() {
+        Some(path) if path.is_file() => path,
+        _ => return,
+    };
+    let file: LocationsFile = match fs::read_to_string(&path).map(|content| serde_json::from_str(&content)) {
+        Ok(Ok(file)) => file,
+        Ok(Err(e)) => {
+            eprintln!("解析访问记录 {} 失败: {}", path.display(), e);
+            return;
+        }
+        Err(e) => {
+            eprintln!("读取访问记录 {} 失败: {}", path.display(), e);
+            return;
+        }
+    };
+    LOCATIONS.lock().unwrap().locations = file.locations;
+}
+
+/// 保存访问记录
+pub fn save_locations() -> io::Result<()> {
+    let path = match locations_file_path() {
+        Some(path) => path,
+        None => return Err(io::Error::new(io::ErrorKind::NotFound, "无法确定配置目录")),
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = LocationsFile {
+        version: LOCATIONS_VERSION,
+        locations: LOCATIONS.lock().unwrap().locations.clone(),
+    };
+    let content = serde_json::to_string_pretty(&file)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(path, content)
+}
+
+/// 记录一次对目录的访问
+pub fn record_visit(path: &Path) {
+    LOCATIONS.lock().unwrap().record(path, now());
+}
+
+/// 最常访问的目录（按 frecency 排序，跳过已经不存在的目录）
+pub fn frecent(limit: usize) -> Vec<PathBuf> {
+    LOCATIONS.lock().unwrap()
+        .ranked(now())
+        .into_iter()
+        .map(|location| location.path.clone())
+        .filter(|path| path.is_dir())
+        .take(limit)
+        .collect()
+}
+
+/// 快速跳转：与输入的路径片段匹配的目录（按 frecency 排序，跳过已经不存在的目录）
+pub fn jump_candidates(keywords: &str, limit: usize) -> Vec<PathBuf> {
+    LOCATIONS.lock().unwrap()
+        .query(keywords, now())
+        .into_iter()
+        .map(|location| location.path.clone())
+        .filter(|path| path.is_dir())
+        .take(limit)
+        .collect()
+}
+
+/// 是否为目录树中的"最近访问"分组节点
+pub fn is_recent_section(item: &FileItem) -> bool {
+    item.path == Path::new(RECENT_SECTION_PATH)
+}
+
+/// 生成目录树中的"最近访问"分组，保留 `previous` 中仍然存在的目录的展开状态和已加载的子目录
+pub fn build_recent_section(previous: Option<&FileItem>) -> FileItem {
+    let children = frecent(RECENT_SECTION_SIZE)
+        .into_iter()
+        .map(|path| {
+            let existing = previous.and_then(|section| section.children.iter().find(|child| child.path == path));
+            match existing {
+                Some(child) => child.clone(),
+                None => {
+                    let name = path.file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_else(|| path.display().to_string());
+                    FileItem {
+                        is_hidden: file_system::is_hidden(&name, fs::metadata(&path).ok().as_ref()),
+                        name,
+                        children: Vec::new(),
+                        is_expanded: false,
+                        path,
+                        is_selected: false,
+                        is_dir: true,
+                        diff: None,
+                        volume: None,
+                    }
+                }
+            }
+        })
+        .collect();
+
+    FileItem {
+        name: RECENT_SECTION_NAME.to_string(),
+        children,
+        is_expanded: previous.is_none_or(|section| section.is_expanded),
+        path: PathBuf::from(RECENT_SECTION_PATH),
+        is_selected: false,
+        is_dir: true,
+        diff: None,
+        volume: None,
+        is_hidden: false,
+    }
+}
+
+/// 访问记录变化后更新目录树中的"最近访问"分组
+pub fn refresh_recent_section(root: &mut FileItem) {
+    if let Some(section) = root.children.iter_mut().find(|child| is_recent_section(child)) {
+        *section = build_recent_section(Some(section));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 60 * 60;
+    const DAY: u64 = 24 * HOUR;
+
+    fn keywords(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_lowercase).collect()
+    }
+
+    fn location(path: &str, rank: f64, last_visit: u64) -> Location {
+        Location { path: PathBuf::from(path), rank, last_visit }
+    }
+
+    #[test]
+    fn record_adds_and_counts_visits() {
+        let mut store = LocationStore::default();
+        store.record(Path::new("/a"), 100);
+        store.record(Path::new("/b"), 150);
+        store.record(Path::new("/a"), 200);
+        assert_eq!(store.locations, vec![location("/a", 2.0, 200), location("/b", 1.0, 150)]);
+    }
+
+    #[test]
+    fn age_decays_and_drops_rare_locations() {
+        let mut store = LocationStore {
+            locations: vec![location("/often", MAX_TOTAL_RANK, 0), location("/rare", 1.05, 0)],
+        };
+        store.record(Path::new("/often"), 10);
+        // 总分超过上限后整体乘以衰减系数，低于最小分数的目录被移除
+        assert_eq!(store.locations.len(), 1);
+        assert_eq!(store.locations[0].path, PathBuf::from("/often"));
+        assert!((store.locations[0].rank - (MAX_TOTAL_RANK + 1.0) * AGING_FACTOR).abs() < 1e-6);
+
+        // 未超过上限时不衰减
+        let mut store = LocationStore { locations: vec![location("/a", 5.0, 0)] };
+        store.age();
+        assert_eq!(store.locations, vec![location("/a", 5.0, 0)]);
+    }
+
+    #[test]
+    fn ranked_weights_recent_visits() {
+        let now = 100 * DAY;
+        let store = LocationStore {
+            locations: vec![
+                location("/old", 10.0, now - 30 * DAY),
+                location("/week", 3.0, now - 2 * DAY),
+                location("/today", 1.0, now - 2 * HOUR),
+                location("/now", 1.0, now),
+            ],
+        };
+        let paths: Vec<_> = store.ranked(now).iter().map(|location| location.path.clone()).collect();
+        // 分数依次为 2.5、1.5、2、4
+        assert_eq!(paths, vec![
+            PathBuf::from("/now"),
+            PathBuf::from("/old"),
+            PathBuf::from("/today"),
+            PathBuf::from("/week"),
+        ]);
+    }
+
+    #[test]
+    fn keywords_match_in_order_and_end_in_last_name() {
+        let reports = Path::new("/home/me/Documents/reports");
+        assert!(matches_keywords(reports, &keywords("doc rep")));
+        assert!(matches_keywords(reports, &keywords("DOC REP")));
+        assert!(matches_keywords(reports, &keywords("rep")));
+        assert!(matches_keywords(reports, &[]));
+        assert!(!matches_keywords(Path::new("/home/me/reports/docs"), &keywords("doc rep")));
+        // 关键词需要按顺序出现
+        assert!(!matches_keywords(Path::new("/rep/doc/reports"), &keywords("doc me rep")));
+        assert!(!matches_keywords(reports, &keywords("doc")));
+    }
+
+    #[test]
+    fn query_filters_ranked_locations() {
+        let store = LocationStore {
+            locations: vec![
+                location("/home/me/reports/docs", 5.0, 0),
+                location("/home/me/Documents/reports", 1.0, 0),
+                location("/home/me/Documents/old-reports", 2.0, 0),
+            ],
+        };
+        let paths: Vec<_> = store.query("doc rep", 0).iter().map(|location| location.path.clone()).collect();
+        assert_eq!(paths, vec![
+            PathBuf::from("/home/me/Documents/old-reports"),
+            PathBuf::from("/home/me/Documents/reports"),
+        ]);
+    }
+}
@@ -9,6 +9,28 @@ mod system;
 mod delegate;
 mod utils;
 mod app;
+mod session;
+mod settings;
+mod file_types;
+mod detection;
+mod properties;
+mod jobs;
+mod permissions;
+mod folder_size;
+mod disk_usage;
+mod duplicates;
+mod checksum;
+mod batch_rename;
+mod dir_compare;
+mod sync;
+mod file_ops;
+mod keyboard_nav;
+mod actions;
+mod keymap;
+mod command_palette;
+mod locations;
+mod volumes;
+mod quick_filter;
 
 // 导入所需的类型和函数
 use druid::Selector;
@@ -1,6 +1,23 @@
 use druid::{Data, Lens, im::Vector};
 use druid_widget_nursery::TreeNode;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use crate::batch_rename::BatchRenameState;
+use crate::checksum::ChecksumState;
+use crate::command_palette::PaletteState;
+use crate::dir_compare::{DiffStatus, DirCompareState};
+use crate::disk_usage::DiskUsageState;
+use crate::duplicates::DuplicateState;
+use crate::file_types::FileKind;
+use crate::folder_size::FolderSize;
+use crate::jobs::JobStatus;
+use crate::keyboard_nav::{find_by_prefix, FocusMove, FocusPane};
+use crate::permissions::PermissionEdit;
+use crate::properties::FileProperties;
+use crate::quick_filter::QuickFilterState;
+use crate::settings::Settings;
+use crate::sync::SyncState;
+use crate::ui::theme::Theme;
+use crate::volumes::Volume;
 
 /// 文件项结构体，表示文件系统中的一个文件或目录
 #[derive(Clone, Data, Lens, Debug, PartialEq)]
@@ -17,6 +34,14 @@ pub struct FileItem {
     pub path: PathBuf,
     /// 当前项是否被选中
     pub is_selected: bool,
+    /// 是否为目录（目录树中的节点都是目录；目录比较结果中用来区分空目录和文件）
+    pub is_dir: bool,
+    /// 目录比较结果中的状态（目录树中为 None）
+    pub diff: Option<DiffStatus>,
+    /// 驱动器节点的卷信息（卷标、文件系统类型和使用情况，其他节点为 None）
+    pub volume: Option<Volume>,
+    /// 是否为隐藏目录（与文件列表使用相同的判断，目录树中淡化显示）
+    pub is_hidden: bool,
 }
 
 /// 实现TreeNode特性，使FileItem可以在Tree控件中使用
@@ -63,14 +88,38 @@ pub struct FileDetail {
     pub name: String,
     /// 文件大小（对目录为0）
     pub size: u64,
-    /// 文件类型（文件/目录）
-    pub file_type: String,
+    /// 文件类型（来自文件类型注册表）
+    pub kind: FileKind,
+    /// 是否已经按文件内容识别过类型（目录无需识别）
+    pub kind_detected: bool,
+    /// 目录的递归大小（开启计算文件夹大小后异步填入）
+    pub folder_size: Option<FolderSize>,
     /// 修改时间
     #[data(same_fn = "PartialEq::eq")]
     pub modified: String,
     /// 文件的完整路径
     #[data(same_fn = "PartialEq::eq")]
     pub full_path: PathBuf,
+    /// 是否为隐藏文件（显示隐藏文件时淡化显示）
+    pub is_hidden: bool,
+    /// 是否在文件列表中被选中
+    pub is_selected: bool,
+    /// 是否为键盘焦点所在的行
+    pub is_focused: bool,
+    /// 快速筛选时名称中匹配部分的字节范围（用于高亮）
+    #[data(same_fn = "PartialEq::eq")]
+    pub name_matches: Vec<(usize, usize)>,
+}
+
+/// 在文件列表中点击时修改选择的方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionMode {
+    /// 只选中点击的项
+    Replace,
+    /// 切换点击项的选中状态（Ctrl+点击）
+    Toggle,
+    /// 选中从上次点击的项到点击项之间的所有项（Shift+点击）
+    Range,
 }
 
 /// 应用程序状态结构体
@@ -83,61 +132,405 @@ pub struct AppState {
     pub selected_path: Option<PathBuf>,
     /// 当前目录下的文件列表（用于右侧面板显示）
     pub current_dir_files: Vector<FileDetail>,
+    /// Shift+点击时范围选择的起点
+    #[data(same_fn = "PartialEq::eq")]
+    pub selection_anchor: Option<PathBuf>,
+    /// 键盘焦点所在的面板
+    pub focus_pane: FocusPane,
+    /// 文件列表中键盘焦点所在的文件
+    #[data(same_fn = "PartialEq::eq")]
+    pub focused_file: Option<PathBuf>,
+    /// 键盘焦点是否在目录树或文件列表中（为 false 时在输入框等控件中）
+    pub panes_focused: bool,
+    /// 命令面板的状态
+    pub palette: PaletteState,
     /// 导航历史记录（已访问的路径）
     #[data(same_fn = "PartialEq::eq")]
     pub navigation_history: Vec<PathBuf>,
     /// 当前在历史记录中的位置
     pub history_position: usize,
+    /// 地址栏中正在输入、尚未确认的路径（为 None 时显示当前目录）
+    pub address_input: Option<String>,
+    /// 右侧面板是否显示"我的电脑"的驱动器概览（而不是文件列表）
+    pub drive_overview: bool,
+    /// 当前目录所在卷的可用空间和总空间（字节），无法获取时为 (0, 0)
+    pub current_space: (u64, u64),
+    /// 当前目录的快速筛选
+    pub quick_filter: QuickFilterState,
+    /// 正在后台加载其余文件的目录（大目录分页加载时）
+    #[data(same_fn = "PartialEq::eq")]
+    pub loading_path: Option<PathBuf>,
+    /// 主窗口尺寸（宽, 高），用于保存会话
+    pub window_size: (f64, f64),
+    /// 目录树与文件列表之间的分割位置，用于保存会话
+    pub split_point: f64,
+    /// 当前生效的设置
+    pub settings: Settings,
+    /// 设置窗口中正在编辑的设置
+    pub settings_draft: Settings,
+    /// 设置窗口中显示的提示（配置文件警告、校验错误、保存结果）
+    pub settings_notice: String,
+    /// 当前主题
+    pub theme: Theme,
+    /// 属性窗口中显示的文件信息
+    pub properties: FileProperties,
+    /// 属性窗口中正在编辑的权限
+    pub permission_edit: PermissionEdit,
+    /// 后台任务（修改权限等）的状态
+    pub jobs: Vector<JobStatus>,
+    /// 主窗口状态栏中的提示（拖放被拒绝、移动或复制失败等），导航到其他目录时清除
+    pub status_notice: String,
+    /// 磁盘占用分析窗口的状态
+    pub disk_usage: DiskUsageState,
+    /// 重复文件窗口的状态
+    pub duplicates: DuplicateState,
+    /// 校验和窗口的状态
+    pub checksums: ChecksumState,
+    /// 批量重命名窗口的状态
+    pub batch_rename: BatchRenameState,
+    /// 目录比较窗口的状态
+    pub dir_compare: DirCompareState,
+    /// 同步任务窗口的状态
+    pub sync: SyncState,
 }
 
 impl AppState {
-    /// 添加路径到导航历史记录
+    /// 添加路径到导航历史记录（丢弃当前位置之后的记录）
     pub fn add_to_history(&mut self, path: PathBuf) {
-        // 检查是否与当前路径相同，避免重复添加
-        if let Some(current) = &self.selected_path {
-            if current == &path {
-                return;
-            }
+        // 与当前记录相同时不重复添加（如刷新或重新选择当前目录）
+        if self.navigation_history.get(self.history_position) == Some(&path) {
+            return;
         }
-        
-        // 如果当前不在历史记录末尾，截断后面的记录
-        if self.history_position < self.navigation_history.len() {
-            self.navigation_history.truncate(self.history_position + 1);
-        }
-        
-        // 添加新路径到历史记录
+
+        self.navigation_history.truncate(self.history_position + 1);
         self.navigation_history.push(path);
-        // 更新位置指向新添加的记录
         self.history_position = self.navigation_history.len() - 1;
     }
-    
+
     /// 导航到历史记录中的上一个路径
     pub fn navigate_back(&mut self) -> Option<PathBuf> {
-        if self.history_position > 0 {
+        if self.can_navigate_back() {
             self.history_position -= 1;
             Some(self.navigation_history[self.history_position].clone())
         } else {
             None
         }
     }
-    
+
     /// 导航到历史记录中的下一个路径
     pub fn navigate_forward(&mut self) -> Option<PathBuf> {
-        if self.history_position < self.navigation_history.len() - 1 {
+        if self.can_navigate_forward() {
             self.history_position += 1;
             Some(self.navigation_history[self.history_position].clone())
         } else {
             None
         }
     }
-    
+
+    /// 直接跳到历史记录中的某一项（不改变历史记录本身）
+    pub fn navigate_to_history_index(&mut self, index: usize) -> Option<PathBuf> {
+        let path = self.navigation_history.get(index)?.clone();
+        self.history_position = index;
+        Some(path)
+    }
+
+    /// 后退可以到达的记录（历史记录中的位置和路径），离当前位置最近的在前
+    pub fn back_entries(&self) -> Vec<(usize, PathBuf)> {
+        let end = self.history_position.min(self.navigation_history.len());
+        self.navigation_history[..end].iter().cloned().enumerate().rev().collect()
+    }
+
+    /// 前进可以到达的记录（历史记录中的位置和路径），离当前位置最近的在前
+    pub fn forward_entries(&self) -> Vec<(usize, PathBuf)> {
+        self.navigation_history.iter()
+            .cloned()
+            .enumerate()
+            .skip(self.history_position + 1)
+            .collect()
+    }
+
     /// 检查是否可以后退
     pub fn can_navigate_back(&self) -> bool {
         self.history_position > 0
     }
     
+    /// 按点击方式修改文件列表的选择
+    pub fn select_file(&mut self, path: &Path, mode: SelectionMode) {
+        let clicked = self.current_dir_files.iter().position(|file| file.full_path == path);
+        let clicked = match clicked {
+            Some(index) => index,
+            None => return,
+        };
+
+        match mode {
+            SelectionMode::Replace => {
+                for (index, file) in self.current_dir_files.iter_mut().enumerate() {
+                    file.is_selected = index == clicked;
+                }
+            }
+            SelectionMode::Toggle => {
+                let file = &mut self.current_dir_files[clicked];
+                file.is_selected = !file.is_selected;
+            }
+            SelectionMode::Range => {
+                // 没有起点时（如用键盘第一次扩展选择）以原来的焦点为起点
+                let previous_focus = self.focused_index();
+                self.focus_file(clicked);
+                let anchor = self.selection_anchor.as_ref()
+                    .and_then(|anchor| self.current_dir_files.iter().position(|file| &file.full_path == anchor))
+                    .or(previous_focus)
+                    .unwrap_or(clicked);
+                if self.selection_anchor.is_none() {
+                    self.selection_anchor = Some(self.current_dir_files[anchor].full_path.clone());
+                }
+                let (start, end) = (anchor.min(clicked), anchor.max(clicked));
+                for (index, file) in self.current_dir_files.iter_mut().enumerate() {
+                    file.is_selected = (start..=end).contains(&index);
+                }
+                // 范围选择不移动起点
+                return;
+            }
+        }
+        self.focus_file(clicked);
+        self.selection_anchor = Some(path.to_path_buf());
+    }
+
+    /// 文件列表中键盘焦点所在的行
+    pub fn focused_index(&self) -> Option<usize> {
+        let focused = self.focused_file.as_ref()?;
+        self.current_dir_files.iter().position(|file| &file.full_path == focused)
+    }
+
+    /// 将键盘焦点移到文件列表的指定行
+    pub fn focus_file(&mut self, index: usize) {
+        self.focused_file = self.current_dir_files.get(index).map(|file| file.full_path.clone());
+        for (row, file) in self.current_dir_files.iter_mut().enumerate() {
+            if file.is_focused != (row == index) {
+                file.is_focused = row == index;
+            }
+        }
+    }
+
+    /// 按键盘移动文件列表中的焦点，`mode` 为 None 时只移动焦点而不修改选择
+    pub fn move_list_focus(&mut self, movement: FocusMove, mode: Option<SelectionMode>) {
+        if self.current_dir_files.is_empty() {
+            return;
+        }
+        let target = movement.apply(self.focused_index(), self.current_dir_files.len());
+        match mode {
+            Some(mode) => {
+                let path = self.current_dir_files[target].full_path.clone();
+                self.select_file(&path, mode);
+            }
+            None => self.focus_file(target),
+        }
+    }
+
+    /// 按名称跳转到文件列表中以 `prefix` 开头的项，并只选中该项
+    pub fn jump_to_name(&mut self, prefix: &str) {
+        let names = self.current_dir_files.iter().map(|file| file.name.as_str());
+        if let Some(index) = find_by_prefix(names, self.focused_index(), prefix) {
+            let path = self.current_dir_files[index].full_path.clone();
+            self.select_file(&path, SelectionMode::Replace);
+        }
+    }
+
+    /// 文件列表重新加载后恢复焦点：焦点所在的文件仍在列表中时保持不变，
+    /// 否则移到 `previous_dir`（返回上级目录时为刚离开的目录），都没有时移到第一行
+    pub fn restore_list_focus(&mut self, previous_dir: Option<&Path>) {
+        let index = self.focused_index()
+            .or_else(|| {
+                let previous_dir = previous_dir?;
+                self.current_dir_files.iter().position(|file| file.full_path == previous_dir)
+            })
+            .unwrap_or(0);
+        if index < self.current_dir_files.len() {
+            self.focus_file(index);
+        } else {
+            self.focused_file = None;
+        }
+    }
+
+    /// 文件列表中选中的所有路径
+    pub fn selected_files(&self) -> Vec<PathBuf> {
+        self.current_dir_files
+            .iter()
+            .filter(|file| file.is_selected)
+            .map(|file| file.full_path.clone())
+            .collect()
+    }
+
+    /// 右键菜单操作的对象：右键点击的项已被选中时为所有选中的项，否则只有该项
+    pub fn selection_for(&self, path: &Path) -> Vec<PathBuf> {
+        let selected = self.selected_files();
+        if selected.iter().any(|selected| selected == path) {
+            selected
+        } else {
+            vec![path.to_path_buf()]
+        }
+    }
+
     /// 检查是否可以前进
     pub fn can_navigate_forward(&self) -> bool {
-        self.history_position < self.navigation_history.len() - 1
+        self.history_position + 1 < self.navigation_history.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 历史记录为 `history`、当前位置为 `position` 的状态
+    fn state(history: &[&str], position: usize) -> AppState {
+        let navigation_history: Vec<PathBuf> = history.iter().map(PathBuf::from).collect();
+        AppState {
+            root: FileItem {
+                name: "文件导航".to_string(),
+                is_expanded: true,
+                children: Vec::new(),
+                path: PathBuf::from("ROOT"),
+                is_selected: false,
+                is_dir: true,
+                diff: None,
+                volume: None,
+                is_hidden: false,
+            },
+            selected_path: navigation_history.get(position).cloned(),
+            current_dir_files: Vector::new(),
+            selection_anchor: None,
+            focus_pane: FocusPane::List,
+            focused_file: None,
+            panes_focused: true,
+            palette: PaletteState::default(),
+            navigation_history,
+            history_position: position,
+            address_input: None,
+            drive_overview: false,
+            current_space: (0, 0),
+            loading_path: None,
+            quick_filter: QuickFilterState::default(),
+            window_size: (800.0, 600.0),
+            split_point: 0.3,
+            settings_draft: Settings::default(),
+            settings: Settings::default(),
+            settings_notice: String::new(),
+            theme: Theme::default_theme(),
+            properties: FileProperties::default(),
+            permission_edit: PermissionEdit::default(),
+            jobs: Vector::new(),
+            status_notice: String::new(),
+            disk_usage: DiskUsageState::default(),
+            duplicates: DuplicateState::default(),
+            checksums: ChecksumState::default(),
+            batch_rename: BatchRenameState::default(),
+            dir_compare: DirCompareState::default(),
+            sync: SyncState::default(),
+        }
+    }
+
+    fn history(data: &AppState) -> Vec<&str> {
+        data.navigation_history.iter().map(|path| path.to_str().unwrap()).collect()
+    }
+
+    fn entries(entries: Vec<(usize, PathBuf)>) -> Vec<(usize, String)> {
+        entries.into_iter().map(|(index, path)| (index, path.display().to_string())).collect()
+    }
+
+    #[test]
+    fn add_to_history_appends_and_moves_to_end() {
+        let mut data = state(&["/a"], 0);
+        data.add_to_history(PathBuf::from("/b"));
+        data.add_to_history(PathBuf::from("/c"));
+        assert_eq!(history(&data), ["/a", "/b", "/c"]);
+        assert_eq!(data.history_position, 2);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn add_to_history_starts_empty_history() {
+        let mut data = state(&[], 0);
+        data.add_to_history(PathBuf::from("/a"));
+        assert_eq!(history(&data), ["/a"]);
+        assert_eq!(data.history_position, 0);
+    }
+
+    #[test]
+    fn add_to_history_ignores_current_entry() {
+        let mut data = state(&["/a", "/b"], 1);
+        data.add_to_history(PathBuf::from("/b"));
+        assert_eq!(history(&data), ["/a", "/b"]);
+        assert_eq!(data.history_position, 1);
+    }
+
+    #[test]
+    fn add_to_history_compares_with_history_not_selected_path() {
+        // 当前路径已经变化（如地址栏输入）时，也要记录新目录
+        let mut data = state(&["/a"], 0);
+        data.selected_path = Some(PathBuf::from("/b"));
+        data.add_to_history(PathBuf::from("/b"));
+        assert_eq!(history(&data), ["/a", "/b"]);
+    }
+
+    #[test]
+    fn add_to_history_discards_forward_entries() {
+        let mut data = state(&["/a", "/b", "/c"], 1);
+        data.add_to_history(PathBuf::from("/d"));
+        assert_eq!(history(&data), ["/a", "/b", "/d"]);
+        assert_eq!(data.history_position, 2);
+        assert!(!data.can_navigate_forward());
+    }
+
+    #[test]
+    fn navigate_back_and_forward_move_position() {
+        let mut data = state(&["/a", "/b", "/c"], 2);
+        assert_eq!(data.navigate_back(), Some(PathBuf::from("/b")));
+        assert_eq!(data.navigate_back(), Some(PathBuf::from("/a")));
+        assert_eq!(data.navigate_back(), None);
+        assert_eq!(data.history_position, 0);
+
+        assert_eq!(data.navigate_forward(), Some(PathBuf::from("/b")));
+        assert_eq!(data.navigate_forward(), Some(PathBuf::from("/c")));
+        assert_eq!(data.navigate_forward(), None);
+        assert_eq!(data.history_position, 2);
+        assert_eq!(history(&data), ["/a", "/b", "/c"]);
+    }
+
+    #[test]
+    fn navigation_on_empty_history() {
+        let mut data = state(&[], 0);
+        assert!(!data.can_navigate_back());
+        assert!(!data.can_navigate_forward());
+        assert_eq!(data.navigate_back(), None);
+        assert_eq!(data.navigate_forward(), None);
+        assert!(data.back_entries().is_empty());
+        assert!(data.forward_entries().is_empty());
+    }
+
+    #[test]
+    fn can_navigate_reflects_position() {
+        let data = state(&["/a", "/b", "/c"], 1);
+        assert!(data.can_navigate_back());
+        assert!(data.can_navigate_forward());
+
+        let data = state(&["/a"], 0);
+        assert!(!data.can_navigate_back());
+        assert!(!data.can_navigate_forward());
+    }
+
+    #[test]
+    fn navigate_to_history_index_keeps_history() {
+        let mut data = state(&["/a", "/b", "/c"], 2);
+        assert_eq!(data.navigate_to_history_index(0), Some(PathBuf::from("/a")));
+        assert_eq!(data.history_position, 0);
+        assert_eq!(history(&data), ["/a", "/b", "/c"]);
+
+        assert_eq!(data.navigate_to_history_index(3), None);
+        assert_eq!(data.history_position, 0);
+    }
+
+    #[test]
+    fn back_and_forward_entries_nearest_first() {
+        let data = state(&["/a", "/b", "/c", "/d", "/e"], 2);
+        assert_eq!(entries(data.back_entries()), [(1, "/b".to_string()), (0, "/a".to_string())]);
+        assert_eq!(entries(data.forward_entries()), [(3, "/d".to_string()), (4, "/e".to_string())]);
+    }
+}
@@ -0,0 +1,406 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use druid::{Data, Lens};
+
+use crate::jobs::JobContext;
+
+/// 递归修改权限时的目标
+#[derive(Clone, Copy, Data, Debug, PartialEq, Eq)]
+pub enum RecursiveTarget {
+    /// 文件和目录
+    All,
+    /// 只修改文件
+    FilesOnly,
+    /// 只修改目录
+    DirectoriesOnly,
+}
+
+/// 属性窗口中正在编辑的权限
+#[derive(Clone, Data, Lens, Debug, PartialEq)]
+pub struct PermissionEdit {
+    pub user_read: bool,
+    pub user_write: bool,
+    pub user_exec: bool,
+    pub group_read: bool,
+    pub group_write: bool,
+    pub group_exec: bool,
+    pub other_read: bool,
+    pub other_write: bool,
+    pub other_exec: bool,
+    pub setuid: bool,
+    pub setgid: bool,
+    pub sticky: bool,
+    /// 八进制形式的权限，与复选框保持同步
+    pub octal: String,
+    /// 新的所有者（用户名或uid，留空则不修改）
+    pub owner: String,
+    /// 新的所属组（组名或gid，留空则不修改）
+    pub group: String,
+    /// 是否递归应用到目录中的所有条目
+    pub recursive: bool,
+    /// 递归应用时的目标
+    pub recursive_target: RecursiveTarget,
+    /// 校验错误等提示
+    pub notice: String,
+    /// 正在运行或最近一次运行的修改任务ID
+    pub job_id: Option<u64>,
+}
+
+impl Default for PermissionEdit {
+    fn default() -> Self {
+        Self::from_mode(0o644)
+    }
+}
+
+impl PermissionEdit {
+    /// 从权限位创建编辑状态
+    pub fn from_mode(mode: u32) -> Self {
+        let mut edit = Self {
+            user_read: false,
+            user_write: false,
+            user_exec: false,
+            group_read: false,
+            group_write: false,
+            group_exec: false,
+            other_read: false,
+            other_write: false,
+            other_exec: false,
+            setuid: false,
+            setgid: false,
+            sticky: false,
+            octal: String::new(),
+            owner: String::new(),
+            group: String::new(),
+            recursive: false,
+            recursive_target: RecursiveTarget::All,
+            notice: String::new(),
+            job_id: None,
+        };
+        edit.set_mode(mode);
+        edit
+    }
+
+    /// 复选框对应的权限位
+    pub fn mode(&self) -> u32 {
+        [
+            (self.setuid, 0o4000),
+            (self.setgid, 0o2000),
+            (self.sticky, 0o1000),
+            (self.user_read, 0o400),
+            (self.user_write, 0o200),
+            (self.user_exec, 0o100),
+            (self.group_read, 0o040),
+            (self.group_write, 0o020),
+            (self.group_exec, 0o010),
+            (self.other_read, 0o004),
+            (self.other_write, 0o002),
+            (self.other_exec, 0o001),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .fold(0, |mode, (_, bit)| mode | bit)
+    }
+
+    /// 按权限位设置复选框和八进制文本
+    pub fn set_mode(&mut self, mode: u32) {
+        self.setuid = mode & 0o4000 != 0;
+        self.setgid = mode & 0o2000 != 0;
+        self.sticky = mode & 0o1000 != 0;
+        self.user_read = mode & 0o400 != 0;
+        self.user_write = mode & 0o200 != 0;
+        self.user_exec = mode & 0o100 != 0;
+        self.group_read = mode & 0o040 != 0;
+        self.group_write = mode & 0o020 != 0;
+        self.group_exec = mode & 0o010 != 0;
+        self.other_read = mode & 0o004 != 0;
+        self.other_write = mode & 0o002 != 0;
+        self.other_exec = mode & 0o001 != 0;
+        self.octal = format_octal(mode);
+    }
+
+    /// 根据编辑内容生成要应用的修改，所有者或组无法识别时返回错误
+    pub fn to_change(&self) -> Result<PermissionChange, String> {
+        let mode = parse_octal(&self.octal)
+            .ok_or_else(|| format!("无效的八进制权限: {}", self.octal))?;
+
+        let owner = match self.owner.trim() {
+            "" => None,
+            name => Some(resolve_user(name).ok_or_else(|| format!("找不到用户: {}", name))?),
+        };
+        let group = match self.group.trim() {
+            "" => None,
+            name => Some(resolve_group(name).ok_or_else(|| format!("找不到组: {}", name))?),
+        };
+
+        Ok(PermissionChange {
+            mode,
+            owner,
+            group,
+            recursive: self.recursive,
+            target: self.recursive_target,
+        })
+    }
+}
+
+/// 将权限位格式化为四位八进制文本
+pub fn format_octal(mode: u32) -> String {
+    format!("{:04o}", mode & 0o7777)
+}
+
+/// 解析三位或四位的八进制权限
+pub fn parse_octal(text: &str) -> Option<u32> {
+    let text = text.trim();
+    if text.is_empty() || text.len() > 4 || !text.chars().all(|c| ('0'..='7').contains(&c)) {
+        return None;
+    }
+    u32::from_str_radix(text, 8).ok()
+}
+
+/// 要应用的权限修改
+#[derive(Clone, Debug, PartialEq)]
+pub struct PermissionChange {
+    /// 新的权限位
+    pub mode: u32,
+    /// 新的所有者uid
+    pub owner: Option<u32>,
+    /// 新的所属组gid
+    pub group: Option<u32>,
+    /// 是否递归
+    pub recursive: bool,
+    /// 递归时的目标
+    pub target: RecursiveTarget,
+}
+
+impl PermissionChange {
+    /// 条目是否是本次修改的目标
+    fn applies_to(&self, is_dir: bool) -> bool {
+        match self.target {
+            RecursiveTarget::All => true,
+            RecursiveTarget::FilesOnly => !is_dir,
+            RecursiveTarget::DirectoriesOnly => is_dir,
+        }
+    }
+}
+
+/// 应用权限修改（在后台任务中运行），失败的路径记录到任务中
+///
+/// 递归时不跟随符号链接，也不修改符号链接本身。
+pub fn apply(root: &Path, change: &PermissionChange, job: &mut JobContext) {
+    if !change.recursive {
+        apply_one(root, change, job);
+        return;
+    }
+
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        if job.is_cancelled() {
+            return;
+        }
+
+        let metadata = match fs::symlink_metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                job.failed(&path, e);
+                continue;
+            }
+        };
+        if metadata.file_type().is_symlink() {
+            continue;
+        }
+
+        let is_dir = metadata.is_dir();
+        if change.applies_to(is_dir) {
+            apply_one(&path, change, job);
+        }
+
+        if is_dir {
+            match fs::read_dir(&path) {
+                Ok(entries) => pending.extend(entries.filter_map(Result::ok).map(|entry| entry.path())),
+                Err(e) => job.failed(&path, format!("无法读取目录: {}", e)),
+            }
+        }
+    }
+}
+
+/// 修改单个路径的权限和所有者
+fn apply_one(path: &Path, change: &PermissionChange, job: &mut JobContext) {
+    let result = set_mode(path, change.mode).and_then(|_| {
+        if change.owner.is_some() || change.group.is_some() {
+            set_owner(path, change.owner, change.group)
+        } else {
+            Ok(())
+        }
+    });
+
+    match result {
+        Ok(()) => job.processed(),
+        Err(e) => job.failed(path, e),
+    }
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+/// 非Unix平台只能修改只读属性（所有者没有写权限时设为只读）
+#[cfg(not(unix))]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_readonly(mode & 0o200 == 0);
+    fs::set_permissions(path, permissions)
+}
+
+#[cfg(unix)]
+fn set_owner(path: &Path, owner: Option<u32>, group: Option<u32>) -> io::Result<()> {
+    std::os::unix::fs::chown(path, owner, group)
+}
+
+#[cfg(not(unix))]
+fn set_owner(_path: &Path, _owner: Option<u32>, _group: Option<u32>) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "当前平台不支持修改所有者"))
+}
+
+/// 将用户名或uid解析为uid
+#[cfg(unix)]
+fn resolve_user(name: &str) -> Option<u32> {
+    name.parse().ok().or_else(|| unix_ids::uid_by_name(name))
+}
+
+/// 将组名或gid解析为gid
+#[cfg(unix)]
+fn resolve_group(name: &str) -> Option<u32> {
+    name.parse().ok().or_else(|| unix_ids::gid_by_name(name))
+}
+
+#[cfg(not(unix))]
+fn resolve_user(name: &str) -> Option<u32> {
+    name.parse().ok()
+}
+
+#[cfg(not(unix))]
+fn resolve_group(name: &str) -> Option<u32> {
+    name.parse().ok()
+}
+
+/// 按用户名和组名查找uid/gid
+#[cfg(unix)]
+mod unix_ids {
+    use std::ffi::CString;
+
+    /// 查询缓冲区大小
+    const BUFFER_SIZE: usize = 4096;
+
+    pub fn uid_by_name(name: &str) -> Option<u32> {
+        let name = CString::new(name).ok()?;
+        let mut buffer = vec![0 as libc::c_char; BUFFER_SIZE];
+        let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+
+        // SAFETY: 所有指针都指向有效的缓冲区
+        let rc = unsafe {
+            libc::getpwnam_r(name.as_ptr(), &mut passwd, buffer.as_mut_ptr(), buffer.len(), &mut result)
+        };
+        if rc != 0 || result.is_null() {
+            return None;
+        }
+        Some(passwd.pw_uid)
+    }
+
+    pub fn gid_by_name(name: &str) -> Option<u32> {
+        let name = CString::new(name).ok()?;
+        let mut buffer = vec![0 as libc::c_char; BUFFER_SIZE];
+        let mut group: libc::group = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+
+        // SAFETY: 同上
+        let rc = unsafe {
+            libc::getgrnam_r(name.as_ptr(), &mut group, buffer.as_mut_ptr(), buffer.len(), &mut result)
+        };
+        if rc != 0 || result.is_null() {
+            return None;
+        }
+        Some(group.gr_gid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_octal_accepts_three_or_four_digits() {
+        assert_eq!(parse_octal("755"), Some(0o755));
+        assert_eq!(parse_octal(" 0644 "), Some(0o644));
+        assert_eq!(parse_octal("4755"), Some(0o4755));
+        assert_eq!(parse_octal("1777"), Some(0o1777));
+        assert_eq!(parse_octal("7"), Some(0o7));
+    }
+
+    #[test]
+    fn parse_octal_rejects_invalid_text() {
+        assert_eq!(parse_octal(""), None);
+        assert_eq!(parse_octal("   "), None);
+        assert_eq!(parse_octal("789"), None);
+        assert_eq!(parse_octal("64a"), None);
+        assert_eq!(parse_octal("-644"), None);
+        assert_eq!(parse_octal("07755"), None);
+    }
+
+    #[test]
+    fn special_bits_round_trip() {
+        let edit = PermissionEdit::from_mode(0o4755);
+        assert!(edit.setuid && !edit.setgid && !edit.sticky);
+        assert!(edit.user_read && edit.user_write && edit.user_exec);
+        assert!(edit.group_read && !edit.group_write && edit.group_exec);
+        assert_eq!(edit.octal, "4755");
+
+        let edit = PermissionEdit::from_mode(0o1777);
+        assert!(edit.sticky && !edit.setuid);
+        assert_eq!(edit.octal, "1777");
+
+        // 文件类型等高位被忽略
+        let edit = PermissionEdit::from_mode(0o100644);
+        assert_eq!(edit.mode(), 0o644);
+        assert_eq!(edit.octal, "0644");
+    }
+
+    #[test]
+    fn every_mode_round_trips() {
+        for mode in 0..=0o7777 {
+            let edit = PermissionEdit::from_mode(mode);
+            assert_eq!(edit.mode(), mode);
+            assert_eq!(parse_octal(&edit.octal), Some(mode));
+        }
+    }
+
+    #[test]
+    fn checkbox_changes_update_mode() {
+        let mut edit = PermissionEdit::default();
+        assert_eq!(edit.mode(), 0o644);
+        edit.other_write = true;
+        edit.setgid = true;
+        assert_eq!(edit.mode(), 0o2646);
+    }
+
+    #[test]
+    fn to_change_uses_octal_text() {
+        let mut edit = PermissionEdit::from_mode(0o644);
+        edit.octal = "2750".to_string();
+        edit.owner = " 1000 ".to_string();
+        edit.recursive = true;
+        edit.recursive_target = RecursiveTarget::DirectoriesOnly;
+
+        let change = edit.to_change().unwrap();
+        assert_eq!(change.mode, 0o2750);
+        assert_eq!(change.owner, Some(1000));
+        assert_eq!(change.group, None);
+        assert!(change.applies_to(true));
+        assert!(!change.applies_to(false));
+
+        edit.octal = "8".to_string();
+        assert!(edit.to_change().is_err());
+    }
+}
@@ -0,0 +1,334 @@
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use druid::{Data, ExtEventSink, Lens, Target};
+
+use crate::commands::DIRECTORY_SUMMARY_PROGRESS;
+use crate::detection;
+use crate::file_types;
+use crate::utils::format_full_time;
+
+/// 统计目录大小时发送进度的间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 目录的递归统计结果
+#[derive(Clone, Copy, Data, Debug, Default, PartialEq)]
+pub struct DirectorySummary {
+    /// 所有文件的总大小
+    pub total_size: u64,
+    /// 文件数量
+    pub file_count: u64,
+    /// 子目录数量
+    pub dir_count: u64,
+    /// 无法读取的目录数量
+    pub error_count: u64,
+    /// 统计是否已经完成
+    pub finished: bool,
+}
+
+/// 属性窗口中显示的文件信息
+#[derive(Clone, Data, Lens, Debug, Default, PartialEq)]
+pub struct FileProperties {
+    /// 文件名
+    pub name: String,
+    /// 完整路径
+    #[data(same_fn = "PartialEq::eq")]
+    pub path: PathBuf,
+    /// 类型描述
+    pub type_description: String,
+    /// 符号链接的目标（不是符号链接时为空）
+    pub link_target: String,
+    /// 是否为目录
+    pub is_dir: bool,
+    /// 精确的字节大小（目录为目录项自身的大小）
+    pub size: u64,
+    /// 创建时间
+    pub created: String,
+    /// 修改时间
+    pub modified: String,
+    /// 访问时间
+    pub accessed: String,
+    /// 权限（Unix为rwx形式，Windows为只读/读写）
+    pub permissions: String,
+    /// 所有者和所属组（仅Unix）
+    pub owner: String,
+    /// 权限位（Windows上由只读属性推算）
+    pub mode: u32,
+    /// 文件属性（仅Windows）
+    pub attributes: String,
+    /// inode编号
+    pub inode: String,
+    /// 硬链接数
+    pub link_count: String,
+    /// 目录的递归统计（仅目录）
+    pub summary: DirectorySummary,
+    /// 读取属性失败时的错误信息
+    pub error: String,
+}
+
+impl FileProperties {
+    /// 读取文件的属性，读取失败时返回只包含路径和错误信息的属性
+    pub fn load(path: &Path) -> Self {
+        match Self::try_load(path) {
+            Ok(properties) => properties,
+            Err(e) => Self {
+                name: display_name(path),
+                path: path.to_path_buf(),
+                error: format!("无法读取属性: {}", e),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn try_load(path: &Path) -> io::Result<Self> {
+        // 不跟随符号链接，显示链接自身的信息
+        let link_metadata = fs::symlink_metadata(path)?;
+        let link_target = if link_metadata.file_type().is_symlink() {
+            fs::read_link(path)
+                .map(|target| target.display().to_string())
+                .unwrap_or_else(|e| format!("无法读取链接目标: {}", e))
+        } else {
+            String::new()
+        };
+
+        // 大小和类型使用链接目标的信息，目标不存在时退回链接自身
+        let metadata = fs::metadata(path).unwrap_or_else(|_| link_metadata.clone());
+        let is_dir = metadata.is_dir();
+        let type_description = if link_metadata.file_type().is_symlink() && !path.exists() {
+            "失效的符号链接".to_string()
+        } else if is_dir {
+            file_types::kind_for_path(path, true).description
+        } else {
+            // 属性窗口按文件内容识别类型，识别不出时使用扩展名
+            detection::detect(path)
+                .unwrap_or_else(|| file_types::kind_for_path(path, false))
+                .description
+        };
+
+        let mut properties = Self {
+            name: display_name(path),
+            path: path.to_path_buf(),
+            type_description,
+            link_target,
+            is_dir,
+            size: metadata.len(),
+            created: format_time(metadata.created()),
+            modified: format_time(metadata.modified()),
+            accessed: format_time(metadata.accessed()),
+            ..Default::default()
+        };
+        fill_platform_details(&mut properties, &metadata);
+
+        Ok(properties)
+    }
+}
+
+/// 路径的显示名称（根目录没有文件名时显示完整路径）
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// 格式化时间，文件系统不支持时显示"不可用"
+fn format_time(time: io::Result<SystemTime>) -> String {
+    time.map(format_full_time).unwrap_or_else(|_| "不可用".to_string())
+}
+
+/// 填充Unix平台的权限、所有者、inode和硬链接数
+#[cfg(unix)]
+fn fill_platform_details(properties: &mut FileProperties, metadata: &Metadata) {
+    use std::os::unix::fs::MetadataExt;
+
+    properties.mode = metadata.mode() & 0o7777;
+    properties.permissions = format!("{} ({:o})", format_mode(metadata.mode()), properties.mode);
+    properties.owner = format!(
+        "{} ({}) / {} ({})",
+        unix_names::user_name(metadata.uid()).unwrap_or_else(|| "?".to_string()),
+        metadata.uid(),
+        unix_names::group_name(metadata.gid()).unwrap_or_else(|| "?".to_string()),
+        metadata.gid(),
+    );
+    properties.inode = metadata.ino().to_string();
+    properties.link_count = metadata.nlink().to_string();
+}
+
+/// 填充Windows平台的只读状态和文件属性
+#[cfg(windows)]
+fn fill_platform_details(properties: &mut FileProperties, metadata: &Metadata) {
+    use std::os::windows::fs::MetadataExt;
+    use windows_sys::Win32::Storage::FileSystem::{
+        FILE_ATTRIBUTE_ARCHIVE, FILE_ATTRIBUTE_COMPRESSED, FILE_ATTRIBUTE_ENCRYPTED,
+        FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_READONLY, FILE_ATTRIBUTE_REPARSE_POINT,
+        FILE_ATTRIBUTE_SYSTEM,
+    };
+
+    fill_readonly(properties, metadata);
+
+    let attributes = metadata.file_attributes();
+    let names: Vec<&str> = [
+        (FILE_ATTRIBUTE_READONLY, "只读"),
+        (FILE_ATTRIBUTE_HIDDEN, "隐藏"),
+        (FILE_ATTRIBUTE_SYSTEM, "系统"),
+        (FILE_ATTRIBUTE_ARCHIVE, "存档"),
+        (FILE_ATTRIBUTE_COMPRESSED, "压缩"),
+        (FILE_ATTRIBUTE_ENCRYPTED, "加密"),
+        (FILE_ATTRIBUTE_REPARSE_POINT, "重分析点"),
+    ]
+    .iter()
+    .filter(|(flag, _)| attributes & flag != 0)
+    .map(|(_, name)| *name)
+    .collect();
+    properties.attributes = if names.is_empty() { "无".to_string() } else { names.join(", ") };
+
+    // 文件索引号和硬链接数在标准库中尚未稳定
+    properties.inode = "不可用".to_string();
+    properties.link_count = "不可用".to_string();
+}
+
+#[cfg(not(any(unix, windows)))]
+fn fill_platform_details(properties: &mut FileProperties, metadata: &Metadata) {
+    fill_readonly(properties, metadata);
+}
+
+/// 没有Unix权限位的平台按只读属性显示权限
+#[cfg(not(unix))]
+fn fill_readonly(properties: &mut FileProperties, metadata: &Metadata) {
+    let readonly = metadata.permissions().readonly();
+    properties.permissions = if readonly { "只读" } else { "读写" }.to_string();
+    properties.mode = if readonly { 0o444 } else { 0o644 };
+}
+
+/// 将Unix权限位格式化为 ls -l 的形式（如 drwxr-xr-x）
+#[cfg(unix)]
+pub fn format_mode(mode: u32) -> String {
+    let file_type = match mode & libc::S_IFMT as u32 {
+        m if m == libc::S_IFDIR as u32 => 'd',
+        m if m == libc::S_IFLNK as u32 => 'l',
+        m if m == libc::S_IFCHR as u32 => 'c',
+        m if m == libc::S_IFBLK as u32 => 'b',
+        m if m == libc::S_IFIFO as u32 => 'p',
+        m if m == libc::S_IFSOCK as u32 => 's',
+        _ => '-',
+    };
+
+    let mut result = String::with_capacity(10);
+    result.push(file_type);
+
+    // (读, 写, 执行, 特殊位, 特殊位带执行时的字符, 特殊位不带执行时的字符)
+    let triplets = [
+        (0o400, 0o200, 0o100, 0o4000, 's', 'S'),
+        (0o040, 0o020, 0o010, 0o2000, 's', 'S'),
+        (0o004, 0o002, 0o001, 0o1000, 't', 'T'),
+    ];
+    for (read, write, exec, special, special_exec, special_no_exec) in triplets {
+        result.push(if mode & read != 0 { 'r' } else { '-' });
+        result.push(if mode & write != 0 { 'w' } else { '-' });
+        result.push(match (mode & exec != 0, mode & special != 0) {
+            (true, true) => special_exec,
+            (false, true) => special_no_exec,
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+
+    result
+}
+
+/// 按uid/gid查找用户名和组名
+#[cfg(unix)]
+mod unix_names {
+    use std::ffi::CStr;
+
+    /// 查询缓冲区大小
+    const BUFFER_SIZE: usize = 4096;
+
+    pub fn user_name(uid: u32) -> Option<String> {
+        let mut buffer = vec![0 as libc::c_char; BUFFER_SIZE];
+        let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+
+        // SAFETY: 所有指针都指向有效的缓冲区，返回的名称在 buffer 存活期间有效
+        let rc = unsafe {
+            libc::getpwuid_r(uid, &mut passwd, buffer.as_mut_ptr(), buffer.len(), &mut result)
+        };
+        if rc != 0 || result.is_null() {
+            return None;
+        }
+
+        Some(unsafe { CStr::from_ptr(passwd.pw_name) }.to_string_lossy().into_owned())
+    }
+
+    pub fn group_name(gid: u32) -> Option<String> {
+        let mut buffer = vec![0 as libc::c_char; BUFFER_SIZE];
+        let mut group: libc::group = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+
+        // SAFETY: 同上
+        let rc = unsafe {
+            libc::getgrgid_r(gid, &mut group, buffer.as_mut_ptr(), buffer.len(), &mut result)
+        };
+        if rc != 0 || result.is_null() {
+            return None;
+        }
+
+        Some(unsafe { CStr::from_ptr(group.gr_name) }.to_string_lossy().into_owned())
+    }
+}
+
+/// 在后台线程中递归统计目录的总大小和文件数量
+///
+/// 不跟随符号链接（避免重复统计和循环），统计过程中定期发送 `DIRECTORY_SUMMARY_PROGRESS`，
+/// `cancel` 被设置后停止统计。
+pub fn spawn_directory_summary(path: PathBuf, cancel: Arc<AtomicBool>, event_sink: ExtEventSink) {
+    thread::spawn(move || {
+        let mut summary = DirectorySummary::default();
+        let mut pending = vec![path.clone()];
+        let mut last_progress = Instant::now();
+
+        while let Some(dir) = pending.pop() {
+            if cancel.load(Ordering::Relaxed) {
+                return;
+            }
+
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(_) => {
+                    summary.error_count += 1;
+                    continue;
+                }
+            };
+
+            for entry in entries.filter_map(Result::ok) {
+                let file_type = match entry.file_type() {
+                    Ok(file_type) => file_type,
+                    Err(_) => continue,
+                };
+
+                if file_type.is_dir() {
+                    summary.dir_count += 1;
+                    pending.push(entry.path());
+                } else {
+                    summary.file_count += 1;
+                    summary.total_size += entry.metadata().map(|m| m.len()).unwrap_or(0);
+                }
+            }
+
+            if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                last_progress = Instant::now();
+                if event_sink.submit_command(DIRECTORY_SUMMARY_PROGRESS, (path.clone(), summary), Target::Auto).is_err() {
+                    return;
+                }
+            }
+        }
+
+        summary.finished = true;
+        if let Err(e) = event_sink.submit_command(DIRECTORY_SUMMARY_PROGRESS, (path, summary), Target::Auto) {
+            eprintln!("发送目录统计结果失败: {:?}", e);
+        }
+    });
+}
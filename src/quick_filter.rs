@@ -0,0 +1,413 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use druid::im::Vector;
+use druid::{Data, Lens};
+use globset::GlobBuilder;
+use regex::RegexBuilder;
+use crate::file_types::FileCategory;
+use crate::models::FileDetail;
+
+/// 筛选文字的写法
+#[derive(Clone, Copy, Data, Debug, Default, PartialEq, Eq)]
+pub enum FilterSyntax {
+    /// 名称中包含输入的文字（不区分大小写）
+    #[default]
+    Substring,
+    /// 通配符，如 `*.rs`、`report-??.pdf`（匹配整个名称）
+    Glob,
+    /// 正则表达式（不区分大小写）
+    Regex,
+}
+
+impl FilterSyntax {
+    /// 切换按钮上显示的名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            FilterSyntax::Substring => "文字",
+            FilterSyntax::Glob => "通配符",
+            FilterSyntax::Regex => "正则",
+        }
+    }
+
+    /// 依次切换到下一种写法
+    pub fn next(&self) -> Self {
+        match self {
+            FilterSyntax::Substring => FilterSyntax::Glob,
+            FilterSyntax::Glob => FilterSyntax::Regex,
+            FilterSyntax::Regex => FilterSyntax::Substring,
+        }
+    }
+}
+
+/// 按类型筛选
+#[derive(Clone, Copy, Data, Debug, Default, PartialEq, Eq)]
+pub enum KindFilter {
+    #[default]
+    All,
+    /// 只显示文件夹（和驱动器）
+    Folders,
+    /// 只显示文件
+    Files,
+    /// 只显示某一类文件
+    Category(FileCategory),
+}
+
+/// 筛选栏中的类型切换按钮
+pub const KIND_FILTERS: &[(KindFilter, &str)] = &[
+    (KindFilter::All, "全部"),
+    (KindFilter::Folders, "文件夹"),
+    (KindFilter::Files, "文件"),
+    (KindFilter::Category(FileCategory::Image), "图片"),
+    (KindFilter::Category(FileCategory::Document), "文档"),
+    (KindFilter::Category(FileCategory::Code), "代码"),
+    (KindFilter::Category(FileCategory::Audio), "音频"),
+    (KindFilter::Category(FileCategory::Video), "视频"),
+    (KindFilter::Category(FileCategory::Archive), "压缩包"),
+];
+
+impl KindFilter {
+    fn accepts(&self, file: &FileDetail) -> bool {
+        match self {
+            KindFilter::All => true,
+            KindFilter::Folders => file.kind.is_directory(),
+            KindFilter::Files => !file.kind.is_directory(),
+            KindFilter::Category(category) => file.kind.category == *category,
+        }
+    }
+}
+
+/// 当前目录的快速筛选
+#[derive(Clone, Data, Lens, Debug, Default)]
+pub struct QuickFilterState {
+    /// 输入的筛选文字
+    pub text: String,
+    pub syntax: FilterSyntax,
+    pub kind: KindFilter,
+    /// 筛选文字无效时的错误（如不完整的正则表达式）
+    pub error: String,
+    /// 筛选前的完整列表，没有筛选时为 None
+    pub source: Option<Vector<FileDetail>>,
+}
+
+impl QuickFilterState {
+    /// 是否正在筛选
+    pub fn is_active(&self) -> bool {
+        !self.text.is_empty() || self.kind != KindFilter::All
+    }
+
+    /// 清除筛选文字和类型（保留写法）
+    pub fn reset(&mut self) {
+        self.text.clear();
+        self.kind = KindFilter::All;
+        self.error.clear();
+        self.source = None;
+    }
+
+    /// 按筛选条件更新显示的列表
+    ///
+    /// 开始筛选时保存已经加载的完整列表，之后每次都从完整列表中筛选；筛选结束时恢复完整列表。
+    pub fn refresh(&mut self, shown: &mut Vector<FileDetail>) {
+        // 在筛选结果中做的选择同步回完整列表
+        if let Some(source) = &mut self.source {
+            sync_selection(source, shown);
+        }
+
+        if !self.is_active() {
+            if let Some(source) = self.source.take() {
+                *shown = source;
+            }
+            self.error.clear();
+            return;
+        }
+
+        let source = self.source.get_or_insert_with(|| shown.clone()).clone();
+        let (files, error) = apply(self, &source);
+        *shown = files;
+        self.error = error.unwrap_or_default();
+    }
+
+    /// 目录重新加载后，按新的内容重新筛选
+    pub fn reload(&mut self, shown: &mut Vector<FileDetail>) {
+        self.source = None;
+        if self.is_active() {
+            self.refresh(shown);
+        }
+    }
+
+    /// 大目录的其余文件在后台加载完成后，用完整列表替换显示的列表；正在筛选时替换完整列表并重新筛选
+    pub fn receive_full_listing(&mut self, shown: &mut Vector<FileDetail>, files: Vector<FileDetail>) {
+        if !self.is_active() {
+            *shown = files;
+            return;
+        }
+        // 保留在第一页中做的选择，显示的筛选结果中的选择在重新筛选时同步
+        let mut files = files;
+        if let Some(source) = &self.source {
+            sync_selection(&mut files, source);
+        }
+        self.source = Some(files);
+        self.refresh(shown);
+    }
+}
+
+/// 将显示的列表中的选中和焦点状态复制到完整列表中的同一文件
+fn sync_selection(source: &mut Vector<FileDetail>, shown: &Vector<FileDetail>) {
+    let shown: HashMap<&PathBuf, &FileDetail> = shown.iter().map(|file| (&file.full_path, file)).collect();
+    for file in source.iter_mut() {
+        // 被筛掉的文件保留原来的选中状态，但不再拥有焦点
+        let (is_selected, is_focused) = match shown.get(&file.full_path) {
+            Some(shown) => (shown.is_selected, shown.is_focused),
+            None => (file.is_selected, false),
+        };
+        if file.is_selected != is_selected || file.is_focused != is_focused {
+            file.is_selected = is_selected;
+            file.is_focused = is_focused;
+        }
+    }
+}
+
+/// 编译后的筛选文字
+enum Matcher {
+    Substring(String),
+    Glob(globset::GlobMatcher),
+    Regex(regex::Regex),
+}
+
+impl Matcher {
+    fn new(text: &str, syntax: FilterSyntax) -> Result<Self, String> {
+        match syntax {
+            FilterSyntax::Substring => Ok(Matcher::Substring(text.to_lowercase())),
+            FilterSyntax::Glob => GlobBuilder::new(text)
+                .case_insensitive(true)
+                .literal_separator(false)
+                .build()
+                .map(|glob| Matcher::Glob(glob.compile_matcher()))
+                .map_err(|e| format!("通配符无效: {}", e)),
+            FilterSyntax::Regex => RegexBuilder::new(text)
+                .case_insensitive(true)
+                .build()
+                .map(Matcher::Regex)
+                .map_err(|e| format!("正则表达式无效: {}", e)),
+        }
+    }
+
+    /// 名称匹配时返回要高亮的字节范围，不匹配时返回 None
+    fn find(&self, name: &str) -> Option<Vec<(usize, usize)>> {
+        match self {
+            Matcher::Substring(needle) => {
+                let lower = name.to_lowercase();
+                if !lower.contains(needle.as_str()) {
+                    return None;
+                }
+                // 转为小写后长度不变时才能按位置高亮
+                if lower.len() != name.len() {
+                    return Some(Vec::new());
+                }
+                Some(lower.match_indices(needle.as_str()).map(|(start, m)| (start, start + m.len())).collect())
+            }
+            // 通配符匹配整个名称
+            Matcher::Glob(glob) => glob.is_match(name).then(|| vec![(0, name.len())]),
+            Matcher::Regex(regex) => {
+                let ranges: Vec<(usize, usize)> = regex.find_iter(name)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| (m.start(), m.end()))
+                    .collect();
+                (!ranges.is_empty() || regex.is_match(name)).then_some(ranges)
+            }
+        }
+    }
+}
+
+/// 按筛选条件从完整列表中选出匹配的项，并记录名称中要高亮的范围
+///
+/// 筛选文字无效时只按类型筛选，并返回错误。
+pub fn apply(filter: &QuickFilterState, source: &Vector<FileDetail>) -> (Vector<FileDetail>, Option<String>) {
+    let (matcher, error) = if filter.text.is_empty() {
+        (None, None)
+    } else {
+        match Matcher::new(&filter.text, filter.syntax) {
+            Ok(matcher) => (Some(matcher), None),
+            Err(e) => (None, Some(e)),
+        }
+    };
+
+    let files = source.iter()
+        .filter(|file| filter.kind.accepts(file))
+        .filter_map(|file| {
+            let name_matches = match &matcher {
+                Some(matcher) => matcher.find(&file.name)?,
+                None => Vec::new(),
+            };
+            Some(FileDetail { name_matches, ..file.clone() })
+        })
+        .collect();
+    (files, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use crate::file_types::FileKind;
+    use super::*;
+
+    fn file(name: &str, kind: FileKind) -> FileDetail {
+        FileDetail {
+            name: name.to_string(),
+            size: 0,
+            kind,
+            kind_detected: true,
+            folder_size: None,
+            modified: String::new(),
+            full_path: PathBuf::from(name),
+            is_hidden: false,
+            is_selected: false,
+            is_focused: false,
+            name_matches: Vec::new(),
+        }
+    }
+
+    fn of_category(category: FileCategory) -> FileKind {
+        FileKind {
+            type_id: "test".to_string(),
+            category,
+            extension: String::new(),
+            description: String::new(),
+        }
+    }
+
+    fn find(text: &str, syntax: FilterSyntax, name: &str) -> Option<Vec<(usize, usize)>> {
+        Matcher::new(text, syntax).unwrap().find(name)
+    }
+
+    fn names(files: &Vector<FileDetail>) -> Vec<&str> {
+        files.iter().map(|file| file.name.as_str()).collect()
+    }
+
+    #[test]
+    fn substring_finds_all_occurrences() {
+        assert_eq!(find("Report", FilterSyntax::Substring, "My REPORT report.pdf"), Some(vec![(3, 9), (10, 16)]));
+        assert_eq!(find("txt", FilterSyntax::Substring, "notes.md"), None);
+        // 转为小写后长度变化时匹配但不高亮
+        assert_eq!(find("stan", FilterSyntax::Substring, "İstanbul"), Some(Vec::new()));
+    }
+
+    #[test]
+    fn glob_matches_whole_name() {
+        assert_eq!(find("*.RS", FilterSyntax::Glob, "main.rs"), Some(vec![(0, 7)]));
+        assert!(find("report-??.pdf", FilterSyntax::Glob, "report-01.pdf").is_some());
+        assert_eq!(find("report-??.pdf", FilterSyntax::Glob, "report-1.pdf"), None);
+        assert_eq!(find("*.rs", FilterSyntax::Glob, "main.rs.bak"), None);
+        assert!(Matcher::new("[", FilterSyntax::Glob).is_err());
+    }
+
+    #[test]
+    fn regex_highlights_non_empty_matches() {
+        assert_eq!(find("^a.c", FilterSyntax::Regex, "ABCabc"), Some(vec![(0, 3)]));
+        assert_eq!(find("\\d+", FilterSyntax::Regex, "v12-3"), Some(vec![(1, 3), (4, 5)]));
+        // 只有空匹配时仍然算作匹配
+        assert_eq!(find("x*", FilterSyntax::Regex, "abc"), Some(Vec::new()));
+        assert_eq!(find("^z", FilterSyntax::Regex, "abc"), None);
+        assert!(Matcher::new("(", FilterSyntax::Regex).is_err());
+    }
+
+    #[test]
+    fn apply_filters_by_text_and_kind() {
+        let source: Vector<FileDetail> = vec![
+            file("docs", FileKind::directory()),
+            file("photo.jpg", of_category(FileCategory::Image)),
+            file("notes.txt", of_category(FileCategory::Text)),
+        ].into_iter().collect();
+
+        let mut filter = QuickFilterState { text: "o".to_string(), ..Default::default() };
+        let (files, error) = apply(&filter, &source);
+        assert_eq!(error, None);
+        assert_eq!(names(&files), vec!["docs", "photo.jpg", "notes.txt"]);
+        assert_eq!(files[1].name_matches, vec![(2, 3), (4, 5)]);
+
+        filter.kind = KindFilter::Files;
+        assert_eq!(names(&apply(&filter, &source).0), vec!["photo.jpg", "notes.txt"]);
+        filter.kind = KindFilter::Folders;
+        assert_eq!(names(&apply(&filter, &source).0), vec!["docs"]);
+        filter.kind = KindFilter::Category(FileCategory::Image);
+        assert_eq!(names(&apply(&filter, &source).0), vec!["photo.jpg"]);
+    }
+
+    #[test]
+    fn invalid_text_only_filters_by_kind() {
+        let source: Vector<FileDetail> = vec![
+            file("docs", FileKind::directory()),
+            file("a.txt", of_category(FileCategory::Text)),
+        ].into_iter().collect();
+        let filter = QuickFilterState {
+            text: "(".to_string(),
+            syntax: FilterSyntax::Regex,
+            kind: KindFilter::Files,
+            ..Default::default()
+        };
+        let (files, error) = apply(&filter, &source);
+        assert!(error.is_some());
+        assert_eq!(names(&files), vec!["a.txt"]);
+        assert!(files[0].name_matches.is_empty());
+    }
+
+    #[test]
+    fn refresh_keeps_source_and_restores_it() {
+        let mut shown: Vector<FileDetail> = vec![
+            file("a.txt", of_category(FileCategory::Text)),
+            file("b.jpg", of_category(FileCategory::Image)),
+        ].into_iter().collect();
+        let mut filter = QuickFilterState { text: "b".to_string(), ..Default::default() };
+        filter.refresh(&mut shown);
+        assert_eq!(names(&shown), vec!["b.jpg"]);
+
+        // 在筛选结果中选中的文件在结束筛选后仍然选中
+        shown[0].is_selected = true;
+        filter.text.clear();
+        filter.refresh(&mut shown);
+        assert_eq!(names(&shown), vec!["a.txt", "b.jpg"]);
+        assert!(filter.source.is_none());
+        assert!(shown[1].is_selected);
+    }
+
+    #[test]
+    fn full_listing_is_filtered_after_first_page() {
+        let page_size = crate::settings::Settings::default().page_size;
+        let all: Vector<FileDetail> = (0..page_size * 3)
+            .map(|i| {
+                let extension = if i % 2 == 0 { "txt" } else { "jpg" };
+                file(&format!("file-{:03}.{}", i, extension), of_category(FileCategory::Text))
+            })
+            .collect();
+        let mut shown: Vector<FileDetail> = all.iter().take(page_size).cloned().collect();
+
+        // 第一页加载后开始筛选，并在筛选结果中选中一个文件
+        let mut filter = QuickFilterState { text: "*.jpg".to_string(), syntax: FilterSyntax::Glob, ..Default::default() };
+        filter.refresh(&mut shown);
+        assert_eq!(shown.len(), page_size / 2);
+        shown[0].is_selected = true;
+
+        // 其余文件加载完成后从完整列表中重新筛选
+        filter.receive_full_listing(&mut shown, all.clone());
+        assert_eq!(shown.len(), page_size * 3 / 2);
+        assert!(shown.iter().all(|file| file.name.ends_with(".jpg")));
+        assert_eq!(shown.last().unwrap().name, format!("file-{:03}.jpg", page_size * 3 - 1));
+        assert!(shown[0].is_selected);
+        assert_eq!(filter.source.as_ref().map(|source| source.len()), Some(page_size * 3));
+
+        // 结束筛选后显示完整列表
+        filter.text.clear();
+        filter.refresh(&mut shown);
+        assert_eq!(shown.len(), page_size * 3);
+        assert!(shown[1].is_selected);
+    }
+
+    #[test]
+    fn full_listing_replaces_shown_when_not_filtering() {
+        let mut shown: Vector<FileDetail> = vec![file("a", FileKind::directory())].into_iter().collect();
+        let mut filter = QuickFilterState::default();
+        let all: Vector<FileDetail> = vec![file("a", FileKind::directory()), file("b", FileKind::directory())].into_iter().collect();
+        filter.receive_full_listing(&mut shown, all);
+        assert_eq!(names(&shown), vec!["a", "b"]);
+        assert!(filter.source.is_none());
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::file_system::build_file_tree;
+use crate::models::{AppState, FileItem};
+use crate::settings;
+
+/// 当前会话文件的格式版本
+/// 新增字段时只需给字段提供默认值；字段语义发生变化时才需要提升版本并在 `migrate` 中转换
+pub const SESSION_VERSION: u32 = 1;
+
+/// 会话文件名
+const SESSION_FILE_NAME: &str = "session.json";
+
+/// 默认窗口尺寸（与旧版本硬编码的尺寸一致）
+pub const DEFAULT_WINDOW_SIZE: (f64, f64) = (1000.0, 600.0);
+
+/// 默认分割位置（左侧目录树占25%）
+pub const DEFAULT_SPLIT_POINT: f64 = 0.25;
+
+/// 保存到磁盘的会话状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    /// 会话文件格式版本
+    pub version: u32,
+    /// 上次打开的目录
+    pub last_directory: Option<PathBuf>,
+    /// 目录树中处于展开状态的节点路径
+    pub expanded_paths: Vec<PathBuf>,
+    /// 导航历史记录
+    pub navigation_history: Vec<PathBuf>,
+    /// 当前在历史记录中的位置
+    pub history_position: usize,
+    /// 主窗口尺寸（宽, 高）
+    pub window_size: (f64, f64),
+    /// 目录树与文件列表之间的分割位置（0.0 - 1.0）
+    pub split_point: f64,
+}
+
+impl Default for SessionState {
+    fn default() -> Self {
+        Self {
+            version: SESSION_VERSION,
+            last_directory: None,
+            expanded_paths: Vec::new(),
+            navigation_history: Vec::new(),
+            history_position: 0,
+            window_size: DEFAULT_WINDOW_SIZE,
+            split_point: DEFAULT_SPLIT_POINT,
+        }
+    }
+}
+
+impl SessionState {
+    /// 从当前应用状态生成会话快照
+    pub fn capture(data: &AppState) -> Self {
+        let mut expanded_paths = Vec::new();
+        collect_expanded_paths(&data.root, &mut expanded_paths);
+
+        Self {
+            version: SESSION_VERSION,
+            last_directory: data.selected_path.clone(),
+            expanded_paths,
+            navigation_history: data.navigation_history.clone(),
+            history_position: data.history_position,
+            window_size: data.window_size,
+            split_point: data.split_point,
+        }
+    }
+
+    /// 清理已失效的数据：不存在的路径回退到最近的存在的上级目录，
+    /// 历史记录中不存在的条目被移除，窗口尺寸和分割位置限制在合理范围内
+    pub fn sanitize(mut self) -> Self {
+        self.last_directory = self.last_directory.as_deref().and_then(nearest_existing_dir);
+        self.expanded_paths.retain(|path| path.is_dir());
+
+        // 移除已经不存在的历史记录，同时保持当前位置指向同一条记录
+        let mut position = 0;
+        let mut history = Vec::new();
+        for (index, path) in self.navigation_history.iter().enumerate() {
+            if path.is_dir() {
+                if index <= self.history_position {
+                    position = history.len();
+                }
+                history.push(path.clone());
+            }
+        }
+        self.navigation_history = history;
+        self.history_position = position;
+
+        let (width, height) = self.window_size;
+        if !(width.is_finite() && height.is_finite()) || width < 200.0 || height < 150.0 {
+            self.window_size = DEFAULT_WINDOW_SIZE;
+        }
+
+        if !self.split_point.is_finite() || self.split_point <= 0.05 || self.split_point >= 0.95 {
+            self.split_point = DEFAULT_SPLIT_POINT;
+        }
+
+        self
+    }
+}
+
+/// 会话文件的保存位置（位于系统配置目录下）
+pub fn session_file_path() -> Option<PathBuf> {
+    settings::config_dir().map(|dir| dir.join(SESSION_FILE_NAME))
+}
+
+/// 读取上次保存的会话，文件不存在或无法解析时返回 None
+pub fn load_session() -> Option<SessionState> {
+    let path = session_file_path()?;
+    let content = fs::read_to_string(&path).ok()?;
+
+    let value: serde_json::Value = match serde_json::from_str(&content) {
+        Ok(value) => value,
+        Err(e) => {
+            eprintln!("解析会话文件失败 {:?}: {}", path, e);
+            return None;
+        }
+    };
+
+    match serde_json::from_value::<SessionState>(migrate(value)) {
+        Ok(session) => {
+            println!("已加载会话文件: {:?}", path);
+            Some(session.sanitize())
+        }
+        Err(e) => {
+            eprintln!("会话文件格式错误 {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// 保存会话到磁盘（先写临时文件再替换，避免写入中断导致文件损坏）
+pub fn save_session(session: &SessionState) -> io::Result<()> {
+    let path = session_file_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "无法确定配置目录"))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut value = serde_json::to_value(session)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    // 已有的会话文件由更新的版本写入时，保留它的版本号和不认识的字段
+    let existing = fs::read_to_string(&path).ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok());
+    if let Some(existing) = existing {
+        value = preserve_newer_fields(existing, value);
+    }
+
+    let content = serde_json::to_string_pretty(&value)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, content)?;
+    fs::rename(&temp_path, &path)?;
+
+    println!("会话已保存: {:?}", path);
+    Ok(())
+}
+
+/// 将旧版本的会话数据转换为当前版本的格式
+///
+/// 更新的版本写入的文件保持原样：只读取认识的字段，版本号和其余字段在保存时保留。
+fn migrate(mut value: serde_json::Value) -> serde_json::Value {
+    let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+
+    if version > SESSION_VERSION as u64 {
+        println!("会话文件版本 {} 高于当前支持的版本 {}，忽略未知字段", version, SESSION_VERSION);
+        return value;
+    }
+
+    // 目前只有版本1，缺少版本号的文件按版本1处理
+    if let Some(object) = value.as_object_mut() {
+        object.insert("version".to_string(), serde_json::Value::from(SESSION_VERSION));
+    }
+
+    value
+}
+
+/// 已有的会话文件版本更高时，把当前会话认识的字段写入其中，保留它的版本号和其余字段
+fn preserve_newer_fields(existing: serde_json::Value, current: serde_json::Value) -> serde_json::Value {
+    let version = existing.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if version <= SESSION_VERSION as u64 {
+        return current;
+    }
+
+    match (existing, current) {
+        (serde_json::Value::Object(mut existing), serde_json::Value::Object(current)) => {
+            for (key, value) in current {
+                if key != "version" {
+                    existing.insert(key, value);
+                }
+            }
+            serde_json::Value::Object(existing)
+        }
+        (_, current) => current,
+    }
+}
+
+/// 查找路径自身或最近的一个存在的上级目录
+fn nearest_existing_dir(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|ancestor| ancestor.is_dir())
+        .map(Path::to_path_buf)
+}
+
+/// 收集目录树中所有展开节点的路径
+fn collect_expanded_paths(item: &FileItem, paths: &mut Vec<PathBuf>) {
+    if item.is_expanded && item.path.is_dir() {
+        paths.push(item.path.clone());
+    }
+
+    for child in &item.children {
+        collect_expanded_paths(child, paths);
+    }
+}
+
+/// 按保存的展开路径恢复目录树的展开状态，必要时加载子目录
+pub fn restore_expanded_paths(item: &mut FileItem, expanded: &HashSet<PathBuf>) {
+    if expanded.contains(&item.path) {
+        item.is_expanded = true;
+
+        if item.children.is_empty() {
+            item.children = build_file_tree(&item.path, 1);
+        }
+    }
+
+    // 只有展开路径的祖先节点才需要继续向下查找
+    for child in &mut item.children {
+        if expanded.iter().any(|path| path.starts_with(&child.path)) {
+            restore_expanded_paths(child, expanded);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn migrate_fills_missing_version() {
+        let value = migrate(json!({ "last_directory": "/tmp" }));
+        assert_eq!(value["version"], json!(SESSION_VERSION));
+        assert_eq!(value["last_directory"], json!("/tmp"));
+    }
+
+    #[test]
+    fn migrate_keeps_newer_version_and_unknown_fields() {
+        let newer = json!({ "version": SESSION_VERSION + 1, "split_point": 0.4, "tabs": ["/a", "/b"] });
+        let value = migrate(newer.clone());
+        assert_eq!(value, newer);
+
+        // 仍然可以读取认识的字段
+        let session: SessionState = serde_json::from_value(value).unwrap();
+        assert_eq!(session.split_point, 0.4);
+    }
+
+    #[test]
+    fn saving_over_newer_file_keeps_its_fields() {
+        let existing = json!({ "version": SESSION_VERSION + 1, "split_point": 0.4, "tabs": ["/a"] });
+        let current = serde_json::to_value(SessionState { split_point: 0.3, ..Default::default() }).unwrap();
+        let merged = preserve_newer_fields(existing, current.clone());
+        assert_eq!(merged["version"], json!(SESSION_VERSION + 1));
+        assert_eq!(merged["tabs"], json!(["/a"]));
+        assert_eq!(merged["split_point"], json!(0.3));
+
+        // 旧版本或当前版本的文件直接覆盖
+        let older = json!({ "version": SESSION_VERSION, "tabs": ["/a"] });
+        assert_eq!(preserve_newer_fields(older, current.clone()), current);
+    }
+
+    #[test]
+    fn sanitize_drops_missing_paths_and_keeps_history_position() {
+        let dir = std::env::temp_dir().join(format!("session-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::create_dir_all(dir.join("c")).unwrap();
+
+        let session = SessionState {
+            last_directory: Some(dir.join("a/missing/deeper")),
+            expanded_paths: vec![dir.join("a"), dir.join("missing")],
+            navigation_history: vec![dir.join("a"), dir.join("b"), dir.join("c")],
+            history_position: 2,
+            ..Default::default()
+        }
+        .sanitize();
+
+        assert_eq!(session.last_directory, Some(dir.join("a")));
+        assert_eq!(session.expanded_paths, vec![dir.join("a")]);
+        assert_eq!(session.navigation_history, vec![dir.join("a"), dir.join("c")]);
+        assert_eq!(session.history_position, 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn sanitize_resets_invalid_window_and_split() {
+        let session = SessionState {
+            window_size: (f64::NAN, 100.0),
+            split_point: 1.5,
+            ..Default::default()
+        }
+        .sanitize();
+        assert_eq!(session.window_size, DEFAULT_WINDOW_SIZE);
+        assert_eq!(session.split_point, DEFAULT_SPLIT_POINT);
+
+        let session = SessionState { window_size: (800.0, 500.0), split_point: 0.4, ..Default::default() }.sanitize();
+        assert_eq!(session.window_size, (800.0, 500.0));
+        assert_eq!(session.split_point, 0.4);
+    }
+}
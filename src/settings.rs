@@ -0,0 +1,385 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use druid::{Data, Lens};
+use serde::{Deserialize, Serialize};
+
+use crate::sync::SyncJob;
+
+/// 配置目录名称（位于系统配置目录下，Linux上遵循XDG规范，如 ~/.config/lemon_explorer）
+const CONFIG_DIR_NAME: &str = "lemon_explorer";
+
+/// TOML格式的配置文件名（优先使用）
+const TOML_FILE_NAME: &str = "config.toml";
+
+/// JSON格式的配置文件名
+const JSON_FILE_NAME: &str = "config.json";
+
+/// 修改时间使用相对格式（刚刚、x 分钟前……）时的特殊取值
+pub const RELATIVE_DATE_FORMAT: &str = "relative";
+
+/// 配置文件中允许出现的键
+const KNOWN_KEYS: &[&str] = &[
+    "page_size",
+    "background_load_limit",
+    "max_cache_entries",
+    "show_hidden",
+    "compute_folder_sizes",
+    "start_directory",
+    "date_format",
+    "theme",
+    "sync_jobs",
+    "bookmarks",
+];
+
+// 当前生效的设置，供没有访问AppState的代码（如后台加载线程）读取
+lazy_static::lazy_static! {
+    static ref CURRENT_SETTINGS: RwLock<Settings> = RwLock::new(Settings::default());
+}
+
+/// 应用程序设置
+#[derive(Clone, Data, Lens, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// 首次显示目录时加载的条目数量
+    pub page_size: usize,
+    /// 后台加载大目录时最多加载的条目数量
+    pub background_load_limit: usize,
+    /// 目录缓存最多保存的目录数量
+    pub max_cache_entries: usize,
+    /// 是否显示隐藏文件
+    pub show_hidden: bool,
+    /// 是否在文件列表中递归计算文件夹大小
+    pub compute_folder_sizes: bool,
+    /// 启动时打开的目录，为空时恢复上次会话的目录
+    pub start_directory: String,
+    /// 修改时间的显示格式（strftime格式，或 "relative" 表示相对时间）
+    pub date_format: String,
+    /// 主题名称
+    pub theme: String,
+    /// 保存的同步任务
+    #[data(same_fn = "PartialEq::eq")]
+    pub sync_jobs: Vec<SyncJob>,
+    /// 加入书签的目录
+    #[data(same_fn = "PartialEq::eq")]
+    pub bookmarks: Vec<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            page_size: 30,
+            background_load_limit: 10000,
+            max_cache_entries: 100,
+            show_hidden: false,
+            compute_folder_sizes: false,
+            start_directory: String::new(),
+            date_format: RELATIVE_DATE_FORMAT.to_string(),
+            theme: "dark".to_string(),
+            sync_jobs: Vec::new(),
+            bookmarks: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// 检查设置是否合法，返回所有问题的描述
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.page_size == 0 || self.page_size > 10000 {
+            problems.push(format!("page_size 必须在 1 到 10000 之间，当前为 {}", self.page_size));
+        }
+
+        if self.background_load_limit < self.page_size {
+            problems.push(format!(
+                "background_load_limit ({}) 不能小于 page_size ({})",
+                self.background_load_limit, self.page_size
+            ));
+        }
+
+        if self.max_cache_entries == 0 {
+            problems.push("max_cache_entries 必须大于 0".to_string());
+        }
+
+        if !self.start_directory.is_empty() && !PathBuf::from(&self.start_directory).is_dir() {
+            problems.push(format!("start_directory 不是有效的目录: {}", self.start_directory));
+        }
+
+        if !is_valid_date_format(&self.date_format) {
+            problems.push(format!("date_format 不是有效的时间格式: {}", self.date_format));
+        }
+
+        if self.theme.trim().is_empty() {
+            problems.push("theme 不能为空".to_string());
+        }
+
+        problems
+    }
+
+    /// 将不合法的字段恢复为默认值，返回被恢复的问题描述
+    fn repair(&mut self) -> Vec<String> {
+        let problems = self.validate();
+        if problems.is_empty() {
+            return problems;
+        }
+
+        let defaults = Settings::default();
+        if self.page_size == 0 || self.page_size > 10000 {
+            self.page_size = defaults.page_size;
+        }
+        if self.background_load_limit < self.page_size {
+            self.background_load_limit = defaults.background_load_limit.max(self.page_size);
+        }
+        if self.max_cache_entries == 0 {
+            self.max_cache_entries = defaults.max_cache_entries;
+        }
+        if !self.start_directory.is_empty() && !PathBuf::from(&self.start_directory).is_dir() {
+            self.start_directory = defaults.start_directory;
+        }
+        if !is_valid_date_format(&self.date_format) {
+            self.date_format = defaults.date_format;
+        }
+        if self.theme.trim().is_empty() {
+            self.theme = defaults.theme;
+        }
+
+        problems
+    }
+
+    /// 启动目录（设置为空时返回 None）
+    pub fn start_directory_path(&self) -> Option<PathBuf> {
+        if self.start_directory.is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(&self.start_directory))
+        }
+    }
+}
+
+/// 检查时间格式是否可用
+pub fn is_valid_date_format(format: &str) -> bool {
+    use chrono::format::{Item, StrftimeItems};
+
+    if format == RELATIVE_DATE_FORMAT {
+        return true;
+    }
+
+    !format.is_empty() && !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+/// 获取当前生效的设置
+pub fn current() -> Settings {
+    CURRENT_SETTINGS.read().unwrap().clone()
+}
+
+/// 更新当前生效的设置
+pub fn set_current(settings: Settings) {
+    *CURRENT_SETTINGS.write().unwrap() = settings;
+}
+
+/// 配置目录（设置、主题等文件所在的目录）
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(CONFIG_DIR_NAME))
+}
+
+/// 查找已存在的配置文件，优先TOML格式
+fn existing_config_file() -> Option<PathBuf> {
+    let dir = config_dir()?;
+    [TOML_FILE_NAME, JSON_FILE_NAME]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// 读取配置文件
+///
+/// 返回读取到的设置以及所有警告（未知的键、类型错误、不合法的取值）。
+/// 配置文件不存在时返回默认设置；有问题的字段会被恢复为默认值。
+pub fn load_settings() -> (Settings, Vec<String>) {
+    let mut warnings = Vec::new();
+
+    let path = match existing_config_file() {
+        Some(path) => path,
+        None => return (Settings::default(), warnings),
+    };
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            warnings.push(format!("无法读取配置文件 {}: {}", path.display(), e));
+            return (Settings::default(), warnings);
+        }
+    };
+
+    let is_toml = path.extension().is_some_and(|ext| ext == "toml");
+    let (settings, warnings) = parse_settings(&content, is_toml, &path.display().to_string());
+    println!("已加载配置文件: {:?}", path);
+    (settings, warnings)
+}
+
+/// 解析配置文件的内容（`source` 用于警告中的文件名），规则与 `load_settings` 相同
+fn parse_settings(content: &str, is_toml: bool, source: &str) -> (Settings, Vec<String>) {
+    let mut warnings = Vec::new();
+    let parsed: Result<serde_json::Value, String> = if is_toml {
+        toml::from_str(content).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_str(content).map_err(|e| e.to_string())
+    };
+
+    let values = match parsed {
+        Ok(serde_json::Value::Object(values)) => values,
+        Ok(_) => {
+            warnings.push(format!("配置文件 {} 的顶层必须是键值表", source));
+            return (Settings::default(), warnings);
+        }
+        Err(e) => {
+            warnings.push(format!("解析配置文件 {} 失败: {}", source, e));
+            return (Settings::default(), warnings);
+        }
+    };
+
+    // 逐个字段合并到默认设置上，单个字段类型错误不影响其他字段
+    let mut merged = match serde_json::to_value(Settings::default()) {
+        Ok(serde_json::Value::Object(defaults)) => defaults,
+        _ => return (Settings::default(), warnings),
+    };
+
+    for (key, value) in values {
+        if !KNOWN_KEYS.contains(&key.as_str()) {
+            warnings.push(format!("未知的配置项: {}", key));
+            continue;
+        }
+
+        let previous = merged.insert(key.clone(), value);
+        if serde_json::from_value::<Settings>(serde_json::Value::Object(merged.clone())).is_err() {
+            warnings.push(format!("配置项 {} 的类型不正确，已使用默认值", key));
+            if let Some(previous) = previous {
+                merged.insert(key, previous);
+            }
+        }
+    }
+
+    let mut settings: Settings = serde_json::from_value(serde_json::Value::Object(merged))
+        .unwrap_or_default();
+
+    for problem in settings.repair() {
+        warnings.push(format!("{}，已使用默认值", problem));
+    }
+    (settings, warnings)
+}
+
+/// 保存设置到配置文件（沿用已存在的文件格式，默认使用TOML）
+pub fn save_settings(settings: &Settings) -> io::Result<PathBuf> {
+    let path = match existing_config_file() {
+        Some(path) => path,
+        None => config_dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "无法确定配置目录"))?
+            .join(TOML_FILE_NAME),
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let content = if path.extension().is_some_and(|ext| ext == "toml") {
+        toml::to_string_pretty(settings).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+    } else {
+        serde_json::to_string_pretty(settings).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+    };
+
+    fs::write(&path, content)?;
+    println!("设置已保存: {:?}", path);
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_toml_keeps_valid_fields() {
+        let content = r#"
+            page_size = 50
+            show_hidden = true
+            date_format = "%Y-%m-%d"
+            bookmarks = ["/tmp"]
+        "#;
+        let (settings, warnings) = parse_settings(content, true, "config.toml");
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(settings.page_size, 50);
+        assert!(settings.show_hidden);
+        assert_eq!(settings.date_format, "%Y-%m-%d");
+        assert_eq!(settings.bookmarks, vec![PathBuf::from("/tmp")]);
+        assert_eq!(settings.theme, Settings::default().theme);
+    }
+
+    #[test]
+    fn unknown_keys_and_wrong_types_fall_back() {
+        let content = r#"{"page_size": "many", "colour": "red", "show_hidden": true, "max_cache_entries": -1}"#;
+        let (settings, warnings) = parse_settings(content, false, "config.json");
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert!(warnings.iter().any(|warning| warning.contains("colour")));
+        // 类型错误的字段使用默认值，其他字段不受影响
+        assert_eq!(settings.page_size, Settings::default().page_size);
+        assert_eq!(settings.max_cache_entries, Settings::default().max_cache_entries);
+        assert!(settings.show_hidden);
+    }
+
+    #[test]
+    fn unparsable_content_uses_defaults() {
+        let (settings, warnings) = parse_settings("page_size = ", true, "config.toml");
+        assert_eq!(settings, Settings::default());
+        assert_eq!(warnings.len(), 1);
+
+        let (settings, warnings) = parse_settings("[1, 2]", false, "config.json");
+        assert_eq!(settings, Settings::default());
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn validate_reports_each_problem() {
+        assert!(Settings::default().validate().is_empty());
+        let settings = Settings {
+            page_size: 0,
+            background_load_limit: 0,
+            max_cache_entries: 0,
+            start_directory: "/no/such/directory/for/settings/test".to_string(),
+            date_format: String::new(),
+            theme: " ".to_string(),
+            ..Settings::default()
+        };
+        assert_eq!(settings.validate().len(), 5, "{:?}", settings.validate());
+    }
+
+    #[test]
+    fn repair_restores_invalid_fields() {
+        let mut settings = Settings {
+            page_size: 20000,
+            background_load_limit: 5,
+            max_cache_entries: 0,
+            start_directory: "/no/such/directory/for/settings/test".to_string(),
+            date_format: String::new(),
+            theme: String::new(),
+            show_hidden: true,
+            ..Settings::default()
+        };
+        assert!(!settings.repair().is_empty());
+        let defaults = Settings::default();
+        assert_eq!(settings.page_size, defaults.page_size);
+        assert_eq!(settings.background_load_limit, defaults.background_load_limit);
+        assert_eq!(settings.max_cache_entries, defaults.max_cache_entries);
+        assert_eq!(settings.start_directory, "");
+        assert_eq!(settings.date_format, RELATIVE_DATE_FORMAT);
+        assert_eq!(settings.theme, defaults.theme);
+        // 合法的字段保持不变
+        assert!(settings.show_hidden);
+        assert!(settings.validate().is_empty());
+
+        // 加载上限小于页大小时至少等于页大小
+        let mut settings = Settings { page_size: 9000, background_load_limit: 10, ..Settings::default() };
+        settings.repair();
+        assert_eq!(settings.background_load_limit, 10000);
+    }
+}
@@ -0,0 +1,628 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use druid::im::Vector;
+use druid::{Data, Lens};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::dir_compare::{self, CompareMode};
+use crate::jobs::JobContext;
+
+/// 保存在设置中的单向同步任务（源目录 → 目标目录）
+#[derive(Clone, Data, Lens, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncJob {
+    /// 任务名称（在所有任务中唯一）
+    pub name: String,
+    pub source: String,
+    pub destination: String,
+    /// 只同步匹配这些模式的文件（为空时同步所有文件），模式相对于源目录，如 `*.jpg`、`docs/**`
+    #[data(same_fn = "PartialEq::eq")]
+    pub include: Vec<String>,
+    /// 跳过匹配这些模式的文件和目录
+    #[data(same_fn = "PartialEq::eq")]
+    pub exclude: Vec<String>,
+    /// 是否删除目标中源目录没有的文件
+    pub delete_extra: bool,
+    /// 是否按内容比较（否则按大小和修改时间比较）
+    pub use_checksum: bool,
+}
+
+/// 同步窗口中正在编辑的任务，模式列表以分号分隔
+#[derive(Clone, Data, Lens, Debug, Default, PartialEq)]
+pub struct SyncJobDraft {
+    pub name: String,
+    pub source: String,
+    pub destination: String,
+    pub include: String,
+    pub exclude: String,
+    pub delete_extra: bool,
+    pub use_checksum: bool,
+}
+
+impl SyncJobDraft {
+    pub fn from_job(job: &SyncJob) -> Self {
+        Self {
+            name: job.name.clone(),
+            source: job.source.clone(),
+            destination: job.destination.clone(),
+            include: job.include.join("; "),
+            exclude: job.exclude.join("; "),
+            delete_extra: job.delete_extra,
+            use_checksum: job.use_checksum,
+        }
+    }
+
+    /// 检查并转换为任务
+    pub fn to_job(&self) -> Result<SyncJob, String> {
+        let job = SyncJob {
+            name: self.name.trim().to_string(),
+            source: self.source.trim().to_string(),
+            destination: self.destination.trim().to_string(),
+            include: split_patterns(&self.include),
+            exclude: split_patterns(&self.exclude),
+            delete_extra: self.delete_extra,
+            use_checksum: self.use_checksum,
+        };
+        job.validate()?;
+        Ok(job)
+    }
+}
+
+fn split_patterns(text: &str) -> Vec<String> {
+    text.split(';')
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl SyncJob {
+    /// 检查任务是否可以执行
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("任务名称不能为空".to_string());
+        }
+        let source = PathBuf::from(&self.source);
+        if !source.is_dir() {
+            return Err(format!("源目录不存在: {}", self.source));
+        }
+        if self.destination.trim().is_empty() {
+            return Err("目标目录不能为空".to_string());
+        }
+        // 目标可以尚不存在，但不能与源目录重叠
+        let source = fs::canonicalize(&source).unwrap_or(source);
+        let destination = PathBuf::from(&self.destination);
+        let destination = fs::canonicalize(&destination).unwrap_or(destination);
+        if destination.starts_with(&source) || source.starts_with(&destination) {
+            return Err("源目录和目标目录不能相同或互相包含".to_string());
+        }
+        Filters::new(self)?;
+        Ok(())
+    }
+
+    fn compare_mode(&self) -> CompareMode {
+        if self.use_checksum {
+            CompareMode::Content
+        } else {
+            CompareMode::SizeAndTime
+        }
+    }
+}
+
+/// 同步时对一个条目执行的操作
+#[derive(Clone, Copy, Data, Debug, PartialEq, Eq)]
+pub enum SyncActionKind {
+    /// 创建目标中没有的目录（源目录中的空目录也会被同步）
+    CreateDir,
+    /// 复制目标中没有的文件
+    Copy,
+    /// 覆盖目标中不同的文件，或替换类型不同的同名条目
+    Update,
+    /// 把目标中多余的文件或目录移到回收站
+    Delete,
+}
+
+impl SyncActionKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            SyncActionKind::CreateDir => "创建目录",
+            SyncActionKind::Copy => "复制",
+            SyncActionKind::Update => "更新",
+            SyncActionKind::Delete => "删除",
+        }
+    }
+}
+
+/// 预览中的一个操作
+#[derive(Clone, Data, Lens, Debug, PartialEq)]
+pub struct SyncAction {
+    pub kind: SyncActionKind,
+    /// 相对于源目录和目标目录的路径
+    #[data(same_fn = "PartialEq::eq")]
+    pub relative: PathBuf,
+    /// 要复制的文件大小（删除时为0）
+    pub size: u64,
+}
+
+/// 同步窗口的状态
+#[derive(Clone, Data, Lens, Debug, Default, PartialEq)]
+pub struct SyncState {
+    /// 所有任务（保存时写回设置）
+    pub jobs: Vector<SyncJob>,
+    /// 正在编辑的任务原来的名称（新建时为 None）
+    pub selected: Option<String>,
+    pub draft: SyncJobDraft,
+    /// 预览任务ID
+    pub preview_job_id: Option<u64>,
+    /// 执行任务ID
+    pub run_job_id: Option<u64>,
+    /// 正在预览的任务
+    #[data(same_fn = "PartialEq::eq")]
+    pub previewing: Option<SyncJob>,
+    /// 预览对应的任务，编辑后需要重新预览才能执行
+    #[data(same_fn = "PartialEq::eq")]
+    pub planned_for: Option<SyncJob>,
+    /// 预览的操作
+    pub actions: Vector<SyncAction>,
+    /// 执行日志
+    pub log: Vector<String>,
+    pub notice: String,
+}
+
+impl SyncState {
+    pub fn new(jobs: &[SyncJob], selected: Option<&str>) -> Self {
+        let mut state = Self {
+            jobs: jobs.iter().cloned().collect(),
+            ..Default::default()
+        };
+        match selected.or_else(|| jobs.first().map(|job| job.name.as_str())) {
+            Some(name) => state.select(name),
+            None => state.new_job(),
+        }
+        state
+    }
+
+    /// 编辑指定名称的任务
+    pub fn select(&mut self, name: &str) {
+        if let Some(job) = self.jobs.iter().find(|job| job.name == name) {
+            self.draft = SyncJobDraft::from_job(job);
+            self.selected = Some(job.name.clone());
+            self.clear_plan();
+        }
+    }
+
+    /// 开始编辑新任务
+    pub fn new_job(&mut self) {
+        self.draft = SyncJobDraft::default();
+        self.selected = None;
+        self.clear_plan();
+    }
+
+    /// 保存正在编辑的任务（新建或替换原任务），名称不能与其他任务重复
+    pub fn save_draft(&mut self) -> Result<(), String> {
+        let job = self.draft.to_job()?;
+        let duplicate = self.jobs.iter()
+            .any(|other| other.name == job.name && Some(&other.name) != self.selected.as_ref());
+        if duplicate {
+            return Err(format!("已有名为 {} 的任务", job.name));
+        }
+
+        let name = job.name.clone();
+        match self.selected.as_ref().and_then(|selected| self.jobs.iter().position(|other| &other.name == selected)) {
+            Some(index) => self.jobs[index] = job,
+            None => self.jobs.push_back(job),
+        }
+        self.selected = Some(name);
+        Ok(())
+    }
+
+    /// 删除正在编辑的任务
+    pub fn delete_selected(&mut self) {
+        if let Some(selected) = self.selected.take() {
+            self.jobs.retain(|job| job.name != selected);
+        }
+        self.new_job();
+    }
+
+    /// 当前预览是否对应正在编辑的任务
+    pub fn can_run(&self) -> bool {
+        self.planned_for.is_some() && self.draft.to_job().ok() == self.planned_for
+    }
+
+    /// 预览的操作数量和要复制的字节数
+    pub fn plan_summary(&self) -> String {
+        let count = |kind| self.actions.iter().filter(|action| action.kind == kind).count();
+        let bytes: u64 = self.actions.iter().map(|action| action.size).sum();
+        format!(
+            "创建目录 {} 个，复制 {} 个，更新 {} 个，删除 {} 个，共需复制 {}",
+            count(SyncActionKind::CreateDir),
+            count(SyncActionKind::Copy),
+            count(SyncActionKind::Update),
+            count(SyncActionKind::Delete),
+            crate::utils::format_size(bytes),
+        )
+    }
+
+    fn clear_plan(&mut self) {
+        self.previewing = None;
+        self.planned_for = None;
+        self.actions = Vector::new();
+        self.log = Vector::new();
+        self.notice = String::new();
+    }
+}
+
+/// 包含和排除模式
+struct Filters {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl Filters {
+    fn new(job: &SyncJob) -> Result<Self, String> {
+        let include = if job.include.is_empty() {
+            None
+        } else {
+            Some(build_globs(&job.include)?)
+        };
+        Ok(Self { include, exclude: build_globs(&job.exclude)? })
+    }
+
+    fn excluded(&self, relative: &Path) -> bool {
+        self.exclude.is_match(relative)
+    }
+
+    /// 文件是否在同步范围内
+    fn includes_file(&self, relative: &Path) -> bool {
+        !self.excluded(relative) && self.include.as_ref().is_none_or(|include| include.is_match(relative))
+    }
+}
+
+fn build_globs(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern).map_err(|e| format!("无效的模式 {}: {}", pattern, e))?);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// 计算同步需要执行的操作（在后台任务中运行），不修改任何文件；任务被取消时返回 None
+///
+/// 符号链接不参与同步。排除的目录不会被遍历，也不会从目标中删除。
+pub fn plan(job: &SyncJob, context: &mut JobContext) -> Option<Result<Vector<SyncAction>, String>> {
+    let filters = match Filters::new(job) {
+        Ok(filters) => filters,
+        Err(e) => return Some(Err(e)),
+    };
+    let source = PathBuf::from(&job.source);
+    let destination = PathBuf::from(&job.destination);
+    let mode = job.compare_mode();
+
+    let mut actions = Vector::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        for (name, is_dir) in list_dir(&source.join(&relative), context) {
+            if context.is_cancelled() {
+                return None;
+            }
+            let child = relative.join(&name);
+            let (from, to) = (source.join(&child), destination.join(&child));
+            if is_dir {
+                if !filters.excluded(&child) {
+                    // 目标中没有时创建目录，同名的是文件时先替换为目录
+                    let kind = match entry_is_dir(&to) {
+                        None => Some(SyncActionKind::CreateDir),
+                        Some(false) => Some(SyncActionKind::Update),
+                        Some(true) => None,
+                    };
+                    if let Some(kind) = kind {
+                        actions.push_back(SyncAction { kind, relative: child.clone(), size: 0 });
+                    }
+                    pending.push(child);
+                }
+                continue;
+            }
+            if !filters.includes_file(&child) {
+                continue;
+            }
+
+            let size = fs::metadata(&from).map(|m| m.len()).unwrap_or(0);
+            let kind = match entry_is_dir(&to) {
+                None => Some(SyncActionKind::Copy),
+                // 目标中同名的是目录，整个替换为文件
+                Some(true) => Some(SyncActionKind::Update),
+                Some(false) => match dir_compare::files_identical(&from, &to, mode) {
+                    Ok(true) => None,
+                    Ok(false) => Some(SyncActionKind::Update),
+                    Err(e) => {
+                        context.failed(&from, e);
+                        None
+                    }
+                },
+            };
+            if let Some(kind) = kind {
+                actions.push_back(SyncAction { kind, relative: child, size });
+            }
+            context.processed();
+        }
+    }
+
+    if job.delete_extra && destination.is_dir() {
+        plan_deletions(&source, &destination, &filters, &mut actions, context)?;
+    }
+
+    actions.sort_by(|a, b| a.relative.cmp(&b.relative));
+    Some(Ok(actions))
+}
+
+/// 目标中源目录没有的条目；没有包含模式时整个多余的目录作为一个操作删除
+///
+/// 源目录中有同名但类型不同的条目时由 `plan` 中的更新操作替换，这里不再删除。
+fn plan_deletions(
+    source: &Path,
+    destination: &Path,
+    filters: &Filters,
+    actions: &mut Vector<SyncAction>,
+    context: &mut JobContext,
+) -> Option<()> {
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        for (name, is_dir) in list_dir(&destination.join(&relative), context) {
+            if context.is_cancelled() {
+                return None;
+            }
+            let child = relative.join(&name);
+            let in_source = entry_is_dir(&source.join(&child));
+            if is_dir {
+                if filters.excluded(&child) {
+                    continue;
+                }
+                match in_source {
+                    Some(true) => pending.push(child),
+                    Some(false) => {}
+                    None if filters.include.is_none() => {
+                        actions.push_back(SyncAction { kind: SyncActionKind::Delete, relative: child, size: 0 });
+                    }
+                    None => pending.push(child),
+                }
+            } else if filters.includes_file(&child) && in_source.is_none() {
+                actions.push_back(SyncAction { kind: SyncActionKind::Delete, relative: child, size: 0 });
+            }
+        }
+    }
+    Some(())
+}
+
+/// 路径是否为目录（不跟随符号链接），不存在时返回 None
+fn entry_is_dir(path: &Path) -> Option<bool> {
+    fs::symlink_metadata(path).ok().map(|metadata| metadata.is_dir())
+}
+
+/// 列出目录中的文件和子目录（跳过符号链接），无法读取时记录到任务中
+fn list_dir(dir: &Path, context: &mut JobContext) -> Vec<(String, bool)> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            context.failed(dir, format!("无法读取目录: {}", e));
+            return Vec::new();
+        }
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let file_type = entry.file_type().ok()?;
+            if file_type.is_symlink() {
+                return None;
+            }
+            Some((entry.file_name().to_string_lossy().to_string(), file_type.is_dir()))
+        })
+        .collect()
+}
+
+/// 执行预览中的操作（在后台任务中运行），返回执行日志
+///
+/// 每个操作失败时记录到任务和日志中并继续执行其余操作。
+pub fn execute(job: &SyncJob, actions: &[SyncAction], context: &mut JobContext) -> Vector<String> {
+    let source = PathBuf::from(&job.source);
+    let destination = PathBuf::from(&job.destination);
+
+    let mut log = Vector::new();
+    log.push_back(format!("同步 {}：{} → {}", job.name, job.source, job.destination));
+    for action in actions {
+        if context.is_cancelled() {
+            log.push_back("已取消".to_string());
+            break;
+        }
+
+        let target = destination.join(&action.relative);
+        let result = match action.kind {
+            SyncActionKind::CreateDir => fs::create_dir_all(&target),
+            SyncActionKind::Copy | SyncActionKind::Update => {
+                replace_target(&source.join(&action.relative), &target)
+            }
+            SyncActionKind::Delete => move_to_trash(&target),
+        };
+
+        match result {
+            Ok(()) => {
+                log.push_back(format!("{} {}", action.kind.name(), action.relative.display()));
+                context.processed();
+            }
+            Err(e) => {
+                log.push_back(format!("{}失败 {}: {}", action.kind.name(), action.relative.display(), e));
+                context.failed(&target, e);
+            }
+        }
+    }
+    log
+}
+
+/// 用源条目替换目标：类型不同时先把目标移到回收站，目录只需创建，文件复制内容
+fn replace_target(from: &Path, to: &Path) -> io::Result<()> {
+    let source_is_dir = from.is_dir();
+    if entry_is_dir(to).is_some_and(|is_dir| is_dir != source_is_dir) {
+        move_to_trash(to)?;
+    }
+    if source_is_dir {
+        fs::create_dir_all(to)
+    } else {
+        dir_compare::copy_file(from, to)
+    }
+}
+
+fn move_to_trash(path: &Path) -> io::Result<()> {
+    trash::delete(path).map_err(|e| io::Error::other(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(source: &Path, destination: &Path) -> SyncJob {
+        SyncJob {
+            name: "测试".to_string(),
+            source: source.to_string_lossy().to_string(),
+            destination: destination.to_string_lossy().to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn planned(job: &SyncJob) -> Vec<(SyncActionKind, String)> {
+        let actions = plan(job, &mut JobContext::detached()).unwrap().unwrap();
+        actions.iter()
+            .map(|action| (action.kind, action.relative.to_string_lossy().replace('\\', "/")))
+            .collect()
+    }
+
+    #[test]
+    fn split_patterns_skips_blanks() {
+        assert_eq!(split_patterns(" *.jpg ; ;docs/** "), vec!["*.jpg", "docs/**"]);
+    }
+
+    #[test]
+    fn filters_apply_include_and_exclude() {
+        let mut job = SyncJob::default();
+        let filters = Filters::new(&job).unwrap();
+        assert!(filters.includes_file(Path::new("a/b.txt")));
+
+        job.include = vec!["*.jpg".to_string()];
+        job.exclude = vec!["**/*.tmp".to_string(), "cache".to_string()];
+        let filters = Filters::new(&job).unwrap();
+        // `*` 可以跨越目录
+        assert!(filters.includes_file(Path::new("photos/a.jpg")));
+        assert!(!filters.includes_file(Path::new("a.png")));
+        assert!(!filters.includes_file(Path::new("x/y.tmp")));
+        assert!(filters.excluded(Path::new("cache")));
+        assert!(!filters.excluded(Path::new("photos")));
+
+        job.exclude = vec!["[".to_string()];
+        assert!(Filters::new(&job).is_err());
+    }
+
+    #[test]
+    fn plan_copies_updates_and_deletes() {
+        let root = std::env::temp_dir().join(format!("sync-test-plan-{}", std::process::id()));
+        let (source, destination) = (root.join("src"), root.join("dst"));
+        write(&source.join("new.txt"), "new");
+        write(&source.join("same.txt"), "same");
+        write(&source.join("changed.txt"), "changed");
+        write(&source.join("sub/inner.txt"), "inner");
+        write(&source.join("skip.tmp"), "tmp");
+        write(&destination.join("same.txt"), "same");
+        write(&destination.join("changed.txt"), "old");
+        write(&destination.join("extra.txt"), "extra");
+        write(&destination.join("old_dir/file.txt"), "old");
+        write(&destination.join("sub/gone.txt"), "gone");
+
+        let mut job = job(&source, &destination);
+        job.exclude = vec!["*.tmp".to_string()];
+        job.use_checksum = true;
+        assert_eq!(planned(&job), vec![
+            (SyncActionKind::Update, "changed.txt".to_string()),
+            (SyncActionKind::Copy, "new.txt".to_string()),
+            (SyncActionKind::Copy, "sub/inner.txt".to_string()),
+        ]);
+
+        // 没有包含模式时多余的目录整个删除
+        job.delete_extra = true;
+        assert_eq!(planned(&job), vec![
+            (SyncActionKind::Update, "changed.txt".to_string()),
+            (SyncActionKind::Delete, "extra.txt".to_string()),
+            (SyncActionKind::Copy, "new.txt".to_string()),
+            (SyncActionKind::Delete, "old_dir".to_string()),
+            (SyncActionKind::Delete, "sub/gone.txt".to_string()),
+            (SyncActionKind::Copy, "sub/inner.txt".to_string()),
+        ]);
+
+        // 有包含模式时只删除匹配的文件
+        job.include = vec!["*.txt".to_string()];
+        job.exclude = vec!["old_dir".to_string()];
+        assert_eq!(planned(&job), vec![
+            (SyncActionKind::Update, "changed.txt".to_string()),
+            (SyncActionKind::Delete, "extra.txt".to_string()),
+            (SyncActionKind::Copy, "new.txt".to_string()),
+            (SyncActionKind::Delete, "sub/gone.txt".to_string()),
+            (SyncActionKind::Copy, "sub/inner.txt".to_string()),
+        ]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn plan_replaces_entries_of_other_type_once() {
+        let root = std::env::temp_dir().join(format!("sync-test-types-{}", std::process::id()));
+        let (source, destination) = (root.join("src"), root.join("dst"));
+        write(&source.join("was_dir"), "file");
+        write(&source.join("was_file/inner.txt"), "inner");
+        write(&destination.join("was_dir/old.txt"), "old");
+        write(&destination.join("was_file"), "file");
+
+        let mut job = job(&source, &destination);
+        job.delete_extra = true;
+        assert_eq!(planned(&job), vec![
+            (SyncActionKind::Update, "was_dir".to_string()),
+            (SyncActionKind::Update, "was_file".to_string()),
+            (SyncActionKind::Copy, "was_file/inner.txt".to_string()),
+        ]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn plan_creates_missing_directories() {
+        let root = std::env::temp_dir().join(format!("sync-test-dirs-{}", std::process::id()));
+        let (source, destination) = (root.join("src"), root.join("dst"));
+        fs::create_dir_all(source.join("empty")).unwrap();
+        fs::create_dir_all(source.join("existing")).unwrap();
+        fs::create_dir_all(source.join("cache")).unwrap();
+        write(&source.join("nested/deep/file.txt"), "file");
+        fs::create_dir_all(destination.join("existing")).unwrap();
+
+        let mut job = job(&source, &destination);
+        job.exclude = vec!["cache".to_string()];
+        assert_eq!(planned(&job), vec![
+            (SyncActionKind::CreateDir, "empty".to_string()),
+            (SyncActionKind::CreateDir, "nested".to_string()),
+            (SyncActionKind::CreateDir, "nested/deep".to_string()),
+            (SyncActionKind::Copy, "nested/deep/file.txt".to_string()),
+        ]);
+
+        // 执行时目录先于其中的文件创建
+        let actions: Vec<SyncAction> = plan(&job, &mut JobContext::detached()).unwrap().unwrap().into_iter().collect();
+        execute(&job, &actions, &mut JobContext::detached());
+        assert!(destination.join("empty").is_dir());
+        assert_eq!(fs::read_to_string(destination.join("nested/deep/file.txt")).unwrap(), "file");
+        assert!(!destination.join("cache").exists());
+        assert!(planned(&job).is_empty());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
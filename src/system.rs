@@ -72,7 +72,24 @@ pub fn get_drive_space(path: &Path) -> (u64, u64) {
         }
     }
     
-    // 默认返回0,0（非Windows系统或出错时）
+    #[cfg(unix)]
+    {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+
+        let c_path = match CString::new(path.as_os_str().as_bytes()) {
+            Ok(c_path) => c_path,
+            Err(_) => return (0, 0),
+        };
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } == 0 {
+            // f_bavail 是普通用户可用的块数，不包括为root保留的部分
+            let block_size = stat.f_frsize as u64;
+            return (stat.f_bavail as u64 * block_size, stat.f_blocks as u64 * block_size);
+        }
+    }
+
+    // 默认返回0,0（出错时）
     (0, 0)
 }
 
@@ -0,0 +1,184 @@
+use druid::widget::{
+    Button, Checkbox, Controller, CrossAxisAlignment, Flex, Label, LineBreaking, List, RadioGroup, Scroll, TextBox
+};
+use druid::{commands, Env, Event, EventCtx, Widget, WidgetExt};
+use crate::batch_rename::{BatchRenameState, CaseChange, ExtensionChange, PlannedRename, RenameOptions};
+use crate::commands::APPLY_BATCH_RENAME;
+use crate::models::AppState;
+use super::job_status::build_job_status;
+use super::theme;
+
+/// 构建批量重命名窗口
+pub fn build_batch_rename_dialog() -> impl Widget<AppState> {
+    theme::themed(build_batch_rename_view())
+}
+
+/// 构建批量重命名窗口的内容：规则、预览和执行按钮
+fn build_batch_rename_view() -> impl Widget<AppState> {
+    let options = build_options().lens(BatchRenameState::options);
+
+    let preview = Scroll::new(List::new(preview_row).lens(BatchRenameState::preview))
+        .vertical()
+        .expand();
+
+    let notice = Label::dynamic(|data: &BatchRenameState, _| data.notice.clone())
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .with_text_color(theme::SECONDARY_TEXT_COLOR)
+        .with_text_size(12.0);
+
+    let state = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(options)
+        .with_spacer(8.0)
+        .with_child(label("预览"))
+        .with_flex_child(preview, 1.0)
+        .with_child(notice.expand_width())
+        .controller(PreviewController)
+        .lens(AppState::batch_rename);
+
+    let buttons = Flex::row()
+        .with_flex_spacer(1.0)
+        .with_child(
+            Button::new("重命名")
+                .on_click(|ctx, _data: &mut AppState, _env| {
+                    ctx.submit_command(APPLY_BATCH_RENAME);
+                })
+                .disabled_if(|data: &AppState, _env| !data.batch_rename.can_apply())
+        )
+        .with_spacer(8.0)
+        .with_child(Button::new("关闭").on_click(|ctx, _data: &mut AppState, _env| {
+            ctx.submit_command(commands::CLOSE_WINDOW.to(ctx.window_id()));
+        }));
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_flex_child(state, 1.0)
+        .with_spacer(6.0)
+        .with_child(build_job_status(|data| data.batch_rename.job_id))
+        .with_spacer(6.0)
+        .with_child(buttons)
+        .padding(12.0)
+        .background(theme::BACKGROUND)
+        .expand()
+}
+
+/// 重命名规则的编辑区域
+fn build_options() -> impl Widget<RenameOptions> {
+    let template = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(option_row("名称模板", TextBox::new().lens(RenameOptions::template).expand_width()))
+        .with_child(
+            Label::new("{name} 原名称，{n} 或 {n:3} 计数器，{date} 或 {date:%Y%m%d} 修改日期")
+                .with_text_color(theme::SECONDARY_TEXT_COLOR)
+                .with_text_size(12.0)
+                .padding((90.0, 0.0, 0.0, 4.0))
+        );
+
+    let counter = Flex::row()
+        .with_child(TextBox::new().lens(RenameOptions::counter_start).fix_width(60.0))
+        .with_spacer(8.0)
+        .with_child(label("步长"))
+        .with_spacer(8.0)
+        .with_child(TextBox::new().lens(RenameOptions::counter_step).fix_width(60.0));
+
+    let find_replace = Flex::row()
+        .with_flex_child(TextBox::new().with_placeholder("查找").lens(RenameOptions::find).expand_width(), 1.0)
+        .with_spacer(8.0)
+        .with_flex_child(TextBox::new().with_placeholder("替换为").lens(RenameOptions::replace).expand_width(), 1.0)
+        .with_spacer(8.0)
+        .with_child(Checkbox::new("正则表达式").lens(RenameOptions::use_regex));
+
+    let case = RadioGroup::row(vec![
+        ("不变", CaseChange::Keep),
+        ("小写", CaseChange::Lower),
+        ("大写", CaseChange::Upper),
+        ("首字母大写", CaseChange::Title),
+    ])
+    .lens(RenameOptions::case);
+
+    let extension = Flex::row()
+        .with_child(
+            RadioGroup::row(vec![
+                ("不变", ExtensionChange::Keep),
+                ("小写", ExtensionChange::Lower),
+                ("大写", ExtensionChange::Upper),
+                ("改为", ExtensionChange::Replace),
+            ])
+            .lens(RenameOptions::extension)
+        )
+        .with_spacer(8.0)
+        .with_child(
+            TextBox::new()
+                .with_placeholder("为空时去掉扩展名")
+                .lens(RenameOptions::new_extension)
+                .fix_width(140.0)
+                .disabled_if(|data: &RenameOptions, _env| data.extension != ExtensionChange::Replace)
+        );
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(template)
+        .with_child(option_row("计数器起始", counter))
+        .with_child(option_row("查找替换", find_replace))
+        .with_child(option_row("大小写", case))
+        .with_child(option_row("扩展名", extension))
+}
+
+/// 预览中的一行：原名称、新名称和冲突说明
+fn preview_row() -> impl Widget<PlannedRename> {
+    let old_name = Label::dynamic(|item: &PlannedRename, _| file_name(&item.from))
+        .with_line_break_mode(LineBreaking::Clip)
+        .with_text_color(theme::SECONDARY_TEXT_COLOR)
+        .with_text_size(13.0)
+        .expand_width();
+
+    let new_name = Label::dynamic(|item: &PlannedRename, _| {
+        if item.conflict.is_empty() {
+            file_name(&item.to)
+        } else {
+            format!("{}（{}）", file_name(&item.to), item.conflict)
+        }
+    })
+    .with_line_break_mode(LineBreaking::Clip)
+    .with_text_color(theme::TEXT_COLOR)
+    .with_text_size(13.0)
+    .expand_width();
+
+    Flex::row()
+        .with_flex_child(old_name, 1.0)
+        .with_child(label("→"))
+        .with_spacer(6.0)
+        .with_flex_child(new_name, 1.0)
+        .padding((0.0, 2.0))
+}
+
+fn file_name(path: &std::path::Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+fn option_row<W: Widget<RenameOptions> + 'static>(name: &str, editor: W) -> impl Widget<RenameOptions> {
+    Flex::row()
+        .with_child(label(name).fix_width(90.0))
+        .with_flex_child(editor, 1.0)
+        .padding((0.0, 3.0))
+}
+
+fn label<T: druid::Data>(text: &str) -> Label<T> {
+    Label::new(text)
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(13.0)
+}
+
+/// 规则变化时重新生成预览
+struct PreviewController;
+
+impl<W: Widget<BatchRenameState>> Controller<BatchRenameState, W> for PreviewController {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut BatchRenameState, env: &Env) {
+        let old_options = data.options.clone();
+        child.event(ctx, event, data, env);
+
+        if data.options != old_options {
+            data.update_preview();
+        }
+    }
+}
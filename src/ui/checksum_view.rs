@@ -0,0 +1,158 @@
+use druid::widget::{Button, CrossAxisAlignment, Either, Flex, Label, LineBreaking, List, Scroll};
+use druid::{commands, Application, Widget, WidgetExt};
+use crate::checksum::{to_sha256sum, ChecksumState, FileChecksums, VerifyEntry, VerifyStatus};
+use crate::models::AppState;
+use crate::utils::format_size;
+use super::job_status::build_job_status;
+use super::theme;
+
+/// 构建校验和窗口（计算校验和或按校验和文件校验）
+pub fn build_checksum_window() -> impl Widget<AppState> {
+    theme::themed(build_checksum_view())
+}
+
+fn build_checksum_view() -> impl Widget<AppState> {
+    let content = Either::new(
+        |data: &ChecksumState, _| data.list_path.is_some(),
+        build_verify_view(),
+        build_results_view(),
+    );
+
+    let notice = Label::dynamic(|data: &ChecksumState, _| data.notice.clone())
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .with_text_color(theme::SECONDARY_TEXT_COLOR)
+        .with_text_size(12.0);
+
+    let state = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_flex_child(content, 1.0)
+        .with_child(notice.expand_width())
+        .lens(AppState::checksums);
+
+    let buttons = Flex::row()
+        .with_flex_spacer(1.0)
+        .with_child(Button::new("关闭").on_click(|ctx, _data: &mut AppState, _env| {
+            ctx.submit_command(commands::CLOSE_WINDOW.to(ctx.window_id()));
+        }));
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_flex_child(state, 1.0)
+        .with_spacer(6.0)
+        .with_child(build_job_status(|data| data.checksums.job_id))
+        .with_spacer(6.0)
+        .with_child(buttons)
+        .padding(12.0)
+        .background(theme::BACKGROUND)
+        .expand()
+}
+
+/// 计算结果：每个文件的四种校验和，可复制为 sha256sum 格式或完整文本
+fn build_results_view() -> impl Widget<ChecksumState> {
+    let copy_buttons = Flex::row()
+        .with_child(Button::new("复制为 sha256sum 格式").on_click(|_ctx, data: &mut ChecksumState, _env| {
+            if data.results.is_empty() {
+                return;
+            }
+            let results: Vec<FileChecksums> = data.results.iter().cloned().collect();
+            Application::global().clipboard().put_string(to_sha256sum(&results));
+            data.notice = format!("已复制 {} 个文件的 SHA-256", results.len());
+        }))
+        .with_spacer(8.0)
+        .with_child(Button::new("复制全部结果").on_click(|_ctx, data: &mut ChecksumState, _env| {
+            if data.results.is_empty() {
+                return;
+            }
+            Application::global().clipboard().put_string(data.full_report());
+            data.notice = "已复制全部结果".to_string();
+        }));
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_flex_child(
+            Scroll::new(List::new(checksum_row).lens(ChecksumState::results))
+                .vertical()
+                .expand(),
+            1.0,
+        )
+        .with_spacer(6.0)
+        .with_child(copy_buttons)
+}
+
+/// 一个文件的校验和
+fn checksum_row() -> impl Widget<FileChecksums> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(
+            Label::dynamic(|result: &FileChecksums, _| {
+                format!("{}（{}）", result.path.display(), format_size(result.size))
+            })
+            .with_line_break_mode(LineBreaking::WordWrap)
+            .with_text_color(theme::TEXT_COLOR)
+            .with_text_size(14.0)
+        )
+        .with_child(hash_line("MD5", |result| result.md5.clone()))
+        .with_child(hash_line("SHA-1", |result| result.sha1.clone()))
+        .with_child(hash_line("SHA-256", |result| result.sha256.clone()))
+        .with_child(hash_line("BLAKE3", |result| result.blake3.clone()))
+        .padding((0.0, 4.0, 0.0, 10.0))
+}
+
+/// 一种算法的结果
+fn hash_line(name: &str, value: fn(&FileChecksums) -> String) -> impl Widget<FileChecksums> {
+    Flex::row()
+        .with_child(
+            Label::new(name)
+                .with_text_color(theme::SECONDARY_TEXT_COLOR)
+                .with_text_size(12.0)
+                .fix_width(70.0)
+        )
+        .with_flex_child(
+            Label::dynamic(move |result: &FileChecksums, _| value(result))
+                .with_line_break_mode(LineBreaking::WordWrap)
+                .with_text_color(theme::TEXT_COLOR)
+                .with_text_size(12.0)
+                .expand_width(),
+            1.0,
+        )
+}
+
+/// 校验结果：各状态的数量，以及不一致、缺失和无法读取的文件
+fn build_verify_view() -> impl Widget<ChecksumState> {
+    let summary = Label::dynamic(|data: &ChecksumState, _| {
+        let list = data.list_path.as_ref().map(|path| path.display().to_string()).unwrap_or_default();
+        format!(
+            "{}\n{} 个一致，{} 个不一致，{} 个缺失，{} 个无法读取",
+            list,
+            data.count(VerifyStatus::Ok),
+            data.count(VerifyStatus::Mismatch),
+            data.count(VerifyStatus::Missing),
+            data.count(VerifyStatus::Error),
+        )
+    })
+    .with_line_break_mode(LineBreaking::WordWrap)
+    .with_text_color(theme::TEXT_COLOR)
+    .with_text_size(14.0);
+
+    let problems = List::new(|| {
+        Label::dynamic(|entry: &VerifyEntry, _| {
+            if entry.detail.is_empty() {
+                format!("{}：{}", entry.status.name(), entry.name)
+            } else {
+                format!("{}：{}（{}）", entry.status.name(), entry.name, entry.detail)
+            }
+        })
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(13.0)
+        .padding((0.0, 2.0))
+        .expand_width()
+    })
+    .lens(ChecksumState::problems);
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(summary.expand_width())
+        .with_spacer(8.0)
+        .with_flex_child(Scroll::new(problems).vertical().expand(), 1.0)
+}
@@ -0,0 +1,148 @@
+use druid::widget::{Controller, CrossAxisAlignment, Either, Flex, Label, LineBreaking, List, Painter, Scroll, SizedBox, TextBox};
+use druid::{Env, Event, EventCtx, KbKey, LifeCycle, LifeCycleCtx, RenderContext, Widget, WidgetExt};
+use crate::command_palette::{PaletteItem, PaletteState};
+use crate::commands::{CLOSE_COMMAND_PALETTE, FOCUS_COMMAND_PALETTE, RUN_PALETTE_ITEM};
+use crate::models::AppState;
+use super::keyboard_nav::ScrollToFocus;
+use super::theme;
+
+/// 命令面板的宽度
+const PALETTE_WIDTH: f64 = 560.0;
+
+/// 结果列表的高度
+const RESULTS_HEIGHT: f64 = 320.0;
+
+/// 构建命令面板（覆盖在主界面上方，关闭时不占空间）
+pub fn build_command_palette() -> impl Widget<AppState> {
+    Either::new(
+        |data: &AppState, _| data.palette.open,
+        build_palette().lens(AppState::palette),
+        SizedBox::empty(),
+    )
+}
+
+fn build_palette() -> impl Widget<PaletteState> {
+    let title = Label::dynamic(|data: &PaletteState, _| data.title().to_string())
+        .with_text_color(theme::SECONDARY_TEXT_COLOR)
+        .with_text_size(12.0);
+
+    let input = TextBox::new()
+        .with_placeholder("输入搜索内容")
+        .with_text_size(15.0)
+        .lens(PaletteState::query)
+        .controller(PaletteInput)
+        .expand_width();
+
+    let results = Either::new(
+        |data: &PaletteState, _| data.items.is_empty(),
+        Label::new("没有匹配的项")
+            .with_text_color(theme::SECONDARY_TEXT_COLOR)
+            .with_text_size(13.0)
+            .padding(8.0),
+        Scroll::new(List::new(|| build_item().controller(ScrollToFocus::new(|item: &PaletteItem| item.is_selected))))
+            .vertical()
+            .lens(PaletteState::items),
+    )
+    .fix_height(RESULTS_HEIGHT);
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(title)
+        .with_spacer(4.0)
+        .with_child(input)
+        .with_spacer(6.0)
+        .with_child(results)
+        .padding(8.0)
+        .background(theme::NAV_BAR_BACKGROUND)
+        .border(theme::SELECTION_BACKGROUND, 1.0)
+        .rounded(6.0)
+        .fix_width(PALETTE_WIDTH)
+        .padding((0.0, 40.0, 0.0, 0.0))
+}
+
+/// 结果列表中的一行：分类、名称、说明和绑定的按键，点击执行
+fn build_item() -> impl Widget<PaletteItem> {
+    let category = Label::dynamic(|item: &PaletteItem, _| item.category.clone())
+        .with_text_color(theme::SECONDARY_TEXT_COLOR)
+        .with_text_size(12.0)
+        .fix_width(64.0);
+
+    let title = Label::dynamic(|item: &PaletteItem, _| item.title.clone())
+        .with_line_break_mode(LineBreaking::Clip)
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(14.0);
+
+    let detail = Label::dynamic(|item: &PaletteItem, _| item.detail.clone())
+        .with_line_break_mode(LineBreaking::Clip)
+        .with_text_color(theme::SECONDARY_TEXT_COLOR)
+        .with_text_size(12.0);
+
+    let keys = Label::dynamic(|item: &PaletteItem, _| item.keys.clone())
+        .with_text_color(theme::SECONDARY_TEXT_COLOR)
+        .with_text_size(12.0);
+
+    Flex::row()
+        .with_child(category)
+        .with_flex_child(
+            Flex::column()
+                .cross_axis_alignment(CrossAxisAlignment::Start)
+                .with_child(title)
+                .with_child(detail)
+                .expand_width(),
+            1.0,
+        )
+        .with_child(keys)
+        .padding((6.0, 3.0))
+        .background(Painter::new(|ctx, item: &PaletteItem, env| {
+            if item.is_selected {
+                let rect = ctx.size().to_rect();
+                ctx.fill(rect, &env.get(theme::SELECTION_BACKGROUND));
+            }
+        }))
+        .on_click(|ctx, item: &mut PaletteItem, _env| {
+            ctx.submit_command(RUN_PALETTE_ITEM.with(item.target.clone()));
+        })
+}
+
+/// 命令面板输入框的按键：上下键选择，回车执行，Esc 关闭；输入变化时重新过滤，失去焦点时关闭面板
+struct PaletteInput;
+
+impl<W: Widget<PaletteState>> Controller<PaletteState, W> for PaletteInput {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut PaletteState, env: &Env) {
+        match event {
+            Event::Command(cmd) if cmd.is(FOCUS_COMMAND_PALETTE) => {
+                ctx.request_focus();
+                ctx.set_handled();
+                return;
+            }
+            Event::KeyDown(key) if matches!(key.key, KbKey::ArrowUp | KbKey::ArrowDown | KbKey::Enter | KbKey::Escape) => {
+                match &key.key {
+                    KbKey::ArrowUp => data.move_selection(-1),
+                    KbKey::ArrowDown => data.move_selection(1),
+                    KbKey::Enter => {
+                        if let Some(target) = data.selected_target() {
+                            ctx.submit_command(RUN_PALETTE_ITEM.with(target));
+                        }
+                    }
+                    _ => ctx.submit_command(CLOSE_COMMAND_PALETTE.with(true)),
+                }
+                ctx.set_handled();
+                return;
+            }
+            _ => {}
+        }
+
+        let query = data.query.clone();
+        child.event(ctx, event, data, env);
+        if data.query != query {
+            data.refilter();
+        }
+    }
+
+    fn lifecycle(&mut self, child: &mut W, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &PaletteState, env: &Env) {
+        if let LifeCycle::FocusChanged(false) = event {
+            ctx.submit_command(CLOSE_COMMAND_PALETTE.with(false));
+        }
+        child.lifecycle(ctx, event, data, env);
+    }
+}
@@ -1,3 +1,5 @@
+//! 深色主题（默认主题）的颜色取值，界面中的控件通过 `theme` 模块的Env键读取颜色
+
 use druid::Color;
 use druid::FontDescriptor;
 use druid::FontFamily;
@@ -6,6 +8,15 @@ use druid::FontWeight;
 /// 当前选中项的背景颜色（适当加深的蓝色，提高对比度）
 pub const SELECTED_COLOR: Color = Color::rgb8(65, 105, 225);
 
+/// 键盘焦点框的颜色（与选中项的蓝色背景形成对比）
+pub const FOCUS_RING_COLOR: Color = Color::rgb8(255, 196, 60);
+
+/// 可用空间不足时使用条的颜色
+pub const USAGE_WARNING_COLOR: Color = Color::rgb8(0xE0, 0x6C, 0x4C);
+
+/// 快速筛选时名称中匹配部分的颜色
+pub const MATCH_HIGHLIGHT_COLOR: Color = Color::rgb8(0xF0, 0xB4, 0x29);
+
 /// 悬停项的背景颜色（轻微高亮）
 #[allow(dead_code)]
 pub const HOVER_COLOR: Color = Color::rgb8(50, 50, 60);
@@ -16,6 +27,9 @@ pub const DARK_BACKGROUND: Color = Color::rgb8(32, 32, 32);
 /// 导航栏背景色（略微比主背景亮）
 pub const NAV_BAR_BACKGROUND: Color = Color::rgb8(42, 42, 42);
 
+/// 目录树背景色
+pub const TREE_BACKGROUND: Color = Color::rgb8(40, 44, 52);
+
 /// 深色主题文本颜色
 #[allow(dead_code)]
 pub const DARK_TEXT: Color = Color::rgb8(230, 230, 230);
@@ -37,42 +51,10 @@ pub const ICON_COLOR: Color = Color::rgb8(200, 200, 200);
 #[allow(dead_code)]
 pub const HEADER_BACKGROUND: Color = Color::rgb8(50, 50, 50);
 
-// 不同文件类型的颜色
-/// Rust文件图标颜色
-#[allow(dead_code)]
-pub const RUST_FILE_COLOR: Color = Color::rgb8(244, 110, 66);
-
-/// HTML文件图标颜色
-#[allow(dead_code)]
-pub const HTML_FILE_COLOR: Color = Color::rgb8(240, 101, 41);
-
-/// JS文件图标颜色
-#[allow(dead_code)]
-pub const JS_FILE_COLOR: Color = Color::rgb8(247, 223, 30);
-
-/// CSS文件图标颜色
-#[allow(dead_code)]
-pub const CSS_FILE_COLOR: Color = Color::rgb8(86, 61, 124);
-
-/// TOML文件图标颜色
-#[allow(dead_code)]
-pub const TOML_FILE_COLOR: Color = Color::rgb8(180, 180, 180);
-
-/// MD文件图标颜色
-#[allow(dead_code)]
-pub const MD_FILE_COLOR: Color = Color::rgb8(108, 165, 209);
-
+// 不同文件类别的颜色（具体文件类型的颜色见文件类型注册表）
 /// SVG/图片文件图标颜色
 pub const IMAGE_FILE_COLOR: Color = Color::rgb8(120, 195, 85);
 
-/// 可执行文件图标颜色
-#[allow(dead_code)]
-pub const EXE_FILE_COLOR: Color = Color::rgb8(80, 220, 100);
-
-/// ICO文件图标颜色
-#[allow(dead_code)]
-pub const ICO_FILE_COLOR: Color = Color::rgb8(255, 165, 0);
-
 /// 中等深度背景色（用于表头等）
 pub const MID_BACKGROUND: Color = Color::rgb8(45, 45, 45);
 
@@ -97,7 +79,20 @@ pub const EXECUTABLE_FILE_COLOR: Color = Color::rgb8(80, 220, 100);
 /// 常规文件颜色
 pub const REGULAR_FILE_COLOR: Color = Color::rgb8(200, 200, 200);
 
+/// 隐藏文件的颜色透明度（淡化显示）
+pub const HIDDEN_ITEM_ALPHA: f64 = 0.45;
+
 /// 粗体字体
 #[allow(dead_code)]
 pub const FONT_BOLD: FontDescriptor = FontDescriptor::new(FontFamily::SYSTEM_UI)
-    .with_weight(FontWeight::BOLD); 
\ No newline at end of file
+    .with_weight(FontWeight::BOLD);
+
+// 目录比较结果中各状态的颜色（在所有主题中保持一致）
+/// 两侧不同
+pub const DIFF_CHANGED_COLOR: Color = Color::rgb8(230, 160, 60);
+
+/// 只在左侧存在
+pub const DIFF_ONLY_LEFT_COLOR: Color = Color::rgb8(90, 160, 230);
+
+/// 只在右侧存在
+pub const DIFF_ONLY_RIGHT_COLOR: Color = Color::rgb8(120, 195, 85);
@@ -0,0 +1,69 @@
+use std::path::{Path, PathBuf};
+use druid::widget::Controller;
+use druid::{Env, Event, EventCtx, Menu, MenuItem, Widget};
+use crate::commands::{ANALYZE_DISK_USAGE, BATCH_RENAME, COMPARE_DIRECTORIES, COMPUTE_CHECKSUMS, FIND_DUPLICATES, SHOW_PROPERTIES, VERIFY_CHECKSUMS};
+use crate::models::AppState;
+
+/// 文件或目录的右键菜单
+pub fn file_context_menu(path: &Path) -> Menu<AppState> {
+    let mut menu = Menu::empty();
+    if path.is_dir() {
+        menu = menu
+            .entry(MenuItem::new("分析磁盘占用").command(ANALYZE_DISK_USAGE.with(path.to_path_buf())))
+            .entry(MenuItem::new("查找重复文件").command(FIND_DUPLICATES.with(path.to_path_buf())))
+            .entry(MenuItem::new("与其他目录比较...").command(COMPARE_DIRECTORIES.with(path.to_path_buf())));
+    } else {
+        // 右键点击已选中的文件时计算所有选中文件的校验和
+        let clicked = path.to_path_buf();
+        menu = menu.entry(MenuItem::new("计算校验和").on_activate(move |ctx, data: &mut AppState, _env| {
+            ctx.submit_command(COMPUTE_CHECKSUMS.with(data.selection_for(&clicked)));
+        }));
+        if is_checksum_list(path) {
+            menu = menu.entry(MenuItem::new("校验此校验和文件").command(VERIFY_CHECKSUMS.with(path.to_path_buf())));
+        }
+    }
+    // 右键点击已选中的项时重命名所有选中的项
+    let clicked = path.to_path_buf();
+    menu = menu.entry(MenuItem::new("批量重命名").on_activate(move |ctx, data: &mut AppState, _env| {
+        ctx.submit_command(BATCH_RENAME.with(data.selection_for(&clicked)));
+    }));
+    menu.entry(MenuItem::new("属性").command(SHOW_PROPERTIES.with(path.to_path_buf())))
+}
+
+/// 文件名看起来是否是校验和文件（如 *.sha256、SHA256SUMS、SHASUMS）
+fn is_checksum_list(path: &Path) -> bool {
+    let name = path.file_name().map(|name| name.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let extension = path.extension().map(|ext| ext.to_string_lossy().to_lowercase()).unwrap_or_default();
+    name.contains("sums")
+        || ["md5", "sha1", "sha256", "b3"].contains(&extension.as_str())
+}
+
+/// 右键点击时显示文件右键菜单的控制器
+///
+/// 文件列表行和目录树节点的数据类型不同，由 `path_of` 取出对应的路径。
+pub struct ContextMenuController<T> {
+    path_of: fn(&T) -> PathBuf,
+}
+
+impl<T> ContextMenuController<T> {
+    pub fn new(path_of: fn(&T) -> PathBuf) -> Self {
+        Self { path_of }
+    }
+}
+
+impl<T, W: Widget<T>> Controller<T, W> for ContextMenuController<T> {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        if let Event::MouseDown(mouse) = event {
+            if mouse.button.is_right() {
+                // "我的电脑"等虚拟节点没有对应的文件，不显示菜单
+                let path = (self.path_of)(data);
+                if path.exists() {
+                    ctx.show_context_menu(file_context_menu(&path), mouse.window_pos);
+                    ctx.set_handled();
+                    return;
+                }
+            }
+        }
+        child.event(ctx, event, data, env);
+    }
+}
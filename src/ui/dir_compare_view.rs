@@ -0,0 +1,226 @@
+use druid::widget::{
+    Button, Checkbox, Controller, CrossAxisAlignment, Flex, Label, LineBreaking, Painter, RadioGroup, Scroll,
+    SizedBox, TextBox
+};
+use druid::{commands, Color, Env, Event, EventCtx, RenderContext, Widget, WidgetExt};
+use druid_widget_nursery::Tree;
+use crate::commands::{COPY_DIFFERENCES, SELECT_COMPARE_ENTRY, START_DIR_COMPARE};
+use crate::dir_compare::{CompareFilter, CompareMode, CopyDirection, DiffStatus, DirCompareState};
+use crate::models::{AppState, FileItem};
+use crate::utils::format_thousands;
+use super::constants::{DIFF_CHANGED_COLOR, DIFF_ONLY_LEFT_COLOR, DIFF_ONLY_RIGHT_COLOR};
+use super::job_status::build_job_status;
+use super::theme;
+
+/// 构建目录比较窗口
+pub fn build_dir_compare_window() -> impl Widget<AppState> {
+    theme::themed(build_dir_compare_view())
+}
+
+/// 构建目录比较窗口的内容：两侧目录、比较方式、合并的结果树、过滤和复制
+fn build_dir_compare_view() -> impl Widget<AppState> {
+    let inputs = Flex::column()
+        .with_child(input_row("左侧", TextBox::new().lens(DirCompareState::left).expand_width()))
+        .with_child(input_row("右侧", TextBox::new().lens(DirCompareState::right).expand_width()))
+        .with_child(
+            Flex::row()
+                .with_child(label("比较方式").fix_width(70.0))
+                .with_child(
+                    RadioGroup::row(vec![
+                        ("大小和修改时间", CompareMode::SizeAndTime),
+                        ("文件内容", CompareMode::Content),
+                    ])
+                    .lens(DirCompareState::mode)
+                )
+                .with_flex_spacer(1.0)
+                .with_child(Button::new("比较").on_click(|ctx, _data: &mut DirCompareState, _env| {
+                    ctx.submit_command(START_DIR_COMPARE);
+                }))
+        );
+
+    let summary = Label::dynamic(|data: &DirCompareState, _| {
+        if data.result.is_none() {
+            return String::new();
+        }
+        let (identical, different, only_left, only_right) = data.counts();
+        format!(
+            "{} 个相同，{} 个不同，{} 个仅在左侧，{} 个仅在右侧",
+            format_thousands(identical as u64),
+            format_thousands(different as u64),
+            format_thousands(only_left as u64),
+            format_thousands(only_right as u64),
+        )
+    })
+    .with_text_color(theme::TEXT_COLOR)
+    .with_text_size(13.0);
+
+    let filters = Flex::row()
+        .with_child(label("显示"))
+        .with_spacer(8.0)
+        .with_child(Checkbox::new("相同").lens(CompareFilter::identical))
+        .with_spacer(8.0)
+        .with_child(Checkbox::new("不同").lens(CompareFilter::different))
+        .with_spacer(8.0)
+        .with_child(Checkbox::new("仅左侧").lens(CompareFilter::only_left))
+        .with_spacer(8.0)
+        .with_child(Checkbox::new("仅右侧").lens(CompareFilter::only_right))
+        .lens(DirCompareState::filter);
+
+    let tree = Scroll::new(build_result_tree().lens(DirCompareState::tree).padding(6.0))
+        .vertical()
+        .background(theme::TREE_BACKGROUND)
+        .expand();
+
+    let copy = Flex::row()
+        .with_child(
+            Label::dynamic(|data: &DirCompareState, _| match data.selected_item() {
+                Some(item) if item.path.as_os_str().is_empty() => "选中：全部".to_string(),
+                Some(item) => format!("选中：{}", item.path.display()),
+                None => "选中一项后可复制其中不同的文件".to_string(),
+            })
+            .with_text_color(theme::SECONDARY_TEXT_COLOR)
+            .with_text_size(12.0)
+        )
+        .with_flex_spacer(1.0)
+        .with_child(copy_button("复制到右侧 →", CopyDirection::LeftToRight))
+        .with_spacer(8.0)
+        .with_child(copy_button("← 复制到左侧", CopyDirection::RightToLeft));
+
+    let notice = Label::dynamic(|data: &DirCompareState, _| data.notice.clone())
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .with_text_color(theme::SECONDARY_TEXT_COLOR)
+        .with_text_size(12.0);
+
+    let state = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(inputs)
+        .with_spacer(8.0)
+        .with_child(summary)
+        .with_spacer(4.0)
+        .with_child(filters)
+        .with_spacer(6.0)
+        .with_flex_child(tree, 1.0)
+        .with_spacer(6.0)
+        .with_child(copy)
+        .with_child(notice.expand_width())
+        .controller(FilterController)
+        .lens(AppState::dir_compare);
+
+    let buttons = Flex::row()
+        .with_flex_spacer(1.0)
+        .with_child(Button::new("关闭").on_click(|ctx, _data: &mut AppState, _env| {
+            ctx.submit_command(commands::CLOSE_WINDOW.to(ctx.window_id()));
+        }));
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_flex_child(state, 1.0)
+        .with_spacer(6.0)
+        .with_child(build_job_status(|data| data.dir_compare.copy_job_id.or(data.dir_compare.job_id)))
+        .with_spacer(6.0)
+        .with_child(buttons)
+        .padding(12.0)
+        .background(theme::BACKGROUND)
+        .expand()
+}
+
+/// 合并的比较结果树：每行为展开标记、状态标记、名称和状态
+fn build_result_tree() -> impl Widget<FileItem> {
+    Tree::new(
+        || {
+            let expander = Label::dynamic(|item: &FileItem, _| {
+                match (item.children.is_empty(), item.is_expanded) {
+                    (true, _) => String::new(),
+                    (false, true) => "▾".to_string(),
+                    (false, false) => "▸".to_string(),
+                }
+            })
+            .with_text_color(theme::ICON_COLOR)
+            .fix_width(16.0)
+            .on_click(|_ctx, item: &mut FileItem, _env| {
+                if !item.children.is_empty() {
+                    item.is_expanded = !item.is_expanded;
+                }
+            });
+
+            let marker = Painter::new(|ctx, item: &FileItem, env| {
+                let rect = ctx.size().to_rect().inset(-3.0);
+                ctx.fill(rect.to_rounded_rect(2.0), &status_color(item.diff, env));
+            })
+            .fix_size(12.0, 12.0);
+
+            let name = Label::dynamic(|item: &FileItem, _| item.name.clone())
+                .with_line_break_mode(LineBreaking::Clip)
+                .with_text_color(theme::TEXT_COLOR)
+                .with_text_size(13.0)
+                .expand_width();
+
+            let status = Label::dynamic(|item: &FileItem, _| item.diff.map(DiffStatus::name).unwrap_or_default().to_string())
+                .with_text_color(theme::SECONDARY_TEXT_COLOR)
+                .with_text_size(12.0);
+
+            Flex::row()
+                .with_child(expander)
+                .with_child(marker)
+                .with_child(SizedBox::empty().fix_width(6.0))
+                .with_flex_child(name, 1.0)
+                .with_child(status)
+                .padding((0.0, 2.0))
+                .background(Painter::new(|ctx, item: &FileItem, env| {
+                    if item.is_selected {
+                        let rect = ctx.size().to_rect();
+                        ctx.fill(rect, &env.get(theme::SELECTION_BACKGROUND));
+                    }
+                }))
+                .on_click(|ctx, item: &mut FileItem, _env| {
+                    ctx.submit_command(SELECT_COMPARE_ENTRY.with(item.path.clone()));
+                })
+        },
+        FileItem::is_expanded,
+    )
+}
+
+/// 状态标记的颜色
+fn status_color(status: Option<DiffStatus>, env: &Env) -> Color {
+    match status {
+        Some(DiffStatus::Different) => DIFF_CHANGED_COLOR,
+        Some(DiffStatus::OnlyLeft) => DIFF_ONLY_LEFT_COLOR,
+        Some(DiffStatus::OnlyRight) => DIFF_ONLY_RIGHT_COLOR,
+        Some(DiffStatus::Identical) | None => env.get(theme::SECONDARY_TEXT_COLOR).with_alpha(0.4),
+    }
+}
+
+fn copy_button(text: &str, direction: CopyDirection) -> impl Widget<DirCompareState> {
+    Button::new(text)
+        .on_click(move |ctx, _data: &mut DirCompareState, _env| {
+            ctx.submit_command(COPY_DIFFERENCES.with(direction));
+        })
+        .disabled_if(|data: &DirCompareState, _env| data.selected_item().is_none())
+}
+
+fn input_row(name: &str, editor: impl Widget<DirCompareState> + 'static) -> impl Widget<DirCompareState> {
+    Flex::row()
+        .with_child(label(name).fix_width(70.0))
+        .with_flex_child(editor, 1.0)
+        .padding((0.0, 3.0))
+}
+
+fn label<T: druid::Data>(text: &str) -> Label<T> {
+    Label::new(text)
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(13.0)
+}
+
+/// 过滤条件变化时重新生成显示的结果
+struct FilterController;
+
+impl<W: Widget<DirCompareState>> Controller<DirCompareState, W> for FilterController {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut DirCompareState, env: &Env) {
+        let old_filter = data.filter.clone();
+        child.event(ctx, event, data, env);
+
+        if data.filter != old_filter {
+            data.apply_filter();
+        }
+    }
+}
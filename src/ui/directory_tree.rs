@@ -1,9 +1,16 @@
-use druid::widget::{Flex, Label, Scroll, Container, Painter, SizedBox, CrossAxisAlignment};
-use druid::{Widget, WidgetExt, RenderContext, Rect, Point, Color};
+use druid::widget::{Flex, Label, Scroll, Container, Painter, SizedBox, CrossAxisAlignment, Either};
+use druid::{Widget, WidgetExt, RenderContext, Rect, Point};
 use druid_widget_nursery::Tree;
+use crate::locations::is_recent_section;
 use crate::models::{AppState, FileItem};
 use crate::{SELECT_DIRECTORY, LOAD_SUBDIRECTORIES};
-use super::constants::*;
+use crate::commands::SHOW_DRIVE_OVERVIEW;
+use super::context_menu::ContextMenuController;
+use super::constants::HIDDEN_ITEM_ALPHA;
+use super::drive_overview::paint_usage_bar;
+use super::drag_drop::{expand_tree_node, DragSource, DropTarget};
+use super::keyboard_nav::{ScrollToFocus, TREE_HAS_FOCUS};
+use super::theme;
 
 /// 构建目录树视图（左侧面板）
 pub fn build_directory_tree() -> impl Widget<AppState> {
@@ -19,13 +26,15 @@ pub fn build_directory_tree() -> impl Widget<AppState> {
                 
             // 添加展开/折叠图标和文件夹图标
             let row = row.with_child(
-                Painter::new(|ctx, item: &FileItem, _env| {
+                Painter::new(|ctx, item: &FileItem, env| {
                     if item.name == "我的电脑" {
                         println!("绘制我的电脑图标, 展开状态: {}, 子项数: {}", 
                                 item.is_expanded, item.children.len());
                     }
                     
                     // 绘制简单的文件夹图标
+                    let folder_color = env.get(theme::FOLDER_COLOR);
+                    let icon_color = env.get(theme::ICON_COLOR);
                     let rect = ctx.size().to_rect();
                     let icon_size = rect.size();
                     
@@ -37,14 +46,14 @@ pub fn build_directory_tree() -> impl Widget<AppState> {
                         Point::new(rect.x0 + 1.0, center_y - 5.0),
                         (icon_size.width - 2.0, 10.0)
                     );
-                    ctx.fill(folder_bottom, &FOLDER_COLOR);
+                    ctx.fill(folder_bottom, &folder_color);
                     
                     // 文件夹顶部 - 调整垂直位置
                     let folder_top = Rect::from_origin_size(
                         Point::new(rect.x0 + 1.0, center_y - 8.0),
                         (icon_size.width * 0.6, 3.0)
                     );
-                    ctx.fill(folder_top, &FOLDER_COLOR);
+                    ctx.fill(folder_top, &folder_color);
                     
                     // 展开/折叠标记 - 调整垂直位置
                     if item.is_expanded {
@@ -52,19 +61,19 @@ pub fn build_directory_tree() -> impl Widget<AppState> {
                             Point::new(rect.x0 + 5.0, center_y),
                             (6.0, 2.0)
                         );
-                        ctx.fill(mark, &ICON_COLOR);
+                        ctx.fill(mark, &icon_color);
                     } else {
                         let mark_h = Rect::from_origin_size(
                             Point::new(rect.x0 + 5.0, center_y),
                             (6.0, 2.0)
                         );
-                        ctx.fill(mark_h, &ICON_COLOR);
+                        ctx.fill(mark_h, &icon_color);
                         
                         let mark_v = Rect::from_origin_size(
                             Point::new(rect.x0 + 7.0, center_y - 3.0),
                             (2.0, 6.0)
                         );
-                        ctx.fill(mark_v, &ICON_COLOR);
+                        ctx.fill(mark_v, &icon_color);
                     }
                 })
                 .fix_size(16.0, 16.0)
@@ -99,21 +108,60 @@ pub fn build_directory_tree() -> impl Widget<AppState> {
             );
             
             // 添加目录名标签
-            let row = row.with_child(
+            let row = row.with_flex_child(
                 Label::dynamic(|item: &FileItem, _| item.name.clone())
-                .with_text_color(SELECTED_TEXT) // 统一使用亮色文本，与深色背景形成对比
+                .with_text_color(theme::TEXT_COLOR) // 使用主题的文本颜色，与背景形成对比
                 .with_text_size(14.0) // 明确设置字体大小
+                // 隐藏目录与文件列表中的隐藏文件一样淡化显示
+                .env_scope(|env, item: &FileItem| {
+                    if item.is_hidden {
+                        let color = env.get(theme::TEXT_COLOR).with_alpha(HIDDEN_ITEM_ALPHA);
+                        env.set(theme::TEXT_COLOR, color);
+                    }
+                })
                 .padding((8.0, 0.0)) // 从4.0增加到8.0，增加文本与周围元素的间距
-                .expand_width() // 让标签占据所有可用宽度
+                .expand_width(), // 让标签占据除驱动器信息外的所有可用宽度
+                1.0,
+            );
+
+            // 驱动器节点显示文件系统类型、使用情况和使用条
+            let row = row.with_child(
+                Label::dynamic(|item: &FileItem, _| {
+                    item.volume.as_ref().map(|volume| volume.summary()).unwrap_or_default()
+                })
+                .with_text_color(theme::SECONDARY_TEXT_COLOR)
+                .with_text_size(12.0)
+                .padding((0.0, 0.0, 8.0, 0.0))
+            );
+            let row = row.with_child(
+                Either::new(
+                    |item: &FileItem, _| item.volume.as_ref().is_some_and(|volume| volume.total > 0),
+                    Painter::new(|ctx, item: &FileItem, env| {
+                        if let Some(volume) = &item.volume {
+                            paint_usage_bar(ctx, volume, env);
+                        }
+                    })
+                    .fix_size(60.0, 6.0)
+                    .padding((0.0, 0.0, 8.0, 0.0)),
+                    SizedBox::empty(),
+                )
             );
             
             // 整个行加上点击事件和背景
-            row.on_click(|ctx, data: &mut FileItem, _| {
-                // 处理我的电脑节点
+            let row = row.on_click(|ctx, data: &mut FileItem, _| {
+                // "最近访问"分组只用于展开和折叠，不对应可以打开的目录
+                if is_recent_section(data) {
+                    data.is_expanded = !data.is_expanded;
+                    return;
+                }
+
+                // 处理我的电脑节点：保持展开，右侧显示驱动器概览
                 if data.name == "我的电脑" {
                     // 确保我的电脑节点始终处于展开状态
                     data.is_expanded = true;
                     println!("点击我的电脑标签 - 保持展开状态");
+                    ctx.submit_command(SHOW_DRIVE_OVERVIEW);
+                    return;
                 }
                 
                 // 获取当前点击的目录路径
@@ -133,17 +181,33 @@ pub fn build_directory_tree() -> impl Widget<AppState> {
                 ctx.submit_command(SELECT_DIRECTORY.with(path));
             })
             .background(
-                Painter::new(|ctx, item: &FileItem, _env| {
+                Painter::new(|ctx, item: &FileItem, env| {
                     let rect = ctx.size().to_rect();
                     
                     if item.is_selected {
                         // 使用选中背景色
-                        ctx.fill(rect, &SELECTED_COLOR);
+                        ctx.fill(rect, &env.get(theme::SELECTION_BACKGROUND));
+                    }
+
+                    // 目录树拥有键盘焦点时为焦点所在的节点绘制焦点框；
+                    // 目录树中方向键移动的就是选择，焦点所在的节点即选中的节点
+                    let focused = item.is_selected && env.try_get(TREE_HAS_FOCUS).unwrap_or(false);
+                    if focused {
+                        ctx.stroke(rect.inset(-1.0), &env.get(theme::FOCUS_RING), 1.5);
                     }
                 })
             )
             .expand_width()
             .fix_height(36.0) // 从32.0增加到36.0，增加每一行的高度
+            // 右键显示属性等菜单
+            .controller(ContextMenuController::new(|item: &FileItem| item.path.clone()))
+            .controller(DragSource::new(|item: &FileItem| item.path.clone()))
+            // 用键盘选中节点时滚动到该节点
+            .controller(ScrollToFocus::new(|item: &FileItem| item.is_selected));
+
+            // 可以拖动目录树节点，也可以将文件拖放到节点上；拖动时停留在折叠的节点上会自动展开
+            DropTarget::new(row, |item: &FileItem| Some(item.path.clone()))
+                .on_hold(expand_tree_node)
         },
         FileItem::is_expanded,
     )
@@ -152,7 +216,7 @@ pub fn build_directory_tree() -> impl Widget<AppState> {
     // 使用Container包装Tree控件，添加内边距和背景色
     let tree_with_padding = Container::new(tree)
         .padding(10.0) // 保持外边距
-        .background(Color::rgb8(40, 44, 52))
+        .background(theme::TREE_BACKGROUND)
         .expand_height();
 
     // 使用Scroll包装带边距的树形控件，使其可滚动
@@ -0,0 +1,441 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use druid::piet::{Text, TextLayoutBuilder};
+use druid::widget::{Button, CrossAxisAlignment, Flex, Label, LineBreaking, List, Scroll, Split};
+use druid::{
+    BoxConstraints, Color, Env, Event, EventCtx, FontFamily, LayoutCtx, LifeCycle, LifeCycleCtx,
+    PaintCtx, Point, Rect, RenderContext, Size, UpdateCtx, Widget, WidgetExt, commands
+};
+use crate::disk_usage::{DiskUsageState, LargeFile, UsageKind, UsageNode};
+use crate::file_types::{self, FileKind};
+use crate::models::AppState;
+use crate::utils::{format_size, format_thousands};
+use crate::SELECT_DIRECTORY;
+use super::job_status::build_job_status;
+use super::theme;
+
+/// 目录块标题栏的高度
+const HEADER_HEIGHT: f64 = 16.0;
+
+/// 目录块足够大时才在其中绘制下一层
+const MIN_NESTED_SIZE: Size = Size::new(40.0, 32.0);
+
+/// 小于该面积的块不绘制
+const MIN_BLOCK_AREA: f64 = 4.0;
+
+/// 扩展名统计中显示的条目数量
+const MAX_EXTENSIONS: usize = 20;
+
+/// 构建磁盘占用分析窗口
+pub fn build_disk_usage_window() -> impl Widget<AppState> {
+    theme::themed(build_disk_usage_view())
+}
+
+/// 构建磁盘占用分析窗口的内容：左侧矩形树图，右侧最大文件和扩展名统计
+fn build_disk_usage_view() -> impl Widget<AppState> {
+    let toolbar = Flex::row()
+        .with_child(Button::new("上一级").on_click(|_ctx, data: &mut DiskUsageState, _env| {
+            data.focus_parent();
+        }))
+        .with_spacer(8.0)
+        .with_flex_child(
+            Label::dynamic(|data: &DiskUsageState, _| focus_summary(data))
+                .with_text_color(theme::TEXT_COLOR)
+                .with_text_size(14.0)
+                .expand_width(),
+            1.0,
+        )
+        .lens(AppState::disk_usage);
+
+    let treemap = Flex::column()
+        .with_flex_child(Treemap::new().expand(), 1.0)
+        .with_spacer(4.0)
+        .with_child(
+            Label::dynamic(|data: &DiskUsageState, _| data.hovered.clone())
+                .with_text_color(theme::SECONDARY_TEXT_COLOR)
+                .with_text_size(12.0)
+                .expand_width()
+        )
+        .lens(AppState::disk_usage);
+
+    let side = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(section_title("最大的文件"))
+        .with_flex_child(
+            Scroll::new(List::new(large_file_row).lens(DiskUsageState::largest_files))
+                .vertical()
+                .expand(),
+            1.0,
+        )
+        .with_spacer(8.0)
+        .with_child(section_title("按扩展名统计"))
+        .with_flex_child(
+            Scroll::new(
+                Label::dynamic(|data: &DiskUsageState, _| extension_summary(data))
+                    .with_text_color(theme::TEXT_COLOR)
+                    .with_text_size(12.0)
+            )
+            .vertical()
+            .expand(),
+            1.0,
+        )
+        .padding((8.0, 0.0, 0.0, 0.0))
+        .lens(AppState::disk_usage);
+
+    let split = Split::columns(treemap, side)
+        .split_point(0.68)
+        .draggable(true)
+        .solid_bar(true);
+
+    let buttons = Flex::row()
+        .with_flex_child(build_job_status(|data| data.disk_usage.job_id).expand_width(), 1.0)
+        .with_spacer(8.0)
+        .with_child(Button::new("关闭").on_click(|ctx, _data: &mut AppState, _env| {
+            ctx.submit_command(commands::CLOSE_WINDOW.to(ctx.window_id()));
+        }));
+
+    Flex::column()
+        .with_child(toolbar)
+        .with_spacer(8.0)
+        .with_flex_child(split, 1.0)
+        .with_spacer(8.0)
+        .with_child(buttons)
+        .padding(12.0)
+        .background(theme::BACKGROUND)
+        .expand()
+}
+
+/// 分区标题
+fn section_title<T: druid::Data>(title: &str) -> impl Widget<T> {
+    Label::new(title)
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(15.0)
+        .padding((0.0, 4.0))
+}
+
+/// 当前显示的目录及其总大小
+fn focus_summary(data: &DiskUsageState) -> String {
+    match data.focus_node() {
+        Some(node) => format!(
+            "{}  {}，{} 个文件",
+            node.path.display(),
+            format_size(node.size),
+            format_thousands(node.file_count),
+        ),
+        None => data.root.display().to_string(),
+    }
+}
+
+/// 最大文件列表中的一行，点击后在资源管理器中打开所在目录
+fn large_file_row() -> impl Widget<LargeFile> {
+    Label::dynamic(|file: &LargeFile, _| format!("{}  {}", format_size(file.size), file.path.display()))
+        .with_line_break_mode(LineBreaking::Clip)
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(12.0)
+        .padding((0.0, 2.0))
+        .expand_width()
+        .on_click(|ctx, file: &mut LargeFile, _env| {
+            if let Some(parent) = file.path.parent() {
+                ctx.submit_command(SELECT_DIRECTORY.with(parent.to_path_buf()));
+            }
+        })
+}
+
+/// 按扩展名统计的占用
+fn extension_summary(data: &DiskUsageState) -> String {
+    let report = match &data.report {
+        Some(report) => report,
+        None => return String::new(),
+    };
+
+    let mut lines: Vec<String> = report.extensions
+        .iter()
+        .take(MAX_EXTENSIONS)
+        .map(|total| {
+            let name = if total.extension.is_empty() { "(无扩展名)".to_string() } else { format!(".{}", total.extension) };
+            format!("{}  {}，{} 个文件", name, format_size(total.bytes), format_thousands(total.count))
+        })
+        .collect();
+    if report.error_count > 0 {
+        lines.push(format!("{} 个目录无法读取，结果偏小", report.error_count));
+    }
+    lines.join("\n")
+}
+
+/// 矩形树图中的一个块
+struct Block {
+    rect: Rect,
+    path: PathBuf,
+    name: String,
+    size: u64,
+    file_count: u64,
+    kind: UsageKind,
+    /// 文件的类型，用于取颜色
+    file_kind: Option<FileKind>,
+    /// 嵌套深度（0为当前目录的直接子节点）
+    depth: usize,
+}
+
+/// 矩形树图：显示当前目录中各个子节点的占用，目录块中再嵌套显示一层
+///
+/// 点击目录块进入该目录并在资源管理器中打开，点击文件块打开所在目录，右键返回上一级。
+struct Treemap {
+    blocks: Vec<Block>,
+    /// 计算布局时显示的目录和控件大小，变化时重新计算
+    layout_key: Option<(PathBuf, Size)>,
+    hovered: Option<usize>,
+}
+
+impl Treemap {
+    fn new() -> Self {
+        Self {
+            blocks: Vec::new(),
+            layout_key: None,
+            hovered: None,
+        }
+    }
+
+    /// 计算当前目录的块布局
+    fn compute_blocks(&mut self, data: &DiskUsageState, size: Size) {
+        self.blocks.clear();
+        self.hovered = None;
+        if let Some(node) = data.focus_node() {
+            self.add_children(node, size.to_rect(), 0);
+        }
+    }
+
+    fn add_children(&mut self, node: &UsageNode, area: Rect, depth: usize) {
+        let children: Vec<&UsageNode> = node.children.iter().filter(|child| child.size > 0).collect();
+        let sizes: Vec<u64> = children.iter().map(|child| child.size).collect();
+
+        for (child, rect) in children.into_iter().zip(squarify(&sizes, area)) {
+            let rect = rect.inset(-1.0);
+            if rect.area() < MIN_BLOCK_AREA {
+                continue;
+            }
+
+            self.blocks.push(Block {
+                rect,
+                path: child.path.clone(),
+                name: child.name.clone(),
+                size: child.size,
+                file_count: child.file_count,
+                kind: child.kind,
+                file_kind: (child.kind == UsageKind::File).then(|| file_types::kind_for_path(&child.path, false)),
+                depth,
+            });
+
+            let nested = child.kind == UsageKind::Directory
+                && depth == 0
+                && rect.width() >= MIN_NESTED_SIZE.width
+                && rect.height() >= MIN_NESTED_SIZE.height;
+            if nested {
+                let inner = Rect::new(rect.x0 + 2.0, rect.y0 + HEADER_HEIGHT, rect.x1 - 2.0, rect.y1 - 2.0);
+                self.add_children(child, inner, depth + 1);
+            }
+        }
+    }
+
+    /// 鼠标位置下最内层的块
+    fn hit_test(&self, pos: Point) -> Option<usize> {
+        self.blocks.iter().rposition(|block| block.rect.contains(pos))
+    }
+
+    /// 鼠标所在块的说明
+    fn describe(block: &Block) -> String {
+        match block.kind {
+            UsageKind::File => format!("{}  {}", block.path.display(), format_size(block.size)),
+            _ => format!(
+                "{}  {}，{} 个文件",
+                if block.kind == UsageKind::Others { block.name.clone() } else { block.path.display().to_string() },
+                format_size(block.size),
+                format_thousands(block.file_count),
+            ),
+        }
+    }
+
+    /// 块的填充颜色：目录使用文件夹颜色，文件使用其类型的颜色
+    fn block_color(block: &Block, env: &Env, colors: &mut HashMap<String, Color>) -> Color {
+        match (&block.kind, &block.file_kind) {
+            (UsageKind::File, Some(kind)) => colors
+                .entry(kind.type_id.clone())
+                .or_insert_with(|| theme::file_color(env, kind))
+                .clone(),
+            (UsageKind::Directory, _) => env.get(theme::FOLDER_COLOR).with_alpha(0.35),
+            _ => env.get(theme::SECONDARY_TEXT_COLOR).with_alpha(0.5),
+        }
+    }
+}
+
+impl Widget<DiskUsageState> for Treemap {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut DiskUsageState, _env: &Env) {
+        match event {
+            Event::MouseMove(mouse) => {
+                let hovered = self.hit_test(mouse.pos);
+                if hovered != self.hovered {
+                    self.hovered = hovered;
+                    data.hovered = hovered.map(|index| Self::describe(&self.blocks[index])).unwrap_or_default();
+                    ctx.request_paint();
+                }
+            }
+            Event::MouseDown(mouse) if mouse.button.is_right() => {
+                data.focus_parent();
+            }
+            Event::MouseDown(mouse) if mouse.button.is_left() => {
+                let block = match self.hit_test(mouse.pos) {
+                    Some(index) => &self.blocks[index],
+                    None => return,
+                };
+
+                let directory = match block.kind {
+                    UsageKind::Directory => {
+                        data.focus = block.path.clone();
+                        Some(block.path.clone())
+                    }
+                    UsageKind::Others => Some(block.path.clone()),
+                    UsageKind::File => block.path.parent().map(Path::to_path_buf),
+                };
+                if let Some(directory) = directory {
+                    ctx.submit_command(SELECT_DIRECTORY.with(directory));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, _data: &DiskUsageState, _env: &Env) {
+        // 鼠标离开时取消高亮
+        if let LifeCycle::HotChanged(false) = event {
+            if self.hovered.take().is_some() {
+                ctx.request_paint();
+            }
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &DiskUsageState, data: &DiskUsageState, _env: &Env) {
+        if !old_data.report.same(&data.report) || old_data.focus != data.focus {
+            self.layout_key = None;
+            ctx.request_layout();
+        }
+    }
+
+    fn layout(&mut self, _ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &DiskUsageState, _env: &Env) -> Size {
+        let size = bc.constrain(bc.max());
+        let key = (data.focus.clone(), size);
+        if self.layout_key.as_ref() != Some(&key) {
+            self.compute_blocks(data, size);
+            self.layout_key = Some(key);
+        }
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &DiskUsageState, env: &Env) {
+        let area = ctx.size().to_rect();
+        ctx.fill(area, &env.get(theme::LIST_BACKGROUND));
+
+        if data.report.is_none() {
+            let layout = ctx.text()
+                .new_text_layout("正在扫描...")
+                .font(FontFamily::SYSTEM_UI, 14.0)
+                .text_color(env.get(theme::SECONDARY_TEXT_COLOR))
+                .build();
+            if let Ok(layout) = layout {
+                ctx.draw_text(&layout, Point::new(12.0, 12.0));
+            }
+            return;
+        }
+
+        let border = env.get(theme::BACKGROUND);
+        let text_color = env.get(theme::TEXT_COLOR);
+        let mut colors = HashMap::new();
+
+        for (index, block) in self.blocks.iter().enumerate() {
+            ctx.fill(block.rect, &Self::block_color(block, env, &mut colors));
+            ctx.stroke(block.rect, &border, 1.0);
+
+            if Some(index) == self.hovered {
+                ctx.stroke(block.rect.inset(-1.0), &text_color, 2.0);
+            }
+
+            // 目录块在标题栏显示名称，其余块空间足够时显示名称
+            let show_name = block.rect.width() > 30.0 && block.rect.height() > 14.0
+                && (block.depth == 0 || block.kind != UsageKind::Directory);
+            if show_name {
+                let layout = ctx.text()
+                    .new_text_layout(format!("{} {}", block.name, format_size(block.size)))
+                    .font(FontFamily::SYSTEM_UI, 11.0)
+                    .text_color(text_color.clone())
+                    .build();
+                if let Ok(layout) = layout {
+                    let clip = block.rect.inset(-2.0);
+                    ctx.with_save(|ctx| {
+                        ctx.clip(clip);
+                        ctx.draw_text(&layout, clip.origin());
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// 按 squarified 算法将矩形划分为面积与 `sizes` 成比例的块
+///
+/// `sizes` 应从大到小排列且都大于0；返回的矩形与 `sizes` 一一对应。
+fn squarify(sizes: &[u64], area: Rect) -> Vec<Rect> {
+    let total: f64 = sizes.iter().map(|&size| size as f64).sum();
+    if total <= 0.0 || area.area() <= 0.0 {
+        return vec![Rect::ZERO; sizes.len()];
+    }
+
+    let scale = area.area() / total;
+    let areas: Vec<f64> = sizes.iter().map(|&size| size as f64 * scale).collect();
+    let mut rects = Vec::with_capacity(areas.len());
+    let mut remaining = area;
+    let mut start = 0;
+
+    while start < areas.len() {
+        // 沿较短的一边排列一行，只要加入下一个块能让最差的长宽比变好就继续加入
+        let side = remaining.width().min(remaining.height());
+        let mut end = start + 1;
+        while end < areas.len() && worst_ratio(&areas[start..=end], side) <= worst_ratio(&areas[start..end], side) {
+            end += 1;
+        }
+
+        let row = &areas[start..end];
+        let row_area: f64 = row.iter().sum();
+        if remaining.width() >= remaining.height() {
+            // 在左侧放一列
+            let width = row_area / remaining.height();
+            let mut y = remaining.y0;
+            for block_area in row {
+                let height = block_area / width;
+                rects.push(Rect::new(remaining.x0, y, remaining.x0 + width, y + height));
+                y += height;
+            }
+            remaining.x0 += width;
+        } else {
+            // 在顶部放一行
+            let height = row_area / remaining.width();
+            let mut x = remaining.x0;
+            for block_area in row {
+                let width = block_area / height;
+                rects.push(Rect::new(x, remaining.y0, x + width, remaining.y0 + height));
+                x += width;
+            }
+            remaining.y0 += height;
+        }
+        start = end;
+    }
+
+    rects
+}
+
+/// 一行块中最差（最大）的长宽比
+fn worst_ratio(row: &[f64], side: f64) -> f64 {
+    let sum: f64 = row.iter().sum();
+    let max = row.iter().cloned().fold(f64::MIN, f64::max);
+    let min = row.iter().cloned().fold(f64::MAX, f64::min);
+    let side2 = side * side;
+    let sum2 = sum * sum;
+    (side2 * max / sum2).max(sum2 / (side2 * min))
+}
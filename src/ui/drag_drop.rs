@@ -0,0 +1,221 @@
+use druid::widget::Controller;
+use druid::{
+    BoxConstraints, Cursor, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx, Point,
+    RenderContext, Size, TimerToken, UpdateCtx, Widget
+};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::commands::{DROP_FILES, END_DRAG, START_DRAG};
+use crate::file_ops::validate_drop;
+use crate::models::FileItem;
+use crate::LOAD_SUBDIRECTORIES;
+use super::theme;
+
+/// 鼠标按下后移动超过该距离才开始拖动，避免影响普通点击
+const DRAG_THRESHOLD: f64 = 6.0;
+
+/// 拖动时在折叠的目录树节点上停留多久后自动展开
+const EXPAND_DELAY: Duration = Duration::from_millis(700);
+
+lazy_static::lazy_static! {
+    /// 正在拖动的文件（没有拖动时为 None）
+    static ref DRAG_SOURCES: Mutex<Option<Vec<PathBuf>>> = Mutex::new(None);
+}
+
+/// 设置正在拖动的文件（由 `START_DRAG` 的处理展开为完整的选择）
+pub fn set_drag_sources(sources: Vec<PathBuf>) {
+    *DRAG_SOURCES.lock().unwrap() = Some(sources);
+}
+
+/// 结束拖动
+pub fn end_drag() {
+    *DRAG_SOURCES.lock().unwrap() = None;
+}
+
+/// 是否正在拖动
+pub fn is_dragging() -> bool {
+    DRAG_SOURCES.lock().unwrap().is_some()
+}
+
+fn drag_sources() -> Option<Vec<PathBuf>> {
+    DRAG_SOURCES.lock().unwrap().clone()
+}
+
+/// 拖动源控制器：按住左键移动一段距离后开始拖动该行对应的文件
+///
+/// 拖动在鼠标松开后通过 `END_DRAG` 命令结束，保证放下目标在同一个
+/// 鼠标事件中仍能读取到拖动的文件。
+pub struct DragSource<T> {
+    path_of: fn(&T) -> PathBuf,
+    press: Option<Point>,
+    dragging: bool,
+}
+
+impl<T> DragSource<T> {
+    pub fn new(path_of: fn(&T) -> PathBuf) -> Self {
+        Self {
+            path_of,
+            press: None,
+            dragging: false,
+        }
+    }
+}
+
+impl<T: Data, W: Widget<T>> Controller<T, W> for DragSource<T> {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        match event {
+            Event::MouseDown(mouse) if mouse.button.is_left() && mouse.count == 1 => {
+                self.press = Some(mouse.window_pos);
+                self.dragging = false;
+            }
+            Event::MouseMove(mouse) if mouse.buttons.has_left() => {
+                if let Some(press) = self.press {
+                    if !self.dragging && press.distance(mouse.window_pos) > DRAG_THRESHOLD {
+                        self.dragging = true;
+                        // 保持活动状态，拖出该行后仍能收到鼠标松开事件
+                        ctx.set_active(true);
+                        ctx.submit_command(START_DRAG.with((self.path_of)(data)));
+                    }
+                }
+            }
+            _ => {}
+        }
+        child.event(ctx, event, data, env);
+
+        if let Event::MouseUp(_) = event {
+            self.press = None;
+            if self.dragging {
+                self.dragging = false;
+                ctx.set_active(false);
+                ctx.submit_command(END_DRAG);
+            }
+        }
+    }
+}
+
+/// 放下目标：拖动经过时高亮显示，松开鼠标时移动或复制文件到对应的目录（按住 Ctrl 为复制）
+///
+/// 先交给子控件处理事件，内层目标处理过的鼠标移动不再处理，因此嵌套时只有最内层的目标高亮并接收文件。
+pub struct DropTarget<T, W> {
+    child: W,
+    /// 目标目录
+    path_of: fn(&T) -> Option<PathBuf>,
+    /// 拖动时停留一段时间后调用（用于展开目录树节点）
+    on_hold: Option<fn(&mut EventCtx, &mut T)>,
+    /// 拖动经过时目标是否有效（没有拖动经过时为 None）
+    hover: Option<bool>,
+    hold_timer: Option<TimerToken>,
+}
+
+impl<T, W: Widget<T>> DropTarget<T, W> {
+    pub fn new(child: W, path_of: fn(&T) -> Option<PathBuf>) -> Self {
+        Self {
+            child,
+            path_of,
+            on_hold: None,
+            hover: None,
+            hold_timer: None,
+        }
+    }
+
+    /// 设置拖动停留时的处理
+    pub fn on_hold(mut self, on_hold: fn(&mut EventCtx, &mut T)) -> Self {
+        self.on_hold = Some(on_hold);
+        self
+    }
+
+    fn set_hover(&mut self, ctx: &mut EventCtx, hover: Option<bool>) {
+        if hover == self.hover {
+            return;
+        }
+        self.hover = hover;
+        self.hold_timer = match (hover, self.on_hold) {
+            (Some(_), Some(_)) => Some(ctx.request_timer(EXPAND_DELAY)),
+            _ => None,
+        };
+        ctx.request_paint();
+    }
+}
+
+impl<T: Data, W: Widget<T>> Widget<T> for DropTarget<T, W> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        self.child.event(ctx, event, data, env);
+
+        match event {
+            Event::MouseMove(_) => {
+                let sources = drag_sources();
+                let hover = match sources {
+                    Some(sources) if ctx.is_hot() && !ctx.is_handled() => Some(
+                        (self.path_of)(data)
+                            .is_some_and(|target| validate_drop(&sources, &target).is_ok())
+                    ),
+                    _ => None,
+                };
+                if let Some(valid) = hover {
+                    ctx.set_cursor(if valid { &Cursor::Pointer } else { &Cursor::NotAllowed });
+                    ctx.set_handled();
+                }
+                self.set_hover(ctx, hover);
+            }
+            Event::MouseUp(mouse) if mouse.button.is_left() => {
+                // 不标记为已处理，拖动源仍需收到鼠标松开事件
+                if self.hover == Some(true) {
+                    if let (Some(sources), Some(target)) = (drag_sources(), (self.path_of)(data)) {
+                        ctx.submit_command(DROP_FILES.with((sources, target, mouse.mods.ctrl())));
+                    }
+                }
+                self.set_hover(ctx, None);
+            }
+            Event::Timer(token) if Some(*token) == self.hold_timer => {
+                self.hold_timer = None;
+                if let Some(on_hold) = self.on_hold {
+                    on_hold(ctx, data);
+                }
+                ctx.set_handled();
+            }
+            _ => {}
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        if let LifeCycle::HotChanged(false) = event {
+            if self.hover.take().is_some() {
+                self.hold_timer = None;
+                ctx.request_paint();
+            }
+        }
+        self.child.lifecycle(ctx, event, data, env);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        self.child.update(ctx, old_data, data, env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        self.child.layout(ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
+        self.child.paint(ctx, data, env);
+
+        if self.hover == Some(true) {
+            // 使用当前主题的选中颜色
+            let color = env.get(theme::SELECTION_BACKGROUND);
+            let rect = ctx.size().to_rect().inset(-1.0);
+            ctx.fill(rect, &color.with_alpha(0.25));
+            ctx.stroke(rect, &color, 2.0);
+        }
+    }
+}
+
+/// 拖动时停留在折叠的目录树节点上，展开该节点并在需要时加载子目录
+pub fn expand_tree_node(ctx: &mut EventCtx, item: &mut FileItem) {
+    if item.is_expanded {
+        return;
+    }
+    item.is_expanded = true;
+    if item.children.is_empty() {
+        ctx.submit_command(LOAD_SUBDIRECTORIES.with(item.path.clone()));
+    }
+}
@@ -0,0 +1,107 @@
+use druid::im::Vector;
+use druid::widget::{CrossAxisAlignment, Flex, Label, LineBreaking, List, Painter, Scroll};
+use druid::{Data, Env, Lens, PaintCtx, Point, Rect, RenderContext, Widget, WidgetExt};
+use crate::models::{AppState, FileItem};
+use crate::volumes::{Volume, USAGE_WARNING_RATIO};
+use crate::SELECT_DIRECTORY;
+use super::theme;
+
+/// 概览中使用条的宽度
+const OVERVIEW_BAR_WIDTH: f64 = 240.0;
+
+/// 绘制卷的使用条：底色为整个卷，前景为已用部分，快满时显示为警告颜色
+pub fn paint_usage_bar(ctx: &mut PaintCtx, volume: &Volume, env: &Env) {
+    let rect = ctx.size().to_rect();
+    ctx.fill(rect.to_rounded_rect(2.0), &env.get(theme::HEADER_BACKGROUND));
+
+    let ratio = volume.usage_ratio().clamp(0.0, 1.0);
+    if ratio > 0.0 {
+        let used = Rect::from_origin_size(Point::ORIGIN, (rect.width() * ratio, rect.height()));
+        let color = if ratio >= USAGE_WARNING_RATIO { theme::USAGE_WARNING_COLOR } else { theme::USAGE_BAR_COLOR };
+        ctx.fill(used.to_rounded_rect(2.0), &env.get(color));
+    }
+}
+
+/// 构建"我的电脑"的驱动器概览：每个驱动器显示卷标、文件系统类型、可用空间和使用条，点击打开
+pub fn build_drive_overview() -> impl Widget<AppState> {
+    let title = Label::new("我的电脑")
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(16.0)
+        .padding((12.0, 10.0));
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(title)
+        .with_flex_child(
+            Scroll::new(List::new(build_drive_row).lens(DrivesLens)).vertical().expand(),
+            1.0,
+        )
+        .background(theme::LIST_BACKGROUND)
+}
+
+/// 概览中的一个驱动器
+fn build_drive_row() -> impl Widget<FileItem> {
+    let name = Label::dynamic(|item: &FileItem, _| item.name.clone())
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(14.0);
+
+    let path = Label::dynamic(|item: &FileItem, _| {
+        match &item.volume {
+            Some(volume) if !volume.device.is_empty() && volume.device != item.name => {
+                format!("{}（{}）", item.path.display(), volume.device)
+            }
+            _ => item.path.display().to_string(),
+        }
+    })
+    .with_line_break_mode(LineBreaking::Clip)
+    .with_text_color(theme::SECONDARY_TEXT_COLOR)
+    .with_text_size(12.0);
+
+    let bar = Painter::new(|ctx, item: &FileItem, env| {
+        if let Some(volume) = &item.volume {
+            paint_usage_bar(ctx, volume, env);
+        }
+    })
+    .fix_size(OVERVIEW_BAR_WIDTH, 8.0);
+
+    let summary = Label::dynamic(|item: &FileItem, _| {
+        item.volume.as_ref().map(|volume| volume.summary()).unwrap_or_default()
+    })
+    .with_text_color(theme::SECONDARY_TEXT_COLOR)
+    .with_text_size(12.0);
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(name)
+        .with_child(path)
+        .with_spacer(4.0)
+        .with_child(bar)
+        .with_spacer(2.0)
+        .with_child(summary)
+        .padding((12.0, 8.0))
+        .expand_width()
+        .on_click(|ctx, item: &mut FileItem, _env| {
+            ctx.submit_command(SELECT_DIRECTORY.with(item.path.clone()));
+        })
+}
+
+/// 目录树中"我的电脑"下的驱动器（只读）
+#[derive(Clone, Data)]
+struct DrivesLens;
+
+impl Lens<AppState, Vector<FileItem>> for DrivesLens {
+    fn with<V, F: FnOnce(&Vector<FileItem>) -> V>(&self, data: &AppState, f: F) -> V {
+        f(&drives(data))
+    }
+
+    fn with_mut<V, F: FnOnce(&mut Vector<FileItem>) -> V>(&self, data: &mut AppState, f: F) -> V {
+        f(&mut drives(data))
+    }
+}
+
+fn drives(data: &AppState) -> Vector<FileItem> {
+    data.root.children.iter()
+        .find(|child| child.name == "我的电脑")
+        .map(|computer| computer.children.iter().cloned().collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,162 @@
+use druid::widget::{Button, Checkbox, CrossAxisAlignment, Flex, Label, LineBreaking, List, RadioGroup, Scroll};
+use druid::{commands, Widget, WidgetExt};
+use crate::commands::APPLY_DUPLICATE_ACTION;
+use crate::duplicates::{DuplicateAction, DuplicateFile, DuplicateGroup, DuplicateState, KeepRule};
+use crate::models::AppState;
+use crate::utils::{format_full_time, format_size, format_thousands};
+use super::job_status::build_job_status;
+use super::theme;
+
+/// 构建重复文件窗口
+pub fn build_duplicates_window() -> impl Widget<AppState> {
+    theme::themed(build_duplicates_view())
+}
+
+/// 构建重复文件窗口的内容：重复文件组列表、自动选择和对选中文件的操作
+fn build_duplicates_view() -> impl Widget<AppState> {
+    let summary = Label::dynamic(|data: &DuplicateState, _| scan_summary(data))
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(14.0);
+
+    let auto_select = Flex::row()
+        .with_child(label("自动选择时保留"))
+        .with_spacer(8.0)
+        .with_child(
+            RadioGroup::row(vec![
+                ("最新的", KeepRule::Newest),
+                ("最早的", KeepRule::Oldest),
+                ("路径最短的", KeepRule::ShortestPath),
+            ])
+            .lens(DuplicateState::keep_rule)
+        )
+        .with_spacer(8.0)
+        .with_child(Button::new("自动选择").on_click(|_ctx, data: &mut DuplicateState, _env| {
+            data.auto_select();
+        }));
+
+    let groups = Scroll::new(List::new(group_view).lens(DuplicateState::groups))
+        .vertical()
+        .expand();
+
+    let selection = Label::dynamic(|data: &DuplicateState, _| {
+        let (count, bytes) = data.selected_summary();
+        format!("已选择 {} 个文件，共 {}", format_thousands(count), format_size(bytes))
+    })
+    .with_text_color(theme::TEXT_COLOR)
+    .with_text_size(13.0);
+
+    let action = Flex::row()
+        .with_child(selection)
+        .with_flex_spacer(1.0)
+        .with_child(
+            RadioGroup::row(vec![
+                ("移到回收站", DuplicateAction::Trash),
+                ("替换为硬链接", DuplicateAction::HardLink),
+            ])
+            .lens(DuplicateState::action)
+        )
+        .with_spacer(8.0)
+        .with_child(Button::new("执行").on_click(|ctx, _data: &mut DuplicateState, _env| {
+            ctx.submit_command(APPLY_DUPLICATE_ACTION);
+        }));
+
+    let notice = Label::dynamic(|data: &DuplicateState, _| data.notice.clone())
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .with_text_color(theme::SECONDARY_TEXT_COLOR)
+        .with_text_size(12.0);
+
+    let state = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(summary.expand_width())
+        .with_spacer(6.0)
+        .with_child(auto_select)
+        .with_spacer(6.0)
+        .with_flex_child(groups, 1.0)
+        .with_spacer(6.0)
+        .with_child(action)
+        .with_child(notice.expand_width())
+        .lens(AppState::duplicates);
+
+    let buttons = Flex::row()
+        .with_flex_spacer(1.0)
+        .with_child(Button::new("关闭").on_click(|ctx, _data: &mut AppState, _env| {
+            ctx.submit_command(commands::CLOSE_WINDOW.to(ctx.window_id()));
+        }));
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_flex_child(state, 1.0)
+        .with_child(build_job_status(|data| data.duplicates.apply_job_id.or(data.duplicates.scan_job_id)))
+        .with_spacer(6.0)
+        .with_child(buttons)
+        .padding(12.0)
+        .background(theme::BACKGROUND)
+        .expand()
+}
+
+/// 查找结果的概要
+fn scan_summary(data: &DuplicateState) -> String {
+    if !data.finished {
+        return format!("正在查找 {} 中的重复文件...", data.root.display());
+    }
+    if data.groups.is_empty() {
+        return format!("{} 中没有重复文件", data.root.display());
+    }
+
+    let files: usize = data.groups.iter().map(|group| group.files.len()).sum();
+    format!(
+        "{} 中有 {} 组重复文件（共 {} 个），可释放 {}",
+        data.root.display(),
+        format_thousands(data.groups.len() as u64),
+        format_thousands(files as u64),
+        format_size(data.total_reclaimable()),
+    )
+}
+
+/// 一组重复文件：标题和每个文件的复选框
+fn group_view() -> impl Widget<DuplicateGroup> {
+    let title = Label::dynamic(|group: &DuplicateGroup, _| {
+        format!(
+            "{} 个相同的文件，每个 {}，可释放 {}",
+            group.files.len(),
+            format_size(group.size),
+            format_size(group.reclaimable()),
+        )
+    })
+    .with_text_color(theme::SECONDARY_TEXT_COLOR)
+    .with_text_size(12.0);
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(title)
+        .with_child(List::new(file_row).lens(DuplicateGroup::files))
+        .padding((0.0, 4.0, 0.0, 8.0))
+}
+
+/// 重复文件中的一行：选中复选框、路径和修改时间
+fn file_row() -> impl Widget<DuplicateFile> {
+    Flex::row()
+        .with_child(Checkbox::new("").lens(DuplicateFile::selected))
+        .with_flex_child(
+            Label::dynamic(|file: &DuplicateFile, _| file.path.display().to_string())
+                .with_line_break_mode(LineBreaking::Clip)
+                .with_text_color(theme::TEXT_COLOR)
+                .with_text_size(13.0)
+                .expand_width(),
+            1.0,
+        )
+        .with_child(
+            Label::dynamic(|file: &DuplicateFile, _| {
+                file.modified.map(format_full_time).unwrap_or_default()
+            })
+            .with_text_color(theme::SECONDARY_TEXT_COLOR)
+            .with_text_size(12.0)
+        )
+}
+
+fn label<T: druid::Data>(text: &str) -> Label<T> {
+    Label::new(text)
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(13.0)
+}
@@ -4,15 +4,23 @@ use druid::{
 };
 use crate::models::FileDetail;
 use crate::commands::{NAVIGATE_TO, RESET_CURSOR};
+use crate::ui::drag_drop::is_dragging;
 use std::time::Duration;
 
 /// 目录项控制器，处理悬停和点击事件
+///
+/// 鼠标松开时才进入目录，按下后拖动该行不会进入目录。
 pub struct DirectoryItemController;
 
 impl<W: Widget<FileDetail>> Controller<FileDetail, W> for DirectoryItemController {
     fn event(&mut self, child: &mut W, ctx: &mut druid::EventCtx, event: &Event, data: &mut FileDetail, env: &druid::Env) {
         match event {
             Event::MouseDown(mouse) if mouse.button.is_left() => {
+                ctx.set_active(true);
+            }
+            Event::MouseUp(mouse) if mouse.button.is_left() && ctx.is_active() && ctx.is_hot() && !is_dragging() => {
+                ctx.set_active(false);
+
                 // 导航到该目录
                 ctx.submit_command(Command::new(
                     NAVIGATE_TO,
@@ -28,6 +36,9 @@ impl<W: Widget<FileDetail>> Controller<FileDetail, W> for DirectoryItemControlle
                 
                 ctx.set_handled();
             }
+            Event::MouseUp(mouse) if mouse.button.is_left() && ctx.is_active() => {
+                ctx.set_active(false);
+            }
             Event::Timer(_) => {
                 // 定时器触发，重置光标
                 ctx.set_cursor(&Cursor::Arrow);
@@ -1,5 +1,7 @@
 mod directory_controller;
 mod item_controller;
+mod selection_controller;
 
 pub use directory_controller::DirectoryItemController;
-pub use item_controller::FileItemController; 
\ No newline at end of file
+pub use item_controller::FileItemController;
+pub use selection_controller::SelectionController; 
\ No newline at end of file
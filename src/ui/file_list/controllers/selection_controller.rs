@@ -0,0 +1,41 @@
+use druid::{
+    widget::Controller,
+    Widget, Event
+};
+use crate::models::{FileDetail, SelectionMode};
+use crate::commands::SELECT_FILE;
+
+/// 文件列表行的选择控制器
+///
+/// 单击文件选中该文件，Ctrl+点击切换选中状态，Shift+点击选中一个范围；
+/// 单击目录仍然进入目录，目录只能通过Ctrl或Shift选中。
+pub struct SelectionController;
+
+impl<W: Widget<FileDetail>> Controller<FileDetail, W> for SelectionController {
+    fn event(&mut self, child: &mut W, ctx: &mut druid::EventCtx, event: &Event, data: &mut FileDetail, env: &druid::Env) {
+        if let Event::MouseDown(mouse) = event {
+            if mouse.button.is_left() && mouse.count == 1 {
+                let mode = if mouse.mods.shift() {
+                    Some(SelectionMode::Range)
+                } else if mouse.mods.ctrl() || mouse.mods.meta() {
+                    Some(SelectionMode::Toggle)
+                } else if data.kind.is_directory() {
+                    None
+                } else {
+                    Some(SelectionMode::Replace)
+                };
+
+                if let Some(mode) = mode {
+                    ctx.submit_command(SELECT_FILE.with((data.full_path.clone(), mode)));
+
+                    // 带修饰键的点击只修改选择，不进入目录
+                    if mode != SelectionMode::Replace {
+                        ctx.set_handled();
+                        return;
+                    }
+                }
+            }
+        }
+        child.event(ctx, event, data, env);
+    }
+}
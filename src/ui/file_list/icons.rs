@@ -2,6 +2,7 @@ use druid::{
     widget::Painter,
     Color, RenderContext, Point, Rect, kurbo::BezPath, Widget, WidgetExt
 };
+use crate::file_types::IconKind;
 use crate::models::FileDetail;
 use std::boxed::Box;
 
@@ -34,8 +35,8 @@ pub fn create_folder_icon(icon_color: Color) -> Box<dyn Widget<FileDetail>> {
     Box::new(painter)
 }
 
-/// 创建文件图标
-pub fn create_file_icon(icon_color: Color) -> Box<dyn Widget<FileDetail>> {
+/// 创建文件图标，按图标样式在文件主体上绘制不同的标记
+pub fn create_file_icon(icon_color: Color, icon_kind: IconKind) -> Box<dyn Widget<FileDetail>> {
     let painter = Painter::new(move |ctx, _data: &FileDetail, _env| {
         // 计算图标区域
         let rect = ctx.size().to_rect();
@@ -62,14 +63,70 @@ pub fn create_file_icon(icon_color: Color) -> Box<dyn Widget<FileDetail>> {
         path.close_path();
         ctx.fill(path, &Color::rgb8(220, 220, 220));
         
-        // 文件线条（模拟文本）
-        for i in 0..3 {
-            let line_y = center_y - icon_size/4.0 + i as f64 * (icon_size/3.0);
-            let line = Rect::from_origin_size(
-                Point::new(center_x - icon_size/3.0, line_y),
-                (icon_size * 0.5, icon_size/12.0)
-            );
-            ctx.fill(line, &Color::rgb8(180, 180, 180));
+        // 按图标样式绘制文件主体上的标记
+        let mark_color = Color::rgb8(180, 180, 180);
+        match icon_kind {
+            IconKind::Image => {
+                // 山形图案
+                let mut mountain = BezPath::new();
+                mountain.move_to((center_x - icon_size/3.0, center_y + icon_size/3.0));
+                mountain.line_to((center_x - icon_size/10.0, center_y - icon_size/12.0));
+                mountain.line_to((center_x + icon_size/5.0, center_y + icon_size/3.0));
+                mountain.close_path();
+                ctx.fill(mountain, &mark_color);
+            }
+            IconKind::Audio | IconKind::Video => {
+                // 播放三角形
+                let mut play = BezPath::new();
+                play.move_to((center_x - icon_size/5.0, center_y - icon_size/5.0));
+                play.line_to((center_x + icon_size/5.0, center_y));
+                play.line_to((center_x - icon_size/5.0, center_y + icon_size/5.0));
+                play.close_path();
+                ctx.fill(play, &mark_color);
+            }
+            IconKind::Archive => {
+                // 拉链
+                for i in 0..4 {
+                    let zip_y = center_y - icon_size/3.0 + i as f64 * (icon_size/6.0);
+                    let offset = if i % 2 == 0 { 0.0 } else { icon_size/10.0 };
+                    let zip = Rect::from_origin_size(
+                        Point::new(center_x - icon_size/10.0 + offset, zip_y),
+                        (icon_size/10.0, icon_size/12.0)
+                    );
+                    ctx.fill(zip, &mark_color);
+                }
+            }
+            IconKind::Executable => {
+                // 窗口标题栏
+                let title_bar = Rect::from_origin_size(
+                    Point::new(center_x - icon_size/3.0, center_y - icon_size/4.0),
+                    (icon_size * 0.5, icon_size/6.0)
+                );
+                ctx.fill(title_bar, &mark_color);
+            }
+            IconKind::Code => {
+                // 缩进的代码行
+                for i in 0..3 {
+                    let indent = if i == 1 { icon_size/8.0 } else { 0.0 };
+                    let line_y = center_y - icon_size/4.0 + i as f64 * (icon_size/4.0);
+                    let line = Rect::from_origin_size(
+                        Point::new(center_x - icon_size/3.0 + indent, line_y),
+                        (icon_size * 0.35, icon_size/12.0)
+                    );
+                    ctx.fill(line, &mark_color);
+                }
+            }
+            IconKind::Document | IconKind::Generic | IconKind::Folder => {
+                // 文件线条（模拟文本）
+                for i in 0..3 {
+                    let line_y = center_y - icon_size/4.0 + i as f64 * (icon_size/3.0);
+                    let line = Rect::from_origin_size(
+                        Point::new(center_x - icon_size/3.0, line_y),
+                        (icon_size * 0.5, icon_size/12.0)
+                    );
+                    ctx.fill(line, &mark_color);
+                }
+            }
         }
     })
     .fix_size(24.0, 24.0);
@@ -1,9 +1,15 @@
 use druid::{
-    widget::{Flex, Label},
-    Widget, WidgetExt, Color
+    lens,
+    text::{Attribute, RichText},
+    widget::{Flex, Label, Painter, RawLabel},
+    Widget, WidgetExt, Color, FontWeight, RenderContext
 };
 use std::boxed::Box;
+use crate::file_types::IconKind;
 use crate::models::FileDetail;
+use crate::ui::keyboard_nav::LIST_HAS_FOCUS;
+use crate::ui::theme;
+use crate::utils::format_size;
 use super::icons::{create_folder_icon, create_file_icon};
 
 /// 截断文件名，如果超过最大长度则添加省略号
@@ -30,23 +36,54 @@ fn truncate_filename(filename: &str, max_length: usize) -> String {
     format!("{}...", truncated)
 }
 
+/// 显示的名称，快速筛选匹配的部分加粗并高亮
+fn name_text(data: &FileDetail) -> RichText {
+    // 对长文件名进行截断处理，最大显示40个字符
+    let shown = truncate_filename(&data.name, 40);
+    let mut text = RichText::new(shown.as_str().into());
+    for (start, end) in highlight_ranges(&data.name, &shown, &data.name_matches) {
+        text.add_attribute(start..end, Attribute::Weight(FontWeight::BOLD));
+        text.add_attribute(start..end, Attribute::text_color(theme::MATCH_HIGHLIGHT));
+    }
+    text
+}
+
+/// 截断后显示的名称中要高亮的字节范围
+///
+/// 名称被截断时只高亮截断后仍然显示的开头部分，不在字符边界上的范围被忽略。
+fn highlight_ranges(name: &str, shown: &str, matches: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let visible = if shown == name {
+        shown.len()
+    } else {
+        name.char_indices()
+            .zip(shown.chars())
+            .take_while(|((_, a), b)| a == b)
+            .map(|((i, a), _)| i + a.len_utf8())
+            .last()
+            .unwrap_or(0)
+    };
+
+    matches.iter()
+        .map(|&(start, end)| (start, end.min(visible)))
+        .filter(|&(start, end)| start < end && shown.is_char_boundary(start) && shown.is_char_boundary(end))
+        .collect()
+}
+
 /// 创建一个行视图，包含名称、大小、类型和时间信息
-pub fn create_file_row(color: Color, icon_color: Color, is_dir: bool) -> Box<dyn Widget<FileDetail>> {
+pub fn create_file_row(color: Color, icon_kind: IconKind, is_dir: bool) -> Box<dyn Widget<FileDetail>> {
     // 创建图标
     let icon = if is_dir {
-        create_folder_icon(icon_color)
+        create_folder_icon(color)
     } else {
-        create_file_icon(icon_color)
+        create_file_icon(color, icon_kind)
     };
     
-    // 名称列 - 添加文本截断功能
-    let name_label = Label::dynamic(|data: &FileDetail, _| {
-        // 对长文件名进行截断处理，最大显示40个字符
-        truncate_filename(&data.name, 40)
-    })
-    .with_text_size(14.0)
-    .with_text_color(color)
-    .align_left();
+    // 名称列 - 添加文本截断功能和筛选匹配的高亮
+    let name_label = RawLabel::new()
+        .with_text_size(14.0)
+        .with_text_color(color)
+        .lens(lens::Map::new(name_text, |_: &mut FileDetail, _: RichText| {}))
+        .align_left();
     
     // 名称行布局（图标+文本）
     let name_row = Flex::row()
@@ -63,7 +100,7 @@ pub fn create_file_row(color: Color, icon_color: Color, is_dir: bool) -> Box<dyn
     .align_left();
 
     // 类型列
-    let type_label = Label::dynamic(|data: &FileDetail, _| data.file_type.clone())
+    let type_label = Label::dynamic(|data: &FileDetail, _| data.kind.description.clone())
         .with_text_size(14.0)
         .with_text_color(color)
         .align_left();
@@ -79,22 +116,81 @@ pub fn create_file_row(color: Color, icon_color: Color, is_dir: bool) -> Box<dyn
         .with_flex_child(size_label, 0.2)
         .with_flex_child(type_label, 0.2)
         .with_flex_child(modified_label, 0.2)
-        .padding(10.0);
+        .padding(10.0)
+        .background(Painter::new(|ctx, data: &FileDetail, env| {
+            // 选中的行使用主题的选中背景色
+            if data.is_selected {
+                let rect = ctx.size().to_rect();
+                ctx.fill(rect, &env.get(theme::SELECTION_BACKGROUND));
+            }
+
+            // 文件列表拥有键盘焦点时为焦点所在的行绘制焦点框
+            if data.is_focused && env.try_get(LIST_HAS_FOCUS).unwrap_or(false) {
+                let rect = ctx.size().to_rect().inset(-1.0);
+                ctx.stroke(rect, &env.get(theme::FOCUS_RING), 1.5);
+            }
+        }));
     
     Box::new(row)
 }
 
 /// 格式化文件大小显示方式
 fn format_file_size(data: &FileDetail) -> String {
-    if data.file_type == "目录" {
-        "".to_string()
-    } else if data.size < 1024 {
-        format!("{} B", data.size)
-    } else if data.size < 1024 * 1024 {
-        format!("{:.1} KB", data.size as f64 / 1024.0)
-    } else if data.size < 1024 * 1024 * 1024 {
-        format!("{:.1} MB", data.size as f64 / (1024.0 * 1024.0))
+    if data.kind.is_directory() {
+        // 目录大小在开启计算文件夹大小后异步填入
+        match data.folder_size {
+            Some(size) if size.incomplete => format!("≥ {}", format_size(size.bytes)),
+            Some(size) => format_size(size.bytes),
+            None => "".to_string(),
+        }
     } else {
-        format!("{:.1} GB", data.size as f64 / (1024.0 * 1024.0 * 1024.0))
+        format_size(data.size)
+    }
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_names_are_not_truncated() {
+        assert_eq!(truncate_filename("report.pdf", 40), "report.pdf");
+        assert_eq!(truncate_filename(&"文".repeat(40), 40), "文".repeat(40));
+    }
+
+    #[test]
+    fn long_names_keep_the_extension() {
+        let name = format!("{}.txt", "abcdefghij".repeat(5));
+        let shown = truncate_filename(&name, 40);
+        assert_eq!(shown, format!("{}....txt", &name[..33]));
+        assert_eq!(shown.chars().count(), 40);
+
+        // 没有扩展名或扩展名过长时直接截断
+        let shown = truncate_filename(&"x".repeat(50), 40);
+        assert_eq!(shown, format!("{}...", "x".repeat(37)));
+        // 按字符而不是字节截断
+        let shown = truncate_filename(&"文件".repeat(30), 40);
+        assert_eq!(shown.chars().count(), 40);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn highlight_ranges_follow_truncation() {
+        assert_eq!(highlight_ranges("report.pdf", "report.pdf", &[(0, 3), (7, 10)]), vec![(0, 3), (7, 10)]);
+
+        // 截断后只保留仍然显示的开头部分
+        let name = format!("{}.txt", "abcdefghij".repeat(5));
+        let shown = truncate_filename(&name, 40);
+        assert_eq!(highlight_ranges(&name, &shown, &[(0, 3), (30, 36), (40, 45)]), vec![(0, 3), (30, 33)]);
+    }
+
+    #[test]
+    fn highlight_ranges_skip_partial_characters() {
+        // "文" 占3个字节
+        let name = "文件abc";
+        assert_eq!(highlight_ranges(name, name, &[(1, 3), (3, 6), (6, 9)]), vec![(3, 6), (6, 9)]);
+
+        let name = "文件".repeat(30);
+        let shown = truncate_filename(&name, 40);
+        assert_eq!(highlight_ranges(&name, &shown, &[(0, 6), (111, 117)]), vec![(0, 6)]);
+    }
+}
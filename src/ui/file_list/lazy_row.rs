@@ -0,0 +1,109 @@
+use druid::{
+    BoxConstraints, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx,
+    PaintCtx, Size, TimerToken, UpdateCtx, Widget, WidgetPod
+};
+use crate::detection;
+use crate::folder_size;
+use crate::models::FileDetail;
+use crate::settings;
+
+/// 文件行的包装控件，在行第一次被绘制（即滚动到可见区域）时请求后台加载额外信息：
+/// 文件按内容识别类型，目录在开启计算文件夹大小时计算递归大小
+///
+/// 滚动区域只绘制与可见区域相交的行，因此不可见的行不会读取文件内容。
+/// 目录大小的缓存过期后，行再次被绘制时重新计算，避免一直显示过时的大小。
+pub struct LazyRow<W> {
+    inner: WidgetPod<FileDetail, W>,
+    /// 当前文件是否已经请求过
+    requested: bool,
+    /// 显示的目录大小是否已经超过缓存有效期
+    size_expired: bool,
+    /// 目录大小过期的计时器
+    expiry_timer: TimerToken,
+}
+
+impl<W: Widget<FileDetail>> LazyRow<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner: WidgetPod::new(inner),
+            requested: false,
+            size_expired: false,
+            expiry_timer: TimerToken::INVALID,
+        }
+    }
+
+    /// 请求当前行还缺少的信息
+    fn request(&self, data: &FileDetail) {
+        if data.kind.is_directory() {
+            if (data.folder_size.is_none() || self.size_expired) && settings::current().compute_folder_sizes {
+                folder_size::request(&data.full_path);
+            }
+        } else if !data.kind_detected {
+            detection::request(&data.full_path);
+        }
+    }
+
+    /// 显示目录大小时开始计算有效期
+    fn needs_expiry_timer(&self, data: &FileDetail) -> bool {
+        self.expiry_timer == TimerToken::INVALID && data.folder_size.is_some()
+    }
+}
+
+impl<W: Widget<FileDetail>> Widget<FileDetail> for LazyRow<W> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut FileDetail, env: &Env) {
+        if let Event::Timer(token) = event {
+            if *token == self.expiry_timer {
+                // 下次绘制（即行可见）时重新计算；计时器继续运行，
+                // 因为重新计算的结果与原来相同时数据不会变化
+                self.size_expired = true;
+                self.requested = false;
+                self.expiry_timer = ctx.request_timer(folder_size::CACHE_TTL);
+                ctx.request_paint();
+                return;
+            }
+        }
+        self.inner.event(ctx, event, data, env);
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &FileDetail, env: &Env) {
+        if let LifeCycle::WidgetAdded = event {
+            if self.needs_expiry_timer(data) {
+                self.expiry_timer = ctx.request_timer(folder_size::CACHE_TTL);
+            }
+        }
+        self.inner.lifecycle(ctx, event, data, env);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &FileDetail, data: &FileDetail, env: &Env) {
+        // 列表项被复用来显示另一个文件时需要重新请求
+        if old_data.full_path != data.full_path {
+            self.requested = false;
+            self.size_expired = false;
+            self.expiry_timer = TimerToken::INVALID;
+        }
+        // 收到新的目录大小后重新开始计算有效期
+        if old_data.folder_size != data.folder_size {
+            self.size_expired = false;
+            self.expiry_timer = TimerToken::INVALID;
+        }
+        if self.needs_expiry_timer(data) {
+            self.expiry_timer = ctx.request_timer(folder_size::CACHE_TTL);
+        }
+        self.inner.update(ctx, data, env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &FileDetail, env: &Env) -> Size {
+        let size = self.inner.layout(ctx, bc, data, env);
+        self.inner.set_origin(ctx, druid::Point::ORIGIN);
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &FileDetail, env: &Env) {
+        if !self.requested {
+            self.requested = true;
+            self.request(data);
+            self.size_expired = false;
+        }
+        self.inner.paint(ctx, data, env);
+    }
+}
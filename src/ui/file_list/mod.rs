@@ -2,45 +2,49 @@ mod row;
 mod controllers;
 mod icons;
 mod item_styles;
+mod lazy_row;
 
 pub use row::file_list_item;
 
 use druid::widget::{Flex, Label, Scroll, List};
 use druid::{Widget, WidgetExt};
 use crate::models::AppState;
-use crate::ui::constants::*;
+use crate::ui::drag_drop::DropTarget;
+use crate::ui::quick_filter_bar::build_quick_filter_bar;
+use crate::ui::theme;
 
-/// 构建文件列表视图，包含表头和内容
+/// 构建文件列表视图，包含快速筛选栏、表头和内容
 pub fn build_file_list() -> impl Widget<AppState> {
     Flex::column()
+        .with_child(build_quick_filter_bar())
         .with_child(build_file_list_header())
         .with_flex_child(build_file_list_content(), 1.0)
-        .background(LIGHT_BACKGROUND)
+        .background(theme::LIST_BACKGROUND)
 }
 
 /// 构建文件列表的表头
 fn build_file_list_header() -> impl Widget<AppState> {
     // 创建表头的各个列标签
     let name_header = Label::new("名称")
-        .with_text_color(SELECTED_TEXT)
+        .with_text_color(theme::TEXT_COLOR)
         .with_text_size(14.0)
         .padding(10.0)
         .align_left();
         
     let size_header = Label::new("大小")
-        .with_text_color(SELECTED_TEXT)
+        .with_text_color(theme::TEXT_COLOR)
         .with_text_size(14.0)
         .padding(10.0)
         .align_left();
         
     let type_header = Label::new("类型")
-        .with_text_color(SELECTED_TEXT)
+        .with_text_color(theme::TEXT_COLOR)
         .with_text_size(14.0)
         .padding(10.0)
         .align_left();
         
     let modified_header = Label::new("修改时间/信息")
-        .with_text_color(SELECTED_TEXT)
+        .with_text_color(theme::TEXT_COLOR)
         .with_text_size(14.0)
         .padding(10.0)
         .align_left();
@@ -51,7 +55,7 @@ fn build_file_list_header() -> impl Widget<AppState> {
         .with_flex_child(size_header, 0.2)
         .with_flex_child(type_header, 0.2)
         .with_flex_child(modified_header, 0.2)
-        .background(MID_BACKGROUND)
+        .background(theme::HEADER_BACKGROUND)
 }
 
 /// 构建文件列表的内容区域
@@ -62,7 +66,10 @@ fn build_file_list_content() -> impl Widget<AppState> {
         .lens(AppState::current_dir_files);
 
     // 使用Scroll包装列表，以便在内容过多时可以滚动
-    Scroll::new(list)
+    let scroll = Scroll::new(list)
         .vertical()
-        .expand()
+        .expand();
+
+    // 拖放到列表空白处或文件行上时放入当前目录
+    DropTarget::new(scroll, |data: &AppState| data.selected_path.clone())
 } 
\ No newline at end of file
@@ -1,62 +1,59 @@
 use druid::{
     widget::{ViewSwitcher},
-    Widget, WidgetExt
+    Color, Env, Widget, WidgetExt
 };
 use std::boxed::Box;
+use crate::file_types;
 use crate::models::FileDetail;
-use crate::ui::constants::*;
+use crate::ui::constants::HIDDEN_ITEM_ALPHA;
+use crate::ui::context_menu::ContextMenuController;
+use crate::ui::drag_drop::{DragSource, DropTarget};
+use crate::ui::keyboard_nav::ScrollToFocus;
+use crate::ui::theme;
 use super::item_styles::create_file_row;
-use super::controllers::{FileItemController, DirectoryItemController};
+use super::lazy_row::LazyRow;
+use super::controllers::{FileItemController, DirectoryItemController, SelectionController};
 
 /// 构建文件列表中的单个文件项
 pub fn file_list_item() -> impl Widget<FileDetail> {
     // 使用ViewSwitcher为不同类型的文件设置不同的颜色
-    ViewSwitcher::new(
-        |data: &FileDetail, _env| data.file_type.clone(),
-        |file_type, _data, _env| {
-            if file_type == "目录" {
-                // 为目录创建带有特殊交互的行
-                let dir_row = create_file_row(FOLDER_COLOR, FOLDER_COLOR, true)
-                    .controller(DirectoryItemController);
-                
-                Box::new(dir_row)
-            } else if file_type == "驱动器" {
-                // 为驱动器创建特殊行
-                let drive_row = create_file_row(FOLDER_COLOR, FOLDER_COLOR, true)
-                    .controller(DirectoryItemController);
-                
-                Box::new(drive_row)
-            } else if file_type.ends_with(" 文件") {
-                // 提取文件扩展名并设置相应颜色
-                let ext = file_type.split_whitespace().next().unwrap_or("");
-                let row = match ext {
-                    "txt" | "md" | "json" | "xml" | "html" | "css" | "js" | "py" | "rs" | "c" | "cpp" | "h" | "hpp" | "java" | "go" | "php" | "rb" => {
-                        create_file_row(TEXT_FILE_COLOR, TEXT_FILE_COLOR, false)
-                    }
-                    "jpg" | "jpeg" | "png" | "gif" | "bmp" | "svg" | "ico" => {
-                        create_file_row(IMAGE_FILE_COLOR, IMAGE_FILE_COLOR, false)
-                    }
-                    "mp3" | "wav" | "flac" | "ogg" | "aac" => {
-                        create_file_row(AUDIO_FILE_COLOR, AUDIO_FILE_COLOR, false)
-                    }
-                    "mp4" | "avi" | "mkv" | "mov" | "wmv" => {
-                        create_file_row(VIDEO_FILE_COLOR, VIDEO_FILE_COLOR, false)
-                    }
-                    "zip" | "rar" | "7z" | "tar" | "gz" => {
-                        create_file_row(ARCHIVE_FILE_COLOR, ARCHIVE_FILE_COLOR, false)
-                    }
-                    "exe" | "dll" | "so" | "dylib" => {
-                        create_file_row(EXECUTABLE_FILE_COLOR, EXECUTABLE_FILE_COLOR, false)
-                    }
-                    _ => create_file_row(REGULAR_FILE_COLOR, REGULAR_FILE_COLOR, false),
-                };
-                
-                // 为所有文件添加双击打开功能
-                Box::new(row.controller(FileItemController::new()))
+    // 颜色来自当前主题，主题切换后颜色变化会触发重建
+    // 行第一次显示时识别文件类型或计算目录大小，识别结果改变类型后同样触发重建
+    let row = ViewSwitcher::new(
+        |data: &FileDetail, env: &Env| {
+            (data.kind.clone(), data.is_hidden, theme::file_color(env, &data.kind).as_rgba_u32())
+        },
+        |(kind, is_hidden, color), _data, _env| {
+            // 隐藏文件使用半透明颜色淡化显示
+            let mut color = Color::from_rgba32_u32(*color);
+            if *is_hidden {
+                color = color.with_alpha(HIDDEN_ITEM_ALPHA);
+            }
+
+            // 右键显示属性等菜单
+            let context_menu = ContextMenuController::new(|data: &FileDetail| data.full_path.clone());
+
+            let icon = file_types::icon_for(kind);
+            if kind.is_directory() {
+                // 为目录和驱动器创建带有特殊交互的行，目录行同时可以接收拖放的文件
+                let dir_row = create_file_row(color, icon, true)
+                    .controller(DirectoryItemController)
+                    .controller(SelectionController)
+                    .controller(DragSource::new(|data: &FileDetail| data.full_path.clone()))
+                    .controller(context_menu);
+
+                Box::new(DropTarget::new(dir_row, |data: &FileDetail| Some(data.full_path.clone())))
             } else {
-                Box::new(create_file_row(REGULAR_FILE_COLOR, REGULAR_FILE_COLOR, false)
-                    .controller(FileItemController::new()))
+                // 为所有文件添加双击打开功能
+                Box::new(create_file_row(color, icon, false)
+                    .controller(FileItemController::new())
+                    .controller(SelectionController)
+                    .controller(DragSource::new(|data: &FileDetail| data.full_path.clone()))
+                    .controller(context_menu))
             }
         },
-    )
-} 
\ No newline at end of file
+    );
+
+    // 用键盘移动焦点时滚动到焦点所在的行
+    LazyRow::new(row).controller(ScrollToFocus::new(|data: &FileDetail| data.is_focused))
+}
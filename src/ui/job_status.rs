@@ -0,0 +1,50 @@
+use druid::widget::{Button, CrossAxisAlignment, Either, Flex, Label, LineBreaking};
+use druid::{Widget, WidgetExt};
+use crate::commands::CANCEL_JOB;
+use crate::jobs::JobStatus;
+use crate::models::AppState;
+use super::theme;
+
+/// 后台任务的状态栏：一行状态、运行中时的取消按钮和失败路径列表
+///
+/// `job_id` 从状态中取出要显示的任务ID（各窗口分别记录自己的任务）。
+pub fn build_job_status(job_id: fn(&AppState) -> Option<u64>) -> impl Widget<AppState> {
+    let status = Label::dynamic(move |data: &AppState, _| {
+        find_job(data, job_id(data))
+            .map(|job| job.summary())
+            .unwrap_or_default()
+    })
+    .with_line_break_mode(LineBreaking::WordWrap)
+    .with_text_color(theme::TEXT_COLOR)
+    .with_text_size(13.0);
+
+    let failures = Label::dynamic(move |data: &AppState, _| {
+        find_job(data, job_id(data))
+            .map(|job| job.failure_details())
+            .unwrap_or_default()
+    })
+    .with_line_break_mode(LineBreaking::WordWrap)
+    .with_text_color(theme::SECONDARY_TEXT_COLOR)
+    .with_text_size(12.0);
+
+    let cancel = Either::new(
+        move |data: &AppState, _| find_job(data, job_id(data)).is_some_and(|job| !job.finished),
+        Button::new("取消").on_click(move |ctx, data: &mut AppState, _env| {
+            if let Some(id) = job_id(data) {
+                ctx.submit_command(CANCEL_JOB.with(id));
+            }
+        }),
+        Flex::row(),
+    );
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Flex::row().with_flex_child(status.expand_width(), 1.0).with_child(cancel))
+        .with_child(failures.expand_width())
+}
+
+/// 按ID查找任务状态
+pub fn find_job(data: &AppState, id: Option<u64>) -> Option<&JobStatus> {
+    let id = id?;
+    data.jobs.iter().find(|job| job.id == id)
+}
@@ -0,0 +1,90 @@
+use druid::widget::{Button, CrossAxisAlignment, Flex, Label, LineBreaking, Scroll};
+use druid::{commands, Color, Widget, WidgetExt};
+use crate::actions::{ActionScope, ACTIONS};
+use crate::keymap::{self, Keymap};
+use crate::models::AppState;
+use super::theme;
+
+/// 按键冲突等问题的文字颜色
+const PROBLEM_COLOR: Color = Color::rgb8(0xE0, 0x6C, 0x4C);
+
+/// 构建键盘快捷键窗口：列出所有动作及其按键，以及加载按键绑定时发现的冲突
+///
+/// 按键绑定只在启动时加载，因此内容在打开窗口时生成一次即可。
+pub fn build_keybindings_dialog() -> impl Widget<AppState> {
+    let content = keymap::with_keymap(build_content);
+
+    let buttons = Flex::row()
+        .with_flex_spacer(1.0)
+        .with_child(Button::new("关闭").on_click(|ctx, _data: &mut AppState, _env| {
+            ctx.submit_command(commands::CLOSE_WINDOW.to(ctx.window_id()));
+        }));
+
+    theme::themed(
+        Flex::column()
+            .with_flex_child(Scroll::new(content).vertical().expand(), 1.0)
+            .with_spacer(10.0)
+            .with_child(buttons)
+            .padding(15.0)
+            .background(theme::BACKGROUND)
+            .expand()
+    )
+}
+
+fn build_content(keymap: &Keymap) -> Flex<AppState> {
+    let mut column = Flex::column().cross_axis_alignment(CrossAxisAlignment::Start);
+
+    if !keymap.problems().is_empty() {
+        column.add_child(section_title("按键绑定问题"));
+        for problem in keymap.problems() {
+            column.add_child(
+                Label::new(problem.as_str())
+                    .with_line_break_mode(LineBreaking::WordWrap)
+                    .with_text_color(PROBLEM_COLOR)
+                    .with_text_size(13.0)
+                    .padding((0.0, 2.0))
+            );
+        }
+        column.add_spacer(12.0);
+    }
+
+    column.add_child(section_title("快捷键"));
+    for action in ACTIONS {
+        let keys = keymap.keys_for(action.id);
+        let keys = if keys.is_empty() { "未绑定".to_string() } else { keys.join("，") };
+        let name = match action.scope {
+            ActionScope::Global => action.name.to_string(),
+            ActionScope::Panes => format!("{}（目录树或文件列表）", action.name),
+        };
+        column.add_child(
+            Flex::row()
+                .with_child(
+                    Label::new(keys)
+                        .with_text_color(theme::TEXT_COLOR)
+                        .with_text_size(13.0)
+                        .fix_width(160.0)
+                )
+                .with_flex_child(
+                    Label::new(name)
+                        .with_text_color(theme::TEXT_COLOR)
+                        .with_text_size(13.0)
+                        .expand_width(),
+                    1.0,
+                )
+                .with_child(
+                    Label::new(action.id)
+                        .with_text_color(theme::SECONDARY_TEXT_COLOR)
+                        .with_text_size(12.0)
+                )
+                .padding((0.0, 3.0))
+        );
+    }
+    column
+}
+
+fn section_title(text: &str) -> impl Widget<AppState> {
+    Label::new(text)
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(15.0)
+        .padding((0.0, 4.0, 0.0, 6.0))
+}
@@ -0,0 +1,98 @@
+use druid::widget::{Controller, EnvScope};
+use druid::{Data, Env, Event, EventCtx, Key, UpdateCtx, Widget, WidgetExt};
+use crate::commands::{FOCUS_PANES, RESTORE_LIST_FOCUS, SET_PANES_FOCUSED};
+use crate::keyboard_nav::FocusPane;
+use crate::models::AppState;
+
+/// 文件列表是否拥有键盘焦点（用于绘制焦点框）
+pub const LIST_HAS_FOCUS: Key<bool> = Key::new("lemon-explorer.keyboard.list-has-focus");
+
+/// 目录树是否拥有键盘焦点（用于绘制焦点框）
+pub const TREE_HAS_FOCUS: Key<bool> = Key::new("lemon-explorer.keyboard.tree-has-focus");
+
+/// 为目录树和文件列表加上键盘导航
+///
+/// 点击其中任意位置后获得键盘焦点；焦点在导航栏的输入框中时按键仍交给输入框处理。
+pub fn with_keyboard_navigation(child: impl Widget<AppState> + 'static) -> impl Widget<AppState> {
+    EnvScope::new(
+        |env, data: &AppState| {
+            env.set(LIST_HAS_FOCUS, data.panes_focused && data.focus_pane == FocusPane::List);
+            env.set(TREE_HAS_FOCUS, data.panes_focused && data.focus_pane == FocusPane::Tree);
+        },
+        child.controller(KeyboardNavController),
+    )
+}
+
+/// 管理目录树和文件列表的键盘焦点
+///
+/// 按键本身由 `keymap::KeyHandler` 在窗口层面按照按键绑定转换为命令，
+/// 这里只负责获取焦点，并记录焦点是否在输入框中。
+struct KeyboardNavController;
+
+impl<W: Widget<AppState>> Controller<AppState, W> for KeyboardNavController {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut AppState, env: &Env) {
+        match event {
+            Event::WindowConnected | Event::MouseDown(_) => ctx.request_focus(),
+            Event::Command(cmd) if cmd.is(FOCUS_PANES) => ctx.request_focus(),
+            _ => {}
+        }
+        child.event(ctx, event, data, env);
+    }
+
+    fn lifecycle(&mut self, child: &mut W, ctx: &mut druid::LifeCycleCtx, event: &druid::LifeCycle, data: &AppState, env: &Env) {
+        match event {
+            druid::LifeCycle::WidgetAdded => ctx.register_for_focus(),
+            druid::LifeCycle::FocusChanged(focused) => ctx.submit_command(SET_PANES_FOCUSED.with(*focused)),
+            _ => {}
+        }
+        child.lifecycle(ctx, event, data, env);
+    }
+
+    fn update(&mut self, child: &mut W, ctx: &mut UpdateCtx, old_data: &AppState, data: &AppState, env: &Env) {
+        // 文件列表重新加载后，焦点所在的行需要重新标记
+        if !old_data.current_dir_files.same(&data.current_dir_files)
+            && !data.current_dir_files.is_empty()
+            && !data.current_dir_files.iter().any(|file| file.is_focused)
+        {
+            ctx.submit_command(RESTORE_LIST_FOCUS.with(old_data.selected_path.clone()));
+        }
+        if old_data.focus_pane != data.focus_pane {
+            ctx.request_paint();
+        }
+        child.update(ctx, old_data, data, env);
+    }
+}
+
+/// 点击目录树或文件列表时将键盘焦点所在的面板切换过去
+pub struct PaneFocus(pub FocusPane);
+
+impl<W: Widget<AppState>> Controller<AppState, W> for PaneFocus {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut AppState, env: &Env) {
+        if let Event::MouseDown(_) = event {
+            if data.focus_pane != self.0 {
+                data.focus_pane = self.0;
+            }
+        }
+        child.event(ctx, event, data, env);
+    }
+}
+
+/// 行成为焦点（或选中）时滚动到可见区域
+pub struct ScrollToFocus<T> {
+    is_focused: fn(&T) -> bool,
+}
+
+impl<T> ScrollToFocus<T> {
+    pub fn new(is_focused: fn(&T) -> bool) -> Self {
+        Self { is_focused }
+    }
+}
+
+impl<T: Data, W: Widget<T>> Controller<T, W> for ScrollToFocus<T> {
+    fn update(&mut self, child: &mut W, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        if (self.is_focused)(data) && !(self.is_focused)(old_data) {
+            ctx.scroll_to_view();
+        }
+        child.update(ctx, old_data, data, env);
+    }
+}
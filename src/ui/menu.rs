@@ -0,0 +1,112 @@
+use druid::{Menu, MenuItem};
+use crate::models::AppState;
+use crate::commands::{SHOW_SETTINGS, TOGGLE_HIDDEN_FILES, TOGGLE_FOLDER_SIZES, SET_THEME, ANALYZE_DISK_USAGE, FIND_DUPLICATES,
+                      COMPUTE_CHECKSUMS, BATCH_RENAME, COMPARE_DIRECTORIES, SHOW_SYNC_JOBS, SHOW_KEYBINDINGS,
+                      SHOW_COMMAND_PALETTE, SHOW_QUICK_JUMP};
+use crate::keymap::shortcut_label;
+use super::theme;
+
+/// 构建主窗口菜单栏
+///
+/// 同步任务列在菜单中，任务变化时重新构建菜单。
+pub fn build_menu(data: &AppState) -> Menu<AppState> {
+    Menu::empty()
+        .entry(build_view_menu())
+        .entry(build_tools_menu())
+        .entry(build_sync_menu(data))
+        .rebuild_on(|old: &AppState, data: &AppState, _env| old.settings.sync_jobs != data.settings.sync_jobs)
+}
+
+/// 构建"查看"菜单
+fn build_view_menu() -> Menu<AppState> {
+    Menu::new("查看")
+        .entry(
+            MenuItem::new(format!("显示隐藏文件{}", shortcut_label("view.toggle_hidden")))
+                .command(TOGGLE_HIDDEN_FILES)
+                .selected_if(|data: &AppState, _env| data.settings.show_hidden)
+        )
+        .entry(
+            MenuItem::new("计算文件夹大小")
+                .command(TOGGLE_FOLDER_SIZES)
+                .selected_if(|data: &AppState, _env| data.settings.compute_folder_sizes)
+        )
+        .entry(build_theme_menu())
+}
+
+/// 构建"主题"子菜单，列出内置主题和用户主题
+fn build_theme_menu() -> Menu<AppState> {
+    theme::available_themes()
+        .into_iter()
+        .fold(Menu::new("主题"), |menu, name| {
+            let selected_name = name.clone();
+            menu.entry(
+                MenuItem::new(name.clone())
+                    .command(SET_THEME.with(name))
+                    .selected_if(move |data: &AppState, _env| data.theme.name == selected_name)
+            )
+        })
+}
+
+/// 构建"同步"菜单：每个保存的任务（打开后立即预览）和任务管理
+fn build_sync_menu(data: &AppState) -> Menu<AppState> {
+    let menu = data.settings.sync_jobs
+        .iter()
+        .fold(Menu::new("同步"), |menu, job| {
+            menu.entry(MenuItem::new(format!("{}...", job.name)).command(SHOW_SYNC_JOBS.with(Some(job.name.clone()))))
+        });
+    let menu = if data.settings.sync_jobs.is_empty() {
+        menu
+    } else {
+        menu.separator()
+    };
+    menu.entry(MenuItem::new("管理同步任务...").command(SHOW_SYNC_JOBS.with(None)))
+}
+
+/// 构建"工具"菜单
+fn build_tools_menu() -> Menu<AppState> {
+    Menu::new("工具")
+        .entry(MenuItem::new(format!("命令面板...{}", shortcut_label("app.command_palette"))).command(SHOW_COMMAND_PALETTE))
+        .entry(MenuItem::new(format!("快速跳转...{}", shortcut_label("navigate.quick_jump"))).command(SHOW_QUICK_JUMP))
+        .separator()
+        .entry(
+            MenuItem::new("分析磁盘占用...")
+                .on_activate(|ctx, data: &mut AppState, _env| {
+                    if let Some(path) = &data.selected_path {
+                        ctx.submit_command(ANALYZE_DISK_USAGE.with(path.clone()));
+                    }
+                })
+                .enabled_if(|data: &AppState, _env| data.selected_path.is_some())
+        )
+        .entry(
+            MenuItem::new("查找重复文件...")
+                .on_activate(|ctx, data: &mut AppState, _env| {
+                    if let Some(path) = &data.selected_path {
+                        ctx.submit_command(FIND_DUPLICATES.with(path.clone()));
+                    }
+                })
+                .enabled_if(|data: &AppState, _env| data.selected_path.is_some())
+        )
+        .entry(
+            MenuItem::new("比较目录...")
+                .on_activate(|ctx, data: &mut AppState, _env| {
+                    let path = data.selected_path.clone().unwrap_or_default();
+                    ctx.submit_command(COMPARE_DIRECTORIES.with(path));
+                })
+        )
+        .entry(
+            MenuItem::new("计算选中文件的校验和...")
+                .on_activate(|ctx, data: &mut AppState, _env| {
+                    ctx.submit_command(COMPUTE_CHECKSUMS.with(data.selected_files()));
+                })
+                .enabled_if(|data: &AppState, _env| data.current_dir_files.iter().any(|file| file.is_selected))
+        )
+        .entry(
+            MenuItem::new("批量重命名选中的文件...")
+                .on_activate(|ctx, data: &mut AppState, _env| {
+                    ctx.submit_command(BATCH_RENAME.with(data.selected_files()));
+                })
+                .enabled_if(|data: &AppState, _env| data.current_dir_files.iter().any(|file| file.is_selected))
+        )
+        .entry(MenuItem::new(format!("键盘快捷键...{}", shortcut_label("app.keybindings"))).command(SHOW_KEYBINDINGS))
+        .entry(MenuItem::new(format!("设置...{}", shortcut_label("app.settings"))).command(SHOW_SETTINGS))
+}
@@ -1,19 +1,58 @@
 mod constants;
+pub mod theme;
 mod directory_tree;
 mod file_list;
 mod utils;
 mod navigation_bar;
+mod split_controller;
+mod menu;
+mod settings_dialog;
+mod properties_dialog;
+mod context_menu;
+mod disk_usage_view;
+mod duplicates_view;
+mod checksum_view;
+mod batch_rename_dialog;
+mod dir_compare_view;
+mod sync_view;
+mod job_status;
+mod drag_drop;
+mod keyboard_nav;
+mod keybindings_dialog;
+mod command_palette;
+mod drive_overview;
+mod status_bar;
+mod quick_filter_bar;
 
-use druid::widget::{Container, Split, Flex};
-use druid::{Widget, WidgetExt};
+use druid::widget::{Container, Either, Split, Flex, ZStack};
+use druid::{UnitPoint, Widget, WidgetExt};
 use crate::models::AppState;
 
 pub use directory_tree::build_directory_tree;
 pub use file_list::build_file_list;
 pub use navigation_bar::build_navigation_bar;
+pub use menu::build_menu;
+pub use settings_dialog::build_settings_dialog;
+pub use properties_dialog::build_properties_dialog;
+pub use disk_usage_view::build_disk_usage_window;
+pub use duplicates_view::build_duplicates_window;
+pub use checksum_view::build_checksum_window;
+pub use batch_rename_dialog::build_batch_rename_dialog;
+pub use dir_compare_view::build_dir_compare_window;
+pub use sync_view::build_sync_window;
+pub use keybindings_dialog::build_keybindings_dialog;
+pub use drag_drop::{end_drag, set_drag_sources};
+use split_controller::SplitPointController;
+use keyboard_nav::{with_keyboard_navigation, PaneFocus};
+use command_palette::build_command_palette;
+use drive_overview::build_drive_overview;
+use status_bar::build_status_bar;
+use crate::keyboard_nav::FocusPane;
 
 /// 构建应用程序的UI界面
-pub fn build_ui() -> impl Widget<AppState> {
+///
+/// `split_point` 为目录树所占的宽度比例（从会话中恢复）
+pub fn build_ui(split_point: f64) -> impl Widget<AppState> {
     // 创建垂直布局
     let main_layout = Flex::column();
     
@@ -22,19 +61,35 @@ pub fn build_ui() -> impl Widget<AppState> {
     
     // 创建分割视图，左侧是目录树，右侧是文件列表
     let split = Split::columns(
-        build_directory_tree(),
-        build_file_list()
+        build_directory_tree()
+            .controller(SplitPointController::new())
+            .controller(PaneFocus(FocusPane::Tree)),
+        // 选择"我的电脑"时右侧显示驱动器概览
+        Either::new(|data: &AppState, _| data.drive_overview, build_drive_overview(), build_file_list())
+            .controller(PaneFocus(FocusPane::List))
     )
-    .split_point(0.25)  // 左侧面板占25%的宽度，减少以给右侧更多空间
+    .split_point(split_point)  // 左侧面板宽度比例，默认25%
     .draggable(true)   // 允许调整分割位置
     .solid_bar(true);  // 使用实心分隔条
+
+    // 方向键、回车、退格、Tab和按名称跳转等键盘操作
+    let split = with_keyboard_navigation(split);
     
     // 将分割视图添加到主布局中
     let main_layout = main_layout.with_flex_child(split, 1.0);
 
+    // 底部状态栏
+    let main_layout = main_layout.with_child(build_status_bar());
+
     // 使用Container包装整个布局，提供边距
-    Container::new(main_layout)
+    let root = Container::new(main_layout)
         .padding(5.0)
-        .background(constants::DARK_BACKGROUND) // 整个应用使用深色背景
-        .expand()
+        .background(theme::BACKGROUND) // 整个应用使用主题背景色
+        .expand();
+
+    // 命令面板覆盖在主界面的上方
+    let root = ZStack::new(root).with_aligned_child(build_command_palette(), UnitPoint::TOP);
+
+    // 通过Env应用当前主题，切换主题时立即生效
+    theme::themed(root)
 } 
\ No newline at end of file
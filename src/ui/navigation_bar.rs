@@ -1,10 +1,21 @@
-use druid::widget::{Button, Flex, TextBox, Align};
-use druid::{Widget, WidgetExt, Color, Data, Lens};
+use druid::widget::{Button, Controller, Flex, TextBox, Align};
+use druid::{Env, Event, EventCtx, KbKey, Menu, MenuItem, Point, TimerToken, Widget, WidgetExt, Color, Data, Lens};
+use std::time::Duration;
+use crate::commands::{NAVIGATE_BACK, NAVIGATE_FORWARD, NAVIGATE_HISTORY, NAVIGATE_HOME, NAVIGATE_UP,
+                      REFRESH_DIRECTORY, SHOW_QUICK_JUMP};
+use crate::keymap::shortcut_label;
+use crate::locations;
 use crate::models::AppState;
-use super::constants::*;
-use std::path::PathBuf;
+use super::theme;
+use std::path::{Path, PathBuf};
 use crate::SELECT_DIRECTORY;
 
+/// 按住后退或前进按钮多久后弹出历史记录菜单
+const LONG_PRESS_DELAY: Duration = Duration::from_millis(500);
+
+/// 历史下拉菜单中列出的常去目录数量
+const FRECENT_MENU_SIZE: usize = 10;
+
 /// 构建导航栏（顶部工具栏）
 pub fn build_navigation_bar() -> impl Widget<AppState> {
     // 创建水平布局
@@ -13,6 +24,7 @@ pub fn build_navigation_bar() -> impl Widget<AppState> {
         .with_child(build_back_button())
         .with_spacer(2.0) // 按钮之间的间距
         .with_child(build_forward_button())
+        .with_child(build_history_button())
         .with_spacer(2.0)
         .with_child(build_up_button())
         .with_spacer(2.0)
@@ -25,6 +37,7 @@ pub fn build_navigation_bar() -> impl Widget<AppState> {
     let address_box = TextBox::new()
         .with_placeholder("输入路径...")
         .lens(CurrentPathLens)
+        .controller(AddressInput)
         .expand_width();
 
     // 将地址栏添加到导航栏
@@ -38,7 +51,7 @@ pub fn build_navigation_bar() -> impl Widget<AppState> {
     // 包装导航栏，添加样式
     nav_bar
         .padding((0.0, 8.0)) // 垂直方向增加内边距
-        .background(NAV_BAR_BACKGROUND)
+        .background(theme::NAV_BAR_BACKGROUND)
         .expand_width()
 }
 
@@ -47,53 +60,173 @@ fn build_icon_label(text: &str) -> String {
     text.to_string()
 }
 
-/// 构建后退按钮
+/// 构建后退按钮（长按或右键列出可以后退到的目录）
 fn build_back_button() -> impl Widget<AppState> {
     Align::centered(
         Button::new("⬅")
-            .on_click(|ctx, data: &mut AppState, _env| {
-                if let Some(path) = data.navigate_back() {
-                    ctx.submit_command(SELECT_DIRECTORY.with(path));
-                }
+            .on_click(|ctx, _data: &mut AppState, _env| {
+                ctx.submit_command(NAVIGATE_BACK);
             })
+            .controller(HistoryButton::new(HistoryDirection::Back))
             .fix_width(36.0)
             .fix_height(36.0)
             .border(Color::TRANSPARENT, 0.0)
     )
 }
 
-/// 构建前进按钮
+/// 构建前进按钮（长按或右键列出可以前进到的目录）
 fn build_forward_button() -> impl Widget<AppState> {
     Align::centered(
         Button::new("➡")
-            .on_click(|ctx, data: &mut AppState, _env| {
-                if let Some(path) = data.navigate_forward() {
-                    ctx.submit_command(SELECT_DIRECTORY.with(path));
-                }
+            .on_click(|ctx, _data: &mut AppState, _env| {
+                ctx.submit_command(NAVIGATE_FORWARD);
             })
+            .controller(HistoryButton::new(HistoryDirection::Forward))
             .fix_width(36.0)
             .fix_height(36.0)
             .border(Color::TRANSPARENT, 0.0)
     )
 }
 
+/// 后退或前进按钮
+#[derive(Clone, Copy)]
+enum HistoryDirection {
+    Back,
+    Forward,
+}
+
+/// 后退和前进按钮：右键或长按时弹出该方向的历史记录菜单，长按弹出菜单后松开不再触发点击
+struct HistoryButton {
+    direction: HistoryDirection,
+    /// 长按计时器及按下的位置
+    press: Option<(TimerToken, Point)>,
+    /// 长按菜单已经弹出，忽略这次按下对应的松开
+    long_pressed: bool,
+}
+
+impl HistoryButton {
+    fn new(direction: HistoryDirection) -> Self {
+        HistoryButton { direction, press: None, long_pressed: false }
+    }
+
+    fn show_menu(&self, ctx: &mut EventCtx, data: &AppState, position: Point) {
+        let entries = match self.direction {
+            HistoryDirection::Back => data.back_entries(),
+            HistoryDirection::Forward => data.forward_entries(),
+        };
+        if entries.is_empty() {
+            return;
+        }
+        let menu = entries.into_iter().fold(Menu::empty(), |menu, (index, path)| {
+            menu.entry(MenuItem::new(menu_label(&path)).command(NAVIGATE_HISTORY.with(index)))
+        });
+        ctx.show_context_menu(menu, position);
+    }
+}
+
+impl<W: Widget<AppState>> Controller<AppState, W> for HistoryButton {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut AppState, env: &Env) {
+        match event {
+            Event::MouseDown(mouse) if mouse.button.is_right() => {
+                self.show_menu(ctx, data, mouse.window_pos);
+                ctx.set_handled();
+                return;
+            }
+            Event::MouseDown(mouse) if mouse.button.is_left() => {
+                self.press = Some((ctx.request_timer(LONG_PRESS_DELAY), mouse.window_pos));
+                self.long_pressed = false;
+            }
+            Event::Timer(token) if self.press.map(|(press, _)| press) == Some(*token) => {
+                if let Some((_, position)) = self.press.take() {
+                    self.long_pressed = true;
+                    self.show_menu(ctx, data, position);
+                }
+                ctx.set_handled();
+                return;
+            }
+            Event::MouseUp(mouse) if mouse.button.is_left() => {
+                self.press = None;
+                if self.long_pressed {
+                    self.long_pressed = false;
+                    ctx.set_active(false);
+                    ctx.request_paint();
+                    ctx.set_handled();
+                    return;
+                }
+            }
+            _ => {}
+        }
+        child.event(ctx, event, data, env);
+    }
+}
+
+/// 构建历史下拉按钮：列出导航历史和最常访问的目录
+fn build_history_button() -> impl Widget<AppState> {
+    Align::centered(
+        Button::new("▾")
+            .controller(HistoryMenuButton)
+            .fix_width(20.0)
+            .fix_height(36.0)
+            .border(Color::TRANSPARENT, 0.0)
+    )
+}
+
+/// 点击时在按钮下方弹出历史菜单
+struct HistoryMenuButton;
+
+impl<W: Widget<AppState>> Controller<AppState, W> for HistoryMenuButton {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut AppState, env: &Env) {
+        if let Event::MouseDown(mouse) = event {
+            if mouse.button.is_left() {
+                ctx.show_context_menu(history_menu(data), mouse.window_pos);
+                ctx.set_handled();
+                return;
+            }
+        }
+        child.event(ctx, event, data, env);
+    }
+}
+
+/// 历史菜单：导航历史（最新的在上，当前位置打勾）和常去的目录
+fn history_menu(data: &AppState) -> Menu<AppState> {
+    let mut menu = Menu::empty();
+
+    for (index, path) in data.navigation_history.iter().enumerate().rev() {
+        menu = menu.entry(
+            MenuItem::new(menu_label(path))
+                .selected_if(move |data: &AppState, _env| data.history_position == index)
+                .command(NAVIGATE_HISTORY.with(index))
+        );
+    }
+
+    let frecent = locations::frecent(FRECENT_MENU_SIZE);
+    if !frecent.is_empty() {
+        menu = menu
+            .separator()
+            .entry(MenuItem::new("常去的目录").enabled(false));
+        for path in frecent {
+            menu = menu.entry(MenuItem::new(menu_label(&path)).command(SELECT_DIRECTORY.with(path)));
+        }
+    }
+
+    menu.separator()
+        .entry(MenuItem::new(format!("快速跳转...{}", shortcut_label("navigate.quick_jump"))).command(SHOW_QUICK_JUMP))
+}
+
+/// 菜单项文字：目录名和完整路径
+fn menu_label(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => format!("{}    {}", name.to_string_lossy(), path.display()),
+        None => path.display().to_string(),
+    }
+}
+
 /// 构建上级目录按钮
 fn build_up_button() -> impl Widget<AppState> {
     Align::centered(
         Button::new("⬆")
-            .on_click(|ctx, data: &mut AppState, _env| {
-                if let Some(current_path) = data.selected_path.clone() {
-                    if let Some(parent) = current_path.parent() {
-                        // 创建父目录路径的拷贝
-                        let parent_path = parent.to_path_buf();
-                        
-                        // 添加到历史记录
-                        data.add_to_history(parent_path.clone());
-                        
-                        // 发送命令导航到父目录
-                        ctx.submit_command(SELECT_DIRECTORY.with(parent_path));
-                    }
-                }
+            .on_click(|ctx, _data: &mut AppState, _env| {
+                ctx.submit_command(NAVIGATE_UP);
             })
             .fix_width(36.0)
             .fix_height(36.0)
@@ -105,11 +238,8 @@ fn build_up_button() -> impl Widget<AppState> {
 fn build_refresh_button() -> impl Widget<AppState> {
     Align::centered(
         Button::new("🔄")
-            .on_click(|ctx, data: &mut AppState, _env| {
-                if let Some(path) = &data.selected_path {
-                    // 重新导航到当前路径，刷新内容
-                    ctx.submit_command(SELECT_DIRECTORY.with(path.clone()));
-                }
+            .on_click(|ctx, _data: &mut AppState, _env| {
+                ctx.submit_command(REFRESH_DIRECTORY);
             })
             .fix_width(36.0)
             .fix_height(36.0)
@@ -122,9 +252,7 @@ fn build_home_button() -> impl Widget<AppState> {
     Align::centered(
         Button::new("🏠")
             .on_click(|ctx, _data: &mut AppState, _env| {
-                if let Some(home_dir) = dirs::home_dir() {
-                    ctx.submit_command(SELECT_DIRECTORY.with(home_dir));
-                }
+                ctx.submit_command(NAVIGATE_HOME);
             })
             .fix_width(36.0)
             .fix_height(36.0)
@@ -136,9 +264,8 @@ fn build_home_button() -> impl Widget<AppState> {
 fn build_goto_button() -> impl Widget<AppState> {
     Align::centered(
         Button::new("➥")
-            .on_click(|_ctx, _data: &mut AppState, _env| {
-                // 这里暂时不需要操作，因为TextBox的lens已经更新了path
-                // 地址变更会自动通过lens处理
+            .on_click(|ctx, data: &mut AppState, _env| {
+                submit_address(ctx, data);
             })
             .fix_width(36.0)
             .fix_height(36.0)
@@ -146,41 +273,65 @@ fn build_goto_button() -> impl Widget<AppState> {
     )
 }
 
-/// 为当前路径字符串创建Lens
+/// 打开地址栏中输入的路径（没有输入时不做任何事）
+fn submit_address(ctx: &mut EventCtx, data: &AppState) {
+    if let Some(input) = &data.address_input {
+        let path = input.trim();
+        if !path.is_empty() {
+            ctx.submit_command(SELECT_DIRECTORY.with(PathBuf::from(path)));
+        }
+    }
+}
+
+/// 地址栏的按键：回车打开输入的路径，Esc 放弃输入、恢复显示当前目录
+struct AddressInput;
+
+impl<W: Widget<AppState>> Controller<AppState, W> for AddressInput {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut AppState, env: &Env) {
+        if let Event::KeyDown(key) = event {
+            match key.key {
+                KbKey::Enter => {
+                    submit_address(ctx, data);
+                    ctx.set_handled();
+                    return;
+                }
+                KbKey::Escape => {
+                    data.address_input = None;
+                    ctx.set_handled();
+                    return;
+                }
+                _ => {}
+            }
+        }
+        child.event(ctx, event, data, env);
+    }
+}
+
+/// 地址栏文字的Lens：显示正在输入的内容或当前目录，输入只修改 `address_input`，确认后才导航
 #[derive(Clone, Data)]
 pub struct CurrentPathLens;
 
 impl Lens<AppState, String> for CurrentPathLens {
     fn with<V, F: FnOnce(&String) -> V>(&self, data: &AppState, f: F) -> V {
-        let path_string = match &data.selected_path {
-            Some(path) => path.to_string_lossy().into_owned(),
-            None => String::new(),
-        };
-        f(&path_string)
+        f(&address_text(data))
     }
 
     fn with_mut<V, F: FnOnce(&mut String) -> V>(&self, data: &mut AppState, f: F) -> V {
-        let mut path_string = match &data.selected_path {
-            Some(path) => path.to_string_lossy().into_owned(),
-            None => String::new(),
-        };
-        
-        let result = f(&mut path_string);
-        
-        // 只有当路径字符串发生变化时才更新
-        if let Some(ref old_path) = data.selected_path {
-            if old_path.to_string_lossy() != path_string {
-                let new_path = PathBuf::from(&path_string);
-                data.add_to_history(new_path.clone());
-                data.selected_path = Some(new_path);
-                // 导航逻辑将通过委托处理
-            }
-        } else if !path_string.is_empty() {
-            let new_path = PathBuf::from(&path_string);
-            data.add_to_history(new_path.clone());
-            data.selected_path = Some(new_path);
+        let mut text = address_text(data);
+        let result = f(&mut text);
+
+        // 只有内容发生变化时才记为正在输入
+        if text != address_text(data) {
+            data.address_input = Some(text);
         }
-        
         result
     }
-} 
\ No newline at end of file
+}
+
+fn address_text(data: &AppState) -> String {
+    match (&data.address_input, &data.selected_path) {
+        (Some(input), _) => input.clone(),
+        (None, Some(path)) => path.to_string_lossy().into_owned(),
+        (None, None) => String::new(),
+    }
+}
@@ -0,0 +1,241 @@
+use druid::widget::{
+    Button, Checkbox, Controller, CrossAxisAlignment, Either, Flex, Label, LineBreaking,
+    RadioGroup, Scroll, TextBox
+};
+use druid::{commands, Env, Event, EventCtx, Lens, Widget, WidgetExt};
+use crate::commands::APPLY_PERMISSIONS;
+use crate::models::AppState;
+use crate::permissions::{parse_octal, format_octal, PermissionEdit, RecursiveTarget};
+use crate::properties::FileProperties;
+use crate::utils::{format_size_exact, format_thousands};
+use super::job_status::build_job_status;
+use super::theme;
+
+/// 构建属性窗口
+pub fn build_properties_dialog() -> impl Widget<AppState> {
+    theme::themed(build_properties_view())
+}
+
+/// 构建属性窗口的内容
+fn build_properties_view() -> impl Widget<AppState> {
+    let details = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(property_row("名称", |p| p.name.clone()))
+        .with_child(property_row("位置", |p| p.path.display().to_string()))
+        .with_child(property_row("类型", |p| p.type_description.clone()))
+        .with_child(property_row("链接目标", |p| or_dash(&p.link_target)))
+        .with_child(property_row("大小", |p| format_size_exact(p.size)))
+        .with_child(property_row("包含", directory_contents))
+        .with_child(property_row("创建时间", |p| p.created.clone()))
+        .with_child(property_row("修改时间", |p| p.modified.clone()))
+        .with_child(property_row("访问时间", |p| p.accessed.clone()))
+        .with_child(property_row("权限", |p| or_dash(&p.permissions)))
+        .with_child(property_row("所有者/组", |p| or_dash(&p.owner)))
+        .with_child(property_row("属性", |p| or_dash(&p.attributes)))
+        .with_child(property_row("inode", |p| or_dash(&p.inode)))
+        .with_child(property_row("硬链接数", |p| or_dash(&p.link_count)))
+        .with_child(
+            Label::dynamic(|p: &FileProperties, _| p.error.clone())
+                .with_line_break_mode(LineBreaking::WordWrap)
+                .with_text_color(theme::SECONDARY_TEXT_COLOR)
+                .with_text_size(12.0)
+        )
+        .lens(AppState::properties);
+
+    let buttons = Flex::row()
+        .with_flex_spacer(1.0)
+        .with_child(Button::new("关闭").on_click(|ctx, _data: &mut AppState, _env| {
+            ctx.submit_command(commands::CLOSE_WINDOW.to(ctx.window_id()));
+        }));
+
+    let content = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(details)
+        .with_spacer(10.0)
+        .with_child(section_title("修改权限"))
+        .with_child(build_permission_editor().lens(AppState::permission_edit))
+        .with_child(build_job_status(|data| data.permission_edit.job_id));
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_flex_child(Scroll::new(content).vertical().expand(), 1.0)
+        .with_spacer(10.0)
+        .with_child(buttons)
+        .padding(15.0)
+        .background(theme::BACKGROUND)
+        .expand()
+}
+
+/// 目录的递归统计：文件数量、子目录数量和总大小
+fn directory_contents(p: &FileProperties) -> String {
+    if !p.is_dir {
+        return "-".to_string();
+    }
+
+    let summary = &p.summary;
+    let mut text = format!(
+        "{} 个文件，{} 个文件夹，共 {}",
+        format_thousands(summary.file_count),
+        format_thousands(summary.dir_count),
+        format_size_exact(summary.total_size),
+    );
+    if summary.error_count > 0 {
+        text.push_str(&format!("（{} 个目录无法读取）", summary.error_count));
+    }
+    if !summary.finished {
+        text.push_str(" 计算中...");
+    }
+    text
+}
+
+/// 空值显示为 "-"
+fn or_dash(value: &str) -> String {
+    if value.is_empty() {
+        "-".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// 构建一行属性：左侧为名称，右侧为可换行的取值
+fn property_row(
+    name: &str,
+    value: impl Fn(&FileProperties) -> String + 'static,
+) -> impl Widget<FileProperties> {
+    Flex::row()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(
+            Label::new(name)
+                .with_text_color(theme::SECONDARY_TEXT_COLOR)
+                .with_text_size(14.0)
+                .fix_width(90.0)
+        )
+        .with_flex_child(
+            Label::dynamic(move |data: &FileProperties, _| value(data))
+                .with_line_break_mode(LineBreaking::WordWrap)
+                .with_text_color(theme::TEXT_COLOR)
+                .with_text_size(14.0)
+                .expand_width(),
+            1.0,
+        )
+        .padding((0.0, 4.0))
+}
+
+/// 分区标题
+fn section_title<T: druid::Data>(title: &str) -> impl Widget<T> {
+    Label::new(title)
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(15.0)
+        .padding((0.0, 6.0))
+}
+
+/// 构建权限编辑器：rwx复选框、特殊权限位、八进制权限、所有者和递归选项
+fn build_permission_editor() -> impl Widget<PermissionEdit> {
+    let grid = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(
+            Flex::row()
+                .with_child(grid_label("").fix_width(90.0))
+                .with_child(grid_label("读").fix_width(50.0))
+                .with_child(grid_label("写").fix_width(50.0))
+                .with_child(grid_label("执行").fix_width(50.0))
+        )
+        .with_child(permission_row("所有者", PermissionEdit::user_read, PermissionEdit::user_write, PermissionEdit::user_exec))
+        .with_child(permission_row("组", PermissionEdit::group_read, PermissionEdit::group_write, PermissionEdit::group_exec))
+        .with_child(permission_row("其他", PermissionEdit::other_read, PermissionEdit::other_write, PermissionEdit::other_exec));
+
+    let special = Flex::row()
+        .with_child(Checkbox::new("setuid").lens(PermissionEdit::setuid))
+        .with_spacer(12.0)
+        .with_child(Checkbox::new("setgid").lens(PermissionEdit::setgid))
+        .with_spacer(12.0)
+        .with_child(Checkbox::new("粘滞位").lens(PermissionEdit::sticky));
+
+    let recursive_target = RadioGroup::row(vec![
+        ("文件和目录", RecursiveTarget::All),
+        ("仅文件", RecursiveTarget::FilesOnly),
+        ("仅目录", RecursiveTarget::DirectoriesOnly),
+    ])
+    .lens(PermissionEdit::recursive_target);
+
+    let notice = Label::dynamic(|data: &PermissionEdit, _| data.notice.clone())
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .with_text_color(theme::SECONDARY_TEXT_COLOR)
+        .with_text_size(12.0);
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(grid)
+        .with_spacer(6.0)
+        .with_child(special)
+        .with_child(editor_row("八进制", TextBox::new().lens(PermissionEdit::octal)))
+        .with_child(editor_row("所有者", TextBox::new().with_placeholder("不修改").lens(PermissionEdit::owner)))
+        .with_child(editor_row("组", TextBox::new().with_placeholder("不修改").lens(PermissionEdit::group)))
+        .with_child(Checkbox::new("递归应用到目录中的所有条目").lens(PermissionEdit::recursive))
+        .with_child(Either::new(
+            |data: &PermissionEdit, _| data.recursive,
+            recursive_target.padding((20.0, 4.0, 0.0, 4.0)),
+            Flex::row(),
+        ))
+        .with_child(notice.expand_width())
+        .with_child(
+            Flex::row()
+                .with_flex_spacer(1.0)
+                .with_child(Button::new("应用").on_click(|ctx, _data: &mut PermissionEdit, _env| {
+                    ctx.submit_command(APPLY_PERMISSIONS);
+                }))
+        )
+        .controller(PermissionSyncController)
+}
+
+/// 权限网格中的标签
+fn grid_label(text: &str) -> Label<PermissionEdit> {
+    Label::new(text)
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(14.0)
+}
+
+/// 权限网格中的一行：读、写、执行三个复选框
+fn permission_row(
+    name: &str,
+    read: impl Lens<PermissionEdit, bool> + 'static,
+    write: impl Lens<PermissionEdit, bool> + 'static,
+    exec: impl Lens<PermissionEdit, bool> + 'static,
+) -> impl Widget<PermissionEdit> {
+    Flex::row()
+        .with_child(grid_label(name).fix_width(90.0))
+        .with_child(Checkbox::new("").lens(read).fix_width(50.0))
+        .with_child(Checkbox::new("").lens(write).fix_width(50.0))
+        .with_child(Checkbox::new("").lens(exec).fix_width(50.0))
+        .padding((0.0, 2.0))
+}
+
+/// 权限编辑器中的一行：左侧为名称，右侧为输入框
+fn editor_row(name: &str, editor: impl Widget<PermissionEdit> + 'static) -> impl Widget<PermissionEdit> {
+    Flex::row()
+        .with_child(grid_label(name).fix_width(90.0))
+        .with_child(editor.fix_width(160.0))
+        .padding((0.0, 4.0))
+}
+
+/// 保持复选框和八进制文本同步：输入合法的八进制时更新复选框，勾选复选框时更新八进制文本
+struct PermissionSyncController;
+
+impl<W: Widget<PermissionEdit>> Controller<PermissionEdit, W> for PermissionSyncController {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut PermissionEdit, env: &Env) {
+        let old_octal = data.octal.clone();
+        let old_mode = data.mode();
+        child.event(ctx, event, data, env);
+
+        if data.octal != old_octal {
+            if let Some(mode) = parse_octal(&data.octal) {
+                // 保留用户输入的文本，只更新复选框
+                let octal = data.octal.clone();
+                data.set_mode(mode);
+                data.octal = octal;
+            }
+        } else if data.mode() != old_mode {
+            data.octal = format_octal(data.mode());
+        }
+    }
+}
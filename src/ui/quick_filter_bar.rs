@@ -0,0 +1,96 @@
+use druid::widget::{Button, Controller, Flex, Label, LineBreaking, Painter, TextBox};
+use druid::{Color, Env, Event, EventCtx, KbKey, LensExt, RenderContext, Widget, WidgetExt};
+use crate::commands::{FOCUS_PANES, FOCUS_QUICK_FILTER, QUICK_FILTER_CHANGED};
+use crate::keymap::shortcut_label;
+use crate::models::AppState;
+use crate::quick_filter::{KindFilter, QuickFilterState, KIND_FILTERS};
+use super::theme;
+
+/// 筛选文字无效时提示的颜色
+const FILTER_ERROR_COLOR: Color = Color::rgb8(0xE0, 0x6C, 0x4C);
+
+/// 构建文件列表上方的快速筛选栏：筛选文字、写法切换、类型切换和错误提示
+pub fn build_quick_filter_bar() -> impl Widget<AppState> {
+    let input = TextBox::new()
+        .with_placeholder(format!("筛选当前目录{}", shortcut_label("view.quick_filter")))
+        .lens(AppState::quick_filter.then(QuickFilterState::text))
+        .controller(QuickFilterInput)
+        .fix_width(220.0);
+
+    // 点击依次切换文字、通配符和正则表达式
+    let syntax = Button::dynamic(|data: &AppState, _| data.quick_filter.syntax.label().to_string())
+        .on_click(|ctx, data: &mut AppState, _env| {
+            data.quick_filter.syntax = data.quick_filter.syntax.next();
+            ctx.submit_command(QUICK_FILTER_CHANGED);
+        });
+
+    let mut kinds = Flex::row();
+    for &(kind, name) in KIND_FILTERS {
+        kinds.add_child(build_kind_toggle(kind, name));
+    }
+
+    let error = Label::dynamic(|data: &AppState, _| data.quick_filter.error.clone())
+        .with_line_break_mode(LineBreaking::Clip)
+        .with_text_color(FILTER_ERROR_COLOR)
+        .with_text_size(12.0);
+
+    Flex::row()
+        .with_child(input)
+        .with_spacer(4.0)
+        .with_child(syntax)
+        .with_spacer(8.0)
+        .with_child(kinds)
+        .with_spacer(8.0)
+        .with_flex_child(error.expand_width(), 1.0)
+        .padding((8.0, 4.0))
+        .background(theme::NAV_BAR_BACKGROUND)
+        .expand_width()
+}
+
+/// 类型切换按钮，当前选择的类型以选中颜色标出，再次点击恢复为全部
+fn build_kind_toggle(kind: KindFilter, name: &'static str) -> impl Widget<AppState> {
+    Label::new(name)
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(12.0)
+        .padding((6.0, 2.0))
+        .background(Painter::new(move |ctx, data: &AppState, env| {
+            if data.quick_filter.kind == kind {
+                let rect = ctx.size().to_rect().to_rounded_rect(3.0);
+                ctx.fill(rect, &env.get(theme::SELECTION_BACKGROUND));
+            }
+        }))
+        .on_click(move |ctx, data: &mut AppState, _env| {
+            data.quick_filter.kind = if data.quick_filter.kind == kind { KindFilter::All } else { kind };
+            ctx.submit_command(QUICK_FILTER_CHANGED);
+        })
+}
+
+/// 筛选输入框：文字变化时重新筛选，Esc 清除筛选并让文件列表重新获得键盘焦点
+struct QuickFilterInput;
+
+impl<W: Widget<AppState>> Controller<AppState, W> for QuickFilterInput {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut AppState, env: &Env) {
+        match event {
+            Event::Command(cmd) if cmd.is(FOCUS_QUICK_FILTER) => {
+                ctx.request_focus();
+                ctx.set_handled();
+                return;
+            }
+            Event::KeyDown(key) if key.key == KbKey::Escape => {
+                data.quick_filter.text.clear();
+                data.quick_filter.kind = KindFilter::All;
+                ctx.submit_command(QUICK_FILTER_CHANGED);
+                ctx.submit_command(FOCUS_PANES);
+                ctx.set_handled();
+                return;
+            }
+            _ => {}
+        }
+
+        let text = data.quick_filter.text.clone();
+        child.event(ctx, event, data, env);
+        if data.quick_filter.text != text {
+            ctx.submit_command(QUICK_FILTER_CHANGED);
+        }
+    }
+}
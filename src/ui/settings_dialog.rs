@@ -0,0 +1,103 @@
+use druid::widget::{Button, Checkbox, CrossAxisAlignment, Flex, Label, LineBreaking, TextBox};
+use druid::text::ParseFormatter;
+use druid::{commands, Widget, WidgetExt};
+use crate::models::AppState;
+use crate::settings::Settings;
+use crate::commands::SAVE_SETTINGS;
+use super::theme;
+
+/// 构建设置窗口
+pub fn build_settings_dialog() -> impl Widget<AppState> {
+    theme::themed(build_settings_form())
+}
+
+/// 构建设置窗口的内容
+fn build_settings_form() -> impl Widget<AppState> {
+    let form = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(setting_row(
+            "每页条目数",
+            TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(Settings::page_size),
+        ))
+        .with_child(setting_row(
+            "后台加载上限",
+            TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(Settings::background_load_limit),
+        ))
+        .with_child(setting_row(
+            "目录缓存数量",
+            TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(Settings::max_cache_entries),
+        ))
+        .with_child(setting_row(
+            "隐藏文件",
+            Checkbox::new("显示隐藏文件").lens(Settings::show_hidden),
+        ))
+        .with_child(setting_row(
+            "文件夹大小",
+            Checkbox::new("计算文件夹大小").lens(Settings::compute_folder_sizes),
+        ))
+        .with_child(setting_row(
+            "启动目录",
+            TextBox::new()
+                .with_placeholder("留空则恢复上次的目录")
+                .lens(Settings::start_directory),
+        ))
+        .with_child(setting_row(
+            "时间格式",
+            TextBox::new()
+                .with_placeholder("relative 或 %Y-%m-%d %H:%M")
+                .lens(Settings::date_format),
+        ))
+        .with_child(setting_row(
+            "主题",
+            TextBox::new().lens(Settings::theme),
+        ))
+        .lens(AppState::settings_draft);
+
+    // 配置文件警告、校验错误和保存结果
+    let notice = Label::dynamic(|data: &AppState, _| data.settings_notice.clone())
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .with_text_color(theme::SECONDARY_TEXT_COLOR)
+        .with_text_size(12.0);
+
+    let buttons = Flex::row()
+        .with_flex_spacer(1.0)
+        .with_child(Button::new("保存").on_click(|ctx, _data: &mut AppState, _env| {
+            ctx.submit_command(SAVE_SETTINGS);
+        }))
+        .with_spacer(8.0)
+        .with_child(Button::new("关闭").on_click(|ctx, _data: &mut AppState, _env| {
+            ctx.submit_command(commands::CLOSE_WINDOW.to(ctx.window_id()));
+        }));
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(form)
+        .with_spacer(10.0)
+        .with_flex_child(notice.expand_width(), 1.0)
+        .with_child(buttons)
+        .padding(15.0)
+        .background(theme::BACKGROUND)
+        .expand()
+}
+
+/// 构建一行设置项：左侧为名称，右侧为编辑控件
+fn setting_row(name: &str, editor: impl Widget<Settings> + 'static) -> impl Widget<Settings> {
+    Flex::row()
+        .with_child(
+            Label::new(name)
+                .with_text_color(theme::TEXT_COLOR)
+                .with_text_size(14.0)
+                .fix_width(110.0)
+        )
+        .with_flex_child(editor.expand_width(), 1.0)
+        .padding((0.0, 4.0))
+}
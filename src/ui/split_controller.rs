@@ -0,0 +1,41 @@
+use druid::widget::Controller;
+use druid::{Env, Event, EventCtx, LifeCycle, LifeCycleCtx, Widget};
+use crate::models::AppState;
+
+/// 分割视图之外占用的宽度：主布局两侧各5像素的内边距加上6像素的分隔条
+const NON_PANE_WIDTH: f64 = 16.0;
+
+/// 分割视图左侧面板的控制器，记录用户拖动后的分割位置
+pub struct SplitPointController {
+    /// 最近一次布局得到的左侧面板宽度
+    pane_width: f64,
+}
+
+impl SplitPointController {
+    pub fn new() -> Self {
+        Self { pane_width: 0.0 }
+    }
+}
+
+impl<W: Widget<AppState>> Controller<AppState, W> for SplitPointController {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut AppState, env: &Env) {
+        // 布局事件中无法修改数据，因此在下一个事件中同步分割位置
+        let total_width = data.window_size.0 - NON_PANE_WIDTH;
+        if self.pane_width > 0.0 && total_width > 0.0 {
+            let split_point = (self.pane_width / total_width).clamp(0.05, 0.95);
+            if (split_point - data.split_point).abs() > 0.005 {
+                data.split_point = split_point;
+            }
+        }
+
+        child.event(ctx, event, data, env);
+    }
+
+    fn lifecycle(&mut self, child: &mut W, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &AppState, env: &Env) {
+        if let LifeCycle::Size(size) = event {
+            self.pane_width = size.width;
+        }
+
+        child.lifecycle(ctx, event, data, env);
+    }
+}
@@ -0,0 +1,104 @@
+use druid::widget::{Flex, Label, LineBreaking};
+use druid::{Widget, WidgetExt};
+use crate::models::AppState;
+use crate::utils::format_size;
+use crate::volumes::drive_for_path;
+use super::theme;
+
+/// 状态栏中各部分之间的间距
+const SECTION_SPACING: f64 = 16.0;
+
+/// 构建主窗口底部的状态栏：目录统计、选择统计、加载状态、后台任务和可用空间
+pub fn build_status_bar() -> impl Widget<AppState> {
+    Flex::row()
+        .with_child(status_label(directory_text))
+        .with_spacer(SECTION_SPACING)
+        .with_child(status_label(selection_text))
+        .with_spacer(SECTION_SPACING)
+        .with_child(status_label(loading_text))
+        .with_spacer(SECTION_SPACING)
+        .with_flex_child(status_label(jobs_text).expand_width(), 1.0)
+        .with_spacer(SECTION_SPACING)
+        .with_child(status_label(free_space_text))
+        .padding((8.0, 4.0))
+        .background(theme::NAV_BAR_BACKGROUND)
+        .expand_width()
+}
+
+fn status_label(text: fn(&AppState) -> String) -> Label<AppState> {
+    Label::dynamic(move |data: &AppState, _| text(data))
+        .with_line_break_mode(LineBreaking::Clip)
+        .with_text_color(theme::SECONDARY_TEXT_COLOR)
+        .with_text_size(12.0)
+}
+
+/// 当前目录（筛选时为筛选结果）的项目数量（分为文件夹和文件）和文件的总大小
+fn directory_text(data: &AppState) -> String {
+    if data.drive_overview || data.selected_path.is_none() {
+        return String::new();
+    }
+    let folders = data.current_dir_files.iter().filter(|file| file.kind.is_directory()).count();
+    let files = data.current_dir_files.len() - folders;
+    let size: u64 = data.current_dir_files.iter()
+        .filter(|file| !file.kind.is_directory())
+        .map(|file| file.size)
+        .sum();
+    let summary = format!("{} 个项目（{} 个文件夹，{} 个文件），共 {}", folders + files, folders, files, format_size(size));
+    // 快速筛选时同时显示完整列表的项目数量
+    match &data.quick_filter.source {
+        Some(source) => format!("已筛选：{} / {} 项 · {}", folders + files, source.len(), summary),
+        None => summary,
+    }
+}
+
+/// 选中的项目数量和大小（只统计文件，文件夹的大小在计算完成后计入）
+fn selection_text(data: &AppState) -> String {
+    if data.drive_overview {
+        return String::new();
+    }
+    let selected: Vec<_> = data.current_dir_files.iter().filter(|file| file.is_selected).collect();
+    if selected.is_empty() {
+        return String::new();
+    }
+    let size: u64 = selected.iter()
+        .map(|file| match &file.folder_size {
+            Some(folder_size) => folder_size.bytes,
+            None => file.size,
+        })
+        .sum();
+    format!("已选择 {} 项，{}", selected.len(), format_size(size))
+}
+
+/// 大目录在后台分页加载时的提示
+fn loading_text(data: &AppState) -> String {
+    match &data.loading_path {
+        Some(path) if data.selected_path.as_ref() == Some(path) => "正在加载其余文件…".to_string(),
+        _ => String::new(),
+    }
+}
+
+/// 正在运行的后台任务：只有一个时显示其进度，多个时显示数量和第一个的进度；
+/// 没有任务时显示最近的提示
+fn jobs_text(data: &AppState) -> String {
+    let running: Vec<_> = data.jobs.iter().filter(|job| !job.finished).collect();
+    match running.as_slice() {
+        [] => data.status_notice.clone(),
+        [job] => job.summary(),
+        [first, ..] => format!("{} 个任务进行中 · {}", running.len(), first.summary()),
+    }
+}
+
+/// 当前目录所在卷的可用空间，如 "根目录 (/)：79.1 GB 可用，共 252.0 GB"
+fn free_space_text(data: &AppState) -> String {
+    let (free, total) = data.current_space;
+    let path = match &data.selected_path {
+        Some(path) if total > 0 => path,
+        _ => return String::new(),
+    };
+    let space = format!("{} 可用，共 {}", format_size(free), format_size(total));
+    match drive_for_path(&data.root, path) {
+        Some(drive) if drive.name == drive.path.to_string_lossy() => format!("{}：{}", drive.name, space),
+        Some(drive) => format!("{} ({})：{}", drive.name, drive.path.display(), space),
+        None => space,
+    }
+}
@@ -0,0 +1,196 @@
+use druid::widget::{Button, Checkbox, CrossAxisAlignment, Either, Flex, Label, LineBreaking, List, Scroll, TextBox};
+use druid::{commands, Widget, WidgetExt};
+use crate::commands::{DELETE_SYNC_JOB, PREVIEW_SYNC, RUN_SYNC, SAVE_SYNC_JOB, SELECT_SYNC_JOB};
+use crate::models::AppState;
+use crate::sync::{SyncAction, SyncJob, SyncJobDraft, SyncState};
+use crate::utils::format_size;
+use super::job_status::build_job_status;
+use super::theme;
+
+/// 构建同步任务窗口
+pub fn build_sync_window() -> impl Widget<AppState> {
+    theme::themed(build_sync_view())
+}
+
+/// 构建同步任务窗口的内容：任务列表、任务编辑、预览和执行日志
+fn build_sync_view() -> impl Widget<AppState> {
+    let job_list = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(label("同步任务"))
+        .with_spacer(4.0)
+        .with_flex_child(
+            Scroll::new(List::new(job_row).lens(SyncState::jobs)).vertical().expand(),
+            1.0,
+        )
+        .with_spacer(4.0)
+        .with_child(Button::new("新建任务").on_click(|_ctx, data: &mut SyncState, _env| {
+            data.new_job();
+        }))
+        .fix_width(180.0);
+
+    let editor_buttons = Flex::row()
+        .with_child(Button::new("保存任务").on_click(|ctx, _data: &mut SyncState, _env| {
+            ctx.submit_command(SAVE_SYNC_JOB);
+        }))
+        .with_spacer(8.0)
+        .with_child(
+            Button::new("删除任务")
+                .on_click(|ctx, _data: &mut SyncState, _env| {
+                    ctx.submit_command(DELETE_SYNC_JOB);
+                })
+                .disabled_if(|data: &SyncState, _env| data.selected.is_none())
+        )
+        .with_flex_spacer(1.0)
+        .with_child(Button::new("预览").on_click(|ctx, _data: &mut SyncState, _env| {
+            ctx.submit_command(PREVIEW_SYNC);
+        }))
+        .with_spacer(8.0)
+        .with_child(
+            Button::new("执行")
+                .on_click(|ctx, _data: &mut SyncState, _env| {
+                    ctx.submit_command(RUN_SYNC);
+                })
+                .disabled_if(|data: &SyncState, _env| !data.can_run())
+        );
+
+    // 执行后显示日志，否则显示预览的操作
+    let output = Either::new(
+        |data: &SyncState, _| data.log.is_empty(),
+        Flex::column()
+            .cross_axis_alignment(CrossAxisAlignment::Start)
+            .with_child(
+                Label::dynamic(|data: &SyncState, _| {
+                    if data.planned_for.is_some() {
+                        data.plan_summary()
+                    } else {
+                        "预览后显示将要执行的操作".to_string()
+                    }
+                })
+                .with_text_color(theme::TEXT_COLOR)
+                .with_text_size(13.0)
+            )
+            .with_flex_child(
+                Scroll::new(List::new(action_row).lens(SyncState::actions)).vertical().expand(),
+                1.0,
+            ),
+        Scroll::new(List::new(log_row).lens(SyncState::log)).vertical().expand(),
+    );
+
+    let notice = Label::dynamic(|data: &SyncState, _| data.notice.clone())
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .with_text_color(theme::SECONDARY_TEXT_COLOR)
+        .with_text_size(12.0);
+
+    let details = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(build_editor().lens(SyncState::draft))
+        .with_spacer(6.0)
+        .with_child(editor_buttons)
+        .with_spacer(8.0)
+        .with_flex_child(output, 1.0)
+        .with_child(notice.expand_width());
+
+    let state = Flex::row()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(job_list)
+        .with_spacer(12.0)
+        .with_flex_child(details, 1.0)
+        .lens(AppState::sync);
+
+    let buttons = Flex::row()
+        .with_flex_spacer(1.0)
+        .with_child(Button::new("关闭").on_click(|ctx, _data: &mut AppState, _env| {
+            ctx.submit_command(commands::CLOSE_WINDOW.to(ctx.window_id()));
+        }));
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_flex_child(state, 1.0)
+        .with_spacer(6.0)
+        .with_child(build_job_status(|data| data.sync.run_job_id.or(data.sync.preview_job_id)))
+        .with_spacer(6.0)
+        .with_child(buttons)
+        .padding(12.0)
+        .background(theme::BACKGROUND)
+        .expand()
+}
+
+/// 任务的编辑区域
+fn build_editor() -> impl Widget<SyncJobDraft> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(editor_row("名称", TextBox::new().lens(SyncJobDraft::name).expand_width()))
+        .with_child(editor_row("源目录", TextBox::new().lens(SyncJobDraft::source).expand_width()))
+        .with_child(editor_row("目标目录", TextBox::new().lens(SyncJobDraft::destination).expand_width()))
+        .with_child(editor_row(
+            "包含",
+            TextBox::new()
+                .with_placeholder("为空时包含所有文件，多个模式用分号分隔，如 *.jpg; docs/**")
+                .lens(SyncJobDraft::include)
+                .expand_width(),
+        ))
+        .with_child(editor_row(
+            "排除",
+            TextBox::new()
+                .with_placeholder("如 .git; **/*.tmp")
+                .lens(SyncJobDraft::exclude)
+                .expand_width(),
+        ))
+        .with_child(editor_row(
+            "选项",
+            Flex::row()
+                .with_child(Checkbox::new("删除目标中多余的文件").lens(SyncJobDraft::delete_extra))
+                .with_spacer(12.0)
+                .with_child(Checkbox::new("按内容比较").lens(SyncJobDraft::use_checksum)),
+        ))
+}
+
+fn editor_row(name: &str, editor: impl Widget<SyncJobDraft> + 'static) -> impl Widget<SyncJobDraft> {
+    Flex::row()
+        .with_child(label(name).fix_width(70.0))
+        .with_flex_child(editor, 1.0)
+        .padding((0.0, 3.0))
+}
+
+/// 任务列表中的一行，点击后编辑该任务
+fn job_row() -> impl Widget<SyncJob> {
+    Label::dynamic(|job: &SyncJob, _| job.name.clone())
+        .with_line_break_mode(LineBreaking::Clip)
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(13.0)
+        .padding((4.0, 4.0))
+        .expand_width()
+        .on_click(|ctx, job: &mut SyncJob, _env| {
+            ctx.submit_command(SELECT_SYNC_JOB.with(job.name.clone()));
+        })
+}
+
+/// 预览中的一个操作
+fn action_row() -> impl Widget<SyncAction> {
+    Label::dynamic(|action: &SyncAction, _| {
+        if action.size > 0 {
+            format!("{}  {}（{}）", action.kind.name(), action.relative.display(), format_size(action.size))
+        } else {
+            format!("{}  {}", action.kind.name(), action.relative.display())
+        }
+    })
+    .with_line_break_mode(LineBreaking::Clip)
+    .with_text_color(theme::TEXT_COLOR)
+    .with_text_size(13.0)
+    .padding((0.0, 1.0))
+    .expand_width()
+}
+
+fn log_row() -> impl Widget<String> {
+    Label::dynamic(|line: &String, _| line.clone())
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(12.0)
+        .expand_width()
+}
+
+fn label<T: druid::Data>(text: &str) -> Label<T> {
+    Label::new(text)
+        .with_text_color(theme::TEXT_COLOR)
+        .with_text_size(13.0)
+}
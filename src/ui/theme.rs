@@ -0,0 +1,448 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use druid::widget::EnvScope;
+use druid::{Color, Data, Env, Key, Widget};
+use serde::Deserialize;
+
+use crate::file_types::{self, FileCategory, FileKind};
+use crate::models::AppState;
+use crate::settings;
+use super::constants as dark;
+
+/// 主题文件所在的子目录（位于配置目录下）
+const THEMES_DIR_NAME: &str = "themes";
+
+/// 内置主题名称
+pub const BUILTIN_THEMES: &[&str] = &["dark", "light", "lemon"];
+
+/// 默认主题名称
+pub const DEFAULT_THEME: &str = "dark";
+
+// 主题颜色对应的Env键
+/// 窗口背景色
+pub const BACKGROUND: Key<Color> = Key::new("lemon-explorer.theme.background");
+/// 导航栏背景色
+pub const NAV_BAR_BACKGROUND: Key<Color> = Key::new("lemon-explorer.theme.nav-bar-background");
+/// 表头背景色
+pub const HEADER_BACKGROUND: Key<Color> = Key::new("lemon-explorer.theme.header-background");
+/// 文件列表背景色
+pub const LIST_BACKGROUND: Key<Color> = Key::new("lemon-explorer.theme.list-background");
+/// 目录树背景色
+pub const TREE_BACKGROUND: Key<Color> = Key::new("lemon-explorer.theme.tree-background");
+/// 选中项背景色
+pub const SELECTION_BACKGROUND: Key<Color> = Key::new("lemon-explorer.theme.selection-background");
+/// 键盘焦点框颜色（需要与选中背景色形成对比）
+pub const FOCUS_RING: Key<Color> = Key::new("lemon-explorer.theme.focus-ring");
+/// 磁盘使用条中已用部分的颜色
+pub const USAGE_BAR_COLOR: Key<Color> = Key::new("lemon-explorer.theme.usage-bar");
+/// 可用空间不足时使用条的颜色
+pub const USAGE_WARNING_COLOR: Key<Color> = Key::new("lemon-explorer.theme.usage-warning");
+/// 快速筛选时名称中匹配部分的颜色
+pub const MATCH_HIGHLIGHT: Key<Color> = Key::new("lemon-explorer.theme.match-highlight");
+/// 文本颜色
+pub const TEXT_COLOR: Key<Color> = Key::new("lemon-explorer.theme.text");
+/// 次要文本颜色（提示信息等）
+pub const SECONDARY_TEXT_COLOR: Key<Color> = Key::new("lemon-explorer.theme.secondary-text");
+/// 控件图标颜色
+pub const ICON_COLOR: Key<Color> = Key::new("lemon-explorer.theme.icon");
+/// 文件夹颜色
+pub const FOLDER_COLOR: Key<Color> = Key::new("lemon-explorer.theme.folder");
+/// 文本文件颜色
+pub const TEXT_FILE_COLOR: Key<Color> = Key::new("lemon-explorer.theme.text-file");
+/// 图片文件颜色
+pub const IMAGE_FILE_COLOR: Key<Color> = Key::new("lemon-explorer.theme.image-file");
+/// 音频文件颜色
+pub const AUDIO_FILE_COLOR: Key<Color> = Key::new("lemon-explorer.theme.audio-file");
+/// 视频文件颜色
+pub const VIDEO_FILE_COLOR: Key<Color> = Key::new("lemon-explorer.theme.video-file");
+/// 存档文件颜色
+pub const ARCHIVE_FILE_COLOR: Key<Color> = Key::new("lemon-explorer.theme.archive-file");
+/// 可执行文件颜色
+pub const EXECUTABLE_FILE_COLOR: Key<Color> = Key::new("lemon-explorer.theme.executable-file");
+/// 常规文件颜色
+pub const REGULAR_FILE_COLOR: Key<Color> = Key::new("lemon-explorer.theme.regular-file");
+
+// 当前主题中按文件类型覆盖的颜色，供文件列表按行查找
+lazy_static::lazy_static! {
+    static ref ACTIVE_FILE_COLORS: RwLock<Arc<HashMap<String, Color>>> =
+        RwLock::new(Arc::new(HashMap::new()));
+}
+
+/// 主题调色板
+#[derive(Clone, Data, Debug, PartialEq)]
+pub struct Palette {
+    pub background: Color,
+    pub nav_bar_background: Color,
+    pub header_background: Color,
+    pub list_background: Color,
+    pub tree_background: Color,
+    pub selection_background: Color,
+    pub focus_ring: Color,
+    pub usage_bar: Color,
+    pub usage_warning: Color,
+    pub match_highlight: Color,
+    pub text: Color,
+    pub secondary_text: Color,
+    pub icon: Color,
+    pub folder: Color,
+    pub text_file: Color,
+    pub image_file: Color,
+    pub audio_file: Color,
+    pub video_file: Color,
+    pub archive_file: Color,
+    pub executable_file: Color,
+    pub regular_file: Color,
+    /// 是否为深色主题（决定druid内置控件的配色）
+    pub is_dark: bool,
+}
+
+impl Palette {
+    /// 深色主题（与原来的界面配色一致）
+    pub fn dark() -> Self {
+        Self {
+            background: dark::DARK_BACKGROUND,
+            nav_bar_background: dark::NAV_BAR_BACKGROUND,
+            header_background: dark::MID_BACKGROUND,
+            list_background: dark::LIGHT_BACKGROUND,
+            tree_background: dark::TREE_BACKGROUND,
+            selection_background: dark::SELECTED_COLOR,
+            focus_ring: dark::FOCUS_RING_COLOR,
+            usage_bar: dark::SELECTED_COLOR,
+            usage_warning: dark::USAGE_WARNING_COLOR,
+            match_highlight: dark::MATCH_HIGHLIGHT_COLOR,
+            text: dark::SELECTED_TEXT,
+            secondary_text: dark::ICON_COLOR,
+            icon: dark::ICON_COLOR,
+            folder: dark::FOLDER_COLOR,
+            text_file: dark::TEXT_FILE_COLOR,
+            image_file: dark::IMAGE_FILE_COLOR,
+            audio_file: dark::AUDIO_FILE_COLOR,
+            video_file: dark::VIDEO_FILE_COLOR,
+            archive_file: dark::ARCHIVE_FILE_COLOR,
+            executable_file: dark::EXECUTABLE_FILE_COLOR,
+            regular_file: dark::REGULAR_FILE_COLOR,
+            is_dark: true,
+        }
+    }
+
+    /// 浅色主题
+    pub fn light() -> Self {
+        Self {
+            background: Color::rgb8(245, 245, 245),
+            nav_bar_background: Color::rgb8(232, 232, 232),
+            header_background: Color::rgb8(225, 225, 225),
+            list_background: Color::rgb8(255, 255, 255),
+            tree_background: Color::rgb8(240, 242, 245),
+            selection_background: Color::rgb8(173, 203, 250),
+            focus_ring: Color::rgb8(20, 60, 150),
+            usage_bar: Color::rgb8(70, 120, 210),
+            usage_warning: Color::rgb8(210, 70, 50),
+            match_highlight: Color::rgb8(200, 110, 0),
+            text: Color::rgb8(30, 30, 30),
+            secondary_text: Color::rgb8(100, 100, 100),
+            icon: Color::rgb8(90, 90, 90),
+            folder: Color::rgb8(230, 170, 40),
+            text_file: Color::rgb8(40, 100, 170),
+            image_file: Color::rgb8(50, 140, 40),
+            audio_file: Color::rgb8(130, 60, 160),
+            video_file: Color::rgb8(200, 95, 10),
+            archive_file: Color::rgb8(110, 110, 110),
+            executable_file: Color::rgb8(20, 150, 60),
+            regular_file: Color::rgb8(60, 60, 60),
+            is_dark: false,
+        }
+    }
+
+    /// 柠檬主题（浅黄色背景，柠檬绿点缀）
+    pub fn lemon() -> Self {
+        Self {
+            background: Color::rgb8(255, 251, 224),
+            nav_bar_background: Color::rgb8(255, 236, 140),
+            header_background: Color::rgb8(250, 226, 110),
+            list_background: Color::rgb8(255, 253, 238),
+            tree_background: Color::rgb8(252, 246, 200),
+            selection_background: Color::rgb8(190, 220, 90),
+            focus_ring: Color::rgb8(110, 70, 0),
+            usage_bar: Color::rgb8(120, 170, 40),
+            usage_warning: Color::rgb8(200, 80, 30),
+            match_highlight: Color::rgb8(190, 80, 0),
+            text: Color::rgb8(60, 50, 10),
+            secondary_text: Color::rgb8(120, 105, 40),
+            icon: Color::rgb8(110, 95, 30),
+            folder: Color::rgb8(240, 190, 20),
+            text_file: Color::rgb8(70, 110, 30),
+            image_file: Color::rgb8(40, 140, 90),
+            audio_file: Color::rgb8(150, 80, 140),
+            video_file: Color::rgb8(210, 110, 20),
+            archive_file: Color::rgb8(130, 120, 80),
+            executable_file: Color::rgb8(30, 150, 50),
+            regular_file: Color::rgb8(80, 70, 30),
+            is_dark: false,
+        }
+    }
+
+    /// 内置主题的调色板
+    fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "lemon" => Some(Self::lemon()),
+            _ => None,
+        }
+    }
+
+    /// 按主题文件中的名称设置颜色，名称未知时返回 false
+    fn set(&mut self, name: &str, color: Color) -> bool {
+        let slot = match name {
+            "background" => &mut self.background,
+            "nav_bar_background" => &mut self.nav_bar_background,
+            "header_background" => &mut self.header_background,
+            "list_background" => &mut self.list_background,
+            "tree_background" => &mut self.tree_background,
+            "selection_background" => &mut self.selection_background,
+            "focus_ring" => &mut self.focus_ring,
+            "usage_bar" => &mut self.usage_bar,
+            "usage_warning" => &mut self.usage_warning,
+            "match_highlight" => &mut self.match_highlight,
+            "text" => &mut self.text,
+            "secondary_text" => &mut self.secondary_text,
+            "icon" => &mut self.icon,
+            "folder" => &mut self.folder,
+            "text_file" => &mut self.text_file,
+            "image_file" => &mut self.image_file,
+            "audio_file" => &mut self.audio_file,
+            "video_file" => &mut self.video_file,
+            "archive_file" => &mut self.archive_file,
+            "executable_file" => &mut self.executable_file,
+            "regular_file" => &mut self.regular_file,
+            _ => return false,
+        };
+        *slot = color;
+        true
+    }
+}
+
+/// 已加载的主题
+#[derive(Clone, Data, Debug)]
+pub struct Theme {
+    /// 主题名称
+    pub name: String,
+    /// 调色板
+    pub palette: Palette,
+    /// 按文件类型（扩展名或类别名）覆盖的颜色
+    pub file_colors: Arc<HashMap<String, Color>>,
+}
+
+impl Theme {
+    /// 默认主题
+    pub fn default_theme() -> Self {
+        Self {
+            name: DEFAULT_THEME.to_string(),
+            palette: Palette::dark(),
+            file_colors: Arc::new(HashMap::new()),
+        }
+    }
+
+    /// 将主题颜色写入Env，包括druid内置控件使用的颜色
+    pub fn apply_to_env(&self, env: &mut Env) {
+        let palette = &self.palette;
+
+        env.set(BACKGROUND, palette.background);
+        env.set(NAV_BAR_BACKGROUND, palette.nav_bar_background);
+        env.set(HEADER_BACKGROUND, palette.header_background);
+        env.set(LIST_BACKGROUND, palette.list_background);
+        env.set(TREE_BACKGROUND, palette.tree_background);
+        env.set(SELECTION_BACKGROUND, palette.selection_background);
+        env.set(FOCUS_RING, palette.focus_ring);
+        env.set(USAGE_BAR_COLOR, palette.usage_bar);
+        env.set(USAGE_WARNING_COLOR, palette.usage_warning);
+        env.set(MATCH_HIGHLIGHT, palette.match_highlight);
+        env.set(TEXT_COLOR, palette.text);
+        env.set(SECONDARY_TEXT_COLOR, palette.secondary_text);
+        env.set(ICON_COLOR, palette.icon);
+        env.set(FOLDER_COLOR, palette.folder);
+        env.set(TEXT_FILE_COLOR, palette.text_file);
+        env.set(IMAGE_FILE_COLOR, palette.image_file);
+        env.set(AUDIO_FILE_COLOR, palette.audio_file);
+        env.set(VIDEO_FILE_COLOR, palette.video_file);
+        env.set(ARCHIVE_FILE_COLOR, palette.archive_file);
+        env.set(EXECUTABLE_FILE_COLOR, palette.executable_file);
+        env.set(REGULAR_FILE_COLOR, palette.regular_file);
+
+        // druid内置控件（文本框、按钮、复选框等）
+        env.set(druid::theme::WINDOW_BACKGROUND_COLOR, palette.background);
+        env.set(druid::theme::TEXT_COLOR, palette.text);
+        env.set(druid::theme::PLACEHOLDER_COLOR, palette.secondary_text);
+        env.set(druid::theme::SELECTION_COLOR, palette.selection_background);
+        env.set(druid::theme::CURSOR_COLOR, palette.text);
+
+        if !palette.is_dark {
+            env.set(druid::theme::BACKGROUND_LIGHT, Color::rgb8(255, 255, 255));
+            env.set(druid::theme::BACKGROUND_DARK, palette.list_background);
+            env.set(druid::theme::BUTTON_LIGHT, palette.nav_bar_background);
+            env.set(druid::theme::BUTTON_DARK, palette.header_background);
+            env.set(druid::theme::BORDER_DARK, palette.secondary_text);
+            env.set(druid::theme::BORDER_LIGHT, palette.icon);
+            env.set(druid::theme::FOREGROUND_LIGHT, palette.text);
+            env.set(druid::theme::FOREGROUND_DARK, palette.text);
+        }
+    }
+}
+
+/// 主题文件格式
+///
+/// ```toml
+/// base = "dark"            # 基于哪个内置主题，默认为 dark
+///
+/// [colors]                 # 覆盖调色板中的颜色
+/// background = "#1e1e1e"
+///
+/// [file_colors]            # 按类型ID、扩展名或类别覆盖文件颜色
+/// rs = "#f46e42"
+/// image = "#78c355"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ThemeFile {
+    base: Option<String>,
+    colors: BTreeMap<String, String>,
+    file_colors: BTreeMap<String, String>,
+}
+
+/// 用户主题文件所在目录
+pub fn themes_dir() -> Option<PathBuf> {
+    settings::config_dir().map(|dir| dir.join(THEMES_DIR_NAME))
+}
+
+/// 查找主题文件（TOML优先）
+fn find_theme_file(name: &str) -> Option<PathBuf> {
+    let dir = themes_dir()?;
+    ["toml", "json"]
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", name, ext)))
+        .find(|path| path.is_file())
+}
+
+/// 列出所有可用的主题名称（内置主题在前，用户主题按名称排序）
+pub fn available_themes() -> Vec<String> {
+    let mut names: Vec<String> = BUILTIN_THEMES.iter().map(|name| name.to_string()).collect();
+
+    let mut user_themes = Vec::new();
+    if let Some(dir) = themes_dir() {
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries.filter_map(Result::ok) {
+                let path = entry.path();
+                let is_theme_file = path.extension().is_some_and(|ext| ext == "toml" || ext == "json");
+                if let (true, Some(stem)) = (is_theme_file, path.file_stem()) {
+                    let name = stem.to_string_lossy().to_string();
+                    if !names.contains(&name) && !user_themes.contains(&name) {
+                        user_themes.push(name);
+                    }
+                }
+            }
+        }
+    }
+
+    user_themes.sort();
+    names.extend(user_themes);
+    names
+}
+
+/// 加载指定名称的主题
+///
+/// 内置主题可以被同名的用户主题文件覆盖；文件中的未知颜色名和无法解析的颜色会被报告并忽略
+pub fn load_theme(name: &str) -> Result<Theme, String> {
+    let file = match find_theme_file(name) {
+        Some(path) => {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| format!("无法读取主题文件 {}: {}", path.display(), e))?;
+            let parsed = if path.extension().is_some_and(|ext| ext == "toml") {
+                toml::from_str::<ThemeFile>(&content).map_err(|e| e.to_string())
+            } else {
+                serde_json::from_str::<ThemeFile>(&content).map_err(|e| e.to_string())
+            };
+            Some(parsed.map_err(|e| format!("解析主题文件 {} 失败: {}", path.display(), e))?)
+        }
+        None => None,
+    };
+
+    // 确定基础调色板：同名内置主题 > 文件中指定的base > 默认主题
+    let mut palette = match (Palette::builtin(name), &file) {
+        (Some(palette), _) => palette,
+        (None, Some(file)) => {
+            let base = file.base.as_deref().unwrap_or(DEFAULT_THEME);
+            Palette::builtin(base).ok_or_else(|| format!("主题 {} 的基础主题 {} 不存在", name, base))?
+        }
+        (None, None) => return Err(format!("找不到主题: {}", name)),
+    };
+
+    let mut file_colors = HashMap::new();
+    if let Some(file) = file {
+        for (key, value) in &file.colors {
+            match Color::from_hex_str(value) {
+                Ok(color) => {
+                    if !palette.set(key, color) {
+                        eprintln!("主题 {} 中未知的颜色名: {}", name, key);
+                    }
+                }
+                Err(_) => eprintln!("主题 {} 中无法解析的颜色 {} = {}", name, key, value),
+            }
+        }
+
+        for (file_type, value) in &file.file_colors {
+            match Color::from_hex_str(value) {
+                Ok(color) => {
+                    file_colors.insert(file_type.to_lowercase(), color);
+                }
+                Err(_) => eprintln!("主题 {} 中无法解析的文件颜色 {} = {}", name, file_type, value),
+            }
+        }
+    }
+
+    Ok(Theme {
+        name: name.to_string(),
+        palette,
+        file_colors: Arc::new(file_colors),
+    })
+}
+
+/// 将主题设为当前主题（更新文件颜色覆盖表）
+pub fn set_active(theme: &Theme) {
+    *ACTIVE_FILE_COLORS.write().unwrap() = theme.file_colors.clone();
+}
+
+/// 查找文件类型的颜色
+///
+/// 依次查找当前主题中按类型ID、扩展名、类别覆盖的颜色，
+/// 然后是注册表中类型专用的颜色，都没有时使用类别在Env中的颜色
+pub fn file_color(env: &Env, kind: &FileKind) -> Color {
+    let overrides = ACTIVE_FILE_COLORS.read().unwrap().clone();
+    overrides
+        .get(&kind.type_id)
+        .or_else(|| overrides.get(&kind.extension))
+        .or_else(|| overrides.get(kind.category.name()))
+        .copied()
+        .or_else(|| file_types::type_color(kind))
+        .unwrap_or_else(|| env.get(category_color_key(kind.category)))
+}
+
+/// 文件类别在主题中的颜色键
+pub fn category_color_key(category: FileCategory) -> Key<Color> {
+    match category {
+        FileCategory::Folder | FileCategory::Drive => FOLDER_COLOR,
+        FileCategory::Text | FileCategory::Code | FileCategory::Document => TEXT_FILE_COLOR,
+        FileCategory::Image => IMAGE_FILE_COLOR,
+        FileCategory::Audio => AUDIO_FILE_COLOR,
+        FileCategory::Video => VIDEO_FILE_COLOR,
+        FileCategory::Archive => ARCHIVE_FILE_COLOR,
+        FileCategory::Executable => EXECUTABLE_FILE_COLOR,
+        FileCategory::Regular => REGULAR_FILE_COLOR,
+    }
+}
+
+/// 用当前主题包装窗口的根控件，主题切换时无需重启即可生效
+pub fn themed<W: Widget<AppState> + 'static>(widget: W) -> impl Widget<AppState> {
+    EnvScope::new(|env, data: &AppState| data.theme.apply_to_env(env), widget)
+}
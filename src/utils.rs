@@ -1,3 +1,7 @@
+use std::time::SystemTime;
+use chrono::{DateTime, Local};
+use crate::settings::RELATIVE_DATE_FORMAT;
+
 /// 格式化文件大小显示方式
 pub fn format_size(size: u64) -> String {
     if size < 1024 {
@@ -9,4 +13,54 @@ pub fn format_size(size: u64) -> String {
     } else {
         format!("{:.1} GB", size as f64 / (1024.0 * 1024.0 * 1024.0))
     }
-} 
\ No newline at end of file
+}
+
+/// 按设置中的时间格式显示修改时间
+///
+/// `date_format` 为 "relative" 时，一天以内显示相对时间，更早的显示日期
+pub fn format_modified_time(time: SystemTime, date_format: &str) -> String {
+    let local: DateTime<Local> = time.into();
+
+    if date_format != RELATIVE_DATE_FORMAT {
+        return local.format(date_format).to_string();
+    }
+
+    let duration = SystemTime::now().duration_since(time).unwrap_or_default();
+    if duration.as_secs() < 60 {
+        "刚刚".to_string()
+    } else if duration.as_secs() < 3600 {
+        format!("{} 分钟前", duration.as_secs() / 60)
+    } else if duration.as_secs() < 86400 {
+        format!("{} 小时前", duration.as_secs() / 3600)
+    } else {
+        local.format("%Y-%m-%d").to_string()
+    }
+}
+
+/// 显示完整的日期和时间（用于属性窗口）
+pub fn format_full_time(time: SystemTime) -> String {
+    let local: DateTime<Local> = time.into();
+    local.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// 按千位分组显示整数（1234567 -> 1,234,567）
+pub fn format_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, ch) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            result.push(',');
+        }
+        result.push(ch);
+    }
+    result
+}
+
+/// 同时显示易读的大小和精确的字节数（1.2 MB (1,234,567 字节)）
+pub fn format_size_exact(size: u64) -> String {
+    if size < 1024 {
+        format!("{} 字节", size)
+    } else {
+        format!("{} ({} 字节)", format_size(size), format_thousands(size))
+    }
+}
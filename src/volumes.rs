@@ -0,0 +1,430 @@
+// 挂载信息只在 Linux 上读取，其他系统上解析函数不会被调用
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use std::path::{Path, PathBuf};
+use druid::Data;
+use crate::models::FileItem;
+use crate::utils::format_size;
+
+/// 驱动器节点显示的卷信息
+#[derive(Clone, Data, Debug, PartialEq)]
+pub struct Volume {
+    /// 卷标，没有卷标时为挂载点的目录名
+    pub label: String,
+    /// 文件系统类型（如 ext4、btrfs、vfat、nfs4）
+    pub fs_type: String,
+    /// 设备或网络来源（如 /dev/sdb1、server:/export）
+    pub device: String,
+    /// 是否为U盘等可移动设备
+    pub removable: bool,
+    /// 可用空间（字节）
+    pub free: u64,
+    /// 总空间（字节），无法获取时为 0
+    pub total: u64,
+}
+
+/// 已用空间超过该比例时使用条显示为警告颜色
+pub const USAGE_WARNING_RATIO: f64 = 0.9;
+
+impl Volume {
+    /// 已用空间占总空间的比例（0 到 1），无法获取空间时为 0
+    pub fn usage_ratio(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.total.saturating_sub(self.free) as f64 / self.total as f64
+    }
+
+    /// 目录树中卷标后面显示的说明：文件系统类型和使用情况
+    pub fn summary(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        if !self.fs_type.is_empty() {
+            parts.push(self.fs_type.clone());
+        }
+        if self.removable {
+            parts.push("可移动".to_string());
+        }
+        if self.total > 0 {
+            parts.push(format!("{} 可用，共 {}", format_size(self.free), format_size(self.total)));
+        }
+        parts.join(" · ")
+    }
+}
+
+/// 目录树中"我的电脑"下包含 `path` 的驱动器（挂载点最长的一个）
+pub fn drive_for_path<'a>(root: &'a FileItem, path: &Path) -> Option<&'a FileItem> {
+    root.children.iter()
+        .find(|child| child.name == "我的电脑")?
+        .children.iter()
+        .filter(|drive| path.starts_with(&drive.path))
+        .max_by_key(|drive| drive.path.as_os_str().len())
+}
+
+/// `/proc/self/mountinfo` 中的一条挂载记录
+#[derive(Clone, Debug, PartialEq)]
+pub struct MountEntry {
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+    pub source: String,
+}
+
+/// 作为驱动器显示的文件系统类型（其余如 proc、sysfs、tmpfs、cgroup 等伪文件系统不显示）
+const REAL_FS_TYPES: &[&str] = &[
+    "ext2", "ext3", "ext4", "btrfs", "xfs", "zfs", "f2fs", "jfs", "reiserfs", "bcachefs",
+    "vfat", "msdos", "exfat", "ntfs", "ntfs3", "fuseblk", "hfs", "hfsplus", "apfs",
+    "iso9660", "udf", "nfs", "nfs4", "cifs", "smb3", "sshfs",
+];
+
+/// 由桌面环境创建、不对应存储设备的 FUSE 文件系统
+const PSEUDO_FUSE_TYPES: &[&str] = &[
+    "fuse.gvfsd-fuse", "fuse.portal", "fuse.xdg-document-portal", "fuse.lxcfs", "fuse.snapfuse",
+];
+
+/// 这些目录下的挂载点属于系统内部，不显示（`/run/media` 是可移动设备的挂载位置，除外）
+const HIDDEN_MOUNT_PREFIXES: &[&str] = &["/proc", "/sys", "/dev", "/run", "/snap", "/var/lib/docker", "/var/snap"];
+
+/// 挂载信息文件
+#[cfg(target_os = "linux")]
+const MOUNTINFO_PATH: &str = "/proc/self/mountinfo";
+
+/// 检查挂载变化的间隔
+#[cfg(target_os = "linux")]
+const WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// 解析 `/proc/self/mountinfo` 的内容
+///
+/// 每行的格式为：`ID 父ID 主:次设备号 根 挂载点 选项 [可选字段...] - 类型 来源 超级块选项`，
+/// 路径中的空格等字符以八进制转义（如 `\040`）。无法解析的行被跳过。
+pub fn parse_mountinfo(content: &str) -> Vec<MountEntry> {
+    content.lines().filter_map(parse_mountinfo_line).collect()
+}
+
+fn parse_mountinfo_line(line: &str) -> Option<MountEntry> {
+    let mut fields = line.split(' ');
+    let mount_point = fields.nth(4)?;
+    // 跳过选项和数量不定的可选字段，直到分隔符 "-"
+    fields.find(|field| *field == "-")?;
+    let fs_type = fields.next()?;
+    let source = fields.next()?;
+    Some(MountEntry {
+        mount_point: PathBuf::from(unescape_octal(mount_point)),
+        fs_type: fs_type.to_string(),
+        source: unescape_octal(source),
+    })
+}
+
+/// 还原挂载信息中的八进制转义（`\040` 为空格，`\134` 为反斜杠）
+fn unescape_octal(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes.get(i + 1..i + 4)
+            .filter(|_| bytes[i] == b'\\')
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+        match escaped {
+            Some(byte) => {
+                result.push(byte);
+                i += 4;
+            }
+            None => {
+                result.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&result).into_owned()
+}
+
+/// 是否为应该显示为驱动器的真实文件系统
+pub fn is_real_filesystem(entry: &MountEntry) -> bool {
+    // 根目录总是显示（在容器中可能是 overlay 等类型）
+    if entry.mount_point == Path::new("/") {
+        return true;
+    }
+    if HIDDEN_MOUNT_PREFIXES.iter().any(|prefix| entry.mount_point.starts_with(prefix))
+        && !entry.mount_point.starts_with("/run/media")
+    {
+        return false;
+    }
+    let fs_type = entry.fs_type.as_str();
+    REAL_FS_TYPES.contains(&fs_type)
+        || (fs_type.starts_with("fuse.") && !PSEUDO_FUSE_TYPES.contains(&fs_type))
+}
+
+/// 需要显示的挂载记录：只保留真实文件系统，同一挂载点被多次挂载时只保留最后一次，根目录排在最前
+pub fn visible_mounts(entries: Vec<MountEntry>) -> Vec<MountEntry> {
+    let mut visible: Vec<MountEntry> = Vec::new();
+    for entry in entries.into_iter().filter(is_real_filesystem) {
+        visible.retain(|existing| existing.mount_point != entry.mount_point);
+        visible.push(entry);
+    }
+    visible.sort_by(|a, b| {
+        (a.mount_point != Path::new("/"), &a.mount_point).cmp(&(b.mount_point != Path::new("/"), &b.mount_point))
+    });
+    visible
+}
+
+/// 当前挂载的卷（挂载点和卷信息）
+#[cfg(target_os = "linux")]
+pub fn list_volumes() -> Vec<(PathBuf, Volume)> {
+    let content = match std::fs::read_to_string(MOUNTINFO_PATH) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("读取挂载信息失败: {}", e);
+            return vec![(PathBuf::from("/"), root_volume())];
+        }
+    };
+
+    let labels = read_labels();
+    visible_mounts(parse_mountinfo(&content))
+        .into_iter()
+        // 绑定挂载的文件（如容器中的 /etc/hosts）不是目录
+        .filter(|entry| entry.mount_point.is_dir())
+        .map(|entry| {
+            let device = canonical_device(&entry.source);
+            let label = labels.iter()
+                .find(|(path, _)| Some(path) == device.as_ref())
+                .map(|(_, label)| label.clone())
+                .unwrap_or_else(|| default_label(&entry.mount_point));
+            let removable = device.as_deref().is_some_and(is_removable)
+                || entry.mount_point.starts_with("/media")
+                || entry.mount_point.starts_with("/run/media");
+            let (free, total) = crate::system::get_drive_space(&entry.mount_point);
+            let volume = Volume {
+                label,
+                fs_type: entry.fs_type,
+                device: entry.source,
+                removable,
+                free,
+                total,
+            };
+            (entry.mount_point, volume)
+        })
+        .collect()
+}
+
+/// 读取挂载信息失败时只显示根目录
+#[cfg(target_os = "linux")]
+fn root_volume() -> Volume {
+    let (free, total) = crate::system::get_drive_space(Path::new("/"));
+    Volume {
+        label: default_label(Path::new("/")),
+        fs_type: String::new(),
+        device: String::new(),
+        removable: false,
+        free,
+        total,
+    }
+}
+
+/// 没有卷标时使用的名称
+fn default_label(mount_point: &Path) -> String {
+    match mount_point.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => "根目录".to_string(),
+    }
+}
+
+/// 设备文件的真实路径（`/dev/disk/by-uuid/...` 等链接解析为 `/dev/sdb1`），网络来源等返回 None
+#[cfg(target_os = "linux")]
+fn canonical_device(source: &str) -> Option<PathBuf> {
+    if !source.starts_with("/dev/") {
+        return None;
+    }
+    std::fs::canonicalize(source).ok()
+}
+
+/// `/dev/disk/by-label` 中的卷标（设备路径和卷标）
+#[cfg(target_os = "linux")]
+fn read_labels() -> Vec<(PathBuf, String)> {
+    let entries = match std::fs::read_dir("/dev/disk/by-label") {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let device = std::fs::canonicalize(entry.path()).ok()?;
+            // 卷标中的空格等字符转义为 \x20
+            let label = unescape_hex(&entry.file_name().to_string_lossy());
+            Some((device, label))
+        })
+        .collect()
+}
+
+/// 还原 udev 卷标中的十六进制转义（`\x20` 为空格）
+fn unescape_hex(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes.get(i..i + 4)
+            .filter(|chunk| chunk[0] == b'\\' && chunk[1] == b'x')
+            .and_then(|chunk| std::str::from_utf8(&chunk[2..]).ok())
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok());
+        match escaped {
+            Some(byte) => {
+                result.push(byte);
+                i += 4;
+            }
+            None => {
+                result.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&result).into_owned()
+}
+
+/// 设备是否可移动：读取 `/sys/class/block/<设备>/removable`，分区读取所在磁盘的
+#[cfg(target_os = "linux")]
+fn is_removable(device: &Path) -> bool {
+    let name = match device.file_name() {
+        Some(name) => name,
+        None => return false,
+    };
+    let sys_path = match std::fs::canonicalize(Path::new("/sys/class/block").join(name)) {
+        Ok(path) => path,
+        Err(_) => return false,
+    };
+    let disk = if sys_path.join("partition").exists() {
+        match sys_path.parent() {
+            Some(parent) => parent.to_path_buf(),
+            None => return false,
+        }
+    } else {
+        sys_path
+    };
+    std::fs::read_to_string(disk.join("removable"))
+        .map(|content| content.trim() == "1")
+        .unwrap_or(false)
+}
+
+/// 读取驱动器（卷信息、可用空间和第一级子目录）并用 `VOLUMES_CHANGED` 命令发送给界面
+///
+/// 需要访问每个挂载点，网络共享可能很慢，因此只在后台线程中调用。
+pub fn send_drives(event_sink: &druid::ExtEventSink) -> Result<(), druid::ExtEventError> {
+    let drives = crate::file_system::get_drives();
+    event_sink.submit_command(crate::commands::VOLUMES_CHANGED, drives, druid::Target::Auto)
+}
+
+/// 在后台线程中重新读取驱动器
+pub fn refresh_drives(event_sink: druid::ExtEventSink) {
+    std::thread::spawn(move || {
+        if let Err(e) = send_drives(&event_sink) {
+            eprintln!("发送驱动器列表失败: {:?}", e);
+        }
+    });
+}
+
+/// 启动后台线程，挂载的卷发生变化（插入U盘、挂载网络共享等）时在该线程中重新读取驱动器并发送
+#[cfg(target_os = "linux")]
+pub fn watch_mounts(event_sink: druid::ExtEventSink) {
+    std::thread::spawn(move || {
+        let read_mounts = || {
+            std::fs::read_to_string(MOUNTINFO_PATH)
+                .map(|content| visible_mounts(parse_mountinfo(&content)))
+                .unwrap_or_default()
+        };
+        let mut mounts = read_mounts();
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let current = read_mounts();
+            if current == mounts {
+                continue;
+            }
+            mounts = current;
+            if let Err(e) = send_drives(&event_sink) {
+                eprintln!("发送挂载变化失败: {:?}", e);
+                break;
+            }
+        }
+    });
+}
+
+/// 其他系统上不检查挂载变化
+#[cfg(not(target_os = "linux"))]
+pub fn watch_mounts(_event_sink: druid::ExtEventSink) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTINFO: &str = "\
+22 1 8:2 / / rw,relatime shared:1 - ext4 /dev/sda2 rw
+23 22 0:21 / /proc rw,nosuid shared:12 - proc proc rw
+24 22 0:5 / /dev rw,nosuid shared:2 - devtmpfs udev rw,size=8G
+40 22 8:17 / /media/me/USB\\040Stick rw,nosuid shared:40 master:1 - vfat /dev/sdb1 rw
+41 22 0:45 / /mnt/share rw shared:41 - nfs4 server:/export rw,vers=4.2
+42 22 0:46 / /run/user/1000/gvfs rw shared:42 - fuse.gvfsd-fuse gvfsd-fuse rw
+43 22 0:47 / /home/me/remote rw shared:43 - fuse.sshfs me@host:/ rw
+44 22 0:48 / /tmp rw shared:44 - tmpfs tmpfs rw
+malformed line
+";
+
+    fn entry(mount_point: &str, fs_type: &str) -> MountEntry {
+        MountEntry {
+            mount_point: PathBuf::from(mount_point),
+            fs_type: fs_type.to_string(),
+            source: String::new(),
+        }
+    }
+
+    #[test]
+    fn parse_mountinfo_reads_fields() {
+        let entries = parse_mountinfo(MOUNTINFO);
+        assert_eq!(entries.len(), 8);
+        assert_eq!(entries[0], MountEntry {
+            mount_point: PathBuf::from("/"),
+            fs_type: "ext4".to_string(),
+            source: "/dev/sda2".to_string(),
+        });
+        // 可选字段的数量不定，空格以八进制转义
+        assert_eq!(entries[3].mount_point, PathBuf::from("/media/me/USB Stick"));
+        assert_eq!(entries[3].fs_type, "vfat");
+        assert_eq!(entries[4].source, "server:/export");
+    }
+
+    #[test]
+    fn unescape_octal_sequences() {
+        assert_eq!(unescape_octal("a\\040b"), "a b");
+        assert_eq!(unescape_octal("tab\\011x\\134y"), "tab\tx\\y");
+        // 不完整或无效的转义保持原样
+        assert_eq!(unescape_octal("end\\04"), "end\\04");
+        assert_eq!(unescape_octal("bad\\999"), "bad\\999");
+        assert_eq!(unescape_hex("My\\x20Disk"), "My Disk");
+    }
+
+    #[test]
+    fn real_filesystems() {
+        assert!(is_real_filesystem(&entry("/", "overlay")));
+        assert!(is_real_filesystem(&entry("/home", "btrfs")));
+        assert!(is_real_filesystem(&entry("/run/media/me/USB", "exfat")));
+        assert!(is_real_filesystem(&entry("/home/me/remote", "fuse.sshfs")));
+        assert!(!is_real_filesystem(&entry("/tmp", "tmpfs")));
+        assert!(!is_real_filesystem(&entry("/run/user/1000/gvfs", "fuse.gvfsd-fuse")));
+        assert!(!is_real_filesystem(&entry("/mnt/portal", "fuse.portal")));
+        assert!(!is_real_filesystem(&entry("/snap/core/1", "squashfs")));
+        assert!(!is_real_filesystem(&entry("/var/lib/docker/overlay2", "ext4")));
+    }
+
+    #[test]
+    fn visible_mounts_filters_and_sorts() {
+        let mounts: Vec<_> = visible_mounts(parse_mountinfo(MOUNTINFO))
+            .into_iter()
+            .map(|entry| entry.mount_point)
+            .collect();
+        assert_eq!(mounts, vec![
+            PathBuf::from("/"),
+            PathBuf::from("/home/me/remote"),
+            PathBuf::from("/media/me/USB Stick"),
+            PathBuf::from("/mnt/share"),
+        ]);
+
+        // 同一挂载点被多次挂载时只保留最后一次
+        let mounts = visible_mounts(vec![entry("/data", "ext4"), entry("/", "ext4"), entry("/data", "xfs")]);
+        assert_eq!(mounts, vec![entry("/", "ext4"), entry("/data", "xfs")]);
+    }
+}